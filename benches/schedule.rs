@@ -0,0 +1,104 @@
+use chrono::{Duration, NaiveDate};
+use criterion::{Criterion, criterion_group, criterion_main};
+use planit::core::models::{Card, Event, Task, TaskTemplate};
+use planit::core::repository::Repository;
+use planit::core::types::{
+    Date, DayOfWeek, TaskOverflowPolicy, TaskPackingMode, TaskSchedulingOrder, TimeRange,
+};
+use planit::scheduler::{ScheduleOptions, ScheduleSnapshot, plan_schedule};
+use std::hint::black_box;
+
+const DAYS: u32 = 365;
+const TASKS: i64 = 3000;
+
+fn year_of_work() -> (
+    Repository<Task>,
+    Repository<Event>,
+    Repository<TaskTemplate>,
+) {
+    let start = NaiveDate::from_ymd_opt(2099, 1, 1).unwrap();
+    let mut tasks = Repository::new();
+    for i in 0..TASKS {
+        let due = start + Duration::days(i % DAYS as i64);
+        let hours = 0.5 + (i % 4) as f32;
+        tasks.insert(Task::new(
+            format!("task {i}"),
+            hours,
+            Some((i % 5) as i32),
+            Date(due),
+        ));
+    }
+    let mut events = Repository::new();
+    events.insert(Event::new(
+        true,
+        "standup",
+        None,
+        vec![
+            DayOfWeek::Mon,
+            DayOfWeek::Tue,
+            DayOfWeek::Wed,
+            DayOfWeek::Thu,
+            DayOfWeek::Fri,
+        ],
+        TimeRange::try_from_str("9AM-9:30AM").unwrap(),
+    ));
+    events.insert(Event::new(
+        true,
+        "lunch",
+        None,
+        vec![DayOfWeek::Mon, DayOfWeek::Wed, DayOfWeek::Fri],
+        TimeRange::try_from_str("12PM-1PM").unwrap(),
+    ));
+    (tasks, events, Repository::new())
+}
+
+fn options(packing: TaskPackingMode, stable: bool) -> ScheduleOptions {
+    ScheduleOptions {
+        day_window: TimeRange::try_from_str("8AM-6PM").unwrap(),
+        start_date: NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+        days: DAYS,
+        order: TaskSchedulingOrder::LongestTaskFirst,
+        overflow_policy: TaskOverflowPolicy::Allow,
+        packing,
+        stable,
+    }
+}
+
+fn bench_year(c: &mut Criterion) {
+    let (tasks, events, templates) = year_of_work();
+    let cards = Repository::<Card>::new();
+    let snapshot = ScheduleSnapshot {
+        tasks: &tasks,
+        events: &events,
+        cards: &cards,
+        templates: &templates,
+    };
+
+    let mut group = c.benchmark_group("plan_year_3000_tasks");
+    group.sample_size(10);
+    group.bench_function("greedy", |b| {
+        let opts = options(TaskPackingMode::Greedy, false);
+        b.iter(|| plan_schedule(black_box(snapshot), &opts).unwrap())
+    });
+    group.bench_function("group_by_card", |b| {
+        let opts = options(TaskPackingMode::GroupByCard, false);
+        b.iter(|| plan_schedule(black_box(snapshot), &opts).unwrap())
+    });
+
+    // Stable rescheduling starts from an existing plan.
+    let planned = plan_schedule(snapshot, &options(TaskPackingMode::Greedy, false))
+        .unwrap()
+        .tasks;
+    let replan = ScheduleSnapshot {
+        tasks: &planned,
+        ..snapshot
+    };
+    group.bench_function("stable", |b| {
+        let opts = options(TaskPackingMode::Greedy, true);
+        b.iter(|| plan_schedule(black_box(replan), &opts).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_year);
+criterion_main!(benches);
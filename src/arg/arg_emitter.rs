@@ -1,12 +1,14 @@
-use crate::arg::args::Arg;
+use crate::arg::args::{Arg, FieldSet};
 use crate::core::aliases::{IdLookup, ResolvedId, SourceId};
-use crate::core::models::{Card, Event, Task};
-use crate::core::types::Bool;
+use crate::core::models::{Card, Details, Event, Task, TaskTemplate};
+use crate::core::types::{Bool, Date, TaskMarker};
+use crate::core::uid::{UID_FIELD, Uid};
 use crate::errors::{Error, Result};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EmitRefKind {
     Card,
+    Template,
 }
 
 pub trait ArgEmitContext {
@@ -14,6 +16,7 @@ pub trait ArgEmitContext {
 }
 pub struct SaveEmitContext<'a> {
     pub id_lookup: &'a IdLookup,
+    pub template_lookup: &'a IdLookup,
 }
 
 impl<'a> ArgEmitContext for SaveEmitContext<'a> {
@@ -25,6 +28,12 @@ impl<'a> ArgEmitContext for SaveEmitContext<'a> {
                     id
                 ))
             }),
+            EmitRefKind::Template => self.template_lookup.get(&id).copied().ok_or_else(|| {
+                Error::Parse(format!(
+                    "Reference to missing template id {} when building save file.",
+                    id
+                ))
+            }),
         }
     }
 }
@@ -49,6 +58,38 @@ pub trait ArgEmitter<E> {
     fn fill_args(&self, entity: &E, ctx: &dyn ArgEmitContext, out: &mut Vec<Arg>) -> Result<()>;
 }
 
+fn set(field: &str, value: &str) -> FieldSet {
+    FieldSet {
+        field: field.to_string(),
+        value: value.to_string(),
+        quoted: false,
+    }
+}
+
+/// A detail set, quoted when its value is the text `none` so it is not read back as a clear.
+fn detail_set(field: &str, value: &str) -> FieldSet {
+    FieldSet {
+        quoted: value.eq_ignore_ascii_case("none"),
+        ..set(field, value)
+    }
+}
+
+/// Trailing `uid=...`, then the entity's own `sets`, then `note=... links=... meta.<key>=...`.
+fn push_trailing(uid: &Uid, own: Vec<FieldSet>, details: &Details, out: &mut Vec<Arg>) {
+    let mut sets = vec![set(UID_FIELD, uid.as_str())];
+    sets.extend(own);
+    if let Some(notes) = &details.notes {
+        sets.push(detail_set("note", notes));
+    }
+    if !details.links.is_empty() {
+        sets.push(detail_set("links", &details.links.join(",")));
+    }
+    for (key, value) in &details.meta {
+        sets.push(detail_set(&format!("meta.{key}"), value));
+    }
+    out.push(Arg::FieldSets(sets));
+}
+
 pub struct CardArgEmitter;
 impl CardArgEmitter {
     pub fn new() -> Self {
@@ -59,6 +100,7 @@ impl ArgEmitter<Card> for CardArgEmitter {
     fn fill_args(&self, card: &Card, _ctx: &dyn ArgEmitContext, out: &mut Vec<Arg>) -> Result<()> {
         out.push(Arg::Name(card.name.clone()));
         out.push(Arg::CardColor(card.color));
+        push_trailing(&card.uid, Vec::new(), &card.details, out);
         Ok(())
     }
 }
@@ -72,13 +114,79 @@ impl TaskArgEmitter {
 impl ArgEmitter<Task> for TaskArgEmitter {
     fn fill_args(&self, task: &Task, ctx: &dyn ArgEmitContext, out: &mut Vec<Arg>) -> Result<()> {
         out.push(Arg::Name(task.name.clone()));
-        out.push(Arg::Int(task.hours.round() as i32));
+        out.push(if task.hours.fract() == 0.0 {
+            Arg::Int(task.hours as i32)
+        } else {
+            Arg::Hours(task.hours)
+        });
+        if let Some(estimate) = task.estimate {
+            out.push(Arg::Estimate(estimate));
+        }
         if let Some(card_id) = task.card_id {
             let mapped = ctx.translate_ref(EmitRefKind::Card, card_id)?;
             out.push(Arg::CardColorId(mapped));
         }
+        if let Some(origin) = &task.origin {
+            let mapped = ctx.translate_ref(EmitRefKind::Template, origin.template_id)?;
+            let occurrence = (origin.occurrence != task.date.0).then_some(Date(origin.occurrence));
+            out.push(Arg::TemplateId(mapped, occurrence));
+        }
+        let markers: Vec<TaskMarker> = [
+            (task.atomic, TaskMarker::Atomic),
+            (task.completed, TaskMarker::Done),
+        ]
+        .into_iter()
+        .filter_map(|(set, marker)| set.then_some(marker))
+        .collect();
+        if !markers.is_empty() {
+            out.push(Arg::TaskMarkers(markers));
+        }
         out.push(Arg::AtSymbol);
         out.push(Arg::Date(task.date.clone()));
+        let items = task
+            .checklist
+            .iter()
+            .map(|item| set("item", &item.to_string()))
+            .collect();
+        push_trailing(&task.uid, items, &task.details, out);
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct TemplateArgEmitter;
+impl TemplateArgEmitter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl ArgEmitter<TaskTemplate> for TemplateArgEmitter {
+    fn fill_args(
+        &self,
+        template: &TaskTemplate,
+        ctx: &dyn ArgEmitContext,
+        out: &mut Vec<Arg>,
+    ) -> Result<()> {
+        out.push(Arg::Name(template.name.clone()));
+        out.push(Arg::Int(template.hours.round() as i32));
+        if let Some(card_id) = template.card_id {
+            let mapped = ctx.translate_ref(EmitRefKind::Card, card_id)?;
+            out.push(Arg::CardColorId(mapped));
+        }
+        out.push(Arg::AtSymbol);
+        out.push(Arg::DaysOfWeek(template.days.clone()));
+        let skipped: Vec<String> = template
+            .skipped
+            .iter()
+            .map(|d| Date(*d).to_string())
+            .collect();
+        let skip = (!skipped.is_empty()).then(|| set("skip", &skipped.join(",")));
+        push_trailing(
+            &template.uid,
+            skip.into_iter().collect(),
+            &template.details,
+            out,
+        );
         Ok(())
     }
 }
@@ -102,6 +210,7 @@ impl ArgEmitter<Event> for EventArgEmitter {
             out.push(Arg::DaysOfWeek(event.days.clone()));
         }
         out.push(Arg::TimeRange(event.time_range.clone()));
+        push_trailing(&event.uid, Vec::new(), &event.details, out);
         Ok(())
     }
 }
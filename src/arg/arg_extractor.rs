@@ -1,9 +1,12 @@
 use crate::arg::arg_matcher::ArgMatcher;
 use crate::arg::args::{
     Arg, AtSymbolArg, BoolArg, CardColorArg, CardColorIdArg, DateArg, DaysOfWeekArg, EntityTypeArg,
-    FlagArg, IntArg, NameArg, TimeRangeArg,
+    EstimateArg, FieldSet, FieldSetsArg, FlagArg, HoursArg, IntArg, NameArg, TaskMarkersArg,
+    TemplateIdArg, TimeRangeArg,
+};
+use crate::core::types::{
+    Bool, CardColor, Date, DayOfWeek, EntityType, Estimate, Flag, TaskMarker, TimeRange,
 };
-use crate::core::types::{Bool, CardColor, Date, DayOfWeek, EntityType, Flag, TimeRange};
 
 pub trait ArgExtractor<'a>: ArgMatcher {
     type Out;
@@ -46,6 +49,17 @@ impl<'a> ArgExtractor<'a> for IntArg {
     }
 }
 
+impl<'a> ArgExtractor<'a> for HoursArg {
+    type Out = f32;
+    fn try_extract(a: &'a Arg) -> Option<Self::Out> {
+        match a {
+            Arg::Int(v) => Some(*v as f32),
+            Arg::Hours(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
 impl<'a> ArgExtractor<'a> for AtSymbolArg {
     type Out = ();
     fn try_extract(a: &'a Arg) -> Option<Self::Out> {
@@ -159,3 +173,55 @@ impl<'a> ArgExtractor<'a> for CardColorIdArg {
         }
     }
 }
+
+impl<'a> ArgExtractor<'a> for TemplateIdArg {
+    type Out = (i32, Option<&'a Date>);
+    fn try_extract(a: &'a Arg) -> Option<Self::Out> {
+        if !TemplateIdArg::matches_variant(a) {
+            return None;
+        }
+        match a {
+            Arg::TemplateId(id, occurrence) => Some((*id, occurrence.as_ref())),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ArgExtractor<'a> for TaskMarkersArg {
+    type Out = &'a Vec<TaskMarker>;
+    fn try_extract(a: &'a Arg) -> Option<Self::Out> {
+        if !TaskMarkersArg::matches_variant(a) {
+            return None;
+        }
+        match a {
+            Arg::TaskMarkers(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ArgExtractor<'a> for EstimateArg {
+    type Out = Estimate; // Copy
+    fn try_extract(a: &'a Arg) -> Option<Self::Out> {
+        if !EstimateArg::matches_variant(a) {
+            return None;
+        }
+        match a {
+            Arg::Estimate(e) => Some(*e),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> ArgExtractor<'a> for FieldSetsArg {
+    type Out = &'a [FieldSet];
+    fn try_extract(a: &'a Arg) -> Option<Self::Out> {
+        if !FieldSetsArg::matches_variant(a) {
+            return None;
+        }
+        match a {
+            Arg::FieldSets(v) => Some(v),
+            _ => None,
+        }
+    }
+}
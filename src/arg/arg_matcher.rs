@@ -1,9 +1,10 @@
 use crate::arg::args::{
     Arg, AtSymbolArg, BoolArg, CardColorArg, CardColorIdArg, DateArg, DaysOfWeekArg, EntityTypeArg,
-    FlagArg, IntArg, NameArg, TimeRangeArg,
+    EstimateArg, FieldSetsArg, FlagArg, HoursArg, IntArg, NameArg, TaskMarkersArg, TemplateIdArg,
+    TimeRangeArg,
 };
 use crate::core::types::{
-    BoolFormat, CardColor, DateFormat, DayOfWeek, EntityType, Flag, TimeFormat,
+    BoolFormat, CardColor, DateFormat, DayOfWeek, EntityType, Flag, TaskMarker, TimeFormat,
 };
 use crate::errors::Error;
 use crate::extensions::enums::valid_csv;
@@ -31,6 +32,16 @@ impl ArgMatcher for IntArg {
     }
 }
 
+/// Whole hours as well as ones with a fraction.
+impl ArgMatcher for HoursArg {
+    fn matches_variant(a: &Arg) -> bool {
+        matches!(a, Arg::Int(_) | Arg::Hours(_))
+    }
+    fn expected_error(provided: &Arg) -> Error {
+        Error::Parse(format!("Expected a number of hours, got {:?}", provided))
+    }
+}
+
 impl ArgMatcher for BoolArg {
     fn matches_variant(actual: &Arg) -> bool {
         matches!(actual, Arg::Bool(_))
@@ -142,3 +153,52 @@ impl ArgMatcher for EntityTypeArg {
         ))
     }
 }
+
+impl ArgMatcher for TemplateIdArg {
+    fn matches_variant(actual: &Arg) -> bool {
+        matches!(actual, Arg::TemplateId(..))
+    }
+    fn expected_error(provided: &Arg) -> Error {
+        Error::Parse(format!(
+            "Expected a template id in the format '+T<integer>', got {:?}.",
+            provided
+        ))
+    }
+}
+
+impl ArgMatcher for TaskMarkersArg {
+    fn matches_variant(actual: &Arg) -> bool {
+        matches!(actual, Arg::TaskMarkers(_))
+    }
+    fn expected_error(provided: &Arg) -> Error {
+        Error::Parse(format!(
+            "Expected task markers, got {:?}. Valid markers: {}",
+            provided,
+            valid_csv::<TaskMarker>()
+        ))
+    }
+}
+
+impl ArgMatcher for EstimateArg {
+    fn matches_variant(actual: &Arg) -> bool {
+        matches!(actual, Arg::Estimate(_))
+    }
+    fn expected_error(provided: &Arg) -> Error {
+        Error::Parse(format!(
+            "Expected an estimate in the format '~<optimistic>/<likely>/<pessimistic>', got {:?}.",
+            provided
+        ))
+    }
+}
+
+impl ArgMatcher for FieldSetsArg {
+    fn matches_variant(actual: &Arg) -> bool {
+        matches!(actual, Arg::FieldSets(_))
+    }
+    fn expected_error(provided: &Arg) -> Error {
+        Error::Parse(format!(
+            "Expected one or more field=value assignments, got {}",
+            provided
+        ))
+    }
+}
@@ -1,8 +1,12 @@
 use std::collections::HashMap;
 
 use crate::arg::arg_parser::ArgParser;
-use crate::arg::args::{Arg, NameArg, SingleTokenArg};
-use crate::errors::Result;
+use crate::arg::args::{Arg, FlagArg, NameArg, SingleTokenArg, TableOptionFactory};
+use crate::core::filter::Selection;
+use crate::core::types::{EntityType, Flag};
+use crate::core::uid::UID_FIELD;
+use crate::core::view::TableOption;
+use crate::errors::{Error, Result};
 
 pub trait ArgParseStrategy {
     fn parse(&self, raw: &[String]) -> Result<Vec<Arg>>;
@@ -40,6 +44,136 @@ impl ArgParseStrategy for ManArgParser {
     }
 }
 
+/// Branches and revisions such as `HEAD~1` or `3fa9c2d1` are bare words, so each token is taken as a name;
+/// only flags keep their meaning.
+pub struct RevArgParser;
+
+impl ArgParseStrategy for RevArgParser {
+    fn parse(&self, raw: &[String]) -> Result<Vec<Arg>> {
+        raw.iter()
+            .map(|tok| {
+                if FlagArg::accepts(tok) {
+                    FlagArg::new(tok)
+                } else {
+                    Ok(Arg::Name(strip_wrapping_quotes(tok)))
+                }
+            })
+            .collect()
+    }
+}
+
+/// `item <action> <taskId> <text>...`: every word is a name, with a quoted text split by whitespace
+/// joined back into one; only flags keep their meaning.
+pub struct ItemArgParser;
+
+impl ArgParseStrategy for ItemArgParser {
+    fn parse(&self, raw: &[String]) -> Result<Vec<Arg>> {
+        let mut args = Vec::new();
+        let mut pending: Option<String> = None;
+        for tok in raw {
+            let Some(word) = join_quoted(&mut pending, tok) else {
+                continue;
+            };
+            args.push(if FlagArg::accepts(&word) {
+                FlagArg::new(&word)?
+            } else {
+                Arg::Name(strip_wrapping_quotes(&word))
+            });
+        }
+        if let Some(open) = pending {
+            return Err(Error::Parse(format!("Unclosed quote in '{open}'.")));
+        }
+        Ok(args)
+    }
+}
+
+/// `find <entity> <filter>...`: the entity type comes first, then one name per filter expression.
+/// A quoted value split by whitespace (`name~"weekly report"`) is joined back into one expression;
+/// `--sort`/`--columns` keep the list that follows them.
+pub struct FindArgParser;
+
+impl ArgParseStrategy for FindArgParser {
+    fn parse(&self, raw: &[String]) -> Result<Vec<Arg>> {
+        let mut args = Vec::new();
+        let mut pending: Option<String> = None;
+        let mut toks = raw.iter();
+        while let Some(tok) = toks.next() {
+            if pending.is_none() && TableOptionFactory::takes_value(tok) {
+                let flag = Flag::try_from(tok.as_str())
+                    .map_err(|_| Error::Parse(format!("Invalid flag: {tok}")))?;
+                let value = toks.next().ok_or_else(|| {
+                    Error::Parse(format!("Expected comma-separated columns after {flag}."))
+                })?;
+                args.push(Arg::TableOption(TableOption::try_from_parts(flag, value)?));
+                continue;
+            }
+            let Some(expr) = join_quoted(&mut pending, tok) else {
+                continue;
+            };
+            args.push(if FlagArg::accepts(&expr) {
+                FlagArg::new(&expr)?
+            } else if args.is_empty()
+                && let Ok(kind) = EntityType::try_from(&expr)
+            {
+                Arg::EntityType(kind)
+            } else {
+                Arg::Name(expr)
+            });
+        }
+        if let Some(open) = pending {
+            return Err(Error::Parse(format!("Unclosed quote in filter '{open}'.")));
+        }
+        Ok(args)
+    }
+}
+
+/// `mod`, `del` and `done <entity> <ids>`: an id list or range (`1,4,7-12`), or `where` followed by
+/// filter expressions, becomes one `Selection`; everything else is parsed as usual. Filters end at
+/// the first flag, or at `set`, which introduces the `field=value` changes of a bulk `mod`.
+pub struct SelectionArgParser;
+
+impl ArgParseStrategy for SelectionArgParser {
+    fn parse(&self, raw: &[String]) -> Result<Vec<Arg>> {
+        let parser = ArgParser::new();
+        let Some(target) = raw.get(1) else {
+            return parser.parse(raw);
+        };
+        let (selection, rest) = if Selection::is_id_list(target) {
+            (Selection::ids_from_str(target)?, &raw[2..])
+        } else if target.eq_ignore_ascii_case("where") {
+            let mut exprs = Vec::new();
+            let mut pending = None;
+            let mut end = raw.len();
+            for (i, tok) in raw.iter().enumerate().skip(2) {
+                if pending.is_none() && (tok.eq_ignore_ascii_case("set") || FlagArg::accepts(tok)) {
+                    end = i;
+                    break;
+                }
+                exprs.extend(join_quoted(&mut pending, tok));
+            }
+            if let Some(open) = pending {
+                return Err(Error::Parse(format!("Unclosed quote in filter '{open}'.")));
+            }
+            if exprs.is_empty() {
+                return Err(Error::Parse(
+                    "Expected one or more filters after 'where', e.g. where due<today".into(),
+                ));
+            }
+            let rest = match raw.get(end) {
+                Some(tok) if tok.eq_ignore_ascii_case("set") => &raw[end + 1..],
+                _ => &raw[end..],
+            };
+            (Selection::Where(exprs), rest)
+        } else {
+            return parser.parse(raw);
+        };
+        let mut args = parser.parse(&raw[..1])?;
+        args.push(Arg::Selection(selection));
+        args.extend(parser.parse(rest)?);
+        Ok(args)
+    }
+}
+
 pub struct CommandArgParser {
     default: StandardArgParser,
     overrides: HashMap<String, Box<dyn ArgParseStrategy>>,
@@ -49,19 +183,55 @@ impl CommandArgParser {
     pub fn new() -> Self {
         let mut overrides: HashMap<String, Box<dyn ArgParseStrategy>> = HashMap::new();
         overrides.insert("man".to_string(), Box::new(ManArgParser));
+        overrides.insert("checkout".to_string(), Box::new(RevArgParser));
+        overrides.insert("diff".to_string(), Box::new(RevArgParser));
+        overrides.insert("branch".to_string(), Box::new(RevArgParser));
+        overrides.insert("merge".to_string(), Box::new(RevArgParser));
+        overrides.insert("find".to_string(), Box::new(FindArgParser));
+        overrides.insert("item".to_string(), Box::new(ItemArgParser));
+        for command in ["mod", "del", "done"] {
+            overrides.insert(command.to_string(), Box::new(SelectionArgParser));
+        }
         Self {
             default: StandardArgParser::new(),
             overrides,
         }
     }
 
+    /// Parses the arguments of a typed or journaled command. A `uid=` assignment is refused:
+    /// entities get their uid when added, and only save files carry one back in.
     pub fn parse(&self, command: &str, raw: &[String]) -> Result<Vec<Arg>> {
         let key = command.trim().to_ascii_lowercase();
-        if let Some(parser) = self.overrides.get(&key) {
-            parser.parse(raw)
+        let args = if let Some(parser) = self.overrides.get(&key) {
+            parser.parse(raw)?
         } else {
-            self.default.parse(raw)
+            self.default.parse(raw)?
+        };
+        let sets_uid = args.iter().any(|arg| {
+            matches!(arg, Arg::FieldSets(sets) if sets.iter().any(|set| set.field == UID_FIELD))
+        });
+        if sets_uid {
+            return Err(Error::Parse(
+                "uid cannot be given; entities get one when added and keep it in save files."
+                    .into(),
+            ));
         }
+        Ok(args)
+    }
+}
+
+/// Adds `tok` to a filter expression whose quoted value may span several tokens, returning the
+/// expression once its quotes are balanced.
+fn join_quoted(pending: &mut Option<String>, tok: &str) -> Option<String> {
+    let expr = match pending.take() {
+        Some(open) => format!("{open} {tok}"),
+        None => tok.to_string(),
+    };
+    if expr.matches('"').count() % 2 == 1 {
+        *pending = Some(expr);
+        None
+    } else {
+        Some(expr)
     }
 }
 
@@ -10,16 +10,22 @@ impl ArgParser {
         Self {
             factories: vec![
                 Box::new(MultiTokenFactory::<NameArg>::new()),
+                Box::new(FieldSetsFactory),
                 Box::new(SingleTokenFactory::<EntityTypeArg>::new()),
                 Box::new(SingleTokenFactory::<AtSymbolArg>::new()),
                 Box::new(SingleTokenFactory::<CardColorArg>::new()),
+                Box::new(TableOptionFactory),
                 Box::new(SingleTokenFactory::<FlagArg>::new()),
                 Box::new(SingleTokenFactory::<BoolArg>::new()),
                 Box::new(SingleTokenFactory::<IntArg>::new()),
+                Box::new(SingleTokenFactory::<HoursArg>::new()),
                 Box::new(MultiTokenFactory::<DaysOfWeekArg>::new()),
                 Box::new(SingleTokenFactory::<TimeRangeArg>::new()),
                 Box::new(SingleTokenFactory::<DateArg>::new()),
                 Box::new(SingleTokenFactory::<CardColorIdArg>::new()),
+                Box::new(SingleTokenFactory::<TemplateIdArg>::new()),
+                Box::new(SingleTokenFactory::<EstimateArg>::new()),
+                Box::new(TaskMarkersFactory),
             ],
         }
     }
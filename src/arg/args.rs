@@ -2,7 +2,11 @@ use crate::extensions::enums::valid_csv;
 use std::fmt;
 use std::marker::PhantomData;
 
-use crate::core::types::{Bool, CardColor, Date, DayOfWeek, EntityType, Flag, TimeRange};
+use crate::core::filter::Selection;
+use crate::core::types::{
+    Bool, CardColor, Date, DayOfWeek, EntityType, Estimate, Flag, TaskMarker, TimeRange,
+};
+use crate::core::view::TableOption;
 use crate::errors::{Error, Result};
 
 #[derive(Debug, Clone)]
@@ -11,6 +15,8 @@ pub enum Arg {
     CardColor(CardColor),
     CardColorId(i32),
     Int(i32),
+    /// Hours with a fraction, which only estimated checklist items give a task.
+    Hours(f32),
     AtSymbol,
     Bool(Bool),
     DaysOfWeek(Vec<DayOfWeek>),
@@ -18,6 +24,12 @@ pub enum Arg {
     Date(Date),
     Name(String),
     EntityType(EntityType),
+    TemplateId(i32, Option<Date>),
+    TaskMarkers(Vec<TaskMarker>),
+    Estimate(Estimate),
+    TableOption(TableOption),
+    FieldSets(Vec<FieldSet>),
+    Selection(Selection),
 }
 
 fn fmt_seq<T: fmt::Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
@@ -39,6 +51,7 @@ impl fmt::Display for Arg {
             Arg::CardColorId(x) => write!(f, "+C{x}"),
             Arg::CardColor(x) => write!(f, "{x}"),
             Arg::Int(x) => write!(f, "{x}"),
+            Arg::Hours(x) => write!(f, "{x}"),
             Arg::AtSymbol => write!(f, "@"),
             Arg::Bool(x) => write!(f, "{x}"),
             Arg::DaysOfWeek(xs) => fmt_seq(f, xs),
@@ -46,12 +59,29 @@ impl fmt::Display for Arg {
             Arg::Date(x) => write!(f, "{x}"),
             Arg::Name(x) => write!(f, "\"{x}\""),
             Arg::EntityType(x) => write!(f, "{x}"),
+            Arg::TemplateId(x, None) => write!(f, "+T{x}"),
+            Arg::TemplateId(x, Some(d)) => write!(f, "+T{x}:{d}"),
+            Arg::TaskMarkers(xs) => {
+                let joined = xs.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", joined.join(" "))
+            }
+            Arg::Estimate(x) => write!(f, "{x}"),
+            Arg::TableOption(x) => write!(f, "{x}"),
+            Arg::FieldSets(xs) => {
+                let joined = xs.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", joined.join(" "))
+            }
+            Arg::Selection(x) => write!(f, "{x}"),
         }
     }
 }
 
 impl Arg {
     pub fn to_tokens(&self) -> Vec<String> {
+        // One token per assignment keeps the whitespace inside quoted values intact.
+        if let Arg::FieldSets(sets) = self {
+            return sets.iter().map(|s| s.to_string()).collect();
+        }
         let rendered = self.to_string();
         if rendered.contains(char::is_whitespace)
             && !rendered.starts_with('"')
@@ -283,6 +313,188 @@ impl SingleTokenArg for CardColorIdArg {
     }
 }
 
+pub struct TemplateIdArg;
+
+impl SingleTokenArg for TemplateIdArg {
+    fn accepts(value: &str) -> bool {
+        let Some(rest) = value.strip_prefix("+T") else {
+            return false;
+        };
+        let (id, occurrence) = match rest.split_once(':') {
+            Some((id, occurrence)) => (id, Some(occurrence)),
+            None => (rest, None),
+        };
+        !id.is_empty()
+            && id.chars().all(|c| c.is_ascii_digit())
+            && occurrence.is_none_or(|d| Date::try_from_str(d).is_ok())
+    }
+
+    fn new(value: &str) -> Result<Arg> {
+        if !Self::accepts(value) {
+            return Err(Error::Parse(format!(
+                "Invalid template id: '{}'. Expected format '+T<number>' (e.g., +T2).",
+                value
+            )));
+        }
+
+        let rest = &value[2..];
+        let (id, occurrence) = match rest.split_once(':') {
+            Some((id, occurrence)) => (id, Some(Date::try_from_str(occurrence)?)),
+            None => (rest, None),
+        };
+        let n: i32 = id.parse().map_err(|_| {
+            Error::Parse(format!(
+                "Invalid number in '{}'. Expected an integer after +T.",
+                value
+            ))
+        })?;
+        Ok(Arg::TemplateId(n, occurrence))
+    }
+}
+
+pub struct EstimateArg;
+impl SingleTokenArg for EstimateArg {
+    fn accepts(value: &str) -> bool {
+        value.starts_with('~')
+    }
+    fn new(value: &str) -> Result<Arg> {
+        Ok(Arg::Estimate(Estimate::try_from_str(value)?))
+    }
+}
+
+/// Consumes every consecutive `!marker` token into a single argument.
+pub struct TaskMarkersFactory;
+
+impl ArgFactory for TaskMarkersFactory {
+    fn can_start(&self, tok: &str) -> bool {
+        tok.starts_with('!')
+    }
+
+    fn parse(&self, ts: &mut TokenStream) -> Result<Arg> {
+        let mut markers = vec![TaskMarker::try_from(&ts.next()?)?];
+        while !ts.eof() && self.can_start(ts.peek()?) {
+            let marker = TaskMarker::try_from(&ts.next()?)?;
+            if !markers.contains(&marker) {
+                markers.push(marker);
+            }
+        }
+        Ok(Arg::TaskMarkers(markers))
+    }
+}
+
+pub struct TaskMarkersArg;
+
+/// `--sort <keys>` / `--columns <names>`: the flag and the comma-separated list after it.
+pub struct TableOptionFactory;
+
+impl TableOptionFactory {
+    pub fn takes_value(tok: &str) -> bool {
+        matches!(Flag::try_from(tok), Ok(Flag::Sort | Flag::Columns))
+    }
+}
+
+impl ArgFactory for TableOptionFactory {
+    fn can_start(&self, tok: &str) -> bool {
+        Self::takes_value(tok)
+    }
+
+    fn parse(&self, ts: &mut TokenStream) -> Result<Arg> {
+        let tok = ts.next()?;
+        let flag = Flag::try_from(tok.as_str())
+            .map_err(|_| Error::Parse(format!("Invalid flag: {tok}")))?;
+        if ts.eof() {
+            return Err(Error::Parse(format!(
+                "Expected comma-separated columns after {flag}, e.g. {flag} id,name"
+            )));
+        }
+        Ok(Arg::TableOption(TableOption::try_from_parts(
+            flag,
+            &ts.next()?,
+        )?))
+    }
+}
+
+/// One `field=value` assignment given to a field-level `mod`, e.g. `hours=5`, or a detail such as
+/// `meta.course=CS101` given when adding an entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSet {
+    pub field: String,
+    pub value: String,
+    /// Whether the value was quoted, so `note="none"` is the text rather than a request to clear.
+    pub quoted: bool,
+}
+
+impl fmt::Display for FieldSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let needs_quotes = self.quoted
+            || self.value.contains(char::is_whitespace)
+            || self.value.starts_with(['"', '\'']);
+        if needs_quotes {
+            write!(f, "{}=\"{}\"", self.field, self.value)
+        } else {
+            write!(f, "{}={}", self.field, self.value)
+        }
+    }
+}
+
+/// Consumes every consecutive `field=value` token into a single argument. A quoted value split by
+/// whitespace (`name="Weekly report"`) is joined back together. Metadata fields are written
+/// `meta.<key>`, the key made of letters, digits, `_` and `-`.
+pub struct FieldSetsFactory;
+
+impl ArgFactory for FieldSetsFactory {
+    fn can_start(&self, tok: &str) -> bool {
+        tok.split_once('=').is_some_and(|(field, _)| {
+            let (name, key) = match field.split_once('.') {
+                Some((name, key)) => (name, Some(key)),
+                None => (field, None),
+            };
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphabetic())
+                && key.is_none_or(|k| {
+                    !k.is_empty()
+                        && k.chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                })
+        })
+    }
+
+    fn parse(&self, ts: &mut TokenStream) -> Result<Arg> {
+        let mut sets = Vec::new();
+        while !ts.eof() && self.can_start(ts.peek()?) {
+            let tok = ts.next()?;
+            let (field, value) = tok.split_once('=').expect("checked by can_start");
+            let mut value = value.to_string();
+            let quote = value.chars().next().filter(|c| matches!(c, '\'' | '"'));
+            if let Some(q) = quote {
+                while value.len() < 2 || !value.ends_with(q) {
+                    if ts.eof() {
+                        return Err(Error::Parse(format!(
+                            "Unclosed quote in '{field}={value}'."
+                        )));
+                    }
+                    value.push(' ');
+                    value.push_str(&ts.next()?);
+                }
+                value = value[1..value.len() - 1].to_string();
+            }
+            if value.trim().is_empty() {
+                return Err(Error::Parse(format!(
+                    "Missing value for '{field}'. Expected {field}=<value>."
+                )));
+            }
+            sets.push(FieldSet {
+                field: field.to_ascii_lowercase(),
+                value,
+                quoted: quote.is_some(),
+            });
+        }
+        Ok(Arg::FieldSets(sets))
+    }
+}
+
+pub struct FieldSetsArg;
+
 pub struct FlagArg;
 impl SingleTokenArg for FlagArg {
     fn accepts(value: &str) -> bool {
@@ -322,6 +534,24 @@ impl SingleTokenArg for IntArg {
     }
 }
 
+/// A whole number of hours lexes as an `Int`; this takes the ones with a fraction, e.g. `4.5`.
+pub struct HoursArg;
+impl SingleTokenArg for HoursArg {
+    fn accepts(value: &str) -> bool {
+        value.split_once('.').is_some_and(|(whole, fraction)| {
+            [whole, fraction]
+                .iter()
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        })
+    }
+    fn new(value: &str) -> Result<Arg> {
+        value
+            .parse::<f32>()
+            .map(Arg::Hours)
+            .map_err(|_| Error::Parse(format!("Expected a number of hours, got '{}'", value)))
+    }
+}
+
 pub struct TimeRangeArg;
 impl SingleTokenArg for TimeRangeArg {
     fn accepts(value: &str) -> bool {
@@ -4,12 +4,18 @@ use crate::arg::arg_emitter::{
 };
 use crate::arg::arg_parse_strategy::{ArgParseStrategy, CommandArgParser, ManArgParser};
 use crate::core::aliases::{IdLookup, TokenList};
+use crate::core::filter::Selection;
+use crate::core::models::TemplateOrigin;
 use crate::core::types::{
-    Bool, BoolFormat, Date, DateFormat, DayOfWeek, EntityType, Flag, TimeFormat, TimeRange,
+    Bool, BoolFormat, Date, DateFormat, DayOfWeek, EntityType, Estimate, Flag, TaskMarker,
+    TimeFormat, TimeRange,
 };
+use crate::core::uid::Uid;
+use crate::core::view::{SortKey, TableOption};
 use crate::core::{models::Card, models::Event, models::Task, types::CardColor};
 use crate::errors::Error;
 use crate::extensions::enums::valid_csv;
+use chrono::NaiveDate;
 
 // ---------- args.rs ----------
 #[test]
@@ -63,6 +69,84 @@ fn card_color_id_validates_prefix_and_number() {
     }
 }
 
+#[test]
+fn template_id_accepts_optional_occurrence() {
+    assert!(TemplateIdArg::accepts("+T3"));
+    assert!(TemplateIdArg::accepts("+T3:2099-01-02"));
+    assert!(!TemplateIdArg::accepts("+T"));
+    assert!(!TemplateIdArg::accepts("+T3:soon"));
+
+    match TemplateIdArg::new("+T3:2099-01-02").unwrap() {
+        Arg::TemplateId(id, Some(d)) => {
+            assert_eq!(id, 3);
+            assert_eq!(d, Date::try_from_str("2099-01-02").unwrap());
+        }
+        other => panic!("expected template id, got {other:?}"),
+    }
+}
+
+#[test]
+fn estimate_arg_parses_tilde_token() {
+    let raw: TokenList = vec!["~1.5/2/4".into(), "@".into()];
+    let args = ArgParser::new().parse(&raw).unwrap();
+    match &args[0] {
+        Arg::Estimate(e) => assert_eq!(*e, Estimate::try_from_str("~1.5/2/4").unwrap()),
+        other => panic!("expected estimate, got {other:?}"),
+    }
+    assert_eq!(EstimateArg::try_extract(&args[0]).unwrap().pessimistic, 4.0);
+    assert!(EstimateArg::new("~4/2/1").is_err());
+}
+
+#[test]
+fn task_markers_factory_collects_consecutive_markers() {
+    let raw: TokenList = vec!["!done".into(), "!atomic".into(), "!DONE".into(), "@".into()];
+    let args = ArgParser::new().parse(&raw).unwrap();
+    assert!(
+        matches!(&args[0], Arg::TaskMarkers(m) if m == &vec![TaskMarker::Done, TaskMarker::Atomic])
+    );
+    assert!(matches!(args[1], Arg::AtSymbol));
+
+    let err = ArgParser::new().parse(&["!later".to_string()]).unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.contains("Invalid task marker")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn field_sets_factory_collects_assignments_and_rejoins_quotes() {
+    let raw: TokenList = vec![
+        "hours=5".into(),
+        "name=\"Weekly".into(),
+        "report\"".into(),
+        "card=+C2".into(),
+    ];
+    let args = ArgParser::new().parse(&raw).unwrap();
+    assert_eq!(args.len(), 1);
+    let Arg::FieldSets(sets) = &args[0] else {
+        panic!("expected field sets, got {:?}", args[0]);
+    };
+    let pairs: Vec<(&str, &str)> = sets
+        .iter()
+        .map(|s| (s.field.as_str(), s.value.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![("hours", "5"), ("name", "Weekly report"), ("card", "+C2")]
+    );
+    assert_eq!(
+        args[0].to_string(),
+        "hours=5 name=\"Weekly report\" card=+C2"
+    );
+
+    assert!(
+        ArgParser::new()
+            .parse(&["name=\"open".to_string()])
+            .is_err()
+    );
+    assert!(ArgParser::new().parse(&["hours=".to_string()]).is_err());
+}
+
 #[test]
 fn factories_parse_single_and_multi_token_args() {
     let mut ts = TokenStream::new(&vec!["\"Hello".into(), "World\"".into()]);
@@ -78,6 +162,16 @@ fn factories_parse_single_and_multi_token_args() {
     assert!(matches!(arg, Arg::Int(42)));
 }
 
+#[test]
+fn hours_with_a_fraction_parse_apart_from_integers() {
+    let args = ArgParser::new().parse(&["4.5".into(), "4".into()]).unwrap();
+    assert!(matches!(args[0], Arg::Hours(h) if h == 4.5));
+    assert!(matches!(args[1], Arg::Int(4)));
+    for value in ["4.", ".5", "4.5.1", "4,5"] {
+        assert!(!HoursArg::accepts(value), "{value}");
+    }
+}
+
 #[test]
 fn date_and_time_args_parse_valid_strings() {
     match DateArg::new("2025-01-05").unwrap() {
@@ -413,8 +507,10 @@ fn extract_at_panics_on_incorrect_variant() {
 
 // ---------- arg_emitter.rs ----------
 
-fn assert_arg_strings(args: &[Arg], expected: &[&str]) {
-    let rendered: TokenList = args.iter().map(|a| a.to_string()).collect();
+/// Every emitted entity ends with its saved uid.
+fn assert_arg_strings(args: &[Arg], uid: &Uid, expected: &[&str]) {
+    let mut rendered: TokenList = args.iter().map(|a| a.to_string()).collect();
+    assert_eq!(rendered.pop(), Some(format!("uid={uid}")));
     assert_eq!(rendered, expected);
 }
 
@@ -425,7 +521,7 @@ fn card_arg_emitter_emits_name_and_color() {
     let ctx = NoRefEmitContext;
 
     let args = emitter.with_entity(&card, &ctx).unwrap();
-    assert_arg_strings(&args, &["\"hello\"", "RED"]);
+    assert_arg_strings(&args, &card.uid, &["\"hello\"", "RED"]);
 }
 
 #[test]
@@ -441,10 +537,15 @@ fn task_arg_emitter_maps_card_and_orders_args() {
     card_map.insert(7, 2);
     let ctx = SaveEmitContext {
         id_lookup: &card_map,
+        template_lookup: &IdLookup::new(),
     };
 
     let args = emitter.with_entity(&task, &ctx).unwrap();
-    assert_arg_strings(&args, &["\"work\"", "4", "+C2", "@", "2025-02-01"]);
+    assert_arg_strings(
+        &args,
+        &task.uid,
+        &["\"work\"", "3.5", "+C2", "@", "2025-02-01"],
+    );
 }
 
 #[test]
@@ -457,7 +558,10 @@ fn task_arg_emitter_errors_when_card_unmapped() {
         Date::try_from_str("2025-02-01").unwrap(),
     );
     let map = IdLookup::new();
-    let ctx = SaveEmitContext { id_lookup: &map };
+    let ctx = SaveEmitContext {
+        id_lookup: &map,
+        template_lookup: &IdLookup::new(),
+    };
 
     let err = emitter.with_entity(&task, &ctx).unwrap_err();
     match err {
@@ -466,6 +570,45 @@ fn task_arg_emitter_errors_when_card_unmapped() {
     }
 }
 
+#[test]
+fn task_arg_emitter_emits_template_and_markers() {
+    let emitter = TaskArgEmitter::new();
+    let mut task = Task::new(
+        "report",
+        2.0,
+        None,
+        Date::try_from_str("2099-01-03").unwrap(),
+    );
+    task.origin = Some(TemplateOrigin {
+        template_id: 5,
+        occurrence: NaiveDate::from_ymd_opt(2099, 1, 2).unwrap(),
+    });
+    task.complete();
+    task.atomic = true;
+    task.estimate = Some(Estimate::try_from_str("~1/2/4").unwrap());
+    let mut template_map = IdLookup::new();
+    template_map.insert(5, 1);
+    let ctx = SaveEmitContext {
+        id_lookup: &IdLookup::new(),
+        template_lookup: &template_map,
+    };
+
+    let args = emitter.with_entity(&task, &ctx).unwrap();
+    assert_arg_strings(
+        &args,
+        &task.uid,
+        &[
+            "\"report\"",
+            "2",
+            "~1/2/4",
+            "+T1:2099-01-02",
+            "!atomic !done",
+            "@",
+            "2099-01-03",
+        ],
+    );
+}
+
 #[test]
 fn event_arg_emitter_emits_days_when_present() {
     let emitter = EventArgEmitter::new();
@@ -478,11 +621,15 @@ fn event_arg_emitter_emits_days_when_present() {
     );
     let mut map = IdLookup::new();
     map.insert(3, 1);
-    let ctx = SaveEmitContext { id_lookup: &map };
+    let ctx = SaveEmitContext {
+        id_lookup: &map,
+        template_lookup: &IdLookup::new(),
+    };
 
     let args = emitter.with_entity(&event, &ctx).unwrap();
     assert_arg_strings(
         &args,
+        &event.uid,
         &[
             "True",
             "\"party\"",
@@ -505,10 +652,17 @@ fn event_arg_emitter_omits_days_when_empty() {
         TimeRange::try_from_str("1PM-2PM").unwrap(),
     );
     let map = IdLookup::new();
-    let ctx = SaveEmitContext { id_lookup: &map };
+    let ctx = SaveEmitContext {
+        id_lookup: &map,
+        template_lookup: &IdLookup::new(),
+    };
 
     let args = emitter.with_entity(&event, &ctx).unwrap();
-    assert_arg_strings(&args, &["False", "\"solo\"", "@", "1:00PM-2:00PM"]);
+    assert_arg_strings(
+        &args,
+        &event.uid,
+        &["False", "\"solo\"", "@", "1:00PM-2:00PM"],
+    );
 }
 
 // ---------- arg_parse_strategy.rs ----------
@@ -551,6 +705,47 @@ fn command_arg_parser_joins_multiple_tokens_for_manual() {
     }
 }
 
+#[test]
+fn command_arg_parser_reads_bulk_selections() {
+    let parser = CommandArgParser::new();
+    let toks = |line: &str| -> TokenList { line.split_whitespace().map(String::from).collect() };
+
+    let args = parser.parse("del", &toks("task 1,4,7-12 --yes")).unwrap();
+    assert!(matches!(args[0], Arg::EntityType(EntityType::Task)));
+    assert!(
+        matches!(&args[1], Arg::Selection(Selection::Ids(spans)) if spans == &vec![(1, 1), (4, 4), (7, 12)])
+    );
+    assert!(matches!(args[2], Arg::Flag(Flag::Yes)));
+
+    let args = parser
+        .parse(
+            "mod",
+            &toks("task where name~\"weekly report\" due<today set hours=3"),
+        )
+        .unwrap();
+    match &args[1] {
+        Arg::Selection(Selection::Where(exprs)) => {
+            assert_eq!(
+                exprs,
+                &vec![
+                    "name~\"weekly report\"".to_string(),
+                    "due<today".to_string()
+                ]
+            )
+        }
+        other => panic!("expected where selection, got {other:?}"),
+    }
+    assert!(matches!(&args[2], Arg::FieldSets(sets) if sets.len() == 1));
+
+    let args = parser
+        .parse("del", &toks("card where color=red --cascade"))
+        .unwrap();
+    assert!(matches!(args[2], Arg::Flag(Flag::Cascade)));
+    let args = parser.parse("del", &toks("task 3")).unwrap();
+    assert!(matches!(args[1], Arg::Int(3)));
+    assert!(parser.parse("del", &toks("task where")).is_err());
+}
+
 #[test]
 fn man_arg_parser_returns_empty_when_no_args() {
     let parser = ManArgParser;
@@ -589,3 +784,122 @@ fn man_arg_parser_preserves_joined_phrase() {
         other => panic!("expected single name arg, got {other:?}"),
     }
 }
+
+#[test]
+fn command_arg_parser_refuses_uid_assignments() {
+    let parser = CommandArgParser::new();
+    let toks = |line: &str| -> TokenList { line.split_whitespace().map(String::from).collect() };
+
+    for (command, line) in [
+        (
+            "task",
+            "\"Essay\" 2 @ 2099-01-01 uid=01J9Z8Q4C6X5W2R7T3M1N0P8KD",
+        ),
+        ("mod", "task 1 uid=01J9Z8Q4C6X5W2R7T3M1N0P8KD"),
+    ] {
+        let err = parser.parse(command, &toks(line)).unwrap_err();
+        assert!(err.to_string().contains("uid cannot be given"), "{err}");
+    }
+    assert!(
+        parser
+            .parse(
+                "mod",
+                &toks("task where uid=01J9Z8Q4C6X5W2R7T3M1N0P8KD set hours=3")
+            )
+            .is_ok()
+    );
+}
+
+#[test]
+fn command_arg_parser_keeps_revisions_as_names() {
+    let parser = CommandArgParser::new();
+    let raw = vec!["HEAD~1".to_string(), "1234abcd".to_string()];
+    let args = parser.parse("diff", &raw).unwrap();
+    match &args[..] {
+        [Arg::Name(old), Arg::Name(new)] => {
+            assert_eq!(old, "HEAD~1");
+            assert_eq!(new, "1234abcd");
+        }
+        other => panic!("expected two name args, got {other:?}"),
+    }
+}
+
+#[test]
+fn command_arg_parser_splits_find_filters_and_rejoins_quotes() {
+    let parser = CommandArgParser::new();
+    let raw: Vec<String> = ["task", "due<fri", "name~\"weekly", "report\"", "!done"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let args = parser.parse("find", &raw).unwrap();
+    match &args[..] {
+        [
+            Arg::EntityType(EntityType::Task),
+            Arg::Name(due),
+            Arg::Name(name),
+            Arg::Name(done),
+        ] => {
+            assert_eq!(due, "due<fri");
+            assert_eq!(name, "name~\"weekly report\"");
+            assert_eq!(done, "!done");
+        }
+        other => panic!("expected entity type and three filters, got {other:?}"),
+    }
+
+    let unclosed = vec!["task".to_string(), "name~\"open".to_string()];
+    assert!(parser.parse("find", &unclosed).is_err());
+}
+
+#[test]
+fn parser_pairs_table_flags_with_their_column_lists() {
+    let raw: Vec<String> = ["--sort", "due,-Hours", "--columns", "id,name"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let args = ArgParser::new().parse(&raw).unwrap();
+    let sort = TableOption::Sort(vec![
+        SortKey {
+            column: "due".into(),
+            descending: false,
+        },
+        SortKey {
+            column: "hours".into(),
+            descending: true,
+        },
+    ]);
+    let columns = TableOption::Columns(vec!["id".into(), "name".into()]);
+    match &args[..] {
+        [Arg::TableOption(s), Arg::TableOption(c)] => {
+            assert_eq!(s, &sort);
+            assert_eq!(c, &columns);
+        }
+        other => panic!("expected two table options, got {other:?}"),
+    }
+    assert_eq!(args[0].to_tokens(), vec!["--sort", "due,-hours"]);
+
+    assert!(ArgParser::new().parse(&["--sort".to_string()]).is_err());
+    assert!(
+        ArgParser::new()
+            .parse(&["--columns".to_string(), "id,,name".to_string()])
+            .is_err()
+    );
+}
+
+#[test]
+fn field_sets_factory_reads_meta_keys_and_saves_one_token_per_field() {
+    let raw: TokenList = vec![
+        "meta.course-code=CS101".into(),
+        "note=\"Read".into(),
+        "ch.".into(),
+        "4\"".into(),
+    ];
+    let args = ArgParser::new().parse(&raw).unwrap();
+    assert_eq!(args.len(), 1);
+    assert_eq!(
+        args[0].to_tokens(),
+        vec!["meta.course-code=CS101", "note=\"Read ch. 4\""]
+    );
+
+    assert!(!FieldSetsFactory.can_start("meta.=x"));
+    assert!(!FieldSetsFactory.can_start("meta.a b=x"));
+}
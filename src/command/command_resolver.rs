@@ -1,7 +1,9 @@
 use crate::arg::args::Arg;
 use crate::command::commands::{
-    Command, CommandDyn, ConfigCommand, EntityCommand, LogCommand, ManCommand, ReadCommand,
-    SaveCommand, ScheduleCommand,
+    BranchCommand, CheckoutCommand, Command, CommandDyn, CommitCommand, ConfigCommand,
+    ConvertCommand, DiffCommand, EntityCommand, FindCommand, ForecastCommand, HistoryCommand,
+    ImportCommand, ItemCommand, LogCommand, ManCommand, MergeCommand, ReadCommand, RedoCommand,
+    SaveCommand, ScheduleCommand, ShowCommand, UndoCommand,
 };
 use crate::core::types::{EntityActionType, EntityType, GlobalCommand, TypeHelpCommand};
 use crate::errors::{Error, Result};
@@ -17,7 +19,10 @@ pub struct EntityActionResolver;
 impl CommandResolver for EntityActionResolver {
     fn can_resolve(&self, command: &str) -> bool {
         if let Ok(action) = EntityActionType::try_from(command) {
-            matches!(action, EntityActionType::Modify | EntityActionType::Delete)
+            matches!(
+                action,
+                EntityActionType::Modify | EntityActionType::Delete | EntityActionType::Complete
+            )
         } else {
             false
         }
@@ -77,11 +82,25 @@ impl CommandResolver for GlobalResolver {
         let command_type = GlobalCommand::try_from(command)?;
         match command_type {
             GlobalCommand::Schedule => Ok(Box::new(ScheduleCommand::new(args))),
+            GlobalCommand::Forecast => Ok(Box::new(ForecastCommand::new(args))),
             GlobalCommand::Config => Ok(Box::new(ConfigCommand::new(args))),
             GlobalCommand::Log => Ok(Box::new(LogCommand::new(args))),
             GlobalCommand::Save => Ok(Box::new(SaveCommand::new(args))),
             GlobalCommand::Read => Ok(Box::new(ReadCommand::new(args))),
+            GlobalCommand::Convert => Ok(Box::new(ConvertCommand::new(args))),
+            GlobalCommand::Import => Ok(Box::new(ImportCommand::new(args))),
             GlobalCommand::Man => Ok(Box::new(ManCommand::new(args))),
+            GlobalCommand::Undo => Ok(Box::new(UndoCommand::new(args))),
+            GlobalCommand::Redo => Ok(Box::new(RedoCommand::new(args))),
+            GlobalCommand::Commit => Ok(Box::new(CommitCommand::new(args))),
+            GlobalCommand::History => Ok(Box::new(HistoryCommand::new(args))),
+            GlobalCommand::Checkout => Ok(Box::new(CheckoutCommand::new(args))),
+            GlobalCommand::Diff => Ok(Box::new(DiffCommand::new(args))),
+            GlobalCommand::Branch => Ok(Box::new(BranchCommand::new(args))),
+            GlobalCommand::Merge => Ok(Box::new(MergeCommand::new(args))),
+            GlobalCommand::Find => Ok(Box::new(FindCommand::new(args))),
+            GlobalCommand::Show => Ok(Box::new(ShowCommand::new(args))),
+            GlobalCommand::Item => Ok(Box::new(ItemCommand::new(args))),
         }
     }
 }
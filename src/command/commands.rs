@@ -1,21 +1,45 @@
 use crate::arg::arg_extractor::extract_at;
-use crate::arg::args::Arg;
 use crate::arg::args::IntArg;
+use crate::arg::args::{Arg, FieldSet};
 use crate::command::entity_spec::{
-    card::CardSpec, core::EntitySpec, event::EventSpec, task::TaskSpec,
+    card::CardSpec, core::EntitySpec, event::EventSpec, task::TaskSpec, template::TemplateSpec,
 };
 use crate::command::manual::ManualCatalog;
-use crate::command::policies::flag_policy::{FlagDecision, FlagPolicy, HelpAtIdx};
+use crate::command::policies::flag_policy::{
+    FlagDecision, FlagPolicy, FlagRule, HelpAtIdx, TableOptionsFor,
+};
+use crate::config::PlanSettings;
 use crate::core::context::AppContext;
-use crate::core::persist::{load_state, save_state};
-use crate::core::types::{EntityActionType, EntityType};
+use crate::core::filter::{Filter, Selection};
+use crate::core::history::{self, HistoryStep};
+use crate::core::import::import_save_file;
+use crate::core::integrity;
+use crate::core::journal::Journaling;
+use crate::core::merge;
+use crate::core::migrations::SAVE_VERSION;
+use crate::core::models::{Card, ChecklistItem, Event, Task, TaskTemplate};
+use crate::core::persist::{
+    SaveFile, apply_save_file, build_save_file, convert_save_file, load_save_file, save_state_as,
+};
+use crate::core::transaction::Transaction;
+use crate::core::types::{
+    ChecklistAction, Date, EntityActionType, EntityType, Flag, TaskConflictPolicy,
+};
+use crate::core::versions::{Head, VersionStore, diff_saves};
+use crate::core::view::TableView;
+use crate::core::workspace;
 use crate::errors::Error::Parse;
 use crate::errors::Result;
+use crate::extensions::enums::valid_csv;
 use crate::logging::LogTarget;
 use crate::prompter::flows::config_edit::ConfigEditFlow;
+use crate::prompter::flows::confirm::ConfirmFlow;
+use crate::prompter::flows::merge_conflicts::MergeConflictFlow;
 use crate::prompter::prompter::Prompter;
-use crate::scheduler::ScheduleManager;
+use crate::scheduler::{DEFAULT_FORECAST_RUNS, Forecaster, ScheduleManager};
 use crate::ui::display_manager::DisplayManager;
+use chrono::Local;
+use std::path::{Path, PathBuf};
 
 pub struct CommandCore<'a> {
     pub args: &'a [Arg],
@@ -39,6 +63,26 @@ pub trait Command<'a>: sealed::Sealed<'a> {
     fn usage(&self) -> String;
     fn perform(&self, ctx: &mut AppContext) -> Result<()>;
 
+    /// Whether a successful run should be recorded for `undo`.
+    fn changes_state(&self) -> bool {
+        false
+    }
+
+    /// The line to journal for a run of `line`, worked out against `ctx` before the run so that
+    /// replaying it later acts on the same entities and dates. Defaults to `line` as typed.
+    fn journal_line(&self, _ctx: &AppContext, line: &str) -> String {
+        line.to_string()
+    }
+
+    /// How a successful run is written to the crash-recovery journal.
+    fn journaling(&self) -> Journaling {
+        if self.changes_state() {
+            Journaling::Line
+        } else {
+            Journaling::Skip
+        }
+    }
+
     fn execute(&self, ctx: &mut AppContext) -> Result<()> {
         let core = self.core(); // available because Sealed is a supertrait (but not public)
         match core.flag_policy.evaluate(core.args) {
@@ -68,23 +112,34 @@ impl<'a> EntityCommand<'a> {
     pub fn new(action: EntityActionType, entity_type: EntityType, args: &'a [Arg]) -> Self {
         let help_idx = match action {
             EntityActionType::Add => 0,
-            EntityActionType::Modify | EntityActionType::Delete => 1,
+            EntityActionType::Modify | EntityActionType::Delete | EntityActionType::Complete => 1,
         };
-        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(help_idx))]);
+        let mut rules: Vec<Box<dyn FlagRule>> = vec![Box::new(HelpAtIdx(help_idx))];
+        if action == EntityActionType::Add {
+            rules.push(Box::new(TableOptionsFor(entity_type)));
+        }
         Self {
-            core: CommandCore::new(args, policy),
+            core: CommandCore::new(args, FlagPolicy::new(rules)),
             action,
             entity_type,
         }
     }
 
     fn handle_add(&self, ctx: &mut AppContext) -> Result<()> {
-        if self.core.args.is_empty() {
+        let listing = self
+            .core
+            .args
+            .iter()
+            .all(|a| matches!(a, Arg::TableOption(_)));
+        if listing {
+            let view = TableView::resolve(self.entity_type, &ctx.config, self.core.args)?;
             DisplayManager::new().display_entities_for(
                 self.entity_type,
                 &ctx.tasks,
                 &ctx.events,
                 &ctx.cards,
+                &ctx.templates,
+                &view,
             );
             return Ok(());
         }
@@ -113,38 +168,50 @@ impl<'a> EntityCommand<'a> {
                     LogTarget::ConsoleAndFile,
                 );
             }
+            EntityType::Template => {
+                let template = TemplateSpec::new().create(ctx, self.core.args)?;
+                let stored = ctx.templates.insert(template);
+                ctx.logger.info(
+                    format!("Added template with id {}: {}", stored.id, stored),
+                    LogTarget::ConsoleAndFile,
+                );
+            }
         }
         Ok(())
     }
 
-    fn handle_modify(&self, ctx: &mut AppContext) -> Result<()> {
-        let id = extract_at::<IntArg>(self.core.args, 1);
+    fn handle_modify(&self, ctx: &mut AppContext, args: &[Arg]) -> Result<()> {
+        let id = extract_at::<IntArg>(args, 1);
         let msg = match self.entity_type {
             EntityType::Task => {
-                let updated = TaskSpec::new().modify(ctx, self.core.args, id)?;
+                let updated = TaskSpec::new().modify(ctx, args, id)?;
                 format!("Modified task with id {}: {}", id, updated)
             }
             EntityType::Event => {
-                let updated = EventSpec::new().modify(ctx, self.core.args, id)?;
+                let updated = EventSpec::new().modify(ctx, args, id)?;
                 format!("Modified event with id {}: {}", id, updated)
             }
             EntityType::Card => {
-                let updated = CardSpec::new().modify(ctx, self.core.args, id)?;
+                let updated = CardSpec::new().modify(ctx, args, id)?;
                 format!("Modified card with id {}: {}", id, updated)
             }
+            EntityType::Template => {
+                let updated = TemplateSpec::new().modify(ctx, args, id)?;
+                format!("Modified template with id {}: {}", id, updated)
+            }
         };
 
         ctx.logger.info(msg, LogTarget::ConsoleAndFile);
         Ok(())
     }
 
-    fn handle_delete(&self, ctx: &mut AppContext) -> Result<()> {
+    fn handle_delete(&self, ctx: &mut AppContext, args: &[Arg]) -> Result<()> {
         match self.entity_type {
-            EntityType::Task => match TaskSpec::new().can_delete(ctx, self.core.args) {
+            EntityType::Task => match TaskSpec::new().can_delete(ctx, args) {
                 Err(Parse(msg)) => return Err(Parse(msg.into())),
                 Ok(_) => {
-                    let id = extract_at::<IntArg>(self.core.args, 1);
-                    ctx.tasks.delete(id)?;
+                    let id = extract_at::<IntArg>(args, 1);
+                    integrity::delete_task(ctx, id)?;
                     ctx.logger.info(
                         format!("Deleted task with id {}.", id),
                         LogTarget::ConsoleAndFile,
@@ -152,10 +219,10 @@ impl<'a> EntityCommand<'a> {
                 }
                 _ => {}
             },
-            EntityType::Event => match EventSpec::new().can_delete(ctx, self.core.args) {
+            EntityType::Event => match EventSpec::new().can_delete(ctx, args) {
                 Err(Parse(msg)) => return Err(Parse(msg.into())),
                 Ok(_) => {
-                    let id = extract_at::<IntArg>(self.core.args, 1);
+                    let id = extract_at::<IntArg>(args, 1);
                     ctx.events.delete(id)?;
                     ctx.logger.info(
                         format!("Deleted event with id {}.", id),
@@ -164,13 +231,32 @@ impl<'a> EntityCommand<'a> {
                 }
                 _ => {}
             },
-            EntityType::Card => match CardSpec::new().can_delete(ctx, self.core.args) {
+            EntityType::Card => match CardSpec::new().can_delete(ctx, args) {
                 Err(Parse(msg)) => return Err(Parse(msg.into())),
                 Ok(_) => {
-                    let id = extract_at::<IntArg>(self.core.args, 1);
-                    ctx.cards.delete(id)?;
+                    let id = extract_at::<IntArg>(args, 1);
+                    let policy = CardSpec::new()
+                        .delete_policy(args)
+                        .unwrap_or(*ctx.config.card_delete_policy());
+                    let changes = integrity::delete_card(ctx, id, policy)?;
+                    let mut lines = vec![format!("Deleted card with id {}.", id)];
+                    lines.extend(changes.iter().map(|change| format!("  {change}")));
+                    ctx.logger.info(lines.join("\n"), LogTarget::ConsoleAndFile);
+                }
+                _ => {}
+            },
+            EntityType::Template => match TemplateSpec::new().can_delete(ctx, args) {
+                Err(Parse(msg)) => return Err(Parse(msg)),
+                Ok(_) => {
+                    let id = extract_at::<IntArg>(args, 1);
+                    ctx.templates.delete(id)?;
+                    // Generated tasks survive their template as ordinary one-off tasks.
+                    ctx.tasks
+                        .query_mut()
+                        .r#where(|t| t.origin.is_some_and(|o| o.template_id == id))
+                        .for_each_mut(|t| t.origin = None);
                     ctx.logger.info(
-                        format!("Deleted card with id {}.", id),
+                        format!("Deleted template with id {}.", id),
                         LogTarget::ConsoleAndFile,
                     );
                 }
@@ -179,6 +265,121 @@ impl<'a> EntityCommand<'a> {
         }
         Ok(())
     }
+
+    fn check_completable(&self) -> Result<()> {
+        if self.entity_type != EntityType::Task {
+            return Err(Parse(format!(
+                "Only tasks can be marked done, got {}.",
+                self.entity_type
+            )));
+        }
+        Ok(())
+    }
+
+    fn handle_complete(&self, ctx: &mut AppContext, args: &[Arg]) -> Result<()> {
+        self.check_completable()?;
+        let spec = TaskSpec::new();
+        spec.assert_matches_pattern(ctx, args, EntityActionType::Complete)?;
+        let id = extract_at::<IntArg>(args, 1);
+        let completed = spec.get_mut(ctx, id)?.complete();
+        let msg = format!("Completed task with id {}: {}", id, completed);
+        ctx.logger.info(msg, LogTarget::ConsoleAndFile);
+        Ok(())
+    }
+
+    fn handle_one(&self, ctx: &mut AppContext, args: &[Arg]) -> Result<()> {
+        match self.action {
+            EntityActionType::Add => self.handle_add(ctx),
+            EntityActionType::Modify => self.handle_modify(ctx, args),
+            EntityActionType::Delete => self.handle_delete(ctx, args),
+            EntityActionType::Complete => self.handle_complete(ctx, args),
+        }
+    }
+
+    fn check_pattern(&self, ctx: &AppContext, args: &[Arg]) -> Result<()> {
+        let action = self.action;
+        match self.entity_type {
+            EntityType::Task => TaskSpec::new()
+                .assert_matches_pattern(ctx, args, action)
+                .map(|_| ()),
+            EntityType::Event => EventSpec::new()
+                .assert_matches_pattern(ctx, args, action)
+                .map(|_| ()),
+            EntityType::Card => CardSpec::new()
+                .assert_matches_pattern(ctx, args, action)
+                .map(|_| ()),
+            EntityType::Template => TemplateSpec::new()
+                .assert_matches_pattern(ctx, args, action)
+                .map(|_| ()),
+        }
+    }
+
+    fn select(&self, ctx: &AppContext, selection: &Selection) -> Result<Vec<i32>> {
+        let today = Local::now().date_naive();
+        match self.entity_type {
+            EntityType::Task => selection.select(&ctx.tasks, today),
+            EntityType::Event => selection.select(&ctx.events, today),
+            EntityType::Card => selection.select(&ctx.cards, today),
+            EntityType::Template => selection.select(&ctx.templates, today),
+        }
+    }
+
+    /// Applies the command to every selected entity inside one transaction, after confirmation
+    /// unless `--yes` was given. A failure on any entity leaves all of them unchanged.
+    fn handle_bulk(
+        &self,
+        ctx: &mut AppContext,
+        selection: &Selection,
+        args: &[Arg],
+        confirmed: bool,
+    ) -> Result<()> {
+        if self.action == EntityActionType::Complete {
+            self.check_completable()?;
+        }
+        let ids = self.select(ctx, selection)?;
+        let kind = self.entity_type;
+        if ids.is_empty() {
+            ctx.logger
+                .info(format!("No {kind} matched."), LogTarget::ConsoleOnly);
+            return Ok(());
+        }
+        let args_for = |id: i32| -> Vec<Arg> {
+            args.iter()
+                .map(|a| match a {
+                    Arg::Selection(_) => Arg::Int(id),
+                    other => other.clone(),
+                })
+                .collect()
+        };
+        self.check_pattern(ctx, &args_for(ids[0]))?;
+
+        let (verb, done) = match self.action {
+            EntityActionType::Modify => ("Modify", "Modified"),
+            EntityActionType::Delete => ("Delete", "Deleted"),
+            EntityActionType::Complete => ("Mark done", "Completed"),
+            EntityActionType::Add => unreachable!("add never takes a selection"),
+        };
+        let ids_text = Selection::of_ids(&ids);
+        if !confirmed {
+            let mut confirmed = false;
+            let question = format!("{verb} {} {kind}(s) with ids {ids_text}?", ids.len());
+            Prompter::new().run(ConfirmFlow::new(question, &mut confirmed), false)?;
+            if !confirmed {
+                ctx.logger.info("Cancelled.", LogTarget::ConsoleOnly);
+                return Ok(());
+            }
+        }
+
+        Transaction::new().run(ctx, false, |ctx| {
+            ids.iter()
+                .try_for_each(|id| self.handle_one(ctx, &args_for(*id)))
+        })?;
+        ctx.logger.info(
+            format!("{done} {} {kind}(s) with ids {ids_text}.", ids.len()),
+            LogTarget::ConsoleAndFile,
+        );
+        Ok(())
+    }
 }
 
 impl<'a> sealed::Sealed<'a> for EntityCommand<'a> {
@@ -224,14 +425,79 @@ impl<'a> Command<'a> for EntityCommand<'a> {
                     &spec.arg_schema().patterns_for(self.action),
                 )
             }
+            EntityType::Template => {
+                let spec = TemplateSpec::new();
+                join(
+                    self.action,
+                    self.entity_type,
+                    &spec.arg_schema().patterns_for(self.action),
+                )
+            }
+        }
+    }
+
+    fn changes_state(&self) -> bool {
+        true
+    }
+
+    /// The arguments as parsed, which already hold absolute dates, with a selection replaced by
+    /// the ids it picks now and confirmed with `--yes`, and a relative `due=` made absolute.
+    fn journal_line(&self, ctx: &AppContext, line: &str) -> String {
+        let name = line.split_whitespace().next().unwrap_or_default();
+        let confirmed = self
+            .core
+            .args
+            .iter()
+            .any(|a| matches!(a, Arg::Flag(Flag::Yes)));
+        let mut tokens = vec![name.to_string()];
+        for arg in self.core.args {
+            match arg {
+                Arg::Selection(selection) => {
+                    let Ok(ids) = self.select(ctx, selection) else {
+                        return line.to_string();
+                    };
+                    tokens.push(Selection::of_ids(&ids).to_string());
+                    if !confirmed {
+                        tokens.extend(Arg::Flag(Flag::Yes).to_tokens());
+                    }
+                }
+                Arg::FieldSets(sets) => tokens.extend(sets.iter().map(|set| {
+                    match Date::try_from_str(&set.value) {
+                        Ok(due) if self.entity_type == EntityType::Task && set.field == "due" => {
+                            FieldSet {
+                                value: due.to_string(),
+                                ..set.clone()
+                            }
+                            .to_string()
+                        }
+                        _ => set.to_string(),
+                    }
+                })),
+                other => tokens.extend(other.to_tokens()),
+            }
         }
+        tokens.join(" ")
     }
 
     fn perform(&self, ctx: &mut AppContext) -> Result<()> {
-        match self.action {
-            EntityActionType::Add => self.handle_add(ctx),
-            EntityActionType::Modify => self.handle_modify(ctx),
-            EntityActionType::Delete => self.handle_delete(ctx),
+        if self.action == EntityActionType::Add {
+            return self.handle_add(ctx);
+        }
+        let confirmed = self
+            .core
+            .args
+            .iter()
+            .any(|a| matches!(a, Arg::Flag(Flag::Yes)));
+        let args: Vec<Arg> = self
+            .core
+            .args
+            .iter()
+            .filter(|a| !matches!(a, Arg::Flag(Flag::Yes)))
+            .cloned()
+            .collect();
+        match args.get(1) {
+            Some(Arg::Selection(selection)) => self.handle_bulk(ctx, selection, &args, confirmed),
+            _ => self.handle_one(ctx, &args),
         }
     }
 }
@@ -265,6 +531,13 @@ impl<'a> Command<'a> for ConfigCommand<'a> {
     fn usage(&self) -> String {
         "config   # View and edit configuration".into()
     }
+    fn changes_state(&self) -> bool {
+        true
+    }
+    /// Config edits are written to the config file as they are made.
+    fn journaling(&self) -> Journaling {
+        Journaling::Skip
+    }
     fn perform(&self, ctx: &mut AppContext) -> Result<()> {
         self.edit(ctx)
     }
@@ -293,6 +566,14 @@ impl<'a> Command<'a> for ScheduleCommand<'a> {
     fn usage(&self) -> String {
         "schedule      # Schedule tasks".into()
     }
+    /// Scheduling fills in slices and generates template tasks, which undo has to keep in step.
+    fn changes_state(&self) -> bool {
+        true
+    }
+    /// The result depends on the day it ran, so it is replayed as the plan it produced.
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
     fn perform(&self, ctx: &mut AppContext) -> Result<()> {
         let mut sched = ScheduleManager::new(ctx);
         sched.compute_schedule()?;
@@ -300,6 +581,52 @@ impl<'a> Command<'a> for ScheduleCommand<'a> {
     }
 }
 
+pub struct ForecastCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> ForecastCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+
+    fn runs_arg(&self) -> Result<usize> {
+        match self.core.args {
+            [] => Ok(DEFAULT_FORECAST_RUNS),
+            [Arg::Int(n)] if *n > 0 => Ok(*n as usize),
+            _ => Err(Parse(format!(
+                "Expected a positive number of runs.\n{}",
+                self.usage()
+            ))),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for ForecastCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for ForecastCommand<'a> {
+    fn usage(&self) -> String {
+        "forecast [runs]  # Simulate schedules to estimate on-time probability per task".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let runs = self.runs_arg()?;
+        let forecasts = Forecaster::new(runs).run(ctx)?;
+        DisplayManager::new().display_forecast(&forecasts, &ctx.cards);
+        ctx.logger.info(
+            format!("Forecast complete ({} runs).", runs),
+            LogTarget::ConsoleAndFile,
+        );
+        Ok(())
+    }
+}
+
 pub struct LogCommand<'a> {
     core: CommandCore<'a>,
 }
@@ -410,23 +737,55 @@ impl<'a> sealed::Sealed<'a> for SaveCommand<'a> {
 
 impl<'a> Command<'a> for SaveCommand<'a> {
     fn usage(&self) -> String {
-        "save <name>   # Save state to schedules/<name>.json".into()
+        "save [name] [--with-config]   # Save state to schedules/<name>.json, or to the workspace"
+            .into()
     }
     fn perform(&self, ctx: &mut AppContext) -> Result<()> {
-        let name = if let Some(Arg::Name(n)) = self.core.args.get(0) {
-            n.clone()
+        let (name, with_config) = match self.core.args {
+            [] => (None, false),
+            [Arg::Name(n)] => (Some(n), false),
+            [Arg::Flag(Flag::WithConfig)] => (None, true),
+            [Arg::Name(n), Arg::Flag(Flag::WithConfig)] => (Some(n), true),
+            _ => {
+                return Err(Parse(
+                    "Expected a file name and --with-config, both optional. Usage: save \"<name>\" [--with-config]"
+                        .into(),
+                ));
+            }
+        };
+        let path = if let Some(n) = name {
+            let mut path = ctx.schedules_dir.join(n);
+            if path.extension().is_none() {
+                path.set_extension("json");
+            }
+            path
+        } else if let Some(path) = workspace::workspace_path(ctx) {
+            path
         } else {
-            return Err(Parse("Expected file name. Usage: save \"<name>\"".into()));
+            return Err(Parse(
+                "Expected file name, or set WORKSPACE to save without one. Usage: save \"<name>\""
+                    .into(),
+            ));
         };
 
-        let mut path = ctx.schedules_dir.join(name);
-        if path.extension().is_none() {
-            path.set_extension("json");
-        }
-
-        let saved = save_state(&ctx.tasks, &ctx.events, &ctx.cards, &path)?;
+        let settings = with_config.then(|| ctx.config.plan_settings());
+        let saved = save_state_as(
+            ctx.config.save_format(),
+            settings.as_ref(),
+            &ctx.tasks,
+            &ctx.events,
+            &ctx.cards,
+            &ctx.templates,
+            &path,
+        )?;
+        workspace::mark_saved(ctx, &saved)?;
+        let with = if with_config {
+            " with plan settings"
+        } else {
+            ""
+        };
         ctx.logger.info(
-            format!("Saved state to {}", saved.display()),
+            format!("Saved state to {}{with}", saved.display()),
             LogTarget::ConsoleOnly,
         );
         Ok(())
@@ -454,29 +813,1010 @@ impl<'a> sealed::Sealed<'a> for ReadCommand<'a> {
 
 impl<'a> Command<'a> for ReadCommand<'a> {
     fn usage(&self) -> String {
-        "read <path>   # Load state from a saved schedule file".into()
+        "read <path> [--preview|--yes]   # Load state from a saved schedule file".into()
+    }
+    fn changes_state(&self) -> bool {
+        !self.has_flag(Flag::Preview)
+    }
+    fn journaling(&self) -> Journaling {
+        if self.has_flag(Flag::Preview) {
+            Journaling::Skip
+        } else {
+            Journaling::Snapshot
+        }
     }
     fn perform(&self, ctx: &mut AppContext) -> Result<()> {
-        let path = if let Some(Arg::Name(n)) = self.core.args.get(0) {
-            let candidate = std::path::PathBuf::from(n.clone());
-            if candidate.is_relative() && candidate.parent().is_none() {
-                ctx.schedules_dir.join(candidate)
-            } else {
-                candidate
-            }
+        const USAGE: &str = "Usage: read \"<path>\" [--preview|--yes]";
+        let path = if let Some(Arg::Name(n)) = self.core.args.first() {
+            save_file_path(ctx, n)
+        } else {
+            return Err(Parse(format!("Expected file path. {USAGE}")));
+        };
+        if let Some(arg) = self
+            .core
+            .args
+            .iter()
+            .skip(1)
+            .find(|arg| !matches!(arg, Arg::Flag(Flag::Preview | Flag::Yes)))
+        {
+            return Err(Parse(format!("Unexpected argument '{arg}'. {USAGE}")));
+        }
+
+        let loaded = load_save_file(&path)?;
+        let upgraded = if loaded.from_version < SAVE_VERSION {
+            format!(" (upgraded from save format {})", loaded.from_version)
         } else {
-            return Err(Parse("Expected file path. Usage: read \"<path>\"".into()));
+            String::new()
+        };
+        let config_changes = match &loaded.config {
+            Some(settings) => ctx.config.plan_settings_changes(settings)?,
+            None => Vec::new(),
+        };
+        if self.has_flag(Flag::Preview) {
+            return preview_save_file(ctx, &path, &loaded.save, &upgraded, &config_changes);
+        }
+
+        apply_save_file(ctx, &loaded.save)?;
+        ctx.logger.info(
+            format!("Loaded state from {}{upgraded}", path.display()),
+            LogTarget::ConsoleOnly,
+        );
+        if let Some(settings) = &loaded.config
+            && !config_changes.is_empty()
+        {
+            self.offer_plan_settings(ctx, settings, &config_changes)?;
+        }
+        Ok(())
+    }
+}
+
+impl ReadCommand<'_> {
+    fn has_flag(&self, flag: Flag) -> bool {
+        self.core
+            .args
+            .iter()
+            .skip(1)
+            .any(|arg| matches!(arg, Arg::Flag(f) if *f == flag))
+    }
+
+    /// Shows how the plan settings saved with the file differ from the config and applies them
+    /// if the user agrees, or right away with `--yes`.
+    fn offer_plan_settings(
+        &self,
+        ctx: &mut AppContext,
+        settings: &PlanSettings,
+        changes: &[(String, String, String)],
+    ) -> Result<()> {
+        ctx.logger.info(
+            format!(
+                "The file was saved with different plan settings:\n{}",
+                config_change_lines(changes).join("\n")
+            ),
+            LogTarget::ConsoleOnly,
+        );
+        let mut confirmed = self.has_flag(Flag::Yes);
+        if !confirmed {
+            Prompter::new().run(
+                ConfirmFlow::new("Apply them to the config?", &mut confirmed),
+                false,
+            )?;
+        }
+        if !confirmed {
+            ctx.logger
+                .info("Kept the current config.", LogTarget::ConsoleOnly);
+            return Ok(());
+        }
+        ctx.config.set_many(settings)?;
+        ctx.logger.info(
+            format!("Applied {} plan setting(s).", changes.len()),
+            LogTarget::ConsoleAndFile,
+        );
+        Ok(())
+    }
+}
+
+/// `KEY: current -> new` for each changed config setting.
+fn config_change_lines(changes: &[(String, String, String)]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|(key, current, new)| format!("  {key}: {current} -> {new}"))
+        .collect()
+}
+
+/// Loads `save` into a scratch context and shows what it holds and what reading it would change,
+/// leaving the session untouched.
+fn preview_save_file(
+    ctx: &AppContext,
+    path: &Path,
+    save: &SaveFile,
+    upgraded: &str,
+    config_changes: &[(String, String, String)],
+) -> Result<()> {
+    let mut scratch = ctx.scratch();
+    apply_save_file(&mut scratch, save)?;
+    ctx.logger.info(
+        format!(
+            "Preview of {}{upgraded}: {} card(s), {} template(s), {} event(s), {} task(s)",
+            path.display(),
+            scratch.cards.len(),
+            scratch.templates.len(),
+            scratch.events.len(),
+            scratch.tasks.len()
+        ),
+        LogTarget::ConsoleOnly,
+    );
+
+    let display = DisplayManager::new();
+    for kind in [
+        EntityType::Card,
+        EntityType::Template,
+        EntityType::Event,
+        EntityType::Task,
+    ] {
+        let view = TableView::resolve(kind, &ctx.config, &[])?;
+        display.display_entities_for(
+            kind,
+            &scratch.tasks,
+            &scratch.events,
+            &scratch.cards,
+            &scratch.templates,
+            &view,
+        );
+    }
+
+    let current = build_save_file(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates)?;
+    let file = build_save_file(
+        &scratch.tasks,
+        &scratch.events,
+        &scratch.cards,
+        &scratch.templates,
+    )?;
+    let lines = diff_saves(&current, &file);
+    let changes = if lines.is_empty() {
+        "Reading it would not change the current plan.".to_string()
+    } else {
+        format!(
+            "Reading it would change the current plan:\n{}",
+            lines.join("\n")
+        )
+    };
+    let mut lines = vec![changes];
+    if !config_changes.is_empty() {
+        lines.push("It was saved with different plan settings:".to_string());
+        lines.extend(config_change_lines(config_changes));
+    }
+    lines.push("Nothing was loaded.".to_string());
+    ctx.logger.info(lines.join("\n"), LogTarget::ConsoleOnly);
+    Ok(())
+}
+
+pub struct ConvertCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> ConvertCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for ConvertCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for ConvertCommand<'a> {
+    fn usage(&self) -> String {
+        "convert <from> <to>   # Write a save file in the other format".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let (Some(Arg::Name(from)), Some(Arg::Name(to))) =
+            (self.core.args.first(), self.core.args.get(1))
+        else {
+            return Err(Parse(
+                "Expected two file paths. Usage: convert \"<from>\" \"<to>\"".into(),
+            ));
+        };
+        let (from, to) = (save_file_path(ctx, from), save_file_path(ctx, to));
+
+        let format = convert_save_file(ctx, &from, &to)?;
+        ctx.logger.info(
+            format!(
+                "Converted {} to {} ({format} format)",
+                from.display(),
+                to.display()
+            ),
+            LogTarget::ConsoleOnly,
+        );
+        Ok(())
+    }
+}
+
+pub struct ImportCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> ImportCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+
+    /// What to do with tasks named like existing ones; `--skip` unless a flag says otherwise.
+    fn conflict_policy(&self) -> Result<TaskConflictPolicy> {
+        let policy = match self.core.args.get(1) {
+            None => return Ok(TaskConflictPolicy::default()),
+            Some(Arg::Flag(flag)) => TaskConflictPolicy::from_flag(*flag),
+            Some(_) => None,
+        };
+        policy.ok_or_else(|| {
+            Parse("Expected a conflict policy: --skip, --rename or --overwrite".into())
+        })
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for ImportCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for ImportCommand<'a> {
+    fn usage(&self) -> String {
+        "import <path> [--skip|--rename|--overwrite]   # Merge a saved schedule file into the plan"
+            .into()
+    }
+    fn changes_state(&self) -> bool {
+        true
+    }
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let Some(Arg::Name(name)) = self.core.args.first() else {
+            return Err(Parse(
+                "Expected file path. Usage: import \"<path>\" [--skip|--rename|--overwrite]".into(),
+            ));
         };
+        let path = save_file_path(ctx, name);
+        let policy = self.conflict_policy()?;
 
-        load_state(ctx, &path)?;
+        let loaded = load_save_file(&path)?;
+        let report = import_save_file(ctx, &loaded.save, policy)?;
         ctx.logger.info(
-            format!("Loaded state from {}", path.display()),
+            format!("{} from {}", report.summary(), path.display()),
             LogTarget::ConsoleOnly,
         );
         Ok(())
     }
 }
 
+/// A path given to `read`, `convert` or `import`; a bare file name is looked up in the schedules
+/// directory.
+fn save_file_path(ctx: &AppContext, name: &str) -> PathBuf {
+    let candidate = PathBuf::from(name);
+    if candidate.parent() == Some(Path::new("")) {
+        ctx.schedules_dir.join(candidate)
+    } else {
+        candidate
+    }
+}
+
+fn report_step(ctx: &AppContext, verb: &str, step: &HistoryStep) {
+    let mut lines = vec![format!("{} '{}'.", verb, step.label)];
+    lines.extend(step.changes.iter().map(|change| format!("  {change}")));
+    ctx.logger.info(lines.join("\n"), LogTarget::ConsoleAndFile);
+}
+
+pub struct UndoCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> UndoCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for UndoCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for UndoCommand<'a> {
+    fn usage(&self) -> String {
+        "undo          # Revert the last change to entities or config".into()
+    }
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let step = history::undo(ctx)?;
+        report_step(ctx, "Undid", &step);
+        Ok(())
+    }
+}
+
+pub struct RedoCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> RedoCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for RedoCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for RedoCommand<'a> {
+    fn usage(&self) -> String {
+        "redo          # Reapply the last undone change".into()
+    }
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let step = history::redo(ctx)?;
+        report_step(ctx, "Redid", &step);
+        Ok(())
+    }
+}
+
+pub struct FindCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> FindCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for FindCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for FindCommand<'a> {
+    fn usage(&self) -> String {
+        format!(
+            "find <entity> [filter...] [--sort <cols>] [--columns <cols>]   # List the entities matching every filter\n{}",
+            Filter::usage()
+        )
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let entity_type = match self.core.args.first() {
+            Some(Arg::EntityType(t)) => *t,
+            _ => {
+                return Err(Parse(format!(
+                    "Expected entity type. Usage: find <entity> [filter...]\nValid entity types: {}",
+                    valid_csv::<EntityType>()
+                )));
+            }
+        };
+        let exprs: Vec<String> = self.core.args[1..]
+            .iter()
+            .filter_map(|a| match a {
+                Arg::Name(expr) => Some(expr.clone()),
+                Arg::TableOption(_) => None,
+                other => Some(other.to_string()),
+            })
+            .collect();
+        let view = TableView::resolve(entity_type, &ctx.config, self.core.args)?;
+
+        let today = Local::now().date_naive();
+        let display = DisplayManager::new();
+        match entity_type {
+            EntityType::Task => {
+                let filter = Filter::parse::<Task>(&exprs, today)?;
+                display.display_tasks(&filter.apply(&ctx.tasks), &ctx.cards, &view);
+            }
+            EntityType::Event => {
+                let filter = Filter::parse::<Event>(&exprs, today)?;
+                display.display_events(&filter.apply(&ctx.events), &ctx.cards, &view);
+            }
+            EntityType::Card => {
+                let filter = Filter::parse::<Card>(&exprs, today)?;
+                display.display_cards(&filter.apply(&ctx.cards), &view);
+            }
+            EntityType::Template => {
+                let filter = Filter::parse::<TaskTemplate>(&exprs, today)?;
+                display.display_templates(&filter.apply(&ctx.templates), &ctx.cards, &view);
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct ShowCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> ShowCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for ShowCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for ShowCommand<'a> {
+    fn usage(&self) -> String {
+        "show <entity> <id>   # Show every field of one entity with its notes, links and metadata"
+            .into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let (entity_type, id) = match self.core.args {
+            [Arg::EntityType(t), Arg::Int(id)] => (*t, *id),
+            _ => {
+                return Err(Parse(format!(
+                    "Expected an entity type and id. Usage: show <entity> <id>\nValid entity types: {}",
+                    valid_csv::<EntityType>()
+                )));
+            }
+        };
+        let display = DisplayManager::new();
+        match entity_type {
+            EntityType::Task => {
+                display.display_details(entity_type, ctx.tasks.get(id)?, &ctx.cards)
+            }
+            EntityType::Event => {
+                display.display_details(entity_type, ctx.events.get(id)?, &ctx.cards)
+            }
+            EntityType::Card => {
+                display.display_details(entity_type, ctx.cards.get(id)?, &ctx.cards)
+            }
+            EntityType::Template => {
+                display.display_details(entity_type, ctx.templates.get(id)?, &ctx.cards)
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct ItemCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> ItemCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+
+    fn action(&self) -> Option<ChecklistAction> {
+        match self.core.args.first() {
+            Some(Arg::Name(word)) => ChecklistAction::try_from(word).ok(),
+            _ => None,
+        }
+    }
+
+    /// The words after the action, or all of them when listing.
+    fn words(&self) -> Result<Vec<&str>> {
+        let skip = usize::from(self.action().is_some());
+        self.core.args[skip..]
+            .iter()
+            .map(|arg| match arg {
+                Arg::Name(word) => Ok(word.as_str()),
+                other => Err(Parse(format!("Unexpected argument: {other}"))),
+            })
+            .collect()
+    }
+}
+
+fn item_usage(action: Option<ChecklistAction>) -> &'static str {
+    match action {
+        None => "item <taskId>",
+        Some(ChecklistAction::Add) => "item add <taskId> <text> [~hours]",
+        Some(ChecklistAction::Check) => "item check <taskId> <n>",
+        Some(ChecklistAction::Uncheck) => "item uncheck <taskId> <n>",
+        Some(ChecklistAction::Move) => "item move <taskId> <n> <position>",
+        Some(ChecklistAction::Del) => "item del <taskId> <n>",
+    }
+}
+
+/// Parses a task id or a 1-based item position.
+fn number_word(word: Option<&&str>, what: &str, usage: &str) -> Result<usize> {
+    let Some(word) = word else {
+        return Err(Parse(format!("Missing {what}. Usage: {usage}")));
+    };
+    match word.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(Parse(format!(
+            "Invalid {what}: '{word}'. Expected a number from 1."
+        ))),
+    }
+}
+
+/// Index of item `n` of the task, counting from 1.
+fn item_index(task: &Task, n: usize) -> Result<usize> {
+    let count = match task.checklist.len() {
+        0 => return Err(Parse(format!("Task {} has no checklist items.", task.id))),
+        1 => "1 item".to_string(),
+        len => format!("{len} items"),
+    };
+    if n > task.checklist.len() {
+        return Err(Parse(format!(
+            "Task {} has no item {} (it has {}).",
+            task.id, n, count
+        )));
+    }
+    Ok(n - 1)
+}
+
+impl<'a> sealed::Sealed<'a> for ItemCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for ItemCommand<'a> {
+    fn usage(&self) -> String {
+        [
+            "item <taskId>   # List the checklist of a task",
+            "item add <taskId> <text> [~hours]   # Add an item; estimates roll up into the task's hours",
+            "item check <taskId> <n>   # Check off item n; its estimate no longer counts as remaining",
+            "item uncheck <taskId> <n>   # Open item n again",
+            "item move <taskId> <n> <position>   # Move item n to another position",
+            "item del <taskId> <n>   # Delete item n",
+        ]
+        .join("\n")
+    }
+    fn changes_state(&self) -> bool {
+        self.action().is_some()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let words = self.words()?;
+        let usage = item_usage(self.action());
+        if self.action().is_none() && words.len() > 1 {
+            ChecklistAction::try_from(words[0])?;
+        }
+        let id = number_word(words.first(), "task id", usage)? as i32;
+        let Some(action) = self.action() else {
+            DisplayManager::new().display_checklist(ctx.tasks.get(id)?);
+            return Ok(());
+        };
+        let task = ctx.tasks.get_mut(id)?;
+        let msg = match action {
+            ChecklistAction::Add => {
+                let text = words[1..].join(" ");
+                if text.is_empty() {
+                    return Err(Parse(format!("Expected item text. Usage: {usage}")));
+                }
+                let item = ChecklistItem::try_from_str(&text)?;
+                let msg = format!(
+                    "Added item {} to task {}: {}",
+                    task.checklist.len() + 1,
+                    id,
+                    item
+                );
+                task.checklist.push(item);
+                task.roll_up_checklist();
+                msg
+            }
+            ChecklistAction::Check | ChecklistAction::Uncheck => {
+                let n = number_word(words.get(1), "item number", usage)?;
+                let ix = item_index(task, n)?;
+                let item = &mut task.checklist[ix];
+                item.done = action == ChecklistAction::Check;
+                let verb = if item.done { "Checked" } else { "Unchecked" };
+                let msg = format!("{} item {} of task {}: {}", verb, n, id, item);
+                if item.hours.is_some() {
+                    task.unschedule();
+                }
+                msg
+            }
+            ChecklistAction::Move => {
+                let n = number_word(words.get(1), "item number", usage)?;
+                let to = number_word(words.get(2), "target position", usage)?;
+                let item = task.checklist.remove(item_index(task, n)?);
+                let to = to.min(task.checklist.len() + 1);
+                task.checklist.insert(to - 1, item);
+                format!("Moved item {} of task {} to position {}.", n, id, to)
+            }
+            ChecklistAction::Del => {
+                let n = number_word(words.get(1), "item number", usage)?;
+                let item = task.checklist.remove(item_index(task, n)?);
+                task.roll_up_checklist();
+                format!("Deleted item {} of task {}: {}", n, id, item)
+            }
+        };
+        ctx.logger.info(msg, LogTarget::ConsoleAndFile);
+        Ok(())
+    }
+}
+
+pub struct CommitCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> CommitCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for CommitCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for CommitCommand<'a> {
+    fn usage(&self) -> String {
+        "commit \"<message>\"   # Record the current plan in the version history".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let message = if let Some(Arg::Name(m)) = self.core.args.first() {
+            m.clone()
+        } else {
+            return Err(Parse(
+                "Expected commit message. Usage: commit \"<message>\"".into(),
+            ));
+        };
+
+        let save = build_save_file(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates)?;
+        let commit = VersionStore::open(&ctx.schedules_dir).commit(&save, &message)?;
+        ctx.logger.info(
+            format!("Committed {} '{}'", commit.short_id(), commit.message),
+            LogTarget::ConsoleAndFile,
+        );
+        Ok(())
+    }
+}
+
+pub struct HistoryCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> HistoryCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for HistoryCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for HistoryCommand<'a> {
+    fn usage(&self) -> String {
+        "history       # List commits from HEAD back to the first one".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let commits = VersionStore::open(&ctx.schedules_dir).log()?;
+        DisplayManager::new().display_history(&commits);
+        Ok(())
+    }
+}
+
+pub struct CheckoutCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> CheckoutCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for CheckoutCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for CheckoutCommand<'a> {
+    fn usage(&self) -> String {
+        "checkout <branch|rev>   # Replace the current plan with a committed one".into()
+    }
+    fn changes_state(&self) -> bool {
+        true
+    }
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let rev = if let Some(Arg::Name(r)) = self.core.args.first() {
+            r.clone()
+        } else {
+            return Err(Parse(
+                "Expected branch or revision. Usage: checkout <branch|rev>".into(),
+            ));
+        };
+
+        let store = VersionStore::open(&ctx.schedules_dir);
+        let commit = store.resolve(&rev)?;
+        let save = store.snapshot(&commit)?;
+        apply_save_file(ctx, &save)?;
+        let msg = if store.has_branch(&rev)? {
+            store.switch_branch(&rev)?;
+            format!(
+                "Switched to branch '{}' at {} '{}'",
+                rev,
+                commit.short_id(),
+                commit.message
+            )
+        } else {
+            store.detach_head(&commit.id)?;
+            format!("Checked out {} '{}'", commit.short_id(), commit.message)
+        };
+        ctx.logger.info(msg, LogTarget::ConsoleAndFile);
+        Ok(())
+    }
+}
+
+pub struct DiffCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> DiffCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for DiffCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for DiffCommand<'a> {
+    fn usage(&self) -> String {
+        "diff <rev> [rev]   # Show what changed between two commits (or a commit and now)".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let store = VersionStore::open(&ctx.schedules_dir);
+        let (old, new) = match self.core.args {
+            [Arg::Name(old)] => (
+                store.snapshot(&store.resolve(old)?)?,
+                build_save_file(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates)?,
+            ),
+            [Arg::Name(old), Arg::Name(new)] => (
+                store.snapshot(&store.resolve(old)?)?,
+                store.snapshot(&store.resolve(new)?)?,
+            ),
+            _ => {
+                return Err(Parse(
+                    "Expected one or two revisions. Usage: diff <rev> [rev]".into(),
+                ));
+            }
+        };
+
+        let lines = diff_saves(&old, &new);
+        let report = if lines.is_empty() {
+            "No differences.".to_string()
+        } else {
+            lines.join("\n")
+        };
+        ctx.logger.info(report, LogTarget::ConsoleOnly);
+        Ok(())
+    }
+}
+
+pub struct BranchCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> BranchCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for BranchCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for BranchCommand<'a> {
+    fn usage(&self) -> String {
+        "branch [name]   # List branches, or start one at HEAD".into()
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let store = VersionStore::open(&ctx.schedules_dir);
+        if let Some(Arg::Name(name)) = self.core.args.first() {
+            let commit = store.create_branch(name)?;
+            ctx.logger.info(
+                format!("Created branch '{}' at {}", name, commit.short_id()),
+                LogTarget::ConsoleAndFile,
+            );
+            return Ok(());
+        }
+
+        let current = match store.head_ref()? {
+            Head::Branch(name) => Some(name),
+            Head::Detached(_) => None,
+        };
+        let branches = store.branches()?;
+        let width = branches
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<String> = branches
+            .iter()
+            .map(|(name, tip)| {
+                let marker = if current.as_deref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                format!(
+                    "{} {:<width$} {} {}",
+                    marker,
+                    name,
+                    tip.short_id(),
+                    tip.message
+                )
+            })
+            .collect();
+        let report = if lines.is_empty() {
+            "No branches yet. The first commit starts 'main'.".to_string()
+        } else {
+            lines.join("\n")
+        };
+        ctx.logger.info(report, LogTarget::ConsoleOnly);
+        Ok(())
+    }
+}
+
+pub struct MergeCommand<'a> {
+    core: CommandCore<'a>,
+}
+
+impl<'a> MergeCommand<'a> {
+    pub fn new(args: &'a [Arg]) -> Self {
+        let policy = FlagPolicy::new(vec![Box::new(HelpAtIdx(0))]);
+        Self {
+            core: CommandCore::new(args, policy),
+        }
+    }
+}
+
+impl<'a> sealed::Sealed<'a> for MergeCommand<'a> {
+    fn core(&self) -> &CommandCore<'a> {
+        &self.core
+    }
+}
+
+impl<'a> Command<'a> for MergeCommand<'a> {
+    fn usage(&self) -> String {
+        "merge <branch|rev>   # Merge another line of history into the current one".into()
+    }
+    fn changes_state(&self) -> bool {
+        true
+    }
+    fn journaling(&self) -> Journaling {
+        Journaling::Snapshot
+    }
+    fn perform(&self, ctx: &mut AppContext) -> Result<()> {
+        let rev = if let Some(Arg::Name(r)) = self.core.args.first() {
+            r.clone()
+        } else {
+            return Err(Parse(
+                "Expected branch or revision. Usage: merge <branch|rev>".into(),
+            ));
+        };
+
+        let store = VersionStore::open(&ctx.schedules_dir);
+        let ours = store.resolve("HEAD")?;
+        let theirs = store.resolve(&rev)?;
+        let ours_save = store.snapshot(&ours)?;
+        let current = build_save_file(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates)?;
+        if current != ours_save {
+            return Err(Parse(
+                "Uncommitted changes: commit them or run 'checkout HEAD' before merging.".into(),
+            ));
+        }
+
+        let base = store.merge_base(&ours, &theirs)?;
+        if base.as_ref().is_some_and(|b| b.id == theirs.id) {
+            ctx.logger
+                .info("Already up to date.", LogTarget::ConsoleOnly);
+            return Ok(());
+        }
+        if base.as_ref().is_some_and(|b| b.id == ours.id) {
+            apply_save_file(ctx, &store.snapshot(&theirs)?)?;
+            store.advance_head(&theirs.id)?;
+            ctx.logger.info(
+                format!(
+                    "Fast-forwarded to {} '{}'",
+                    theirs.short_id(),
+                    theirs.message
+                ),
+                LogTarget::ConsoleAndFile,
+            );
+            return Ok(());
+        }
+
+        let base_save = match &base {
+            Some(commit) => store.snapshot(commit)?,
+            None => SaveFile::default(),
+        };
+        let plan = merge::three_way(&base_save, &ours_save, &store.snapshot(&theirs)?);
+        let mut choices = Vec::new();
+        if !plan.conflicts.is_empty() {
+            ctx.logger.info(
+                format!("{} conflict(s) need a decision.", plan.conflicts.len()),
+                LogTarget::ConsoleOnly,
+            );
+            let flow = MergeConflictFlow::new(&plan.conflicts, &mut choices);
+            Prompter::new().run(flow, false)?;
+        }
+        let merged = plan.resolve(&choices)?;
+
+        apply_save_file(ctx, &merged)?;
+        let into = match store.head_ref()? {
+            Head::Branch(name) => name,
+            Head::Detached(_) => "HEAD".to_string(),
+        };
+        let message = format!("Merge {rev} into {into}");
+        let commit = store.commit_merge(&merged, &message, &theirs.id)?;
+        ctx.logger.info(
+            format!(
+                "Merged {} into {} as {} ({} conflict(s) resolved)",
+                rev,
+                into,
+                commit.short_id(),
+                plan.conflicts.len()
+            ),
+            LogTarget::ConsoleAndFile,
+        );
+        Ok(())
+    }
+}
+
 pub struct TypeHelpCommand<'a> {
     core: CommandCore<'a>,
     command_type: crate::core::types::TypeHelpCommand,
@@ -1,12 +1,17 @@
-use crate::arg::args::{Arg, CardColorArg, NameArg};
+use crate::arg::args::{Arg, CardColorArg, FieldSetsArg, FlagArg, NameArg};
 use crate::command::entity_spec::common::{entity_slot, id_slot};
 use crate::command::entity_spec::core::{
     ArgPattern, ArgSchema, ArgSlot, ArgValidator, ColumnIndexer, EntityBuilder, EntitySpec,
     PatternIdExt,
 };
+use crate::command::entity_spec::fields::{
+    DetailChange, FieldChange, fields_slot, name_value, parse_changes, parse_trailing,
+    trailing_slot,
+};
 use crate::core::context::AppContext;
 use crate::core::models::Card;
-use crate::core::types::{EntityActionType, EntityType};
+use crate::core::types::{CardColor, CardDeletePolicy, EntityActionType, EntityType};
+use crate::core::uid::Uid;
 use crate::errors::{Error, Result};
 use std::fmt;
 
@@ -16,6 +21,7 @@ impl CardArgSchema {
         vec![
             ArgSlot::is_of_arg_type::<NameArg>(),
             ArgSlot::is_of_arg_type::<CardColorArg>(),
+            trailing_slot::<CardChange>(),
         ]
     }
 
@@ -23,17 +29,39 @@ impl CardArgSchema {
         vec![entity_slot(EntityType::Card), id_slot()]
     }
 
+    fn pattern_delete() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.push(
+            ArgSlot::is_of_arg_type::<FlagArg>()
+                .with_validator(|arg| match arg {
+                    Arg::Flag(flag) if CardDeletePolicy::from_flag(*flag).is_some() => Ok(()),
+                    _ => Err(Error::Parse(
+                        "Expected a delete policy: --restrict, --cascade or --set-null".into(),
+                    )),
+                })
+                .optional(),
+        );
+        v
+    }
+
     fn pattern_entity_first() -> ArgPattern {
         let mut v = Self::pattern_entity_id();
         v.extend(Self::pattern_base());
         v
     }
+
+    fn pattern_fields() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.push(fields_slot::<CardChange>());
+        v
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CardPat {
     Base,
     EntityFirst,
+    Fields,
     EntityId,
 }
 
@@ -41,24 +69,39 @@ impl CardPat {
     const fn usage(self) -> &'static str {
         match self {
             CardPat::Base => {
-                r#"card "<name>" <color>
+                r#"card "<name>" <color> [details]
 Required:
   name  - (string)    Name of card, wrapped in single or double quotes
-  color - (CardColor) Valid card color. Run 'colors -h' to see valid card colors."#
+  color - (CardColor) Valid card color. Run 'colors -h' to see valid card colors.
+Optional:
+  details - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
             }
 
             CardPat::EntityFirst => {
-                r#"card <id> "<name>" <color>
+                r#"card <id> "<name>" <color> [details]
 Required:
   id    - (int)       id of card
   name  - (string)    Name of card, wrapped in single or double quotes
-  color - (CardColor) Valid card color. Run 'colors -h' to see valid card colors"#
+  color - (CardColor) Valid card color. Run 'colors -h' to see valid card colors
+Optional:
+  details - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
+            }
+
+            CardPat::Fields => {
+                r#"card <id> <field>=<value>...
+Required:
+  id     - (int) id of card
+  fields - One or more of: name, color, note, links, meta.<key>. Only the named fields change.
+           e.g. color=blue, name="New name""#
             }
 
             CardPat::EntityId => {
-                r#"card <id>
+                r#"card <id> [policy]
 Required:
-  id    - (int)       id of card"#
+  id     - (int)       id of card
+Optional:
+  policy - (flag)      --restrict, --cascade or --set-null; what happens to the tasks,
+                       events and templates using the card. Defaults to CARD_DELETE_POLICY."#
             }
         }
     }
@@ -75,7 +118,8 @@ impl PatternIdExt for CardPat {
         match self {
             CardPat::Base => CardArgSchema::pattern_base(),
             CardPat::EntityFirst => CardArgSchema::pattern_entity_first(),
-            CardPat::EntityId => CardArgSchema::pattern_entity_id(),
+            CardPat::Fields => CardArgSchema::pattern_fields(),
+            CardPat::EntityId => CardArgSchema::pattern_delete(),
         }
     }
 }
@@ -86,8 +130,9 @@ impl ArgSchema for CardArgSchema {
     fn patterns_for(&self, action: EntityActionType) -> Vec<CardPat> {
         match action {
             EntityActionType::Add => vec![CardPat::Base],
-            EntityActionType::Modify => vec![CardPat::EntityFirst],
+            EntityActionType::Modify => vec![CardPat::EntityFirst, CardPat::Fields],
             EntityActionType::Delete => vec![CardPat::EntityId],
+            EntityActionType::Complete => vec![],
         }
     }
 }
@@ -116,10 +161,15 @@ impl EntityBuilder<Card> for CardBuilder {
             CardPat::Base => {
                 let pattern = pat_id.pattern();
                 let mut ix = ColumnIndexer::new(args, &pattern);
-                Ok(Card::new(
+                let mut card = Card::new(
                     ix.next::<NameArg>().clone(),
                     ix.next::<CardColorArg>().clone(),
-                ))
+                );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<CardChange>(details)? {
+                    change.apply(&mut card);
+                }
+                Ok(card)
             }
             _ => Err(Error::Parse(
                 "No valid ADD pattern matched for card.".into(),
@@ -141,6 +191,20 @@ impl EntityBuilder<Card> for CardBuilder {
                     ix.advance_times(2).next::<NameArg>().clone(),
                     ix.next::<CardColorArg>().clone(),
                 );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<CardChange>(details)? {
+                    change.apply(existing);
+                }
+                Ok(&*existing)
+            }
+            CardPat::Fields => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                let changes =
+                    parse_changes::<CardChange>(ix.advance_times(2).next::<FieldSetsArg>())?;
+                for change in changes {
+                    change.apply(existing);
+                }
                 Ok(&*existing)
             }
             _ => Err(Error::Parse(
@@ -150,6 +214,47 @@ impl EntityBuilder<Card> for CardBuilder {
     }
 }
 
+/// A field of `mod card <id> field=value...`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CardChange {
+    Name(String),
+    Color(CardColor),
+    Details(DetailChange),
+    Uid(Uid),
+}
+
+impl CardChange {
+    fn apply(self, card: &mut Card) {
+        match self {
+            CardChange::Name(name) => card.name = name,
+            CardChange::Color(color) => card.color = color,
+            CardChange::Details(change) => change.apply(&mut card.details),
+            CardChange::Uid(uid) => card.uid = uid,
+        }
+    }
+}
+
+impl FieldChange for CardChange {
+    const ENTITY: EntityType = EntityType::Card;
+    const FIELDS: &'static [&'static str] = &["name", "color"];
+
+    fn parse(field: &str, value: &str) -> Result<Self> {
+        Ok(match field {
+            "name" => CardChange::Name(name_value(value)?),
+            "color" => CardChange::Color(CardColor::try_from(value)?),
+            _ => unreachable!("field checked against FIELDS"),
+        })
+    }
+
+    fn from_detail(change: DetailChange) -> Self {
+        CardChange::Details(change)
+    }
+
+    fn from_uid(uid: Uid) -> Self {
+        CardChange::Uid(uid)
+    }
+}
+
 pub struct CardSpec {
     schema: CardArgSchema,
     validator: CardArgValidator,
@@ -164,6 +269,14 @@ impl CardSpec {
             builder: CardBuilder,
         }
     }
+
+    /// Policy flag given to `del card <id> [policy]`, if any.
+    pub fn delete_policy(&self, args: &[Arg]) -> Option<CardDeletePolicy> {
+        match args.get(2) {
+            Some(Arg::Flag(flag)) => CardDeletePolicy::from_flag(*flag),
+            _ => None,
+        }
+    }
 }
 
 impl EntitySpec<Card> for CardSpec {
@@ -1,4 +1,4 @@
-use crate::arg::args::{Arg, BoolArg, DaysOfWeekArg, EntityTypeArg, IntArg};
+use crate::arg::args::{Arg, BoolArg, DaysOfWeekArg, EntityTypeArg, HoursArg, IntArg};
 use crate::command::entity_spec::core::{ArgSlot, ColumnIndexer, PatternIdExt};
 use crate::command::entity_spec::event::EventPat;
 use crate::core::context::AppContext;
@@ -16,6 +16,22 @@ pub fn id_slot() -> ArgSlot {
     })
 }
 
+pub fn hours_slot() -> ArgSlot {
+    ArgSlot::is_of_arg_type::<IntArg>().with_validator(|arg| match arg {
+        Arg::Int(h) if *h > 0 => Ok(()),
+        _ => Err(Error::Parse("Hours must be greater than 0.".into())),
+    })
+}
+
+/// A task's hours, which may carry a fraction when estimated checklist items add up to them.
+pub fn task_hours_slot() -> ArgSlot {
+    ArgSlot::is_of_arg_type::<HoursArg>().with_validator(|arg| match arg {
+        Arg::Int(h) if *h > 0 => Ok(()),
+        Arg::Hours(h) if *h > 0.0 => Ok(()),
+        _ => Err(Error::Parse("Hours must be greater than 0.".into())),
+    })
+}
+
 pub fn entity_slot(expected: EntityType) -> ArgSlot {
     ArgSlot::is_of_arg_type::<EntityTypeArg>().with_validator(move |a| match a {
         Arg::EntityType(t) if *t == expected => Ok(()),
@@ -29,6 +45,8 @@ pub fn entity_slot(expected: EntityType) -> ArgSlot {
 
 // Validators
 
+pub type ContextValidator = Box<dyn Fn(&Arg, &AppContext) -> Result<()> + 'static>;
+
 pub fn card_id_validator() -> Box<dyn Fn(&Arg, &AppContext) -> Result<()> + 'static> {
     Box::new(|arg, ctx| {
         if let Arg::CardColorId(id) = arg {
@@ -40,6 +58,15 @@ pub fn card_id_validator() -> Box<dyn Fn(&Arg, &AppContext) -> Result<()> + 'sta
     })
 }
 
+pub fn template_id_validator() -> ContextValidator {
+    Box::new(|arg, ctx| match arg {
+        Arg::TemplateId(id, _) if !ctx.templates.exists_including_staged(*id) => {
+            Err(Error::Parse(format!("Template id {} does not exist.", id)))
+        }
+        _ => Ok(()),
+    })
+}
+
 pub fn daily_hour_range_validator() -> Box<dyn Fn(&Arg, &AppContext) -> Result<()> + 'static> {
     Box::new(|arg, ctx| {
         if let Arg::TimeRange(range) = arg {
@@ -96,7 +123,7 @@ pub fn validate_event_recurring_days(args: &[Arg], pid: EventPat) -> Result<()>
                 }
             }
         }
-        EventPat::EntityId => {}
+        EventPat::Fields | EventPat::EntityId => {}
     }
 
     Ok(())
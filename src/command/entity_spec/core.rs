@@ -357,7 +357,8 @@ impl<'a> ColumnIndexer<'a> {
                 return Some(v);
             }
 
-            if slot.is_optional() {
+            // An optional slot that claims the current arg belongs to a later read.
+            if slot.is_optional() && !slot.kind_matches(a) {
                 self.slot_idx += 1;
                 continue;
             } else {
@@ -1,5 +1,5 @@
 use crate::arg::args::{
-    Arg, AtSymbolArg, BoolArg, CardColorIdArg, DaysOfWeekArg, NameArg, TimeRangeArg,
+    Arg, AtSymbolArg, BoolArg, CardColorIdArg, DaysOfWeekArg, FieldSetsArg, NameArg, TimeRangeArg,
 };
 use crate::command::entity_spec::common::{
     card_id_validator, daily_hour_range_validator, default_days_for, entity_slot, id_slot,
@@ -9,9 +9,14 @@ use crate::command::entity_spec::core::{
     ArgPattern, ArgSchema, ArgSlot, ArgValidator, ColumnIndexer, EntityBuilder, EntitySpec,
     PatternIdExt,
 };
+use crate::command::entity_spec::fields::{
+    DetailChange, FieldChange, bool_value, card_value, days_value, fields_slot, name_value,
+    parse_changes, parse_trailing, trailing_slot, validate_card,
+};
 use crate::core::context::AppContext;
 use crate::core::models::Event;
-use crate::core::types::{EntityActionType, EntityType};
+use crate::core::types::{DayOfWeek, EntityActionType, EntityType, TimeRange};
+use crate::core::uid::Uid;
 use crate::errors::{Error, Result};
 use std::fmt;
 
@@ -29,6 +34,7 @@ impl EventArgSchema {
             ArgSlot::is_of_arg_type::<DaysOfWeekArg>().optional(),
             ArgSlot::is_of_arg_type::<TimeRangeArg>()
                 .with_validator_ctx(daily_hour_range_validator()),
+            trailing_slot::<EventChange>(),
         ]
     }
 
@@ -41,12 +47,19 @@ impl EventArgSchema {
         v.extend(Self::pattern_base());
         v
     }
+
+    fn pattern_fields() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.push(fields_slot::<EventChange>());
+        v
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EventPat {
     Base,
     EntityFirst,
+    Fields,
     EntityId,
 }
 
@@ -54,18 +67,19 @@ impl EventPat {
     const fn usage(self) -> &'static str {
         match self {
             EventPat::Base => {
-                r#"event <bool> "<name>" [cardId] @ [days of week] <time range>
+                r#"event <bool> "<name>" [cardId] @ [days of week] <time range> [details]
 Required:
   bool         - (bool)      Whether the event is recurring (true/false)
   name         - (string)    Name of event, wrapped in single or double quotes
   time range   - (TimeRange) Start & end time of the event. Run 'time -h' to see valid time formats for start/end.
 Optional:
   cardId       - (integer)   Id referencing a Card for its tag and color. Must prefix with '+C'
-  days of week - (DayOfWeek) Comma separated list of one or more days of the week"#
+  days of week - (DayOfWeek) Comma separated list of one or more days of the week
+  details      - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
             }
 
             EventPat::EntityFirst => {
-                r#"event <id> <bool> "<name>" [cardId] @ <days of week> <time range>
+                r#"event <id> <bool> "<name>" [cardId] @ <days of week> <time range> [details]
 Required:
   id           - (int)       id of event
   bool         - (bool)      Whether the event is recurring (true/false)
@@ -73,7 +87,17 @@ Required:
   time range   - (TimeRange) Start & end time of the event. Run 'time -h' to see valid time formats for start/end
 Optional:
   cardId       - (integer) Id referencing a Card for its tag and color. Must prefix with '+C'
-  days of week - (DayOfWeek) Comma separated list of one or more days of the week"#
+  days of week - (DayOfWeek) Comma separated list of one or more days of the week
+  details      - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
+            }
+
+            EventPat::Fields => {
+                r#"event <id> <field>=<value>...
+Required:
+  id     - (int) id of event
+  fields - One or more of: name, card, recurring, days, time, note, links, meta.<key>.
+           Only the named fields change.
+           e.g. time=9-10AM, days=mon,wed, recurring=false, card=+C2 or card=none, name="New name""#
             }
 
             EventPat::EntityId => {
@@ -96,6 +120,7 @@ impl PatternIdExt for EventPat {
         match self {
             EventPat::Base => EventArgSchema::pattern_base(),
             EventPat::EntityFirst => EventArgSchema::pattern_entity_first(),
+            EventPat::Fields => EventArgSchema::pattern_fields(),
             EventPat::EntityId => EventArgSchema::pattern_entity_id(),
         }
     }
@@ -107,8 +132,9 @@ impl ArgSchema for EventArgSchema {
     fn patterns_for(&self, action: EntityActionType) -> Vec<EventPat> {
         match action {
             EntityActionType::Add => vec![EventPat::Base],
-            EntityActionType::Modify => vec![EventPat::EntityFirst],
+            EntityActionType::Modify => vec![EventPat::EntityFirst, EventPat::Fields],
             EntityActionType::Delete => vec![EventPat::EntityId],
+            EntityActionType::Complete => vec![],
         }
     }
 }
@@ -121,7 +147,7 @@ impl ArgValidator for EventArgValidator {
         match pat_id {
             EventPat::Base => validate_event_recurring_days(args, pat_id),
             EventPat::EntityFirst => validate_event_recurring_days(args, pat_id),
-            EventPat::EntityId => Ok(()),
+            EventPat::Fields | EventPat::EntityId => Ok(()),
         }
     }
 }
@@ -136,7 +162,7 @@ impl EntityBuilder<Event> for EventBuilder {
                 let pattern = pat_id.pattern();
                 let mut ix = ColumnIndexer::new(args, &pattern);
                 let recurring = ix.next::<BoolArg>().0;
-                Ok(Event::new(
+                let mut event = Event::new(
                     recurring,
                     ix.next::<NameArg>().clone(),
                     ix.next_opt::<CardColorIdArg>(),
@@ -145,7 +171,12 @@ impl EntityBuilder<Event> for EventBuilder {
                         .map(|v| v.clone())
                         .unwrap_or_else(|| default_days_for(recurring)),
                     ix.next::<TimeRangeArg>().clone(),
-                ))
+                );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<EventChange>(details)? {
+                    change.apply(&mut event);
+                }
+                Ok(event)
             }
             _ => Err(Error::Parse(
                 "No valid ADD pattern matched for event.".into(),
@@ -174,6 +205,27 @@ impl EntityBuilder<Event> for EventBuilder {
                         .unwrap_or_else(|| default_days_for(recurring)),
                     ix.next::<TimeRangeArg>().clone(),
                 );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<EventChange>(details)? {
+                    change.apply(existing);
+                }
+                Ok(&*existing)
+            }
+            EventPat::Fields => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                let changes =
+                    parse_changes::<EventChange>(ix.advance_times(2).next::<FieldSetsArg>())?;
+                let mut updated = existing.clone();
+                for change in changes {
+                    change.apply(&mut updated);
+                }
+                if !updated.recurring && updated.days.len() != 1 {
+                    return Err(Error::Parse(
+                        "Non-recurring events must have exactly one day.".into(),
+                    ));
+                }
+                *existing = updated;
                 Ok(&*existing)
             }
             _ => Err(Error::Parse(
@@ -183,6 +235,66 @@ impl EntityBuilder<Event> for EventBuilder {
     }
 }
 
+/// A field of `mod event <id> field=value...`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventChange {
+    Name(String),
+    Card(Option<i32>),
+    Recurring(bool),
+    Days(Vec<DayOfWeek>),
+    Time(TimeRange),
+    Details(DetailChange),
+    Uid(Uid),
+}
+
+impl EventChange {
+    fn apply(self, event: &mut Event) {
+        match self {
+            EventChange::Name(name) => event.name = name,
+            EventChange::Card(card_id) => event.card_id = card_id,
+            EventChange::Recurring(recurring) => event.recurring = recurring,
+            EventChange::Days(days) => event.days = days,
+            EventChange::Time(time_range) => event.time_range = time_range,
+            EventChange::Details(change) => change.apply(&mut event.details),
+            EventChange::Uid(uid) => event.uid = uid,
+        }
+    }
+}
+
+impl FieldChange for EventChange {
+    const ENTITY: EntityType = EntityType::Event;
+    const FIELDS: &'static [&'static str] = &["name", "card", "recurring", "days", "time"];
+
+    fn parse(field: &str, value: &str) -> Result<Self> {
+        Ok(match field {
+            "name" => EventChange::Name(name_value(value)?),
+            "card" => EventChange::Card(card_value(value)?),
+            "recurring" => EventChange::Recurring(bool_value(value)?),
+            "days" => EventChange::Days(days_value(value)?),
+            "time" => EventChange::Time(TimeRange::try_from_str(value)?),
+            _ => unreachable!("field checked against FIELDS"),
+        })
+    }
+
+    fn from_detail(change: DetailChange) -> Self {
+        EventChange::Details(change)
+    }
+
+    fn from_uid(uid: Uid) -> Self {
+        EventChange::Uid(uid)
+    }
+
+    fn validate(&self, ctx: &AppContext) -> Result<()> {
+        match self {
+            EventChange::Card(card_id) => validate_card(*card_id, ctx),
+            EventChange::Time(range) => {
+                daily_hour_range_validator()(&Arg::TimeRange(range.clone()), ctx)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
 pub struct EventSpec {
     schema: EventArgSchema,
     validator: EventArgValidator,
@@ -0,0 +1,218 @@
+use crate::arg::args::{Arg, CardColorIdArg, FieldSet, FieldSetsArg, IntArg, SingleTokenArg};
+use crate::command::entity_spec::common::card_id_validator;
+use crate::command::entity_spec::core::ArgSlot;
+use crate::core::context::AppContext;
+use crate::core::models::{DETAIL_FIELDS, Details};
+use crate::core::types::{Bool, DayOfWeek, EntityType};
+use crate::core::uid::{UID_FIELD, Uid};
+use crate::errors::{Error, Result};
+
+/// A single field update of `mod <entity> <id> field=value...`.
+pub trait FieldChange: Sized {
+    const ENTITY: EntityType;
+    const FIELDS: &'static [&'static str];
+    /// Fields besides the details that may follow the arguments of an add or full modify.
+    const TRAILING: &'static [&'static str] = &[];
+    /// Fields that may be given more than once, each adding a value.
+    const REPEATABLE: &'static [&'static str] = &[];
+
+    /// Parses the value of `field`, which is one of `FIELDS`.
+    fn parse(field: &str, value: &str) -> Result<Self>;
+
+    /// Wraps a change to the notes, links or metadata every entity has.
+    fn from_detail(change: DetailChange) -> Self;
+
+    /// Wraps the saved identity given when an entity is loaded.
+    fn from_uid(uid: Uid) -> Self;
+
+    /// Checks that need the current state, such as a referenced card existing.
+    fn validate(&self, _ctx: &AppContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Parses every assignment, rejecting unknown fields and fields given twice.
+pub fn parse_changes<C: FieldChange>(sets: &[FieldSet]) -> Result<Vec<C>> {
+    parse_sets::<C>(C::FIELDS, false, sets)
+}
+
+/// Parses the assignments after the arguments of an add or full modify, if any.
+pub fn parse_trailing<C: FieldChange>(sets: Option<&[FieldSet]>) -> Result<Vec<C>> {
+    parse_sets::<C>(C::TRAILING, true, sets.unwrap_or_default())
+}
+
+fn parse_sets<C: FieldChange>(
+    fields: &[&str],
+    with_uid: bool,
+    sets: &[FieldSet],
+) -> Result<Vec<C>> {
+    let mut seen: Vec<&str> = Vec::new();
+    sets.iter()
+        .map(|set| {
+            let is_uid = with_uid && set.field == UID_FIELD;
+            if !fields.contains(&set.field.as_str())
+                && !DetailChange::is_field(&set.field)
+                && !is_uid
+            {
+                let valid: Vec<&str> = fields.iter().chain(DETAIL_FIELDS).copied().collect();
+                return Err(Error::Parse(format!(
+                    "Unknown {} field: '{}'. Valid fields: {}",
+                    C::ENTITY,
+                    set.field,
+                    valid.join(", ")
+                )));
+            }
+            if seen.contains(&set.field.as_str()) && !C::REPEATABLE.contains(&set.field.as_str()) {
+                return Err(Error::Parse(format!(
+                    "Field '{}' is given more than once",
+                    set.field
+                )));
+            }
+            seen.push(&set.field);
+            let parsed = if DetailChange::is_field(&set.field) {
+                DetailChange::parse(set).map(C::from_detail)
+            } else if is_uid {
+                Uid::try_from_str(&set.value).map(C::from_uid)
+            } else {
+                C::parse(&set.field, &set.value)
+            };
+            parsed.map_err(|e| {
+                let msg = match e {
+                    Error::Parse(msg) => msg,
+                    other => other.to_string(),
+                };
+                Error::Parse(format!("Invalid {}: {}", set.field, msg))
+            })
+        })
+        .collect()
+}
+
+pub fn fields_slot<C: FieldChange>() -> ArgSlot {
+    ArgSlot::is_of_arg_type::<FieldSetsArg>().with_validator_ctx(|arg, ctx| match arg {
+        Arg::FieldSets(sets) => parse_changes::<C>(sets)?
+            .iter()
+            .try_for_each(|change| change.validate(ctx)),
+        _ => Ok(()),
+    })
+}
+
+/// Optional trailing `note=... links=... meta.<key>=...` of an add or full modify, along with
+/// the entity's own `TRAILING` fields and the `uid` of a saved entity.
+pub fn trailing_slot<C: FieldChange>() -> ArgSlot {
+    ArgSlot::is_of_arg_type::<FieldSetsArg>()
+        .with_validator_ctx(|arg, ctx| match arg {
+            Arg::FieldSets(sets) => parse_trailing::<C>(Some(sets))?
+                .iter()
+                .try_for_each(|change| change.validate(ctx)),
+            _ => Ok(()),
+        })
+        .optional()
+}
+
+// Details
+
+/// A change to the notes, links or metadata of an entity. `none` clears a field or removes a key;
+/// a quoted `"none"` is kept as text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetailChange {
+    Note(Option<String>),
+    Links(Vec<String>),
+    Meta(String, Option<String>),
+}
+
+impl DetailChange {
+    fn is_field(field: &str) -> bool {
+        matches!(field, "note" | "links") || field.starts_with("meta.")
+    }
+
+    fn parse(set: &FieldSet) -> Result<Self> {
+        let (field, value) = (set.field.as_str(), set.value.as_str());
+        let none = !set.quoted && value.eq_ignore_ascii_case("none");
+        Ok(match field {
+            "note" if none => DetailChange::Note(None),
+            "note" => DetailChange::Note(Some(value.trim().to_string())),
+            "links" if none => DetailChange::Links(Vec::new()),
+            "links" => DetailChange::Links(links_value(value)?),
+            _ => {
+                let key = field.trim_start_matches("meta.").to_string();
+                DetailChange::Meta(key, (!none).then(|| value.trim().to_string()))
+            }
+        })
+    }
+
+    pub fn apply(self, details: &mut Details) {
+        match self {
+            DetailChange::Note(notes) => details.notes = notes,
+            DetailChange::Links(links) => details.links = links,
+            DetailChange::Meta(key, Some(value)) => {
+                details.meta.insert(key, value);
+            }
+            DetailChange::Meta(key, None) => {
+                details.meta.remove(&key);
+            }
+        }
+    }
+}
+
+/// Comma-separated links, each without whitespace.
+fn links_value(value: &str) -> Result<Vec<String>> {
+    value
+        .split(',')
+        .map(|link| {
+            let link = link.trim();
+            if link.is_empty() || link.contains(char::is_whitespace) {
+                Err(Error::Parse(format!(
+                    "Invalid link list: '{value}'. Expected comma-separated links, e.g. https://a.org,https://b.org"
+                )))
+            } else {
+                Ok(link.to_string())
+            }
+        })
+        .collect()
+}
+
+// Values
+
+pub fn name_value(value: &str) -> Result<String> {
+    let name = value.trim();
+    if name.is_empty() {
+        return Err(Error::Parse("Name must contain text.".into()));
+    }
+    Ok(name.to_string())
+}
+
+pub fn hours_value(value: &str) -> Result<f32> {
+    match IntArg::new(value)? {
+        Arg::Int(h) if h > 0 => Ok(h as f32),
+        _ => Err(Error::Parse("Hours must be greater than 0.".into())),
+    }
+}
+
+/// `+C<id>`, or `none` to remove the card.
+pub fn card_value(value: &str) -> Result<Option<i32>> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    match CardColorIdArg::new(value)? {
+        Arg::CardColorId(id) => Ok(Some(id)),
+        _ => unreachable!("CardColorIdArg only builds card ids"),
+    }
+}
+
+pub fn bool_value(value: &str) -> Result<bool> {
+    Ok(Bool::try_from_str(value)?.0)
+}
+
+pub fn days_value(value: &str) -> Result<Vec<DayOfWeek>> {
+    value
+        .split(',')
+        .map(|d| DayOfWeek::try_from(d.trim()))
+        .collect()
+}
+
+pub fn validate_card(card_id: Option<i32>, ctx: &AppContext) -> Result<()> {
+    match card_id {
+        Some(id) => card_id_validator()(&Arg::CardColorId(id), ctx),
+        None => Ok(()),
+    }
+}
@@ -2,6 +2,8 @@ pub mod card;
 mod common;
 pub mod core;
 pub mod event;
+mod fields;
 pub mod task;
+pub mod template;
 #[cfg(test)]
 mod tests;
@@ -1,36 +1,44 @@
-use crate::arg::args::{Arg, AtSymbolArg, CardColorIdArg, DateArg, IntArg, NameArg};
+use crate::arg::args::{
+    Arg, AtSymbolArg, CardColorIdArg, DateArg, EstimateArg, FieldSetsArg, HoursArg, NameArg,
+    TaskMarkersArg, TemplateIdArg,
+};
 use crate::command::entity_spec::common::{
-    card_id_validator, entity_slot, id_slot, task_start_date_validator,
+    card_id_validator, entity_slot, id_slot, task_hours_slot, task_start_date_validator,
+    template_id_validator,
 };
 use crate::command::entity_spec::core::{
     ArgPattern, ArgSchema, ArgSlot, ArgValidator, ColumnIndexer, EntityBuilder, EntitySpec,
     PatternIdExt,
 };
+use crate::command::entity_spec::fields::{
+    DetailChange, FieldChange, bool_value, card_value, fields_slot, hours_value, name_value,
+    parse_changes, parse_trailing, trailing_slot, validate_card,
+};
 use crate::core::context::AppContext;
-use crate::core::models::Task;
-use crate::core::types::{EntityActionType, EntityType};
+use crate::core::models::{ChecklistItem, Task, TemplateOrigin};
+use crate::core::types::{Date, EntityActionType, EntityType, Estimate, TaskMarker};
+use crate::core::uid::Uid;
 use crate::errors::{Error, Result};
 use std::fmt;
 
 pub struct TaskArgSchema;
 
 impl TaskArgSchema {
-    fn hours_slot() -> ArgSlot {
-        ArgSlot::is_of_arg_type::<IntArg>().with_validator(|arg| match arg {
-            Arg::Int(h) if *h > 0 => Ok(()),
-            _ => Err(Error::Parse("Hours must be greater than 0.".into())),
-        })
-    }
-
     fn pattern_base() -> ArgPattern {
         vec![
             ArgSlot::is_of_arg_type::<NameArg>(),
-            Self::hours_slot(),
+            task_hours_slot(),
+            ArgSlot::is_of_arg_type::<EstimateArg>().optional(),
             ArgSlot::is_of_arg_type::<CardColorIdArg>()
                 .with_validator_ctx(card_id_validator())
                 .optional(),
+            ArgSlot::is_of_arg_type::<TemplateIdArg>()
+                .with_validator_ctx(template_id_validator())
+                .optional(),
+            ArgSlot::is_of_arg_type::<TaskMarkersArg>().optional(),
             ArgSlot::is_of_arg_type::<AtSymbolArg>(),
             ArgSlot::is_of_arg_type::<DateArg>().with_validator_ctx(task_start_date_validator()),
+            trailing_slot::<TaskChange>(),
         ]
     }
 
@@ -43,12 +51,19 @@ impl TaskArgSchema {
         v.extend(Self::pattern_base());
         v
     }
+
+    fn pattern_fields() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.push(fields_slot::<TaskChange>());
+        v
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TaskPat {
     Base,
     EntityFirst,
+    Fields,
     EntityId,
 }
 
@@ -56,24 +71,46 @@ impl TaskPat {
     const fn usage(self) -> &'static str {
         match self {
             TaskPat::Base => {
-                r#"task "<name>" <hours> [cardId] @ <date>
+                r#"task "<name>" <hours> [estimate] [cardId] [templateId] [markers] @ <date> [details]
 Required:
   name  - (string) Name of task, wrapped in single or double quotes
-  hours - (int)    Number of hours to complete the task
+  hours - (int)    Number of hours to complete the task; estimated items replace it with their sum
   date  - (Date)   Due date to complete the task by. Run 'date -h' to see valid formats for date
 Optional:
-  cardId - (integer) Id referencing a Card for its tag and color. Must prefix with '+C'"#
+  estimate   - Optimistic/likely/pessimistic hours used by 'forecast', e.g. ~2/3/6
+  cardId     - (integer) Id referencing a Card for its tag and color. Must prefix with '+C'
+  templateId - (integer) Id of the template this task was generated from. Must prefix with '+T'
+  markers    - One or more of: !atomic (task must fit in one contiguous block),
+               !done (task is completed and will not be scheduled)
+  details    - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
             }
 
             TaskPat::EntityFirst => {
-                r#"task <id> "<name>" <hours> [cardId] @ <date>
+                r#"task <id> "<name>" <hours> [estimate] [cardId] [templateId] [markers] @ <date> [details]
 Required:
   id    - (int)    id of task
   name  - (string) Name of task, wrapped in single or double quotes
-  hours - (int)    Number of hours to complete the task
+  hours - (int)    Number of hours to complete the task; estimated items replace it with their sum
   date  - (Date)   Due date to complete the task by. Run 'date -h' to see valid formats for date
 Optional:
-  cardId - (integer) Id referencing a Card for its tag and color. Must prefix with '+C'"#
+  estimate   - Optimistic/likely/pessimistic hours used by 'forecast', e.g. ~2/3/6
+  cardId     - (integer) Id referencing a Card for its tag and color. Must prefix with '+C'
+  templateId - (integer) Id of the template this task was generated from. Must prefix with '+T'
+  markers    - One or more of: !atomic (task must fit in one contiguous block),
+               !done (task is completed and will not be scheduled)
+  details    - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>"#
+            }
+
+            TaskPat::Fields => {
+                r#"task <id> <field>=<value>...
+Required:
+  id     - (int) id of task
+  fields - One or more of: name, hours, due, card, estimate, atomic, done, note, links,
+           meta.<key>.
+           Only the named fields change; changing hours, due, atomic or done clears the task's schedule.
+           hours cannot be set while checklist items carry estimates.
+           e.g. hours=5, due=fri, card=+C2 or card=none, estimate=~2/3/6, name="New name",
+           meta.course=CS101 (meta.course=none removes the key)"#
             }
 
             TaskPat::EntityId => {
@@ -96,6 +133,7 @@ impl PatternIdExt for TaskPat {
         match self {
             TaskPat::Base => TaskArgSchema::pattern_base(),
             TaskPat::EntityFirst => TaskArgSchema::pattern_entity_first(),
+            TaskPat::Fields => TaskArgSchema::pattern_fields(),
             TaskPat::EntityId => TaskArgSchema::pattern_entity_id(),
         }
     }
@@ -107,8 +145,8 @@ impl ArgSchema for TaskArgSchema {
     fn patterns_for(&self, action: EntityActionType) -> Vec<TaskPat> {
         match action {
             EntityActionType::Add => vec![TaskPat::Base],
-            EntityActionType::Modify => vec![TaskPat::EntityFirst],
-            EntityActionType::Delete => vec![TaskPat::EntityId],
+            EntityActionType::Modify => vec![TaskPat::EntityFirst, TaskPat::Fields],
+            EntityActionType::Delete | EntityActionType::Complete => vec![TaskPat::EntityId],
         }
     }
 }
@@ -127,6 +165,17 @@ impl ArgValidator for TaskArgValidator {
     }
 }
 
+/// Hours with a fraction only come from estimated checklist items, which replace them anyway.
+fn whole_or_rolled_up(hours: f32, task: &Task) -> Result<()> {
+    if hours.fract() == 0.0 || task.checklist_hours().is_some() {
+        return Ok(());
+    }
+    Err(Error::Parse(format!(
+        "Task '{}' has {hours} hours; hours must be a whole number unless estimated checklist items add up to them.",
+        task.name
+    )))
+}
+
 pub struct TaskBuilder;
 impl EntityBuilder<Task> for TaskBuilder {
     type PatternId = TaskPat;
@@ -135,12 +184,29 @@ impl EntityBuilder<Task> for TaskBuilder {
             TaskPat::Base => {
                 let pattern = pat_id.pattern();
                 let mut ix = ColumnIndexer::new(args, &pattern);
-                Ok(Task::new(
-                    ix.next::<NameArg>().clone(),
-                    ix.next::<IntArg>() as f32,
-                    ix.next_opt::<CardColorIdArg>(),
-                    ix.advance().next::<DateArg>().clone(),
-                ))
+                let name = ix.next::<NameArg>().clone();
+                let hours = ix.next::<HoursArg>();
+                let estimate = ix.next_opt::<EstimateArg>();
+                let card_id = ix.next_opt::<CardColorIdArg>();
+                let template = ix.next_opt::<TemplateIdArg>();
+                let markers = ix.next_opt::<TaskMarkersArg>();
+                let date = ix.advance().next::<DateArg>().clone();
+                let details = ix.next_opt::<FieldSetsArg>();
+
+                let mut task = Task::new(name, hours, card_id, date);
+                task.estimate = estimate;
+                for change in parse_trailing::<TaskChange>(details)? {
+                    change.apply(&mut task);
+                }
+                whole_or_rolled_up(hours, &task)?;
+                if let Some((template_id, occurrence)) = template {
+                    task.origin = Some(TemplateOrigin {
+                        template_id,
+                        occurrence: occurrence.unwrap_or(&task.date).0,
+                    });
+                }
+                apply_markers(&mut task, markers);
+                Ok(task)
             }
             _ => Err(Error::Parse(
                 "No valid ADD pattern matched for task.".into(),
@@ -158,12 +224,55 @@ impl EntityBuilder<Task> for TaskBuilder {
             TaskPat::EntityFirst => {
                 let pattern = pat_id.pattern();
                 let mut ix = ColumnIndexer::new(args, &pattern);
-                existing.modify(
-                    ix.advance().advance().next::<NameArg>().clone(),
-                    ix.next::<IntArg>() as f32,
-                    ix.next_opt::<CardColorIdArg>(),
-                    ix.advance().next::<DateArg>().clone(),
-                );
+                let name = ix.advance().advance().next::<NameArg>().clone();
+                let hours = ix.next::<HoursArg>();
+                let estimate = ix.next_opt::<EstimateArg>();
+                let card_id = ix.next_opt::<CardColorIdArg>();
+                let template = ix.next_opt::<TemplateIdArg>();
+                let markers = ix.next_opt::<TaskMarkersArg>();
+                let date = ix.advance().next::<DateArg>().clone();
+                let details = ix.next_opt::<FieldSetsArg>();
+
+                existing.modify(name, hours, card_id, date);
+                existing.estimate = estimate;
+                for change in parse_trailing::<TaskChange>(details)? {
+                    change.apply(existing);
+                }
+                whole_or_rolled_up(hours, existing)?;
+                // An instance stays linked to its template occurrence unless re-targeted.
+                if let Some((template_id, occurrence)) = template {
+                    existing.origin = Some(TemplateOrigin {
+                        template_id,
+                        occurrence: occurrence.unwrap_or(&existing.date).0,
+                    });
+                }
+                existing.completed = false;
+                existing.atomic = false;
+                // As on add, estimated checklist items replace the given hours.
+                if existing.checklist_hours().is_some() {
+                    existing.roll_up_checklist();
+                }
+                apply_markers(existing, markers);
+                Ok(&*existing)
+            }
+            TaskPat::Fields => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                let changes =
+                    parse_changes::<TaskChange>(ix.advance_times(2).next::<FieldSetsArg>())?;
+                let sets_hours = changes.iter().any(|c| matches!(c, TaskChange::Hours(_)));
+                let adds_estimate = changes
+                    .iter()
+                    .any(|c| matches!(c, TaskChange::Item(item) if item.hours.is_some()));
+                if sets_hours && (adds_estimate || existing.checklist_hours().is_some()) {
+                    return Err(Error::Parse(format!(
+                        "Task {} takes its hours from its checklist estimates; change the items instead.",
+                        existing.id
+                    )));
+                }
+                for change in changes {
+                    change.apply(existing);
+                }
                 Ok(&*existing)
             }
             _ => Err(Error::Parse(
@@ -173,6 +282,111 @@ impl EntityBuilder<Task> for TaskBuilder {
     }
 }
 
+fn apply_markers(task: &mut Task, markers: Option<&Vec<TaskMarker>>) {
+    for marker in markers.into_iter().flatten() {
+        match marker {
+            TaskMarker::Atomic => {
+                task.atomic = true;
+            }
+            TaskMarker::Done => {
+                task.complete();
+            }
+        }
+    }
+}
+
+/// A field of `mod task <id> field=value...`. Name, card, estimate and details leave the schedule
+/// as it is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskChange {
+    Name(String),
+    Hours(f32),
+    Due(Date),
+    Card(Option<i32>),
+    Estimate(Option<Estimate>),
+    Atomic(bool),
+    Done(bool),
+    /// Appends a checklist item; estimated items roll up into the task's hours.
+    Item(ChecklistItem),
+    Details(DetailChange),
+    Uid(Uid),
+}
+
+impl TaskChange {
+    fn apply(self, task: &mut Task) {
+        match self {
+            TaskChange::Name(name) => task.name = name,
+            TaskChange::Card(card_id) => task.card_id = card_id,
+            TaskChange::Estimate(estimate) => task.estimate = estimate,
+            TaskChange::Details(change) => change.apply(&mut task.details),
+            TaskChange::Uid(uid) => task.uid = uid,
+            TaskChange::Hours(hours) => {
+                task.hours = hours;
+                task.unschedule();
+            }
+            TaskChange::Due(date) => {
+                task.date = date;
+                task.unschedule();
+            }
+            TaskChange::Atomic(atomic) => {
+                task.atomic = atomic;
+                task.unschedule();
+            }
+            TaskChange::Done(true) => {
+                task.complete();
+            }
+            TaskChange::Done(false) => {
+                task.completed = false;
+                task.unschedule();
+            }
+            TaskChange::Item(item) => {
+                task.checklist.push(item);
+                task.roll_up_checklist();
+            }
+        }
+    }
+}
+
+impl FieldChange for TaskChange {
+    const ENTITY: EntityType = EntityType::Task;
+    const FIELDS: &'static [&'static str] = &[
+        "name", "hours", "due", "card", "estimate", "atomic", "done", "item",
+    ];
+    const TRAILING: &'static [&'static str] = &["item"];
+    const REPEATABLE: &'static [&'static str] = &["item"];
+
+    fn parse(field: &str, value: &str) -> Result<Self> {
+        Ok(match field {
+            "name" => TaskChange::Name(name_value(value)?),
+            "hours" => TaskChange::Hours(hours_value(value)?),
+            "due" => TaskChange::Due(Date::try_from_str(value)?),
+            "card" => TaskChange::Card(card_value(value)?),
+            "estimate" if value.eq_ignore_ascii_case("none") => TaskChange::Estimate(None),
+            "estimate" => TaskChange::Estimate(Some(Estimate::try_from_str(value)?)),
+            "atomic" => TaskChange::Atomic(bool_value(value)?),
+            "done" => TaskChange::Done(bool_value(value)?),
+            "item" => TaskChange::Item(ChecklistItem::try_from_str(value)?),
+            _ => unreachable!("field checked against FIELDS"),
+        })
+    }
+
+    fn from_detail(change: DetailChange) -> Self {
+        TaskChange::Details(change)
+    }
+
+    fn from_uid(uid: Uid) -> Self {
+        TaskChange::Uid(uid)
+    }
+
+    fn validate(&self, ctx: &AppContext) -> Result<()> {
+        match self {
+            TaskChange::Card(card_id) => validate_card(*card_id, ctx),
+            TaskChange::Due(date) => task_start_date_validator()(&Arg::Date(date.clone()), ctx),
+            _ => Ok(()),
+        }
+    }
+}
+
 pub struct TaskSpec {
     schema: TaskArgSchema,
     validator: TaskArgValidator,
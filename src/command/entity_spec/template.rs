@@ -0,0 +1,311 @@
+use crate::arg::args::{
+    Arg, AtSymbolArg, CardColorIdArg, DaysOfWeekArg, FieldSetsArg, IntArg, NameArg,
+};
+use crate::command::entity_spec::common::{card_id_validator, entity_slot, hours_slot, id_slot};
+use crate::command::entity_spec::core::{
+    ArgPattern, ArgSchema, ArgSlot, ArgValidator, ColumnIndexer, EntityBuilder, EntitySpec,
+    PatternIdExt,
+};
+use crate::command::entity_spec::fields::{
+    DetailChange, FieldChange, card_value, days_value, fields_slot, hours_value, name_value,
+    parse_changes, parse_trailing, trailing_slot, validate_card,
+};
+use crate::core::context::AppContext;
+use crate::core::models::TaskTemplate;
+use crate::core::types::{Date, DayOfWeek, EntityActionType, EntityType};
+use crate::core::uid::Uid;
+use crate::errors::{Error, Result};
+use chrono::NaiveDate;
+use std::fmt;
+
+pub struct TemplateArgSchema;
+
+impl TemplateArgSchema {
+    fn pattern_base() -> ArgPattern {
+        vec![
+            ArgSlot::is_of_arg_type::<NameArg>(),
+            hours_slot(),
+            ArgSlot::is_of_arg_type::<CardColorIdArg>()
+                .with_validator_ctx(card_id_validator())
+                .optional(),
+            ArgSlot::is_of_arg_type::<AtSymbolArg>(),
+            ArgSlot::is_of_arg_type::<DaysOfWeekArg>(),
+            trailing_slot::<TemplateChange>(),
+        ]
+    }
+
+    fn pattern_entity_id() -> ArgPattern {
+        vec![entity_slot(EntityType::Template), id_slot()]
+    }
+
+    fn pattern_entity_first() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.extend(Self::pattern_base());
+        v
+    }
+
+    fn pattern_fields() -> ArgPattern {
+        let mut v = Self::pattern_entity_id();
+        v.push(fields_slot::<TemplateChange>());
+        v
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplatePat {
+    Base,
+    EntityFirst,
+    Fields,
+    EntityId,
+}
+
+impl TemplatePat {
+    const fn usage(self) -> &'static str {
+        match self {
+            TemplatePat::Base => {
+                r#"template "<name>" <hours> [cardId] @ <days of week> [details]
+Required:
+  name         - (string)    Name given to every generated task, wrapped in single or double quotes
+  hours        - (int)       Number of hours each generated task takes
+  days of week - (DayOfWeek) Comma separated list of days a task is due on
+Optional:
+  cardId       - (integer)   Id referencing a Card for its tag and color. Must prefix with '+C'
+  details      - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>, copied to
+                 every generated task
+  skip         - skip=<date>[,<date>...], occurrences not to generate; deleting a generated
+                 task adds its date"#
+            }
+
+            TemplatePat::EntityFirst => {
+                r#"template <id> "<name>" <hours> [cardId] @ <days of week> [details]
+Required:
+  id           - (int)       id of template
+  name         - (string)    Name given to every generated task, wrapped in single or double quotes
+  hours        - (int)       Number of hours each generated task takes
+  days of week - (DayOfWeek) Comma separated list of days a task is due on
+Optional:
+  cardId       - (integer)   Id referencing a Card for its tag and color. Must prefix with '+C'
+  details      - note="<text>", links=<url>[,<url>...] and meta.<key>=<value>, copied to
+                 every generated task
+  skip         - skip=<date>[,<date>...], occurrences not to generate; deleting a generated
+                 task adds its date"#
+            }
+
+            TemplatePat::Fields => {
+                r#"template <id> <field>=<value>...
+Required:
+  id     - (int) id of template
+  fields - One or more of: name, hours, card, days, note, links, meta.<key>.
+           Only the named fields change.
+           e.g. hours=2, days=mon,fri, card=+C2 or card=none, name="New name""#
+            }
+
+            TemplatePat::EntityId => {
+                r#"template <id>
+Required:
+  id    - (int)    id of template"#
+            }
+        }
+    }
+}
+
+impl fmt::Display for TemplatePat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.usage())
+    }
+}
+
+impl PatternIdExt for TemplatePat {
+    fn pattern(&self) -> ArgPattern {
+        match self {
+            TemplatePat::Base => TemplateArgSchema::pattern_base(),
+            TemplatePat::EntityFirst => TemplateArgSchema::pattern_entity_first(),
+            TemplatePat::Fields => TemplateArgSchema::pattern_fields(),
+            TemplatePat::EntityId => TemplateArgSchema::pattern_entity_id(),
+        }
+    }
+}
+
+impl ArgSchema for TemplateArgSchema {
+    type PatternId = TemplatePat;
+
+    fn patterns_for(&self, action: EntityActionType) -> Vec<TemplatePat> {
+        match action {
+            EntityActionType::Add => vec![TemplatePat::Base],
+            EntityActionType::Modify => vec![TemplatePat::EntityFirst, TemplatePat::Fields],
+            EntityActionType::Delete => vec![TemplatePat::EntityId],
+            EntityActionType::Complete => vec![],
+        }
+    }
+}
+
+pub struct TemplateArgValidator;
+impl ArgValidator for TemplateArgValidator {
+    type PatternId = TemplatePat;
+}
+
+pub struct TemplateBuilder;
+impl EntityBuilder<TaskTemplate> for TemplateBuilder {
+    type PatternId = TemplatePat;
+
+    fn create(&self, args: &[Arg], pat_id: TemplatePat) -> Result<TaskTemplate> {
+        match pat_id {
+            TemplatePat::Base => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                let mut template = TaskTemplate::new(
+                    ix.next::<NameArg>().clone(),
+                    ix.next::<IntArg>() as f32,
+                    ix.next_opt::<CardColorIdArg>(),
+                    ix.advance().next::<DaysOfWeekArg>().clone(),
+                );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<TemplateChange>(details)? {
+                    change.apply(&mut template);
+                }
+                Ok(template)
+            }
+            _ => Err(Error::Parse(
+                "No valid ADD pattern matched for template.".into(),
+            )),
+        }
+    }
+
+    fn modify<'a>(
+        &self,
+        existing: &'a mut TaskTemplate,
+        args: &[Arg],
+        pat_id: TemplatePat,
+    ) -> Result<&'a TaskTemplate> {
+        match pat_id {
+            TemplatePat::EntityFirst => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                existing.modify(
+                    ix.advance().advance().next::<NameArg>().clone(),
+                    ix.next::<IntArg>() as f32,
+                    ix.next_opt::<CardColorIdArg>(),
+                    ix.advance().next::<DaysOfWeekArg>().clone(),
+                );
+                let details = ix.next_opt::<FieldSetsArg>();
+                for change in parse_trailing::<TemplateChange>(details)? {
+                    change.apply(existing);
+                }
+                Ok(&*existing)
+            }
+            TemplatePat::Fields => {
+                let pattern = pat_id.pattern();
+                let mut ix = ColumnIndexer::new(args, &pattern);
+                let changes =
+                    parse_changes::<TemplateChange>(ix.advance_times(2).next::<FieldSetsArg>())?;
+                for change in changes {
+                    change.apply(existing);
+                }
+                Ok(&*existing)
+            }
+            _ => Err(Error::Parse(
+                "No valid MODIFY pattern matched for template.".into(),
+            )),
+        }
+    }
+}
+
+/// A field of `mod template <id> field=value...`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplateChange {
+    Name(String),
+    Hours(f32),
+    Card(Option<i32>),
+    Days(Vec<DayOfWeek>),
+    Skipped(Vec<NaiveDate>),
+    Details(DetailChange),
+    Uid(Uid),
+}
+
+impl TemplateChange {
+    fn apply(self, template: &mut TaskTemplate) {
+        match self {
+            TemplateChange::Name(name) => template.name = name,
+            TemplateChange::Hours(hours) => template.hours = hours,
+            TemplateChange::Card(card_id) => template.card_id = card_id,
+            TemplateChange::Days(days) => template.days = days,
+            TemplateChange::Skipped(dates) => {
+                template.skipped.clear();
+                dates.into_iter().for_each(|date| template.skip(date));
+            }
+            TemplateChange::Details(change) => change.apply(&mut template.details),
+            TemplateChange::Uid(uid) => template.uid = uid,
+        }
+    }
+}
+
+impl FieldChange for TemplateChange {
+    const ENTITY: EntityType = EntityType::Template;
+    const FIELDS: &'static [&'static str] = &["name", "hours", "card", "days"];
+    const TRAILING: &'static [&'static str] = &["skip"];
+
+    fn parse(field: &str, value: &str) -> Result<Self> {
+        Ok(match field {
+            "name" => TemplateChange::Name(name_value(value)?),
+            "hours" => TemplateChange::Hours(hours_value(value)?),
+            "card" => TemplateChange::Card(card_value(value)?),
+            "days" => TemplateChange::Days(days_value(value)?),
+            "skip" => TemplateChange::Skipped(
+                value
+                    .split(',')
+                    .map(|d| Date::try_from_str(d.trim()).map(|d| d.0))
+                    .collect::<Result<_>>()?,
+            ),
+            _ => unreachable!("field checked against FIELDS"),
+        })
+    }
+
+    fn from_detail(change: DetailChange) -> Self {
+        TemplateChange::Details(change)
+    }
+
+    fn from_uid(uid: Uid) -> Self {
+        TemplateChange::Uid(uid)
+    }
+
+    fn validate(&self, ctx: &AppContext) -> Result<()> {
+        match self {
+            TemplateChange::Card(card_id) => validate_card(*card_id, ctx),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct TemplateSpec {
+    schema: TemplateArgSchema,
+    validator: TemplateArgValidator,
+    builder: TemplateBuilder,
+}
+
+impl TemplateSpec {
+    pub fn new() -> Self {
+        Self {
+            schema: TemplateArgSchema,
+            validator: TemplateArgValidator,
+            builder: TemplateBuilder,
+        }
+    }
+}
+
+impl EntitySpec<TaskTemplate> for TemplateSpec {
+    type PatternId = TemplatePat;
+
+    fn arg_schema(&self) -> &dyn ArgSchema<PatternId = TemplatePat> {
+        &self.schema
+    }
+    fn arg_validator(&self) -> &dyn ArgValidator<PatternId = TemplatePat> {
+        &self.validator
+    }
+    fn entity_builder(&self) -> &dyn EntityBuilder<TaskTemplate, PatternId = TemplatePat> {
+        &self.builder
+    }
+
+    fn get_mut<'a>(&self, ctx: &'a mut AppContext, id: i32) -> Result<&'a mut TaskTemplate> {
+        ctx.templates.get_mut(id)
+    }
+}
@@ -4,13 +4,14 @@ use super::{
     core::{ArgSlot, ColumnIndexer, EntityBuilder, EntitySpec, PatternIdExt, SlotMatch},
     event::{EventBuilder, EventPat, EventSpec},
     task::{TaskBuilder, TaskPat, TaskSpec},
+    template::{TemplatePat, TemplateSpec},
 };
-use crate::arg::args::Arg;
+use crate::arg::args::{Arg, FieldSet};
 use crate::arg::args::{AtSymbolArg, CardColorIdArg, DateArg, IntArg, NameArg};
 use crate::core::context::AppContext;
-use crate::core::models::Card;
+use crate::core::models::{Card, ChecklistItem, Event, Task, TaskTemplate};
 use crate::core::types::{
-    Bool, CardColor, Date, DayOfWeek, EntityActionType, EntityType, TimeRange,
+    Bool, CardColor, Date, DayOfWeek, EntityActionType, EntityType, Estimate, TaskMarker, TimeRange,
 };
 use crate::errors::Error;
 use strum::IntoEnumIterator;
@@ -265,6 +266,104 @@ fn task_builder_creates_and_modifies() {
     assert!(updated.subtasks.is_empty());
 }
 
+#[test]
+fn task_spec_links_template_and_applies_markers() {
+    let mut ctx = ctx();
+    let template_id = ctx
+        .templates
+        .insert(TaskTemplate::new("Report", 2.0, None, vec![DayOfWeek::Fri]))
+        .id;
+    let args = vec![
+        Arg::Name("Report".into()),
+        Arg::Int(2),
+        Arg::TemplateId(template_id, None),
+        Arg::TaskMarkers(vec![TaskMarker::Done]),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+    ];
+    let task = TaskSpec::new().create(&ctx, &args).unwrap();
+    let origin = task.origin.expect("task should keep its template origin");
+    assert_eq!(origin.template_id, template_id);
+    assert_eq!(origin.occurrence, future_date().0);
+    assert!(task.completed);
+    assert_eq!(task.remaining_hours, 0.0);
+
+    let stored_id = ctx.tasks.insert(task).id;
+    let args_mod = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(stored_id),
+        Arg::Name("Report v2".into()),
+        Arg::Int(3),
+        Arg::AtSymbol,
+        Arg::Date(Date::try_from_str("2099-01-05").unwrap()),
+    ];
+    let updated = TaskSpec::new()
+        .modify(&mut ctx, &args_mod, stored_id)
+        .unwrap();
+    assert_eq!(updated.origin, Some(origin));
+    assert!(!updated.completed);
+}
+
+#[test]
+fn task_spec_rejects_unknown_template() {
+    let ctx = ctx();
+    let args = vec![
+        Arg::Name("Report".into()),
+        Arg::Int(2),
+        Arg::TemplateId(4, None),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+    ];
+    match TaskSpec::new().create(&ctx, &args) {
+        Err(Error::Parse(msg)) => assert!(msg.contains("Template id 4 does not exist")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+// ---------- template ----------
+#[test]
+fn template_spec_creates_and_modifies() {
+    let mut ctx = ctx();
+    let args = vec![
+        Arg::Name("Status report".into()),
+        Arg::Int(2),
+        Arg::AtSymbol,
+        Arg::DaysOfWeek(vec![DayOfWeek::Fri]),
+    ];
+    let template = TemplateSpec::new().create(&ctx, &args).unwrap();
+    assert_eq!(template.name, "Status report");
+    assert_eq!(template.hours, 2.0);
+    assert_eq!(template.days, vec![DayOfWeek::Fri]);
+
+    let stored_id = ctx.templates.insert(template).id;
+    let args_mod = vec![
+        Arg::EntityType(EntityType::Template),
+        Arg::Int(stored_id),
+        Arg::Name("Chores".into()),
+        Arg::Int(1),
+        Arg::AtSymbol,
+        Arg::DaysOfWeek(vec![DayOfWeek::Sat, DayOfWeek::Sun]),
+    ];
+    let updated = TemplateSpec::new()
+        .modify(&mut ctx, &args_mod, stored_id)
+        .unwrap();
+    assert_eq!(updated.name, "Chores");
+    assert_eq!(updated.days, vec![DayOfWeek::Sat, DayOfWeek::Sun]);
+}
+
+#[test]
+fn template_spec_requires_days() {
+    let ctx = ctx();
+    let args = vec![Arg::Name("Report".into()), Arg::Int(2), Arg::AtSymbol];
+    assert!(TemplateSpec::new().create(&ctx, &args).is_err());
+    assert_eq!(
+        TemplateSpec::new()
+            .arg_schema()
+            .patterns_for(EntityActionType::Complete),
+        Vec::<TemplatePat>::new()
+    );
+}
+
 // ---------- event ----------
 #[test]
 fn event_builder_creates_event() {
@@ -372,3 +471,320 @@ fn entity_specs_match_patterns() {
     let event = EventSpec::new().create(&ctx, &event_args).unwrap();
     assert_eq!(event.name, "E");
 }
+
+#[test]
+fn task_spec_reads_optional_estimate_and_modify_replaces_it() {
+    let mut ctx = ctx();
+    let estimate = Estimate::try_from_str("~1/2/5").unwrap();
+    let args = vec![
+        Arg::Name("Essay".into()),
+        Arg::Int(2),
+        Arg::Estimate(estimate),
+        Arg::CardColorId(1),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+    ];
+    ctx.cards.insert(Card::new("School", CardColor::Blue));
+    let task = TaskSpec::new().create(&ctx, &args).unwrap();
+    assert_eq!(task.estimate, Some(estimate));
+    assert_eq!(task.card_id, Some(1));
+
+    let stored_id = ctx.tasks.insert(task).id;
+    let args_mod = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(stored_id),
+        Arg::Name("Essay".into()),
+        Arg::Int(2),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+    ];
+    let updated = TaskSpec::new()
+        .modify(&mut ctx, &args_mod, stored_id)
+        .unwrap();
+    assert_eq!(updated.estimate, None);
+}
+
+fn field_sets(pairs: &[(&str, &str)]) -> Arg {
+    Arg::FieldSets(
+        pairs
+            .iter()
+            .map(|(field, value)| FieldSet {
+                field: field.to_string(),
+                value: value.to_string(),
+                quoted: false,
+            })
+            .collect(),
+    )
+}
+
+#[test]
+fn task_fields_pattern_changes_only_named_fields() {
+    let mut ctx = ctx();
+    ctx.cards.insert(Card::new("School", CardColor::Blue));
+    let mut task = Task::new("Essay", 2.0, None, future_date());
+    task.push_subtask_with_hours(sample_time_range(), future_date().0, 1.0);
+    let id = ctx.tasks.insert(task).id;
+
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(id),
+        field_sets(&[("name", "Long essay"), ("card", "+C1")]),
+    ];
+    let updated = TaskSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert_eq!(updated.name, "Long essay");
+    assert_eq!(updated.card_id, Some(1));
+    assert_eq!(updated.hours, 2.0);
+    assert_eq!(updated.subtasks.len(), 1, "renaming keeps the schedule");
+    assert_eq!(updated.remaining_hours, 1.0);
+
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(id),
+        field_sets(&[("hours", "5"), ("card", "none")]),
+    ];
+    let updated = TaskSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert_eq!(updated.hours, 5.0);
+    assert_eq!(updated.card_id, None);
+    assert!(updated.subtasks.is_empty(), "new hours need a new schedule");
+    assert_eq!(updated.remaining_hours, 5.0);
+}
+
+#[test]
+fn fields_pattern_rejects_unknown_fields_and_invalid_values() {
+    let mut ctx = ctx();
+    let id = ctx
+        .tasks
+        .insert(Task::new("Essay", 2.0, None, future_date()))
+        .id;
+    let err_for = |ctx: &mut AppContext, pairs: &[(&str, &str)]| {
+        let args = vec![
+            Arg::EntityType(EntityType::Task),
+            Arg::Int(id),
+            field_sets(pairs),
+        ];
+        match TaskSpec::new().modify(ctx, &args, id) {
+            Err(Error::Parse(msg)) => msg,
+            other => panic!("expected parse error, got {other:?}"),
+        }
+    };
+
+    let msg = err_for(&mut ctx, &[("colour", "red")]);
+    assert!(msg.starts_with("Unknown task field: 'colour'. Valid fields: name, hours"));
+    assert!(err_for(&mut ctx, &[("hours", "0")]).contains("Hours must be greater than 0"));
+    assert!(err_for(&mut ctx, &[("card", "+C4")]).contains("Card id 4 does not exist"));
+    assert!(err_for(&mut ctx, &[("name", "a"), ("name", "b")]).contains("more than once"));
+    assert_eq!(ctx.tasks.get(id).unwrap().name, "Essay");
+}
+
+#[test]
+fn event_fields_pattern_keeps_recurring_days_consistent() {
+    let mut ctx = ctx();
+    let event = Event::new(
+        true,
+        "Standup",
+        None,
+        vec![DayOfWeek::Mon, DayOfWeek::Wed],
+        sample_time_range(),
+    );
+    let id = ctx.events.insert(event).id;
+
+    let args = vec![
+        Arg::EntityType(EntityType::Event),
+        Arg::Int(id),
+        field_sets(&[("time", "10-11AM")]),
+    ];
+    let updated = EventSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert_eq!(updated.time_range.to_string(), "10:00AM-11:00AM");
+    assert_eq!(updated.days, vec![DayOfWeek::Mon, DayOfWeek::Wed]);
+
+    let args = vec![
+        Arg::EntityType(EntityType::Event),
+        Arg::Int(id),
+        field_sets(&[("recurring", "false")]),
+    ];
+    assert!(EventSpec::new().modify(&mut ctx, &args, id).is_err());
+    assert!(
+        ctx.events.get(id).unwrap().recurring,
+        "failed modify changes nothing"
+    );
+
+    let args = vec![
+        Arg::EntityType(EntityType::Event),
+        Arg::Int(id),
+        field_sets(&[("recurring", "false"), ("days", "tue")]),
+    ];
+    let updated = EventSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert!(!updated.recurring);
+    assert_eq!(updated.days, vec![DayOfWeek::Tue]);
+}
+
+#[test]
+fn details_are_set_on_add_and_changed_by_field_mod() {
+    let mut ctx = ctx();
+    let args = vec![
+        Arg::Name("Study".into()),
+        Arg::CardColor(CardColor::Blue),
+        field_sets(&[
+            ("note", "Course cards"),
+            ("links", "https://a.org,https://b.org"),
+            ("meta.term", "fall"),
+        ]),
+    ];
+    let card = CardSpec::new().create(&ctx, &args).unwrap();
+    assert_eq!(card.details.notes.as_deref(), Some("Course cards"));
+    assert_eq!(card.details.links, vec!["https://a.org", "https://b.org"]);
+    assert_eq!(
+        card.details.meta.get("term").map(String::as_str),
+        Some("fall")
+    );
+    let id = ctx.cards.insert(card).id;
+
+    let args = vec![
+        Arg::EntityType(EntityType::Card),
+        Arg::Int(id),
+        field_sets(&[("note", "none"), ("meta.term", "none"), ("meta.room", "B2")]),
+    ];
+    let updated = CardSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert_eq!(updated.details.notes, None);
+    assert_eq!(updated.details.links.len(), 2, "links were not named");
+    assert_eq!(
+        updated.details.meta.keys().collect::<Vec<_>>(),
+        vec!["room"]
+    );
+
+    let args = vec![
+        Arg::Name("Essay".into()),
+        Arg::Int(2),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+        field_sets(&[("hours", "3")]),
+    ];
+    match TaskSpec::new().create(&ctx, &args) {
+        Err(Error::Parse(msg)) => {
+            assert!(msg.contains("Unknown task field: 'hours'. Valid fields: item, note, links"))
+        }
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn checklist_items_roll_up_on_add_and_append_by_field_mod() {
+    let mut ctx = ctx();
+    let args = vec![
+        Arg::Name("Essay".into()),
+        Arg::Int(1),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+        field_sets(&[
+            ("item", "Outline ~1.5"),
+            ("item", "[x] Draft ~2"),
+            ("note", "Chapters 3-4"),
+        ]),
+    ];
+    let task = TaskSpec::new().create(&ctx, &args).unwrap();
+    assert_eq!(task.hours, 3.5, "estimates replace the given hours");
+    assert_eq!(task.open_hours(), 1.5, "the checked item is no longer open");
+    assert_eq!(task.progress(), Some((1, 2)));
+    assert_eq!(task.checklist[1].to_string(), "[x] Draft ~2");
+    let id = ctx.tasks.insert(task).id;
+
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(id),
+        field_sets(&[("item", "Proofread"), ("item", "Submit ~0.5")]),
+    ];
+    let updated = TaskSpec::new().modify(&mut ctx, &args, id).unwrap();
+    let texts: Vec<&str> = updated.checklist.iter().map(|i| i.text.as_str()).collect();
+    assert_eq!(texts, ["Outline", "Draft", "Proofread", "Submit"]);
+    assert_eq!(updated.hours, 4.0);
+
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(id),
+        field_sets(&[("item", "Slides ~0")]),
+    ];
+    match TaskSpec::new().modify(&mut ctx, &args, id) {
+        Err(Error::Parse(msg)) => assert!(msg.contains("Invalid item hours: '0'"), "{msg}"),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn fractional_hours_are_only_accepted_alongside_estimated_items() {
+    let ctx = ctx();
+    let args = |details: &[(&str, &str)]| {
+        vec![
+            Arg::Name("Essay".into()),
+            Arg::Hours(4.5),
+            Arg::AtSymbol,
+            Arg::Date(future_date()),
+            field_sets(details),
+        ]
+    };
+    let task = TaskSpec::new()
+        .create(
+            &ctx,
+            &args(&[("item", "Outline ~2"), ("item", "Draft ~2.5")]),
+        )
+        .unwrap();
+    assert_eq!(task.hours, 4.5);
+
+    match TaskSpec::new().create(&ctx, &args(&[("item", "Outline")])) {
+        Err(Error::Parse(msg)) => assert!(msg.contains("must be a whole number"), "{msg}"),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn estimated_checklist_items_keep_deciding_the_hours_on_mod() {
+    let mut ctx = ctx();
+    let mut task = Task::new("Essay", 1.0, None, future_date());
+    task.checklist
+        .push(ChecklistItem::new("Outline", Some(1.5)));
+    task.roll_up_checklist();
+    let id = ctx.tasks.insert(task).id;
+
+    for sets in [
+        field_sets(&[("hours", "5")]),
+        field_sets(&[("hours", "5"), ("name", "Paper")]),
+    ] {
+        let args = vec![Arg::EntityType(EntityType::Task), Arg::Int(id), sets];
+        match TaskSpec::new().modify(&mut ctx, &args, id) {
+            Err(Error::Parse(msg)) => assert!(
+                msg.contains("takes its hours from its checklist estimates"),
+                "{msg}"
+            ),
+            other => panic!("expected parse error, got {other:?}"),
+        }
+    }
+    assert_eq!(ctx.tasks.get(id).unwrap().name, "Essay");
+
+    // A full modify gives hours too; the estimates replace them as on add.
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(id),
+        Arg::Name("Paper".into()),
+        Arg::Int(5),
+        Arg::AtSymbol,
+        Arg::Date(future_date()),
+    ];
+    let updated = TaskSpec::new().modify(&mut ctx, &args, id).unwrap();
+    assert_eq!((updated.name.as_str(), updated.hours), ("Paper", 1.5));
+
+    // Without estimates, hours are set as usual.
+    let mut plain = Task::new("Slides", 1.0, None, future_date());
+    plain.checklist.push(ChecklistItem::new("Draft", None));
+    let plain_id = ctx.tasks.insert(plain).id;
+    let args = vec![
+        Arg::EntityType(EntityType::Task),
+        Arg::Int(plain_id),
+        field_sets(&[("hours", "4")]),
+    ];
+    assert_eq!(
+        TaskSpec::new()
+            .modify(&mut ctx, &args, plain_id)
+            .unwrap()
+            .hours,
+        4.0
+    );
+}
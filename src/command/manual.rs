@@ -9,8 +9,10 @@ use crate::command::entity_spec::{
     core::{EntitySpec, PatternIdExt},
     event::EventSpec,
     task::TaskSpec,
+    template::TemplateSpec,
 };
 use crate::core::types::{EntityActionType, EntityType, GlobalCommand, TypeHelpCommand};
+use crate::core::view;
 use crate::errors::{Error, Result};
 use crate::extensions::enums::valid_csv;
 
@@ -23,11 +25,33 @@ pub enum ManualTopic {
     Task,
     Event,
     Card,
+    Template,
     Config,
     Schedule,
+    Forecast,
     Log,
     Save,
+    #[strum(
+        serialize = "read",
+        serialize = "convert",
+        serialize = "import",
+        to_string = "read"
+    )]
     Read,
+    Undo,
+    Find,
+    Show,
+    Item,
+    #[strum(
+        serialize = "commit",
+        serialize = "history",
+        serialize = "checkout",
+        serialize = "diff",
+        serialize = "branch",
+        serialize = "merge",
+        to_string = "commit"
+    )]
+    Commit,
     Man,
     Date,
     Time,
@@ -145,6 +169,12 @@ impl ManualCatalog {
                 vec![
                     "Tasks track required hours and a due date.".to_string(),
                     "They are scheduled before the due date when possible.".to_string(),
+                    "Completed tasks (done task <id> or !done) are no longer scheduled."
+                        .to_string(),
+                    "Atomic tasks (!atomic) are placed in one contiguous block, ahead of other tasks."
+                        .to_string(),
+                    "A checklist (see man item) shows its progress as (x/y) after the name."
+                        .to_string(),
                 ],
                 TaskSpec::new(),
             ),
@@ -163,9 +193,26 @@ impl ManualCatalog {
                 vec![
                     "Cards provide colored labels for tasks and events.".to_string(),
                     "Reference cards with +C<id> when adding entities.".to_string(),
+                    "Deleting a card in use follows CARD_DELETE_POLICY, or a flag:".to_string(),
+                    "--restrict refuses and lists the users, --cascade deletes them,".to_string(),
+                    "--set-null keeps them without a card.".to_string(),
                 ],
                 CardSpec::new(),
             ),
+            ManualTopic::Template => self.entity_page(
+                EntityType::Template,
+                "Create and manage recurring task templates.",
+                vec![
+                    "Templates generate one task per matching day inside the planning window."
+                        .to_string(),
+                    "Generated tasks can be modified or marked done like any other task."
+                        .to_string(),
+                    "Deleting a generated task skips its date, so it is not generated again."
+                        .to_string(),
+                    "Deleting a template keeps its generated tasks as one-off tasks.".to_string(),
+                ],
+                TemplateSpec::new(),
+            ),
             ManualTopic::Config => self.simple_page(
                 "config",
                 "View or edit configuration values.",
@@ -179,7 +226,33 @@ impl ManualCatalog {
                 "schedule",
                 "Generate a schedule based on current tasks and events.",
                 vec!["schedule".to_string()],
-                vec!["Uses the current config to build a schedule.".to_string()],
+                vec![
+                    "Uses the current config to build a schedule.".to_string(),
+                    "Reports the number of context switches between differently tagged tasks."
+                        .to_string(),
+                    "TASK_PACKING_MODE=group-by-card keeps tasks sharing a card back to back."
+                        .to_string(),
+                    "STABLE_RESCHEDULING=True keeps previous slices where they still fit and"
+                        .to_string(),
+                    "reports how many slices moved.".to_string(),
+                ],
+            ),
+            ManualTopic::Forecast => self.simple_page(
+                "forecast",
+                "Estimate how likely each task is to finish by its due date.",
+                vec!["forecast [runs]".to_string()],
+                vec![
+                    "Plans the schedule many times (default 500) without changing it.".to_string(),
+                    "Tasks with an estimate (~optimistic/likely/pessimistic hours) get sampled hours."
+                        .to_string(),
+                    "Shows the on-time probability and the P50/P90 completion dates per task."
+                        .to_string(),
+                    "Hours that miss the due date are planned on the days after it, so a late"
+                        .to_string(),
+                    "percentile shows when the task would finish. 'missed' means that share of"
+                        .to_string(),
+                    "runs did not finish within a year of the due date.".to_string(),
+                ],
             ),
             ManualTopic::Log => self.simple_page(
                 "log",
@@ -193,14 +266,180 @@ impl ManualCatalog {
             ManualTopic::Save => self.simple_page(
                 "save",
                 "Save tasks, events, and cards to a schedule file.",
-                vec!["save \"<name>\"".to_string()],
-                vec!["Writes to schedules/<name>.json.".to_string()],
+                vec![
+                    "save \"<name>\" [--with-config]".to_string(),
+                    "save [--with-config]".to_string(),
+                ],
+                vec![
+                    "Writes to schedules/<name>.json; without a name, to the workspace.".to_string(),
+                    "--with-config also writes the settings the schedule is built with (RANGE,"
+                        .to_string(),
+                    "order, overflow, packing, start date), which read then offers to apply."
+                        .to_string(),
+                    "Set WORKSPACE (e.g. main) to load schedules/main.json at startup and".to_string(),
+                    "autosave to it every AUTOSAVE_INTERVAL changes and on exit (0 turns".to_string(),
+                    "autosave off). The line above the prompt shows unsaved changes.".to_string(),
+                    "Changes made since startup are also journaled under schedules/.journal until"
+                        .to_string(),
+                    "a clean exit; after a crash, planit offers to replay them on the next start."
+                        .to_string(),
+                ],
             ),
             ManualTopic::Read => self.simple_page(
                 "read",
                 "Load tasks, events, and cards from a schedule file.",
-                vec!["read \"<path>\"".to_string()],
-                vec!["Loads entities into the current session.".to_string()],
+                vec![
+                    "read \"<path>\" [--preview|--yes]".to_string(),
+                    "import \"<path>\" [--skip|--rename|--overwrite]".to_string(),
+                    "convert \"<from>\" \"<to>\"".to_string(),
+                ],
+                vec![
+                    "read replaces the entities in the current session with the file's.".to_string(),
+                    "import adds them instead. Cards matching an existing card by name and color"
+                        .to_string(),
+                    "are reused; a task named like an existing one is skipped (--skip, default),"
+                        .to_string(),
+                    "imported as \"<name> (2)\" (--rename) or replaces it (--overwrite).".to_string(),
+                    "A failed import leaves the plan as it was.".to_string(),
+                    "--preview shows the file's entities and what reading it would change,"
+                        .to_string(),
+                    "without loading anything. A row that fails to load is named in the error,"
+                        .to_string(),
+                    "e.g. 'Invalid save file at tasks row 2 (...)'.".to_string(),
+                    "A file saved with --with-config lists the plan settings that differ from"
+                        .to_string(),
+                    "the config and asks before applying them; --yes applies them right away."
+                        .to_string(),
+                    "Files written by older versions of planit are upgraded as they are read;"
+                        .to_string(),
+                    "files from a newer version are refused until planit is updated.".to_string(),
+                    "Either save format is accepted: token lists, or typed fields per entity"
+                        .to_string(),
+                    "(SAVE_FORMAT chooses which one save writes). convert writes <from> to <to>"
+                        .to_string(),
+                    "in the other format.".to_string(),
+                ],
+            ),
+            ManualTopic::Undo => self.simple_page(
+                "undo",
+                "Revert or reapply changes made in this session.",
+                vec!["undo".to_string(), "redo".to_string()],
+                vec![
+                    "Add, mod, del, done, config, schedule, read and import can be undone.".to_string(),
+                    "Undo restores entities and config as they were before the command and"
+                        .to_string(),
+                    "lists what it reverted; redo reapplies it.".to_string(),
+                    "A new change after undo clears the redo history.".to_string(),
+                    "HISTORY_DEPTH sets how many changes are kept (0 turns undo off).".to_string(),
+                ],
+            ),
+            ManualTopic::Find => self.simple_page(
+                "find",
+                "List the entities of one type that match a set of filters.",
+                vec!["find <entity> [filter...] [--sort <columns>] [--columns <columns>]".to_string()],
+                vec![
+                    "Every filter must match; with no filters all entities are listed.".to_string(),
+                    "A filter is <field><op><value>, or a yes/no field on its own (! negates)."
+                        .to_string(),
+                    "Task fields: name, hours, due, card, done, atomic, scheduled, unscheduled,"
+                        .to_string(),
+                    "overdue. Event fields: name, hours, card, day, recurring.".to_string(),
+                    "Template fields: name, hours, card, day. Card fields: name, color.".to_string(),
+                    "Use = != < <= > >= on numbers and dates, = != ~ on names and = != on"
+                        .to_string(),
+                    "card, day and color. name~text matches a substring, name~/regex/ a pattern."
+                        .to_string(),
+                    "Dates take today, tomorrow, a weekday (the next one, today included) or a"
+                        .to_string(),
+                    "date. Cards take +C<id>, <id> or none. Quote values with spaces.".to_string(),
+                    "Example: find task due<fri card=+C2 name~\"report\" !done".to_string(),
+                    "--sort and --columns work as when listing entities (see man task)."
+                        .to_string(),
+                    "Every entity also has note, links and meta.<key>: text fields that match"
+                        .to_string(),
+                    "with = != ~, or test for presence on their own (e.g. meta.course).".to_string(),
+                    "uid is a text field holding the entity's permanent identifier.".to_string(),
+                ],
+            ),
+            ManualTopic::Show => self.simple_page(
+                "show",
+                "Show one entity in full, with its notes, links and metadata.",
+                vec!["show <entity> <id>".to_string()],
+                vec![
+                    "Lists every column of the entity, then its note, links and meta.<key>"
+                        .to_string(),
+                    "values. Set them with note=\"<text>\", links=<url>[,<url>...] and".to_string(),
+                    "meta.<key>=<value> after the arguments of an add, or with".to_string(),
+                    "mod <entity> <id> note=...; the value none clears a field or removes a key."
+                        .to_string(),
+                    "Tasks generated from a template copy its details.".to_string(),
+                    "The uid row is the entity's permanent identifier: ids are numbered afresh"
+                        .to_string(),
+                    "each session, but the uid is kept in save files and history. It is given"
+                        .to_string(),
+                    "when an entity is added and cannot be set with add or mod.".to_string(),
+                ],
+            ),
+            ManualTopic::Item => self.simple_page(
+                "item",
+                "Keep a checklist of steps within a task.",
+                vec![
+                    "item <taskId>".to_string(),
+                    "item add <taskId> <text> [~hours]".to_string(),
+                    "item check <taskId> <n> | item uncheck <taskId> <n>".to_string(),
+                    "item move <taskId> <n> <position>".to_string(),
+                    "item del <taskId> <n>".to_string(),
+                ],
+                vec![
+                    "Items are numbered from 1 in their order. Once any item has an estimate"
+                        .to_string(),
+                    "(e.g. ~1.5), the task's hours are the sum of the item estimates, and".to_string(),
+                    "mod task <id> hours=... is refused.".to_string(),
+                    "Checked items with an estimate no longer count toward remaining hours."
+                        .to_string(),
+                    "Items can also end an add or be appended with mod task <id>, as".to_string(),
+                    "item=\"[x] <text> ~<hours>\" (the mark and estimate are optional)."
+                        .to_string(),
+                    "The task table shows the checked and total items after the name; show"
+                        .to_string(),
+                    "task <id> lists them in a progress row.".to_string(),
+                ],
+            ),
+            ManualTopic::Commit => self.simple_page(
+                "commit",
+                "Keep a version history of the plan.",
+                vec![
+                    "commit \"<message>\"".to_string(),
+                    "history".to_string(),
+                    "checkout <branch|rev>".to_string(),
+                    "diff <rev> [rev]".to_string(),
+                    "branch [name]".to_string(),
+                    "merge <branch|rev>".to_string(),
+                ],
+                vec![
+                    "Commit records the current entities, author and time on top of HEAD."
+                        .to_string(),
+                    "History lists commits from HEAD back to the first one.".to_string(),
+                    "Checkout replaces the current entities with a commit's and moves HEAD;"
+                        .to_string(),
+                    "a branch name switches to that branch. It can be undone.".to_string(),
+                    "Diff prints -/+ lines per entity that differs; with one revision it"
+                        .to_string(),
+                    "compares against the current plan.".to_string(),
+                    "Branch lists branches (* marks the current one) or starts one at HEAD."
+                        .to_string(),
+                    "Merge combines another branch's changes since the common commit with"
+                        .to_string(),
+                    "yours and commits the result. Entities are matched by uid; one changed"
+                        .to_string(),
+                    "differently on both sides is a conflict you resolve by keeping ours or"
+                        .to_string(),
+                    "theirs. Commit pending changes before merging.".to_string(),
+                    "A revision is a branch, HEAD, HEAD~<n> or at least 4 characters of a"
+                        .to_string(),
+                    "commit hash.".to_string(),
+                    "History is stored under schedules/.planit.".to_string(),
+                ],
             ),
             ManualTopic::Man => self.simple_page(
                 "man",
@@ -262,26 +501,58 @@ impl ManualCatalog {
         P: Copy + Eq + PatternIdExt + Display,
     {
         let mut usage = Vec::new();
-        for (idx, group) in [
-            self.usage_lines(EntityActionType::Add, &spec),
-            self.usage_lines(EntityActionType::Modify, &spec),
-            self.usage_lines(EntityActionType::Delete, &spec),
-        ]
-        .into_iter()
-        .enumerate()
+        for group in EntityActionType::iter()
+            .map(|action| self.usage_lines(action, &spec))
+            .filter(|group| !group.is_empty())
         {
-            usage.extend(group);
-            if idx < 2 {
+            if !usage.is_empty() {
                 usage.push(String::new());
             }
+            usage.extend(group);
         }
 
+        let listing = vec![
+            format!("{entity} [--sort <columns>] [--columns <columns>]"),
+            format!("Columns: {}", view::columns(entity).join(", ")),
+            "--sort orders rows by the listed columns in turn; prefix - for descending."
+                .to_string(),
+            "--columns picks which columns to show, in that order.".to_string(),
+            "DEFAULT_SORT and DEFAULT_COLUMNS in config set the defaults.".to_string(),
+        ];
+
+        let done = if entity == EntityType::Task {
+            "|done"
+        } else {
+            ""
+        };
+        let bulk = vec![
+            format!("mod|del{done} {entity} <ids> ...  # ids and ranges, e.g. 1,4,7-12"),
+            format!("mod|del{done} {entity} where <filter...> ...  # filters as in 'find'"),
+            format!("mod {entity} where <filter...> set <field>=<value>..."),
+            "Runs once per selected id, all or nothing, after a confirmation prompt.".to_string(),
+            "Add --yes to skip the prompt.".to_string(),
+        ];
+
+        let details = vec![
+            "note=\"<text>\" links=<url>[,<url>...] meta.<key>=<value> can end an add or"
+                .to_string(),
+            format!("mod {entity} <id> ..., or be set alone with mod {entity} <id> note=..."),
+            "The value none clears a note or links, or removes a metadata key; quote it"
+                .to_string(),
+            "(note=\"none\") to keep the word as text.".to_string(),
+            format!("show {entity} <id> lists them; find filters on note, links and meta.<key>."),
+        ];
+
         ManualPageBuilder::new(entity.to_string(), summary)
             .section("SYNOPSIS", usage)
             .section("DESCRIPTION", description)
+            .section("LISTING", listing)
+            .section("BULK", bulk)
+            .section("DETAILS", details)
             .section(
                 "SEE ALSO",
                 vec![
+                    GlobalCommand::Show.to_string(),
                     GlobalCommand::Schedule.to_string(),
                     GlobalCommand::Config.to_string(),
                     GlobalCommand::Man.to_string(),
@@ -315,15 +586,34 @@ impl ManualCatalog {
 
 fn general_command_lines() -> Vec<String> {
     vec![
-        "task \"<name>\" <hours> [cardId] @ <date>  # Add a task".to_string(),
+        "task \"<name>\" <hours> [estimate] [cardId] @ <date>  # Add a task".to_string(),
+        "template \"<name>\" <hours> [cardId] @ <days>  # Add a recurring task template"
+            .to_string(),
         "event <recurring> \"<name>\" [cardId] @ [days] <timeRange>  # Add an event".to_string(),
         "card \"<name>\" <color>                   # Add a card".to_string(),
         "mod <entity> <id> ...                     # Modify an entity".to_string(),
+        "mod <entity> <id> <field>=<value>...     # Change only the named fields".to_string(),
         "del <entity> <id>                         # Delete an entity".to_string(),
+        "del <entity> 1,4,7-12 | where <filter...> # Delete several entities at once".to_string(),
+        "done task <id>                            # Mark a task as completed".to_string(),
         "schedule                                 # Build the schedule".to_string(),
+        "forecast [runs]                          # Simulate on-time probability per task"
+            .to_string(),
         "config                                   # View or edit config".to_string(),
-        "save \"<name>\"                           # Save to schedules/<name>.json".to_string(),
-        "read \"<path>\"                           # Load from a saved schedule file".to_string(),
+        "find <entity> [filter...]                # List entities matching filters".to_string(),
+        "show <entity> <id>                       # Show an entity with notes, links, metadata"
+            .to_string(),
+        "item <taskId> [add|check|move|del] ...   # Manage a task's checklist".to_string(),
+        "undo | redo                              # Revert or reapply the last change".to_string(),
+        "commit \"<message>\" | history              # Record or list plan versions".to_string(),
+        "checkout <rev> | diff <rev> [rev]        # Restore or compare plan versions".to_string(),
+        "save \"<name>\" [--with-config]            # Save to schedules/<name>.json".to_string(),
+        "read \"<path>\" [--preview]                # Load (or preview) a saved schedule file"
+            .to_string(),
+        "import \"<path>\" [--rename|...]           # Merge a saved schedule file into the plan"
+            .to_string(),
+        "convert \"<from>\" \"<to>\"                  # Rewrite a save file in the other format"
+            .to_string(),
         "log                                      # Print the session log".to_string(),
         "man [topic]                              # Show manual pages".to_string(),
         "date | time | colors                     # Type helper commands".to_string(),
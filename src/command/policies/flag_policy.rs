@@ -1,5 +1,6 @@
 use crate::arg::args::Arg;
-use crate::core::types::Flag;
+use crate::core::types::{EntityType, Flag};
+use crate::core::view::TableView;
 
 #[derive(Debug)]
 pub enum FlagDecision {
@@ -27,6 +28,17 @@ impl FlagRule for HelpAtIdx {
     }
 }
 
+/// Rejects `--sort`/`--columns` options that name a column the entity table does not have.
+pub struct TableOptionsFor(pub EntityType);
+impl FlagRule for TableOptionsFor {
+    fn check(&self, args: &[Arg]) -> FlagDecision {
+        match TableView::check_args(self.0, args) {
+            Ok(()) => FlagDecision::Continue,
+            Err(e) => FlagDecision::Error(e),
+        }
+    }
+}
+
 pub struct FlagPolicy {
     rules: Vec<Box<dyn FlagRule>>,
 }
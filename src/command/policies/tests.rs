@@ -1,6 +1,7 @@
-use super::flag_policy::{FlagDecision, FlagPolicy, FlagRule, HelpAtIdx};
+use super::flag_policy::{FlagDecision, FlagPolicy, FlagRule, HelpAtIdx, TableOptionsFor};
 use crate::arg::args::Arg;
-use crate::core::types::Flag;
+use crate::core::types::{EntityType, Flag};
+use crate::core::view::TableOption;
 
 struct AlwaysError;
 impl FlagRule for AlwaysError {
@@ -41,3 +42,19 @@ fn first_rule_wins_in_order() {
         other => panic!("expected error from first rule, got {other:?}"),
     }
 }
+
+#[test]
+fn table_options_rule_rejects_columns_the_entity_lacks() {
+    let policy = FlagPolicy::new(vec![Box::new(TableOptionsFor(EntityType::Card))]);
+    let args = vec![Arg::TableOption(TableOption::Columns(vec![
+        "name".into(),
+        "due".into(),
+    ]))];
+    match policy.evaluate(&args) {
+        FlagDecision::Error(e) => assert!(e.to_string().contains("Unknown card column: 'due'")),
+        other => panic!("expected unknown column error, got {other:?}"),
+    }
+
+    let args = vec![Arg::TableOption(TableOption::Columns(vec!["color".into()]))];
+    assert!(matches!(policy.evaluate(&args), FlagDecision::Continue));
+}
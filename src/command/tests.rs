@@ -55,12 +55,24 @@ fn entity_action_resolver_builds_command_when_args_valid() {
     assert!(usage.to_lowercase().contains("task"));
 }
 
+#[test]
+fn entity_action_resolver_resolves_done_for_tasks() {
+    let resolver = EntityActionResolver;
+    assert!(resolver.can_resolve("done"));
+    let args = vec![Arg::EntityType(EntityType::Task), Arg::Int(1)];
+    let cmd = resolver
+        .resolve("done", &args)
+        .expect("should resolve done");
+    assert!(usage_of(cmd.as_ref()).contains("done task <id>"));
+}
+
 #[test]
 fn add_entity_resolver_matches_entity_types() {
     let resolver = AddEntityResolver;
     assert!(resolver.can_resolve("task"));
     assert!(resolver.can_resolve("event"));
     assert!(resolver.can_resolve("card"));
+    assert!(resolver.can_resolve("template"));
     assert!(!resolver.can_resolve("mod"));
 }
 
@@ -83,6 +95,11 @@ fn global_resolver_matches_schedule_and_config() {
 
     let man_cmd = resolver.resolve("man", &[]).expect("man should resolve");
     assert!(man_cmd.usage().to_lowercase().contains("man"));
+
+    let forecast_cmd = resolver
+        .resolve("forecast", &[])
+        .expect("forecast should resolve");
+    assert!(forecast_cmd.usage().starts_with("forecast [runs]"));
 }
 
 #[test]
@@ -2,6 +2,7 @@ pub mod models;
 #[cfg(test)]
 mod tests;
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::ops::Index;
 use std::path::{Path, PathBuf};
@@ -11,10 +12,17 @@ use strum::IntoEnumIterator;
 use strum_macros::{AsRefStr, Display, EnumIter as EnumIterDerive, EnumString};
 
 use crate::config::models::{
-    ConfigItem, FileLoggingConfigItem, RangeConfigItem, StartDateConfigItem,
-    TaskOverflowPolicyConfigItem, TaskSchedulingOrderConfigItem,
+    AutosaveIntervalConfigItem, CardDeletePolicyConfigItem, ConfigItem, DefaultColumnsConfigItem,
+    DefaultSortConfigItem, FileLoggingConfigItem, HistoryDepthConfigItem, RangeConfigItem,
+    SaveFormatConfigItem, StableReschedulingConfigItem, StartDateConfigItem,
+    TaskOverflowPolicyConfigItem, TaskPackingModeConfigItem, TaskSchedulingOrderConfigItem,
+    WorkspaceConfigItem,
 };
-use crate::core::types::{TaskOverflowPolicy, TaskSchedulingOrder, TimeRange};
+use crate::core::types::{
+    CardDeletePolicy, SaveFormat, TaskOverflowPolicy, TaskPackingMode, TaskSchedulingOrder,
+    TimeRange,
+};
+use crate::core::view::{ColumnDefaults, SortDefaults};
 use crate::errors::{Error, Result};
 use crate::extensions::enums::valid_csv;
 use chrono::NaiveDate;
@@ -27,8 +35,36 @@ pub enum ConfigKey {
     TaskSchedulingOrder,
     ScheduleStartDate,
     FileLoggingEnabled,
+    TaskPackingMode,
+    StableRescheduling,
+    HistoryDepth,
+    CardDeletePolicy,
+    DefaultSort,
+    DefaultColumns,
+    Workspace,
+    AutosaveInterval,
+    SaveFormat,
+}
+
+impl ConfigKey {
+    /// Settings that shape the schedule built from a plan, which `save --with-config` writes
+    /// into the save file. The rest are personal to whoever runs planit.
+    pub fn is_plan_setting(&self) -> bool {
+        matches!(
+            self,
+            ConfigKey::Range
+                | ConfigKey::TaskOverflowPolicy
+                | ConfigKey::TaskSchedulingOrder
+                | ConfigKey::ScheduleStartDate
+                | ConfigKey::TaskPackingMode
+                | ConfigKey::StableRescheduling
+        )
+    }
 }
 
+/// Plan settings by key name, as shown in the config table, e.g. `RANGE` => `8:00AM-5:00PM`.
+pub type PlanSettings = BTreeMap<String, String>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigFile {
     pub range: RangeConfigItem,
@@ -38,6 +74,24 @@ pub struct ConfigFile {
     pub schedule_start_date: StartDateConfigItem,
     #[serde(default)]
     pub file_logging_enabled: FileLoggingConfigItem,
+    #[serde(default)]
+    pub task_packing_mode: TaskPackingModeConfigItem,
+    #[serde(default)]
+    pub stable_rescheduling: StableReschedulingConfigItem,
+    #[serde(default)]
+    pub history_depth: HistoryDepthConfigItem,
+    #[serde(default)]
+    pub card_delete_policy: CardDeletePolicyConfigItem,
+    #[serde(default)]
+    pub default_sort: DefaultSortConfigItem,
+    #[serde(default)]
+    pub default_columns: DefaultColumnsConfigItem,
+    #[serde(default)]
+    pub workspace: WorkspaceConfigItem,
+    #[serde(default)]
+    pub autosave_interval: AutosaveIntervalConfigItem,
+    #[serde(default)]
+    pub save_format: SaveFormatConfigItem,
 }
 
 #[derive(Debug, Clone)]
@@ -47,7 +101,7 @@ pub struct Config {
     pub last_change: Option<(String, String, String)>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ConfigRows(Vec<(String, String, String)>);
 
 impl ConfigRows {
@@ -114,6 +168,33 @@ impl Config {
     pub fn file_logging_enabled(&self) -> bool {
         self.data.file_logging_enabled.get_value().0
     }
+    pub fn task_packing_mode(&self) -> &TaskPackingMode {
+        self.data.task_packing_mode.get_value()
+    }
+    pub fn stable_rescheduling(&self) -> bool {
+        self.data.stable_rescheduling.get_value().0
+    }
+    pub fn history_depth(&self) -> usize {
+        *self.data.history_depth.get_value() as usize
+    }
+    pub fn card_delete_policy(&self) -> &CardDeletePolicy {
+        self.data.card_delete_policy.get_value()
+    }
+    pub fn default_sort(&self) -> &SortDefaults {
+        self.data.default_sort.get_value()
+    }
+    pub fn default_columns(&self) -> &ColumnDefaults {
+        self.data.default_columns.get_value()
+    }
+    pub fn workspace(&self) -> Option<&str> {
+        self.data.workspace.get_value().as_deref()
+    }
+    pub fn autosave_interval(&self) -> usize {
+        *self.data.autosave_interval.get_value() as usize
+    }
+    pub fn save_format(&self) -> SaveFormat {
+        *self.data.save_format.get_value()
+    }
 
     pub fn rows(&self) -> ConfigRows {
         let mut rows = Vec::new();
@@ -148,11 +229,81 @@ impl Config {
                     self.data.file_logging_enabled.description().to_string(),
                     self.data.file_logging_enabled.get_value().to_string(),
                 )),
+                ConfigKey::TaskPackingMode => rows.push((
+                    key.to_string(),
+                    self.data.task_packing_mode.description().to_string(),
+                    self.data.task_packing_mode.get_value().to_string(),
+                )),
+                ConfigKey::StableRescheduling => rows.push((
+                    key.to_string(),
+                    self.data.stable_rescheduling.description().to_string(),
+                    self.data.stable_rescheduling.get_value().to_string(),
+                )),
+                ConfigKey::HistoryDepth => rows.push((
+                    key.to_string(),
+                    self.data.history_depth.description().to_string(),
+                    self.data.history_depth.get_value().to_string(),
+                )),
+                ConfigKey::CardDeletePolicy => rows.push((
+                    key.to_string(),
+                    self.data.card_delete_policy.description().to_string(),
+                    self.data.card_delete_policy.get_value().to_string(),
+                )),
+                ConfigKey::DefaultSort => rows.push((
+                    key.to_string(),
+                    self.data.default_sort.description().to_string(),
+                    self.data.default_sort.get_value().to_string(),
+                )),
+                ConfigKey::DefaultColumns => rows.push((
+                    key.to_string(),
+                    self.data.default_columns.description().to_string(),
+                    self.data.default_columns.get_value().to_string(),
+                )),
+                ConfigKey::Workspace => rows.push((
+                    key.to_string(),
+                    self.data.workspace.description().to_string(),
+                    self.workspace_value(),
+                )),
+                ConfigKey::AutosaveInterval => rows.push((
+                    key.to_string(),
+                    self.data.autosave_interval.description().to_string(),
+                    self.data.autosave_interval.get_value().to_string(),
+                )),
+                ConfigKey::SaveFormat => rows.push((
+                    key.to_string(),
+                    self.data.save_format.description().to_string(),
+                    self.data.save_format.get_value().to_string(),
+                )),
             }
         }
         ConfigRows(rows)
     }
 
+    pub fn plan_settings(&self) -> PlanSettings {
+        ConfigKey::iter()
+            .zip(self.rows().iter())
+            .filter(|(key, _)| key.is_plan_setting())
+            .map(|(_, (name, _, value))| (name.clone(), value.clone()))
+            .collect()
+    }
+
+    /// The settings that applying `settings` would change, as `(key, current, new)`. Fails,
+    /// without changing anything, if a value is invalid.
+    pub fn plan_settings_changes(
+        &self,
+        settings: &PlanSettings,
+    ) -> Result<Vec<(String, String, String)>> {
+        let mut proposed = self.clone();
+        set_values(&mut proposed.data, settings)?;
+        Ok(self
+            .rows()
+            .iter()
+            .zip(proposed.rows().iter())
+            .filter(|(current, new)| current.2 != new.2)
+            .map(|((key, _, current), (_, _, new))| (key.clone(), current.clone(), new.clone()))
+            .collect())
+    }
+
     pub fn set_by_index(&mut self, index: usize, new_value: &str) -> Result<()> {
         let key = ConfigKey::iter()
             .nth(index)
@@ -192,6 +343,51 @@ impl Config {
                 let res = self.edit(|cfg| cfg.file_logging_enabled.set_value(new_value));
                 (old, res)
             }
+            ConfigKey::TaskPackingMode => {
+                let old = self.data.task_packing_mode.get_value().to_string();
+                let res = self.edit(|cfg| cfg.task_packing_mode.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::StableRescheduling => {
+                let old = self.data.stable_rescheduling.get_value().to_string();
+                let res = self.edit(|cfg| cfg.stable_rescheduling.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::HistoryDepth => {
+                let old = self.data.history_depth.get_value().to_string();
+                let res = self.edit(|cfg| cfg.history_depth.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::CardDeletePolicy => {
+                let old = self.data.card_delete_policy.get_value().to_string();
+                let res = self.edit(|cfg| cfg.card_delete_policy.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::DefaultSort => {
+                let old = self.data.default_sort.get_value().to_string();
+                let res = self.edit(|cfg| cfg.default_sort.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::DefaultColumns => {
+                let old = self.data.default_columns.get_value().to_string();
+                let res = self.edit(|cfg| cfg.default_columns.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::Workspace => {
+                let old = self.workspace_value();
+                let res = self.edit(|cfg| cfg.workspace.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::AutosaveInterval => {
+                let old = self.data.autosave_interval.get_value().to_string();
+                let res = self.edit(|cfg| cfg.autosave_interval.set_value(new_value));
+                (old, res)
+            }
+            ConfigKey::SaveFormat => {
+                let old = self.data.save_format.get_value().to_string();
+                let res = self.edit(|cfg| cfg.save_format.set_value(new_value));
+                (old, res)
+            }
         };
 
         if res.is_ok() {
@@ -212,6 +408,17 @@ impl Config {
                 ConfigKey::FileLoggingEnabled => {
                     self.data.file_logging_enabled.get_value().to_string()
                 }
+                ConfigKey::TaskPackingMode => self.data.task_packing_mode.get_value().to_string(),
+                ConfigKey::StableRescheduling => {
+                    self.data.stable_rescheduling.get_value().to_string()
+                }
+                ConfigKey::HistoryDepth => self.data.history_depth.get_value().to_string(),
+                ConfigKey::CardDeletePolicy => self.data.card_delete_policy.get_value().to_string(),
+                ConfigKey::DefaultSort => self.data.default_sort.get_value().to_string(),
+                ConfigKey::DefaultColumns => self.data.default_columns.get_value().to_string(),
+                ConfigKey::Workspace => self.workspace_value(),
+                ConfigKey::AutosaveInterval => self.data.autosave_interval.get_value().to_string(),
+                ConfigKey::SaveFormat => self.data.save_format.get_value().to_string(),
             };
             // stash for caller to log. We store last change for external logging.
             self.last_change = Some((key.to_string(), old, new_val));
@@ -220,6 +427,14 @@ impl Config {
         res
     }
 
+    /// Swaps in previously captured settings and writes them back to disk.
+    pub fn replace(&mut self, data: ConfigFile) -> Result<()> {
+        self.edit(|cfg| {
+            *cfg = data;
+            Ok(())
+        })
+    }
+
     pub fn take_last_change(&mut self) -> Option<(String, String, String)> {
         self.last_change.take()
     }
@@ -242,35 +457,11 @@ impl Config {
         K: AsRef<str>,
         V: AsRef<str>,
     {
-        self.edit(|cfg| {
-            for (k, v) in pairs {
-                use std::str::FromStr;
-                let key = ConfigKey::from_str(k.as_ref()).map_err(|_| {
-                    Error::Parse(format!(
-                        "Unknown configuration key '{}'. Valid keys: {}",
-                        k.as_ref(),
-                        valid_csv::<ConfigKey>()
-                    ))
-                })?;
+        self.edit(|cfg| set_values(cfg, pairs))
+    }
 
-                match key {
-                    ConfigKey::Range => cfg.range.set_value(v.as_ref())?,
-                    ConfigKey::TaskOverflowPolicy => {
-                        cfg.task_overflow_policy.set_value(v.as_ref())?
-                    }
-                    ConfigKey::TaskSchedulingOrder => {
-                        cfg.task_scheduling_order.set_value(v.as_ref())?
-                    }
-                    ConfigKey::ScheduleStartDate => {
-                        cfg.schedule_start_date.set_value(v.as_ref())?
-                    }
-                    ConfigKey::FileLoggingEnabled => {
-                        cfg.file_logging_enabled.set_value(v.as_ref())?
-                    }
-                }
-            }
-            Ok(())
-        })
+    fn workspace_value(&self) -> String {
+        self.workspace().unwrap_or("-").to_string()
     }
 
     fn edit<F>(&mut self, f: F) -> Result<()>
@@ -288,3 +479,39 @@ impl Config {
             .map_err(|e| Error::Parse(format!("Failed to write {}: {}", self.path.display(), e)))
     }
 }
+
+fn set_values<I, K, V>(cfg: &mut ConfigFile, pairs: I) -> Result<()>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    for (k, v) in pairs {
+        use std::str::FromStr;
+        let key = ConfigKey::from_str(k.as_ref()).map_err(|_| {
+            Error::Parse(format!(
+                "Unknown configuration key '{}'. Valid keys: {}",
+                k.as_ref(),
+                valid_csv::<ConfigKey>()
+            ))
+        })?;
+
+        match key {
+            ConfigKey::Range => cfg.range.set_value(v.as_ref())?,
+            ConfigKey::TaskOverflowPolicy => cfg.task_overflow_policy.set_value(v.as_ref())?,
+            ConfigKey::TaskSchedulingOrder => cfg.task_scheduling_order.set_value(v.as_ref())?,
+            ConfigKey::ScheduleStartDate => cfg.schedule_start_date.set_value(v.as_ref())?,
+            ConfigKey::FileLoggingEnabled => cfg.file_logging_enabled.set_value(v.as_ref())?,
+            ConfigKey::TaskPackingMode => cfg.task_packing_mode.set_value(v.as_ref())?,
+            ConfigKey::StableRescheduling => cfg.stable_rescheduling.set_value(v.as_ref())?,
+            ConfigKey::HistoryDepth => cfg.history_depth.set_value(v.as_ref())?,
+            ConfigKey::CardDeletePolicy => cfg.card_delete_policy.set_value(v.as_ref())?,
+            ConfigKey::DefaultSort => cfg.default_sort.set_value(v.as_ref())?,
+            ConfigKey::DefaultColumns => cfg.default_columns.set_value(v.as_ref())?,
+            ConfigKey::Workspace => cfg.workspace.set_value(v.as_ref())?,
+            ConfigKey::AutosaveInterval => cfg.autosave_interval.set_value(v.as_ref())?,
+            ConfigKey::SaveFormat => cfg.save_format.set_value(v.as_ref())?,
+        }
+    }
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::core::types::{Bool, Date, TaskOverflowPolicy, TaskSchedulingOrder, TimeRange};
+use crate::core::types::{
+    Bool, CardDeletePolicy, Date, SaveFormat, TaskOverflowPolicy, TaskPackingMode,
+    TaskSchedulingOrder, TimeRange,
+};
+use crate::core::view::{ColumnDefaults, SortDefaults};
 use crate::errors::Error;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
@@ -19,7 +23,7 @@ impl ConfigItem<Option<NaiveDate>> for StartDateConfigItem {
         &self.value
     }
     fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
-        if new_value.trim().is_empty() {
+        if matches!(new_value.trim(), "" | "-") {
             self.value = None;
             return Ok(());
         }
@@ -83,6 +87,62 @@ impl ConfigItem<TaskSchedulingOrder> for TaskSchedulingOrderConfigItem {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPackingModeConfigItem {
+    pub value: TaskPackingMode,
+    pub description: String,
+}
+
+impl Default for TaskPackingModeConfigItem {
+    fn default() -> Self {
+        Self {
+            value: TaskPackingMode::default(),
+            description: "How scheduled tasks are arranged within a day".into(),
+        }
+    }
+}
+
+impl ConfigItem<TaskPackingMode> for TaskPackingModeConfigItem {
+    fn get_value(&self) -> &TaskPackingMode {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = TaskPackingMode::try_from(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StableReschedulingConfigItem {
+    pub value: Bool,
+    pub description: String,
+}
+
+impl Default for StableReschedulingConfigItem {
+    fn default() -> Self {
+        Self {
+            value: Bool(false),
+            description: "Keep previously scheduled slices where still feasible".into(),
+        }
+    }
+}
+
+impl ConfigItem<Bool> for StableReschedulingConfigItem {
+    fn get_value(&self) -> &Bool {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = Bool::try_from_str(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileLoggingConfigItem {
     pub value: Bool,
@@ -109,3 +169,219 @@ impl ConfigItem<Bool> for FileLoggingConfigItem {
         &self.description
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryDepthConfigItem {
+    pub value: u32,
+    pub description: String,
+}
+
+impl Default for HistoryDepthConfigItem {
+    fn default() -> Self {
+        Self {
+            value: 50,
+            description: "Number of changes that can be undone".into(),
+        }
+    }
+}
+
+impl ConfigItem<u32> for HistoryDepthConfigItem {
+    fn get_value(&self) -> &u32 {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = new_value.trim().parse().map_err(|_| {
+            Error::Parse(format!(
+                "Invalid history depth: '{}'. Expected a whole number of changes (e.g., 50); 0 turns undo off.",
+                new_value.trim()
+            ))
+        })?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceConfigItem {
+    pub value: Option<String>,
+    pub description: String,
+}
+
+impl Default for WorkspaceConfigItem {
+    fn default() -> Self {
+        Self {
+            value: None,
+            description: "Save file loaded at startup and autosaved to".into(),
+        }
+    }
+}
+
+impl ConfigItem<Option<String>> for WorkspaceConfigItem {
+    fn get_value(&self) -> &Option<String> {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        let name = new_value.trim().trim_matches('"').trim();
+        if name.is_empty() || name == "-" {
+            self.value = None;
+            return Ok(());
+        }
+        if name.contains(['/', '\\']) {
+            return Err(Error::Parse(format!(
+                "Invalid workspace: '{name}'. Expected a file name in the schedules directory (e.g., main), or - for none."
+            )));
+        }
+        self.value = Some(name.to_string());
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutosaveIntervalConfigItem {
+    pub value: u32,
+    pub description: String,
+}
+
+impl Default for AutosaveIntervalConfigItem {
+    fn default() -> Self {
+        Self {
+            value: 1,
+            description: "Changes between autosaves to the workspace".into(),
+        }
+    }
+}
+
+impl ConfigItem<u32> for AutosaveIntervalConfigItem {
+    fn get_value(&self) -> &u32 {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = new_value.trim().parse().map_err(|_| {
+            Error::Parse(format!(
+                "Invalid autosave interval: '{}'. Expected a whole number of changes (e.g., 1 saves after every change); 0 turns autosave off.",
+                new_value.trim()
+            ))
+        })?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFormatConfigItem {
+    pub value: SaveFormat,
+    pub description: String,
+}
+
+impl Default for SaveFormatConfigItem {
+    fn default() -> Self {
+        Self {
+            value: SaveFormat::default(),
+            description: "How save and autosave write the plan".into(),
+        }
+    }
+}
+
+impl ConfigItem<SaveFormat> for SaveFormatConfigItem {
+    fn get_value(&self) -> &SaveFormat {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = SaveFormat::try_from(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardDeletePolicyConfigItem {
+    pub value: CardDeletePolicy,
+    pub description: String,
+}
+
+impl Default for CardDeletePolicyConfigItem {
+    fn default() -> Self {
+        Self {
+            value: CardDeletePolicy::default(),
+            description: "What deleting a card does to the entities that use it".into(),
+        }
+    }
+}
+
+impl ConfigItem<CardDeletePolicy> for CardDeletePolicyConfigItem {
+    fn get_value(&self) -> &CardDeletePolicy {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = CardDeletePolicy::try_from(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultSortConfigItem {
+    pub value: SortDefaults,
+    pub description: String,
+}
+
+impl Default for DefaultSortConfigItem {
+    fn default() -> Self {
+        Self {
+            value: SortDefaults::default(),
+            description: "Default row order per entity table (e.g., task=due,-hours)".into(),
+        }
+    }
+}
+
+impl ConfigItem<SortDefaults> for DefaultSortConfigItem {
+    fn get_value(&self) -> &SortDefaults {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = SortDefaults::try_from_str(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultColumnsConfigItem {
+    pub value: ColumnDefaults,
+    pub description: String,
+}
+
+impl Default for DefaultColumnsConfigItem {
+    fn default() -> Self {
+        Self {
+            value: ColumnDefaults::default(),
+            description: "Default columns per entity table (e.g., task=id,name,due)".into(),
+        }
+    }
+}
+
+impl ConfigItem<ColumnDefaults> for DefaultColumnsConfigItem {
+    fn get_value(&self) -> &ColumnDefaults {
+        &self.value
+    }
+    fn set_value(&mut self, new_value: &str) -> Result<(), Error> {
+        self.value = ColumnDefaults::try_from_str(new_value)?;
+        Ok(())
+    }
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
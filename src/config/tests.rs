@@ -1,5 +1,8 @@
 use super::{Config, ConfigKey, models::*};
-use crate::core::types::{Bool, TaskOverflowPolicy, TaskSchedulingOrder, TimeRange};
+use crate::core::types::{
+    Bool, CardDeletePolicy, EntityType, SaveFormat, TaskOverflowPolicy, TaskPackingMode,
+    TaskSchedulingOrder, TimeRange,
+};
 use crate::errors::Error;
 use crate::extensions::enums::valid_csv;
 use chrono::NaiveDate;
@@ -50,9 +53,16 @@ fn load_from_reads_config_and_rows() {
         &Some(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap())
     );
     assert!(cfg.file_logging_enabled());
+    assert_eq!(cfg.task_packing_mode(), &TaskPackingMode::Greedy);
+    assert!(!cfg.stable_rescheduling());
+    assert_eq!(cfg.history_depth(), 50);
+    assert_eq!(cfg.card_delete_policy(), &CardDeletePolicy::Restrict);
+    assert_eq!(cfg.workspace(), None);
+    assert_eq!(cfg.autosave_interval(), 1);
+    assert_eq!(cfg.save_format(), SaveFormat::Tokens);
 
     let rows = cfg.rows();
-    assert_eq!(rows.len(), 5);
+    assert_eq!(rows.len(), 14);
     assert!(rows.iter().any(|(k, _, _)| k == "RANGE"));
 }
 
@@ -347,3 +357,204 @@ fn set_key_reports_write_error() {
         other => panic!("expected parse error, got {other:?}"),
     }
 }
+
+#[test]
+fn set_key_parses_workspace_and_autosave_interval() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+
+    cfg.set_key(ConfigKey::Workspace, "\"main\"").unwrap();
+    assert_eq!(cfg.workspace(), Some("main"));
+    assert_eq!(
+        cfg.take_last_change(),
+        Some(("WORKSPACE".into(), "-".into(), "main".into()))
+    );
+    assert!(cfg.set_key(ConfigKey::Workspace, "../main").is_err());
+    cfg.set_key(ConfigKey::Workspace, "-").unwrap();
+    assert_eq!(cfg.workspace(), None);
+
+    cfg.set_key(ConfigKey::AutosaveInterval, "5").unwrap();
+    assert_eq!(cfg.autosave_interval(), 5);
+    let err = cfg
+        .set_key(ConfigKey::AutosaveInterval, "often")
+        .unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.starts_with("Invalid autosave interval: 'often'.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn set_key_parses_save_format() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+
+    cfg.set_key(ConfigKey::SaveFormat, "Typed").unwrap();
+    assert_eq!(cfg.save_format(), SaveFormat::Typed);
+    assert_eq!(
+        cfg.take_last_change(),
+        Some(("SAVE_FORMAT".into(), "tokens".into(), "typed".into()))
+    );
+    let err = cfg.set_key(ConfigKey::SaveFormat, "yaml").unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.starts_with("Invalid save format: 'yaml'.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn set_key_parses_history_depth() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+
+    cfg.set_key(ConfigKey::HistoryDepth, "3").unwrap();
+    assert_eq!(cfg.history_depth(), 3);
+    let err = cfg.set_key(ConfigKey::HistoryDepth, "-1").unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.starts_with("Invalid history depth: '-1'.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn set_key_parses_card_delete_policy() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+
+    cfg.set_key(ConfigKey::CardDeletePolicy, "Set-Null")
+        .unwrap();
+    assert_eq!(cfg.card_delete_policy(), &CardDeletePolicy::SetNull);
+    let err = cfg
+        .set_key(ConfigKey::CardDeletePolicy, "orphan")
+        .unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.starts_with("Invalid card delete policy: 'orphan'.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn replace_restores_captured_settings() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+    let before = cfg.view().clone();
+
+    cfg.set_key(ConfigKey::Range, "9:00AM-3:00PM").unwrap();
+    cfg.replace(before).unwrap();
+
+    assert_eq!(
+        cfg.range(),
+        &TimeRange::try_from_str("8:00AM-5:00PM").unwrap()
+    );
+    let reloaded = Config::load_from(&path).unwrap();
+    assert_eq!(reloaded.rows(), cfg.rows());
+}
+
+#[test]
+fn set_key_parses_default_sort_and_columns() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+    assert_eq!(cfg.default_sort().to_string(), "-");
+
+    cfg.set_key(ConfigKey::DefaultSort, "task=Due,-hours; event=time")
+        .unwrap();
+    cfg.set_key(ConfigKey::DefaultColumns, "task=id,name,remaining")
+        .unwrap();
+    assert_eq!(
+        cfg.default_sort().to_string(),
+        "task=due,-hours; event=time"
+    );
+    assert_eq!(
+        cfg.default_columns().get(EntityType::Task),
+        Some(
+            &[
+                "id".to_string(),
+                "name".to_string(),
+                "remaining".to_string()
+            ][..]
+        )
+    );
+    assert!(cfg.default_columns().get(EntityType::Card).is_none());
+
+    let reloaded = Config::load_from(&path).unwrap();
+    assert_eq!(reloaded.default_sort(), cfg.default_sort());
+
+    let err = cfg
+        .set_key(ConfigKey::DefaultColumns, "card=id,due")
+        .unwrap_err();
+    match err {
+        Error::Parse(msg) => assert!(msg.starts_with("Unknown card column: 'due'.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(cfg.set_key(ConfigKey::DefaultSort, "task due").is_err());
+    cfg.set_key(ConfigKey::DefaultSort, "-").unwrap();
+    assert!(cfg.default_sort().get(EntityType::Task).is_none());
+}
+
+#[test]
+fn plan_settings_round_trip_and_report_changes() {
+    let path = temp_path();
+    sample_config_file(&path);
+    let mut cfg = Config::load_from(&path).unwrap();
+    cfg.set_many([
+        ("TASK_OVERFLOW_POLICY", "block"),
+        ("STABLE_RESCHEDULING", "true"),
+    ])
+    .unwrap();
+
+    let settings = cfg.plan_settings();
+    let keys = settings.keys().map(String::as_str).collect::<Vec<_>>();
+    assert_eq!(
+        keys,
+        [
+            "RANGE",
+            "SCHEDULE_START_DATE",
+            "STABLE_RESCHEDULING",
+            "TASK_OVERFLOW_POLICY",
+            "TASK_PACKING_MODE",
+            "TASK_SCHEDULING_ORDER",
+        ]
+    );
+    assert!(cfg.plan_settings_changes(&settings).unwrap().is_empty());
+
+    let other_path = temp_path();
+    sample_config_file(&other_path);
+    let mut other = Config::load_from(&other_path).unwrap();
+    other.set_many([("SCHEDULE_START_DATE", "-")]).unwrap();
+    assert_eq!(other.schedule_start_date(), &None);
+    let changes = other.plan_settings_changes(&settings).unwrap();
+    assert_eq!(
+        changes,
+        [
+            (
+                "TASK_OVERFLOW_POLICY".to_string(),
+                "allow".to_string(),
+                TaskOverflowPolicy::Block.to_string()
+            ),
+            (
+                "SCHEDULE_START_DATE".to_string(),
+                "-".to_string(),
+                "2099-01-01".to_string()
+            ),
+            (
+                "STABLE_RESCHEDULING".to_string(),
+                "False".to_string(),
+                "True".to_string()
+            ),
+        ]
+    );
+    assert_eq!(other.task_overflow_policy(), &TaskOverflowPolicy::Allow);
+
+    other.set_many(&settings).unwrap();
+    assert_eq!(other.plan_settings(), settings);
+
+    let mut bad = settings.clone();
+    bad.insert("RANGE".into(), "5:00PM-8:00AM".into());
+    assert!(other.plan_settings_changes(&bad).is_err());
+}
@@ -1,6 +1,8 @@
 use crate::config::Config;
-use crate::core::models::{Card, Event, Task};
+use crate::core::history::History;
+use crate::core::models::{Card, Event, Task, TaskTemplate};
 use crate::core::repository::Repository;
+use crate::core::workspace::WorkspaceStatus;
 
 use crate::errors::Result;
 use crate::logging::Logger;
@@ -12,7 +14,10 @@ pub struct AppContext {
     pub tasks: Repository<Task>,
     pub events: Repository<Event>,
     pub cards: Repository<Card>,
+    pub templates: Repository<TaskTemplate>,
     pub logger: Logger,
+    pub history: History,
+    pub workspace: WorkspaceStatus,
     pub startup_displayed: bool,
     pub config_path: PathBuf,
     pub schedules_dir: PathBuf,
@@ -38,6 +43,7 @@ impl AppContext {
         let tasks = Repository::<Task>::new();
         let events = Repository::<Event>::new();
         let cards = Repository::<Card>::new();
+        let templates = Repository::<TaskTemplate>::new();
 
         let logger = Logger::new();
         logger.set_log_dir(&logs_dir);
@@ -48,11 +54,33 @@ impl AppContext {
             tasks,
             events,
             cards,
+            templates,
             logger,
+            history: History::new(),
+            workspace: WorkspaceStatus::default(),
             startup_displayed: false,
             config_path,
             schedules_dir,
             logs_dir,
         })
     }
+
+    /// An empty plan with this context's config and paths and a silent logger, for loading a
+    /// save file without touching the session.
+    pub fn scratch(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            tasks: Repository::new(),
+            events: Repository::new(),
+            cards: Repository::new(),
+            templates: Repository::new(),
+            logger: Logger::silent(),
+            history: History::new(),
+            workspace: WorkspaceStatus::default(),
+            startup_displayed: false,
+            config_path: self.config_path.clone(),
+            schedules_dir: self.schedules_dir.clone(),
+            logs_dir: self.logs_dir.clone(),
+        }
+    }
 }
@@ -0,0 +1,579 @@
+use crate::arg::args::{Arg, CardColorIdArg, SingleTokenArg};
+use crate::core::models::{BaseEntity, Card, DETAIL_FIELDS, Event, Task, TaskTemplate};
+use crate::core::repository::{Repository, Sort};
+use crate::core::types::{CardColor, Date, DayOfWeek};
+use crate::errors::{Error, Result};
+use crate::extensions::chrono::WeekdayExt;
+use chrono::{Datelike, Duration, NaiveDate};
+use regex::{Regex, RegexBuilder};
+use std::fmt;
+
+/// What kind of value a filter field holds; decides which operators and operands it accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Number,
+    Date,
+    Card,
+    Days,
+    Color,
+    Flag,
+}
+
+/// The value of one field on one entity, as seen by a filter.
+pub enum FieldValue<'a> {
+    Text(&'a str),
+    Number(f32),
+    Date(NaiveDate),
+    Card(Option<i32>),
+    Days(&'a [DayOfWeek]),
+    Color(CardColor),
+    Flag(bool),
+}
+
+/// An entity `find` can filter. `FIELDS` lists the names accepted in filter expressions, besides
+/// the `DETAIL_FIELDS` every entity has.
+pub trait Filterable: BaseEntity {
+    const FIELDS: &'static [(&'static str, FieldKind)];
+
+    /// Value of `field` (one of `FIELDS`); `today` anchors date-relative fields like `overdue`.
+    fn field(&self, field: &str, today: NaiveDate) -> FieldValue<'_>;
+}
+
+impl Filterable for Task {
+    const FIELDS: &'static [(&'static str, FieldKind)] = &[
+        ("name", FieldKind::Text),
+        ("hours", FieldKind::Number),
+        ("due", FieldKind::Date),
+        ("card", FieldKind::Card),
+        ("done", FieldKind::Flag),
+        ("atomic", FieldKind::Flag),
+        ("scheduled", FieldKind::Flag),
+        ("unscheduled", FieldKind::Flag),
+        ("overdue", FieldKind::Flag),
+        ("uid", FieldKind::Text),
+    ];
+
+    fn field(&self, field: &str, today: NaiveDate) -> FieldValue<'_> {
+        match field {
+            "name" => FieldValue::Text(&self.name),
+            "uid" => FieldValue::Text(self.uid.as_str()),
+            "hours" => FieldValue::Number(self.hours),
+            "due" => FieldValue::Date(self.date.0),
+            "card" => FieldValue::Card(self.card_id),
+            "done" => FieldValue::Flag(self.completed),
+            "atomic" => FieldValue::Flag(self.atomic),
+            "scheduled" => FieldValue::Flag(!self.subtasks.is_empty()),
+            "unscheduled" => FieldValue::Flag(!self.completed && self.subtasks.is_empty()),
+            _ => FieldValue::Flag(!self.completed && self.date.0 < today),
+        }
+    }
+}
+
+impl Filterable for Event {
+    const FIELDS: &'static [(&'static str, FieldKind)] = &[
+        ("name", FieldKind::Text),
+        ("hours", FieldKind::Number),
+        ("card", FieldKind::Card),
+        ("day", FieldKind::Days),
+        ("recurring", FieldKind::Flag),
+        ("uid", FieldKind::Text),
+    ];
+
+    fn field(&self, field: &str, _today: NaiveDate) -> FieldValue<'_> {
+        match field {
+            "name" => FieldValue::Text(&self.name),
+            "uid" => FieldValue::Text(self.uid.as_str()),
+            "hours" => FieldValue::Number(self.hours()),
+            "card" => FieldValue::Card(self.card_id),
+            "day" => FieldValue::Days(&self.days),
+            _ => FieldValue::Flag(self.recurring),
+        }
+    }
+}
+
+impl Filterable for TaskTemplate {
+    const FIELDS: &'static [(&'static str, FieldKind)] = &[
+        ("name", FieldKind::Text),
+        ("hours", FieldKind::Number),
+        ("card", FieldKind::Card),
+        ("day", FieldKind::Days),
+        ("uid", FieldKind::Text),
+    ];
+
+    fn field(&self, field: &str, _today: NaiveDate) -> FieldValue<'_> {
+        match field {
+            "name" => FieldValue::Text(&self.name),
+            "uid" => FieldValue::Text(self.uid.as_str()),
+            "hours" => FieldValue::Number(self.hours),
+            "card" => FieldValue::Card(self.card_id),
+            _ => FieldValue::Days(&self.days),
+        }
+    }
+}
+
+impl Filterable for Card {
+    const FIELDS: &'static [(&'static str, FieldKind)] = &[
+        ("name", FieldKind::Text),
+        ("color", FieldKind::Color),
+        ("uid", FieldKind::Text),
+    ];
+
+    fn field(&self, field: &str, _today: NaiveDate) -> FieldValue<'_> {
+        match field {
+            "name" => FieldValue::Text(&self.name),
+            "uid" => FieldValue::Text(self.uid.as_str()),
+            _ => FieldValue::Color(self.color),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+}
+
+impl Op {
+    /// Longest operators first so `<=` is not read as `<`.
+    const ALL: [(&'static str, Op); 7] = [
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("!=", Op::Ne),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+        ("=", Op::Eq),
+        ("~", Op::Match),
+    ];
+
+    fn allowed(kind: FieldKind) -> &'static [Op] {
+        match kind {
+            FieldKind::Number | FieldKind::Date => {
+                &[Op::Eq, Op::Ne, Op::Lt, Op::Le, Op::Gt, Op::Ge]
+            }
+            FieldKind::Text => &[Op::Eq, Op::Ne, Op::Match],
+            FieldKind::Card | FieldKind::Days | FieldKind::Color => &[Op::Eq, Op::Ne],
+            FieldKind::Flag => &[],
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        Op::ALL
+            .iter()
+            .find(|(_, op)| *op == self)
+            .map(|(s, _)| *s)
+            .unwrap_or_default()
+    }
+
+    fn holds<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            Op::Eq | Op::Match => lhs == rhs,
+            Op::Ne => lhs != rhs,
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Text(String),
+    Pattern(Regex),
+    Number(f32),
+    Date(NaiveDate),
+    Card(Option<i32>),
+    Day(DayOfWeek),
+    Color(CardColor),
+    Flag(bool),
+}
+
+/// Where a condition reads its value: an entity field or one of its details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    Field(&'static str, FieldKind),
+    Note,
+    Link,
+    Meta(String),
+}
+
+impl Target {
+    fn kind(&self) -> FieldKind {
+        match self {
+            Target::Field(_, kind) => *kind,
+            _ => FieldKind::Text,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Target::Field(name, _) => name.to_string(),
+            Target::Note => "note".into(),
+            Target::Link => "links".into(),
+            Target::Meta(key) => format!("meta.{key}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    target: Target,
+    op: Op,
+    operand: Operand,
+}
+
+impl Condition {
+    fn matches<T: Filterable>(&self, entity: &T, today: NaiveDate) -> bool {
+        let details = entity.details();
+        let values: Vec<FieldValue<'_>> = match (&self.target, &self.operand) {
+            (Target::Field(field, _), _) => return self.holds(entity.field(field, today)),
+            (Target::Note, Operand::Flag(b)) => return details.notes.is_some() == *b,
+            (Target::Link, Operand::Flag(b)) => return details.links.is_empty() != *b,
+            (Target::Meta(key), Operand::Flag(b)) => return details.meta.contains_key(key) == *b,
+            (Target::Note, _) => details
+                .notes
+                .as_deref()
+                .map(FieldValue::Text)
+                .into_iter()
+                .collect(),
+            (Target::Link, _) => details.links.iter().map(|l| FieldValue::Text(l)).collect(),
+            (Target::Meta(key), _) => details
+                .meta
+                .get(key)
+                .map(|v| FieldValue::Text(v))
+                .into_iter()
+                .collect(),
+        };
+        // A detail may be missing or hold several values: `!=` holds when none of them equal the
+        // operand, any other operator when one of them matches.
+        if self.op == Op::Ne {
+            let eq = Condition {
+                op: Op::Eq,
+                ..self.clone()
+            };
+            !values.into_iter().any(|v| eq.holds(v))
+        } else {
+            values.into_iter().any(|v| self.holds(v))
+        }
+    }
+
+    fn holds(&self, value: FieldValue<'_>) -> bool {
+        let op = self.op;
+        match (value, &self.operand) {
+            (FieldValue::Text(v), Operand::Text(s)) => {
+                let (v, s) = (v.to_lowercase(), s.to_lowercase());
+                match op {
+                    Op::Match => v.contains(&s),
+                    _ => op.holds(v, s),
+                }
+            }
+            (FieldValue::Text(v), Operand::Pattern(re)) => re.is_match(v),
+            (FieldValue::Number(v), Operand::Number(n)) => op.holds(v, *n),
+            (FieldValue::Date(v), Operand::Date(d)) => op.holds(v, *d),
+            (FieldValue::Card(v), Operand::Card(c)) => op.holds(v, *c),
+            (FieldValue::Days(days), Operand::Day(d)) => op.holds(days.contains(d), true),
+            (FieldValue::Color(v), Operand::Color(c)) => (v == *c) == (op == Op::Eq),
+            (FieldValue::Flag(v), Operand::Flag(b)) => v == *b,
+            _ => false,
+        }
+    }
+}
+
+/// A conjunction of filter expressions over one entity kind, e.g. `due<fri card=+C2 name~report`.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    conditions: Vec<Condition>,
+    today: NaiveDate,
+}
+
+impl Filter {
+    pub fn usage() -> String {
+        [
+            "Filters: <field><op><value> or a bare flag field (prefix ! to negate).",
+            "Operators: = != < <= > >= for numbers and dates, = != ~ for names (~ matches a",
+            "substring, ~/regex/ a pattern), = != for card, day and color.",
+            "Dates accept today, tomorrow, a weekday (next one on or after today) or a date.",
+            "Card accepts +C<id>, <id> or none.",
+            "note, links and meta.<key> match text on every entity; alone they test presence.",
+        ]
+        .join("\n")
+    }
+
+    /// Parses the expressions for entity kind `T`; weekday and relative dates resolve against `today`.
+    pub fn parse<T: Filterable>(exprs: &[String], today: NaiveDate) -> Result<Self> {
+        let conditions = exprs
+            .iter()
+            .map(|e| parse_condition::<T>(e, today))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { conditions, today })
+    }
+
+    pub fn matches<T: Filterable>(&self, entity: &T) -> bool {
+        self.conditions
+            .iter()
+            .all(|c| c.matches(entity, self.today))
+    }
+
+    /// Copy of `repo` holding only the entities the filter matches, ids unchanged.
+    pub fn apply<T: Filterable + Clone>(&self, repo: &Repository<T>) -> Repository<T> {
+        let mut kept = repo.clone();
+        let dropped = repo
+            .query()
+            .r#where(|e| !self.matches(e))
+            .order(Sort::IdAsc)
+            .ids();
+        for id in dropped {
+            let _ = kept.delete(id);
+        }
+        kept
+    }
+}
+
+/// The entities a bulk `mod`, `del` or `done` applies to: ids and ranges such as `1,4,7-12`, or
+/// the filters after `where`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    Ids(Vec<(i32, i32)>),
+    Where(Vec<String>),
+}
+
+impl Selection {
+    /// Whether `tok` is an id list or range rather than a single id.
+    pub fn is_id_list(tok: &str) -> bool {
+        (tok.contains(',') || tok.contains('-'))
+            && tok
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ',' || c == '-')
+    }
+
+    pub fn ids_from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::Parse(format!(
+                "Invalid id list: '{s}'. Expected ids and ranges such as 1,4,7-12"
+            ))
+        };
+        let spans = s
+            .split(',')
+            .map(|part| {
+                let (start, end) = part.split_once('-').unwrap_or((part, part));
+                let start: i32 = start.parse().map_err(|_| invalid())?;
+                let end: i32 = end.parse().map_err(|_| invalid())?;
+                if start <= 0 || end < start {
+                    return Err(invalid());
+                }
+                Ok((start, end))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Selection::Ids(spans))
+    }
+
+    /// Selection of exactly `ids` (ascending), with consecutive ids folded into ranges.
+    pub fn of_ids(ids: &[i32]) -> Self {
+        let mut spans: Vec<(i32, i32)> = Vec::new();
+        for &id in ids {
+            match spans.last_mut() {
+                Some((_, end)) if *end + 1 == id => *end = id,
+                _ => spans.push((id, id)),
+            }
+        }
+        Selection::Ids(spans)
+    }
+
+    /// Ids of the selected entities in ascending order. A single id must exist; a range only
+    /// covers the ids present in it.
+    pub fn select<T: Filterable>(
+        &self,
+        repo: &Repository<T>,
+        today: NaiveDate,
+    ) -> Result<Vec<i32>> {
+        match self {
+            Selection::Ids(spans) => {
+                for (start, end) in spans {
+                    if start == end {
+                        repo.get(*start)?;
+                    }
+                }
+                Ok(repo
+                    .query()
+                    .r#where(|e| spans.iter().any(|(s, end)| (*s..=*end).contains(&e.id())))
+                    .order(Sort::IdAsc)
+                    .ids())
+            }
+            Selection::Where(exprs) => {
+                let filter = Filter::parse::<T>(exprs, today)?;
+                Ok(repo
+                    .query()
+                    .r#where(|e| filter.matches(e))
+                    .order(Sort::IdAsc)
+                    .ids())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::Ids(spans) => {
+                let parts: Vec<String> = spans
+                    .iter()
+                    .map(|(start, end)| {
+                        if start == end {
+                            start.to_string()
+                        } else {
+                            format!("{start}-{end}")
+                        }
+                    })
+                    .collect();
+                write!(f, "{}", parts.join(","))
+            }
+            Selection::Where(exprs) => write!(f, "where {}", exprs.join(" ")),
+        }
+    }
+}
+
+fn fields_csv<T: Filterable>() -> String {
+    T::FIELDS
+        .iter()
+        .map(|(name, _)| *name)
+        .chain(DETAIL_FIELDS.iter().copied())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn lookup<T: Filterable>(name: &str) -> Result<Target> {
+    let name = name.trim().to_ascii_lowercase();
+    match name.as_str() {
+        "note" => return Ok(Target::Note),
+        "links" => return Ok(Target::Link),
+        _ => {}
+    }
+    if let Some(key) = name.strip_prefix("meta.")
+        && !key.is_empty()
+    {
+        return Ok(Target::Meta(key.to_string()));
+    }
+    T::FIELDS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(field, kind)| Target::Field(field, *kind))
+        .ok_or_else(|| {
+            Error::Parse(format!(
+                "Unknown filter field: '{}'. Valid fields: {}",
+                name,
+                fields_csv::<T>()
+            ))
+        })
+}
+
+fn parse_condition<T: Filterable>(expr: &str, today: NaiveDate) -> Result<Condition> {
+    let split = expr.char_indices().find_map(|(i, _)| {
+        Op::ALL
+            .iter()
+            .find(|(sym, _)| expr[i..].starts_with(sym))
+            .map(|(sym, op)| (i, *sym, *op))
+    });
+
+    let Some((at, sym, op)) = split else {
+        let (negated, name) = match expr.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, expr),
+        };
+        let target = lookup::<T>(name)?;
+        if matches!(target, Target::Field(_, kind) if kind != FieldKind::Flag) {
+            return Err(Error::Parse(format!(
+                "Filter '{}' needs an operator and a value, e.g. {}=<value>.",
+                expr,
+                target.name()
+            )));
+        }
+        return Ok(Condition {
+            target,
+            op: Op::Eq,
+            operand: Operand::Flag(!negated),
+        });
+    };
+
+    let target = lookup::<T>(&expr[..at])?;
+    let (field, kind) = (target.name(), target.kind());
+    let allowed = Op::allowed(kind);
+    if !allowed.contains(&op) {
+        let hint = if allowed.is_empty() {
+            format!("use '{field}' or '!{field}'")
+        } else {
+            let ops: Vec<&str> = allowed.iter().map(|o| o.symbol()).collect();
+            format!("allowed: {}", ops.join(" "))
+        };
+        return Err(Error::Parse(format!(
+            "Operator '{}' does not apply to '{}' ({}).",
+            sym, field, hint
+        )));
+    }
+
+    let raw = unquote(&expr[at + sym.len()..]);
+    let invalid = |what: &str| Error::Parse(format!("Invalid {what} in filter '{expr}'."));
+    let operand = match kind {
+        FieldKind::Text => match raw.strip_prefix('/').and_then(|r| r.strip_suffix('/')) {
+            Some(pattern) if op == Op::Match => Operand::Pattern(
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| Error::Parse(format!("Invalid pattern in '{expr}': {e}")))?,
+            ),
+            _ => Operand::Text(raw.to_string()),
+        },
+        FieldKind::Number => Operand::Number(raw.parse().map_err(|_| invalid("number"))?),
+        FieldKind::Date => Operand::Date(parse_date(raw, today)?),
+        FieldKind::Card => Operand::Card(parse_card(raw).ok_or_else(|| invalid("card"))?),
+        FieldKind::Days => Operand::Day(DayOfWeek::try_from(raw)?),
+        FieldKind::Color => Operand::Color(CardColor::try_from(raw)?),
+        FieldKind::Flag => unreachable!("flags take no operator"),
+    };
+
+    Ok(Condition {
+        target,
+        op,
+        operand,
+    })
+}
+
+fn unquote(raw: &str) -> &str {
+    raw.strip_prefix('"')
+        .and_then(|r| r.strip_suffix('"'))
+        .unwrap_or(raw)
+}
+
+/// `today`, `tomorrow`, a weekday (the next one on or after `today`) or any date `Date` accepts.
+fn parse_date(raw: &str, today: NaiveDate) -> Result<NaiveDate> {
+    match raw.to_ascii_lowercase().as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today + Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(day) = DayOfWeek::try_from(raw) {
+        let ahead = (0..7)
+            .map(|n| today + Duration::days(n))
+            .find(|d| d.weekday().to_day_of_week() == day)
+            .unwrap_or(today);
+        return Ok(ahead);
+    }
+    Date::try_from_str(raw).map(|d| d.0)
+}
+
+fn parse_card(raw: &str) -> Option<Option<i32>> {
+    if raw.eq_ignore_ascii_case("none") {
+        return Some(None);
+    }
+    if CardColorIdArg::accepts(raw)
+        && let Ok(Arg::CardColorId(id)) = CardColorIdArg::new(raw)
+    {
+        return Some(Some(id));
+    }
+    raw.parse().ok().map(Some)
+}
@@ -0,0 +1,175 @@
+use crate::config::Config;
+use crate::core::context::AppContext;
+use crate::core::models::{BaseEntity, Card, Event, Task, TaskTemplate};
+use crate::core::repository::{Repository, Sort};
+use crate::core::types::EntityType;
+use crate::errors::{Error, Result};
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+/// Entities and settings as they were at one point in the session.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    tasks: Repository<Task>,
+    events: Repository<Event>,
+    cards: Repository<Card>,
+    templates: Repository<TaskTemplate>,
+    config: Config,
+}
+
+impl Snapshot {
+    pub fn capture(ctx: &AppContext) -> Self {
+        Self {
+            tasks: ctx.tasks.clone(),
+            events: ctx.events.clone(),
+            cards: ctx.cards.clone(),
+            templates: ctx.templates.clone(),
+            config: ctx.config.clone(),
+        }
+    }
+
+    fn matches(&self, ctx: &AppContext) -> bool {
+        self.tasks == ctx.tasks
+            && self.events == ctx.events
+            && self.cards == ctx.cards
+            && self.templates == ctx.templates
+            && self.config.rows() == ctx.config.rows()
+    }
+
+    /// Puts this state back into `ctx` and returns one line per entity or setting it changed.
+    fn restore(self, ctx: &mut AppContext) -> Result<Vec<String>> {
+        let mut changes = Vec::new();
+        describe_changes(EntityType::Card, &ctx.cards, &self.cards, &mut changes);
+        describe_changes(
+            EntityType::Template,
+            &ctx.templates,
+            &self.templates,
+            &mut changes,
+        );
+        describe_changes(EntityType::Event, &ctx.events, &self.events, &mut changes);
+        describe_changes(EntityType::Task, &ctx.tasks, &self.tasks, &mut changes);
+
+        let config_changes: Vec<String> = ctx
+            .config
+            .rows()
+            .iter()
+            .zip(self.config.rows().iter())
+            .filter(|(now, then)| now.2 != then.2)
+            .map(|(now, then)| format!("config {}: {} -> {}", now.0, now.2, then.2))
+            .collect();
+        if !config_changes.is_empty() {
+            ctx.config.replace(self.config.view().clone())?;
+            ctx.logger
+                .set_file_logging_enabled(ctx.config.file_logging_enabled());
+            changes.extend(config_changes);
+        }
+
+        ctx.tasks = self.tasks;
+        ctx.events = self.events;
+        ctx.cards = self.cards;
+        ctx.templates = self.templates;
+        Ok(changes)
+    }
+}
+
+fn describe_changes<T: BaseEntity + PartialEq + Display>(
+    kind: EntityType,
+    current: &Repository<T>,
+    target: &Repository<T>,
+    out: &mut Vec<String>,
+) {
+    for entity in current.values(Sort::IdAsc) {
+        match target.get(entity.id()) {
+            Err(_) => out.push(format!("removed {} {}", kind, entity.id())),
+            Ok(restored) if restored != entity => {
+                out.push(format!("changed {} {}: {}", kind, entity.id(), restored))
+            }
+            Ok(_) => {}
+        }
+    }
+    for entity in target.values(Sort::IdAsc) {
+        if current.get(entity.id()).is_err() {
+            out.push(format!("added {} {}: {}", kind, entity.id(), entity));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    label: String,
+    state: Snapshot,
+}
+
+/// A command undone or redone, with what it changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryStep {
+    pub label: String,
+    pub changes: Vec<String>,
+}
+
+/// Undo and redo stacks of whole-state snapshots, bounded by `HISTORY_DEPTH`.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Records `label` as undoable if the state moved away from `before`, and returns whether it
+/// did. A new change drops anything that was undone, as is usual for editors.
+pub fn record(ctx: &mut AppContext, label: &str, before: Snapshot) -> bool {
+    if before.matches(ctx) {
+        return false;
+    }
+    let depth = ctx.config.history_depth();
+    let history = &mut ctx.history;
+    history.redo.clear();
+    history.undo.push_back(Entry {
+        label: label.to_string(),
+        state: before,
+    });
+    while history.undo.len() > depth {
+        history.undo.pop_front();
+    }
+    true
+}
+
+pub fn undo(ctx: &mut AppContext) -> Result<HistoryStep> {
+    let current = Snapshot::capture(ctx);
+    let entry = ctx
+        .history
+        .undo
+        .back()
+        .ok_or_else(|| Error::Parse("Nothing to undo.".into()))?;
+    let label = entry.label.clone();
+    // The entry stays put until the restore succeeds, so a failed write can be retried.
+    let changes = entry.state.clone().restore(ctx)?;
+    ctx.history.undo.pop_back();
+    ctx.history.redo.push(Entry {
+        label: label.clone(),
+        state: current,
+    });
+    Ok(HistoryStep { label, changes })
+}
+
+pub fn redo(ctx: &mut AppContext) -> Result<HistoryStep> {
+    let current = Snapshot::capture(ctx);
+    let entry = ctx
+        .history
+        .redo
+        .last()
+        .ok_or_else(|| Error::Parse("Nothing to redo.".into()))?;
+    let label = entry.label.clone();
+    let changes = entry.state.clone().restore(ctx)?;
+    ctx.history.redo.pop();
+    ctx.history.undo.push_back(Entry {
+        label: label.clone(),
+        state: current,
+    });
+    Ok(HistoryStep { label, changes })
+}
@@ -0,0 +1,210 @@
+use crate::core::context::AppContext;
+use crate::core::models::{BaseEntity, Task};
+use crate::core::persist::{SaveFile, apply_save_file};
+use crate::core::repository::{Repository, Sort};
+use crate::core::transaction::Transaction;
+use crate::core::types::TaskConflictPolicy;
+use crate::core::uid::Uid;
+use crate::errors::Result;
+use std::collections::{HashMap, HashSet};
+
+/// What `import` added to the plan, and what it did with tasks named like existing ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub cards_added: usize,
+    /// Imported cards that matched an existing card by name and color and were not added.
+    pub cards_matched: usize,
+    pub templates_added: usize,
+    pub events_added: usize,
+    pub tasks_added: usize,
+    pub tasks_skipped: usize,
+    pub tasks_renamed: usize,
+    pub tasks_overwritten: usize,
+}
+
+impl ImportReport {
+    pub fn summary(&self) -> String {
+        let mut parts = vec![
+            format!(
+                "{} card(s) ({} matched existing)",
+                self.cards_added, self.cards_matched
+            ),
+            format!("{} template(s)", self.templates_added),
+            format!("{} event(s)", self.events_added),
+            format!("{} task(s)", self.tasks_added),
+        ];
+        let conflicts = [
+            (self.tasks_skipped, "skipped"),
+            (self.tasks_renamed, "renamed"),
+            (self.tasks_overwritten, "overwritten"),
+        ];
+        parts.extend(
+            conflicts
+                .iter()
+                .filter(|(count, _)| *count > 0)
+                .map(|(count, verb)| format!("{count} task(s) {verb}")),
+        );
+        format!("Imported {}", parts.join(", "))
+    }
+}
+
+/// Adds the entities in `save_file` to the plan in `ctx` instead of replacing it.
+///
+/// The file is first loaded into a scratch context, so it is checked the way `read` checks it.
+/// Cards that match an existing card by name and color are reused, and the templates, events and
+/// tasks that pointed at them are remapped. Imported tasks named like a task already in the plan
+/// are handled by `policy`. Imported uids that are already taken get fresh ones. Everything runs
+/// in one `Transaction`, so a failed import leaves the plan as it was.
+pub fn import_save_file(
+    ctx: &mut AppContext,
+    save_file: &SaveFile,
+    policy: TaskConflictPolicy,
+) -> Result<ImportReport> {
+    let mut source = ctx.scratch();
+    apply_save_file(&mut source, save_file)?;
+
+    let mut report = ImportReport::default();
+    Transaction::new().run(ctx, false, |ctx| {
+        let cards = import_cards(ctx, &source, &mut report);
+
+        let mut templates = HashMap::new();
+        let mut taken = uids(&ctx.templates);
+        for template in source.templates.values(Sort::IdAsc) {
+            let mut template = template.clone();
+            template.card_id = template.card_id.and_then(|id| cards.get(&id).copied());
+            fresh_uid(&mut template.uid, &mut taken);
+            let source_id = template.id;
+            templates.insert(source_id, ctx.templates.insert(template).id);
+            report.templates_added += 1;
+        }
+
+        let mut taken = uids(&ctx.events);
+        for event in source.events.values(Sort::IdAsc) {
+            let mut event = event.clone();
+            event.card_id = event.card_id.and_then(|id| cards.get(&id).copied());
+            fresh_uid(&mut event.uid, &mut taken);
+            ctx.events.insert(event);
+            report.events_added += 1;
+        }
+
+        import_tasks(ctx, &source, &cards, &templates, policy, &mut report)
+    })?;
+    Ok(report)
+}
+
+/// Adds the cards of `source` that have no match in `ctx` and maps every source card id to the
+/// id it has in `ctx`.
+fn import_cards(
+    ctx: &mut AppContext,
+    source: &AppContext,
+    report: &mut ImportReport,
+) -> HashMap<i32, i32> {
+    let existing = ctx
+        .cards
+        .values(Sort::IdAsc)
+        .into_iter()
+        .map(|card| ((card.name.clone(), card.color), card.id))
+        .collect::<HashMap<_, _>>();
+    let mut taken = uids(&ctx.cards);
+    let mut ids = HashMap::new();
+
+    for card in source.cards.values(Sort::IdAsc) {
+        if let Some(id) = existing.get(&(card.name.clone(), card.color)) {
+            ids.insert(card.id, *id);
+            report.cards_matched += 1;
+            continue;
+        }
+        let mut card = card.clone();
+        fresh_uid(&mut card.uid, &mut taken);
+        let source_id = card.id;
+        ids.insert(source_id, ctx.cards.insert(card).id);
+        report.cards_added += 1;
+    }
+    ids
+}
+
+fn import_tasks(
+    ctx: &mut AppContext,
+    source: &AppContext,
+    cards: &HashMap<i32, i32>,
+    templates: &HashMap<i32, i32>,
+    policy: TaskConflictPolicy,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let mut existing: HashMap<String, Vec<i32>> = HashMap::new();
+    for task in ctx.tasks.values(Sort::IdAsc) {
+        existing.entry(task.name.clone()).or_default().push(task.id);
+    }
+    // Renamed tasks also steer clear of the names in the file.
+    let mut names = existing.keys().cloned().collect::<HashSet<_>>();
+    names.extend(
+        source
+            .tasks
+            .values(Sort::Unordered)
+            .into_iter()
+            .map(|t| t.name.clone()),
+    );
+    let mut taken = uids(&ctx.tasks);
+
+    for task in source.tasks.values(Sort::IdAsc) {
+        let mut task = task.clone();
+        if existing.contains_key(&task.name) {
+            match policy {
+                TaskConflictPolicy::Skip => {
+                    report.tasks_skipped += 1;
+                    continue;
+                }
+                TaskConflictPolicy::Rename => {
+                    task.name = unused_name(&task.name, &names);
+                    report.tasks_renamed += 1;
+                }
+                TaskConflictPolicy::Overwrite => {
+                    for id in existing.remove(&task.name).unwrap_or_default() {
+                        let replaced = ctx.tasks.delete(id)?;
+                        taken.remove(&replaced.uid);
+                        report.tasks_overwritten += 1;
+                    }
+                }
+            }
+        }
+        remap_task(&mut task, cards, templates);
+        fresh_uid(&mut task.uid, &mut taken);
+        names.insert(task.name.clone());
+        ctx.tasks.insert(task);
+        report.tasks_added += 1;
+    }
+    Ok(())
+}
+
+fn remap_task(task: &mut Task, cards: &HashMap<i32, i32>, templates: &HashMap<i32, i32>) {
+    task.card_id = task.card_id.and_then(|id| cards.get(&id).copied());
+    if let Some(origin) = &mut task.origin {
+        match templates.get(&origin.template_id) {
+            Some(id) => origin.template_id = *id,
+            None => task.origin = None,
+        }
+    }
+}
+
+/// `"<name> (2)"`, `"<name> (3)"`, ... whichever is first not in `names`.
+fn unused_name(name: &str, names: &HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{name} ({n})"))
+        .find(|candidate| !names.contains(candidate))
+        .expect("an unused name exists")
+}
+
+fn uids<E: BaseEntity>(repo: &Repository<E>) -> HashSet<Uid> {
+    repo.values(Sort::Unordered)
+        .into_iter()
+        .map(|entity| entity.uid().clone())
+        .collect()
+}
+
+/// Gives `uid` a fresh value when it is already taken, and marks the result as taken.
+fn fresh_uid(uid: &mut Uid, taken: &mut HashSet<Uid>) {
+    if taken.contains(uid) {
+        *uid = Uid::new();
+    }
+    taken.insert(uid.clone());
+}
@@ -0,0 +1,111 @@
+use crate::core::context::AppContext;
+use crate::core::models::Task;
+use crate::core::repository::Sort;
+use crate::core::types::{CardDeletePolicy, EntityType};
+use crate::errors::{Error, Result};
+
+/// Templates, events and tasks that use card `card_id`, in that order and by id.
+pub fn card_referencers(ctx: &AppContext, card_id: i32) -> Vec<(EntityType, i32)> {
+    let uses = |id: Option<i32>| id == Some(card_id);
+    let templates = ctx
+        .templates
+        .query()
+        .r#where(move |t| uses(t.card_id))
+        .order(Sort::IdAsc)
+        .ids();
+    let events = ctx
+        .events
+        .query()
+        .r#where(move |e| uses(e.card_id))
+        .order(Sort::IdAsc)
+        .ids();
+    let tasks = ctx
+        .tasks
+        .query()
+        .r#where(move |t| uses(t.card_id))
+        .order(Sort::IdAsc)
+        .ids();
+
+    let tag = |kind: EntityType| move |id: i32| (kind, id);
+    templates
+        .into_iter()
+        .map(tag(EntityType::Template))
+        .chain(events.into_iter().map(tag(EntityType::Event)))
+        .chain(tasks.into_iter().map(tag(EntityType::Task)))
+        .collect()
+}
+
+/// Deletes task `task_id`. A task generated from a template leaves its occurrence skipped on
+/// the template, so scheduling does not generate it again.
+pub fn delete_task(ctx: &mut AppContext, task_id: i32) -> Result<Task> {
+    let task = ctx.tasks.delete(task_id)?;
+    if let Some(origin) = task.origin
+        && let Ok(template) = ctx.templates.get_mut(origin.template_id)
+    {
+        template.skip(origin.occurrence);
+    }
+    Ok(task)
+}
+
+/// Deletes card `card_id`, handling the entities that use it according to `policy`.
+/// Returns one line per entity that was deleted or lost its card.
+pub fn delete_card(
+    ctx: &mut AppContext,
+    card_id: i32,
+    policy: CardDeletePolicy,
+) -> Result<Vec<String>> {
+    ctx.cards.get(card_id)?;
+    let referencers = card_referencers(ctx, card_id);
+    let mut changes = Vec::new();
+
+    match policy {
+        CardDeletePolicy::Restrict if !referencers.is_empty() => {
+            let list: Vec<String> = referencers
+                .iter()
+                .map(|(kind, id)| format!("{kind} {id}"))
+                .collect();
+            return Err(Error::Parse(format!(
+                "Card {} is used by {}. Use --cascade to delete them too, or --set-null to keep them without a card.",
+                card_id,
+                list.join(", ")
+            )));
+        }
+        CardDeletePolicy::Restrict => {}
+        CardDeletePolicy::Cascade => {
+            for (kind, id) in referencers {
+                match kind {
+                    EntityType::Template => {
+                        ctx.templates.delete(id)?;
+                        // Same as `del template`: surviving occurrences become one-off tasks.
+                        ctx.tasks
+                            .query_mut()
+                            .r#where(|t| t.origin.is_some_and(|o| o.template_id == id))
+                            .for_each_mut(|t| t.origin = None);
+                    }
+                    EntityType::Event => {
+                        ctx.events.delete(id)?;
+                    }
+                    EntityType::Task => {
+                        delete_task(ctx, id)?;
+                    }
+                    EntityType::Card => {}
+                }
+                changes.push(format!("deleted {kind} {id}"));
+            }
+        }
+        CardDeletePolicy::SetNull => {
+            for (kind, id) in referencers {
+                match kind {
+                    EntityType::Template => ctx.templates.get_mut(id)?.card_id = None,
+                    EntityType::Event => ctx.events.get_mut(id)?.card_id = None,
+                    EntityType::Task => ctx.tasks.get_mut(id)?.card_id = None,
+                    EntityType::Card => {}
+                }
+                changes.push(format!("cleared card on {kind} {id}"));
+            }
+        }
+    }
+
+    ctx.cards.delete(card_id)?;
+    Ok(changes)
+}
@@ -0,0 +1,260 @@
+use crate::arg::arg_parse_strategy::CommandArgParser;
+use crate::command::command_parser::CommandParser;
+use crate::core::context::AppContext;
+use crate::core::models::BaseEntity;
+use crate::core::persist::save_state;
+use crate::core::repository::{Repository, Sort};
+use crate::core::transaction::CommandQueue;
+use crate::errors::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Directory under the schedules directory that holds the journal of the running session.
+const JOURNAL_DIR: &str = ".journal";
+/// One command line per entry, in the order they ran.
+const COMMANDS_FILE: &str = "commands";
+
+/// How a command that succeeded is written to the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Journaling {
+    /// Nothing to replay: the command left the plan alone or saved its own result (config).
+    Skip,
+    /// The command line is replayed, with relative dates and filters resolved as they stood when
+    /// it ran (see `Command::journal_line`).
+    Line,
+    /// The command replaced the plan with one from outside the session (read, checkout, merge)
+    /// or from its undo history (undo, redo). The resulting plan is kept next to the journal and
+    /// replayed with `read`, so recovery depends neither on files that may have changed since
+    /// nor on an undo history the journal does not fully hold.
+    Snapshot,
+}
+
+/// The ids a session gave the entities of a plan, listed in the order a save file holds them.
+/// Loading a save numbers entities from 1, so these give the session's ids back and the lines
+/// journaled after the save still name the entities they named when they ran.
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionIds {
+    cards: RepoIds,
+    templates: RepoIds,
+    events: RepoIds,
+    tasks: RepoIds,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RepoIds {
+    ids: Vec<i32>,
+    next_id: i32,
+}
+
+impl RepoIds {
+    fn of<T: BaseEntity + Clone>(repo: &Repository<T>) -> Self {
+        Self {
+            ids: repo.values(Sort::IdAsc).iter().map(|e| e.id()).collect(),
+            next_id: repo.peek_next_id(),
+        }
+    }
+
+    /// Whether loading the entities from a save would give them these ids anyway.
+    fn as_loaded(&self) -> bool {
+        (1..).zip(&self.ids).all(|(loaded, id)| loaded == *id)
+            && self.next_id == self.ids.len() as i32 + 1
+    }
+}
+
+impl SessionIds {
+    /// The ids in `ctx`, or `None` when loading its plan from a save would give the same ones.
+    fn capture(ctx: &AppContext) -> Option<Self> {
+        let ids = Self {
+            cards: RepoIds::of(&ctx.cards),
+            templates: RepoIds::of(&ctx.templates),
+            events: RepoIds::of(&ctx.events),
+            tasks: RepoIds::of(&ctx.tasks),
+        };
+        let as_loaded = [&ids.cards, &ids.templates, &ids.events, &ids.tasks]
+            .iter()
+            .all(|repo| repo.as_loaded());
+        (!as_loaded).then_some(ids)
+    }
+
+    /// Gives the plan just loaded into `ctx` these ids, along with every reference to them.
+    fn restore(&self, ctx: &mut AppContext) -> Result<()> {
+        let cards = ctx.cards.reassign(&self.cards.ids, self.cards.next_id)?;
+        let templates = ctx
+            .templates
+            .reassign(&self.templates.ids, self.templates.next_id)?;
+        ctx.events.reassign(&self.events.ids, self.events.next_id)?;
+        let tasks = ctx.tasks.reassign(&self.tasks.ids, self.tasks.next_id)?;
+        let card = |id: &mut Option<i32>| *id = id.and_then(|old| cards.get(&old).copied());
+
+        for template in ctx.templates.values_mut() {
+            card(&mut template.card_id);
+        }
+        for event in ctx.events.values_mut() {
+            card(&mut event.card_id);
+        }
+        for task in ctx.tasks.values_mut() {
+            card(&mut task.card_id);
+            if let Some(origin) = &mut task.origin {
+                origin.template_id = templates[&origin.template_id];
+            }
+            for slice in &mut task.subtasks {
+                slice.task_id = tasks[&slice.task_id];
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write-ahead journal of the commands that changed the plan since startup. A clean exit clears
+/// it, so a journal found at startup means the last session ended without saving its work.
+pub struct Journal {
+    root: PathBuf,
+}
+
+impl Journal {
+    pub fn open(schedules_dir: &Path) -> Self {
+        Self {
+            root: schedules_dir.join(JOURNAL_DIR),
+        }
+    }
+
+    fn commands_path(&self) -> PathBuf {
+        self.root.join(COMMANDS_FILE)
+    }
+
+    /// The session's ids after entry `index`, or for the saved workspace the journal starts
+    /// from when `index` is 0. Only kept when loading that plan would number it differently.
+    fn ids_path(&self, index: usize) -> PathBuf {
+        self.root.join(format!("ids-{index}.json"))
+    }
+
+    fn keep_ids(&self, ctx: &AppContext, index: usize) -> Result<()> {
+        match SessionIds::capture(ctx) {
+            Some(ids) => {
+                fs::create_dir_all(&self.root)?;
+                fs::write(self.ids_path(index), serde_json::to_string(&ids)?)?;
+                Ok(())
+            }
+            None => remove_if_present(&self.ids_path(index)),
+        }
+    }
+
+    fn restore_ids(&self, ctx: &mut AppContext, index: usize) -> Result<()> {
+        match fs::read_to_string(self.ids_path(index)) {
+            Ok(contents) => serde_json::from_str::<SessionIds>(&contents)?.restore(ctx),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Starts an empty journal on top of the plan `ctx` just saved to the workspace.
+    pub fn restart(&self, ctx: &AppContext) -> Result<()> {
+        self.clear()?;
+        self.keep_ids(ctx, 0)
+    }
+
+    /// Appends `line` as `journaling` asks, flushing it to disk before returning.
+    pub fn record(&self, ctx: &AppContext, line: &str, journaling: Journaling) -> Result<()> {
+        let entry = match journaling {
+            Journaling::Skip => return Ok(()),
+            Journaling::Line => line.to_string(),
+            Journaling::Snapshot => {
+                let index = self.entries()?.len() + 1;
+                let path = self.root.join(format!("snapshot-{index}.json"));
+                save_state(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates, &path)?;
+                self.keep_ids(ctx, index)?;
+                format!("read \"{}\"", std::path::absolute(&path)?.display())
+            }
+        };
+        fs::create_dir_all(&self.root)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.commands_path())?;
+        writeln!(file, "{entry}")?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// The journaled command lines, oldest first; empty when there is no journal.
+    pub fn entries(&self) -> Result<Vec<String>> {
+        match fs::read_to_string(self.commands_path()) {
+            Ok(contents) => Ok(contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Keeps the first `len` entries, e.g. the ones a partial replay managed to apply.
+    pub fn truncate(&self, len: usize) -> Result<()> {
+        let entries = self.entries()?;
+        for index in len + 1..=entries.len() {
+            remove_if_present(&self.ids_path(index))?;
+        }
+        let kept = entries.into_iter().take(len).collect::<Vec<_>>();
+        if kept.is_empty() {
+            return remove_if_present(&self.commands_path());
+        }
+        fs::write(self.commands_path(), format!("{}\n", kept.join("\n")))?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_dir_all(&self.root) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Replays this journal's `entries` into `ctx`, which holds the saved workspace, through a
+    /// `CommandQueue`, parsing each one the way the prompt does and giving entities back the
+    /// ids the session had. Returns how many entries were applied and the error that stopped
+    /// the replay, if any.
+    pub fn replay(&self, ctx: &mut AppContext, entries: &[String]) -> (usize, Result<()>) {
+        if let Err(err) = self.restore_ids(ctx, 0) {
+            return (0, Err(err));
+        }
+        let arg_parser = CommandArgParser::new();
+        let mut queue = CommandQueue::new();
+        let mut parse_error = None;
+
+        for line in entries {
+            let mut parts = line.split_whitespace();
+            let name = parts.next().unwrap_or_default();
+            let raw_args = parts.map(str::to_string).collect::<Vec<_>>();
+            match arg_parser.parse(name, &raw_args) {
+                Ok(args) => queue.push_labeled(line, name, args),
+                Err(err) => {
+                    parse_error = Some(failed_at(line, err));
+                    break;
+                }
+            }
+        }
+
+        let (applied, result) = queue.replay(ctx, &CommandParser::new(), |index, ctx| {
+            self.restore_ids(ctx, index + 1)
+        });
+        match (result, parse_error) {
+            (Err(err), _) => (applied, Err(failed_at(&entries[applied], err))),
+            (Ok(()), Some(err)) => (applied, Err(err)),
+            (Ok(()), None) => (applied, Ok(())),
+        }
+    }
+}
+
+fn remove_if_present(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+fn failed_at(line: &str, err: Error) -> Error {
+    Error::Parse(format!("Replay stopped at '{line}'. {err}"))
+}
@@ -0,0 +1,233 @@
+use crate::arg::args::{NameArg, SingleTokenArg};
+use crate::core::aliases::{TokenList, TokenMatrix};
+use crate::core::persist::SaveFile;
+use crate::core::uid::saved_uid;
+use crate::errors::{Error, Result};
+use std::collections::HashMap;
+
+const CARD_REF: &str = "+C";
+const TEMPLATE_REF: &str = "+T";
+const SECTIONS: [&str; 4] = ["card", "template", "event", "task"];
+
+/// Which version of a conflicting entity to keep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+/// An entity both sides changed differently since the merge base. `None` means the side (or
+/// the base) does not have it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub kind: &'static str,
+    pub name: String,
+    pub base: Option<TokenList>,
+    pub ours: Option<TokenList>,
+    pub theirs: Option<TokenList>,
+}
+
+#[derive(Debug, Clone)]
+enum Slot {
+    Resolved(Option<TokenList>),
+    Conflict(usize),
+}
+
+/// Outcome of a three-way merge before conflicts are decided.
+#[derive(Debug, Clone)]
+pub struct MergePlan {
+    sections: Vec<Vec<(String, Slot)>>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merges the changes `ours` and `theirs` made since `base`, entity by entity.
+///
+/// Saved ids are positions, so entities are matched by their saved uid, or by name when a save
+/// predates uids (the second entity of the same name is `"name"#1`, and so on), and
+/// card/template references are compared by the key they point at. Changes that touch different
+/// entities merge on their own; an entity changed on both sides in different ways becomes a
+/// conflict.
+pub fn three_way(base: &SaveFile, ours: &SaveFile, theirs: &SaveFile) -> MergePlan {
+    let (base, ours, theirs) = (keyed(base), keyed(ours), keyed(theirs));
+    let mut sections = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for (i, kind) in SECTIONS.iter().enumerate() {
+        let base_map: HashMap<&str, &TokenList> = by_key(&base[i]);
+        let ours_map = by_key(&ours[i]);
+        let theirs_map = by_key(&theirs[i]);
+
+        let mut keys: Vec<&str> = ours[i].iter().map(|(k, _)| k.as_str()).collect();
+        keys.extend(
+            theirs[i]
+                .iter()
+                .map(|(k, _)| k.as_str())
+                .filter(|k| !ours_map.contains_key(k)),
+        );
+
+        let mut section = Vec::new();
+        for key in keys {
+            let b = base_map.get(key).copied();
+            let o = ours_map.get(key).copied();
+            let t = theirs_map.get(key).copied();
+            let slot = if o == t || t == b {
+                Slot::Resolved(o.cloned())
+            } else if o == b {
+                Slot::Resolved(t.cloned())
+            } else {
+                let name = [o, t, b]
+                    .into_iter()
+                    .flatten()
+                    .find_map(display_name)
+                    .unwrap_or_else(|| key.to_string());
+                conflicts.push(Conflict {
+                    kind,
+                    name,
+                    base: b.cloned(),
+                    ours: o.cloned(),
+                    theirs: t.cloned(),
+                });
+                Slot::Conflict(conflicts.len() - 1)
+            };
+            section.push((key.to_string(), slot));
+        }
+        sections.push(section);
+    }
+
+    MergePlan {
+        sections,
+        conflicts,
+    }
+}
+
+impl MergePlan {
+    /// Builds the merged plan with one decision per conflict, in order. References to a card or
+    /// template the merge removed are dropped.
+    pub fn resolve(&self, choices: &[Side]) -> Result<SaveFile> {
+        if choices.len() < self.conflicts.len() {
+            return Err(Error::Parse(format!(
+                "Merge aborted: {} conflict(s) left unresolved.",
+                self.conflicts.len() - choices.len()
+            )));
+        }
+
+        let mut matrices: Vec<TokenMatrix> = Vec::new();
+        let mut positions: Vec<HashMap<&str, usize>> = Vec::new();
+        for section in &self.sections {
+            let kept: Vec<(&str, &TokenList)> = section
+                .iter()
+                .filter_map(|(key, slot)| {
+                    let tokens = match slot {
+                        Slot::Resolved(tokens) => tokens.as_ref(),
+                        Slot::Conflict(idx) => match choices[*idx] {
+                            Side::Ours => self.conflicts[*idx].ours.as_ref(),
+                            Side::Theirs => self.conflicts[*idx].theirs.as_ref(),
+                        },
+                    };
+                    tokens.map(|t| (key.as_str(), t))
+                })
+                .collect();
+            positions.push(
+                kept.iter()
+                    .enumerate()
+                    .map(|(i, (key, _))| (*key, i + 1))
+                    .collect(),
+            );
+            let (cards, templates) = (&positions[0], positions.get(1));
+            matrices.push(
+                kept.iter()
+                    .map(|(_, tokens)| resolve_refs(tokens, cards, templates))
+                    .collect(),
+            );
+        }
+
+        let mut matrices = matrices.into_iter();
+        Ok(SaveFile {
+            cards: matrices.next().unwrap_or_default(),
+            templates: matrices.next().unwrap_or_default(),
+            events: matrices.next().unwrap_or_default(),
+            tasks: matrices.next().unwrap_or_default(),
+        })
+    }
+}
+
+type Keyed = Vec<(String, TokenList)>;
+
+fn by_key(entries: &Keyed) -> HashMap<&str, &TokenList> {
+    entries.iter().map(|(k, t)| (k.as_str(), t)).collect()
+}
+
+/// Sections in `SECTIONS` order, each entity keyed by uid or name with references made symbolic.
+fn keyed(save: &SaveFile) -> [Keyed; 4] {
+    let cards = key_section(&save.cards, &[], &[]);
+    let card_keys: Vec<String> = cards.iter().map(|(k, _)| k.clone()).collect();
+    let templates = key_section(&save.templates, &card_keys, &[]);
+    let template_keys: Vec<String> = templates.iter().map(|(k, _)| k.clone()).collect();
+    let events = key_section(&save.events, &card_keys, &template_keys);
+    let tasks = key_section(&save.tasks, &card_keys, &template_keys);
+    [cards, templates, events, tasks]
+}
+
+fn key_section(section: &TokenMatrix, card_keys: &[String], template_keys: &[String]) -> Keyed {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    section
+        .iter()
+        .map(|tokens| {
+            let uid = tokens.iter().find_map(|t| saved_uid(t));
+            let key = match uid {
+                Some(uid) => uid.to_ascii_uppercase(),
+                None => {
+                    let name = display_name(tokens).unwrap_or_else(|| tokens.join(" "));
+                    let count = seen.entry(name.clone()).or_default();
+                    let key = match *count {
+                        0 => name,
+                        n => format!("{name}#{n}"),
+                    };
+                    *count += 1;
+                    key
+                }
+            };
+            let symbolic = tokens
+                .iter()
+                .map(|t| {
+                    symbolic_ref(t, CARD_REF, card_keys)
+                        .or_else(|| symbolic_ref(t, TEMPLATE_REF, template_keys))
+                        .unwrap_or_else(|| t.clone())
+                })
+                .collect();
+            (key, symbolic)
+        })
+        .collect()
+}
+
+fn display_name(tokens: &TokenList) -> Option<String> {
+    tokens.iter().find(|t| NameArg::accepts(t)).cloned()
+}
+
+/// `+C2` becomes `+C<key of the second card>`.
+fn symbolic_ref(token: &str, prefix: &str, keys: &[String]) -> Option<String> {
+    let idx: usize = token.strip_prefix(prefix)?.parse().ok()?;
+    let key = keys.get(idx.checked_sub(1)?)?;
+    Some(format!("{prefix}{key}"))
+}
+
+fn resolve_refs(
+    tokens: &TokenList,
+    cards: &HashMap<&str, usize>,
+    templates: Option<&HashMap<&str, usize>>,
+) -> TokenList {
+    tokens
+        .iter()
+        .filter_map(|t| {
+            if let Some(key) = t.strip_prefix(CARD_REF) {
+                cards.get(key).map(|pos| format!("{CARD_REF}{pos}"))
+            } else if let Some(key) = t.strip_prefix(TEMPLATE_REF) {
+                templates
+                    .and_then(|m| m.get(key))
+                    .map(|pos| format!("{TEMPLATE_REF}{pos}"))
+            } else {
+                Some(t.clone())
+            }
+        })
+        .collect()
+}
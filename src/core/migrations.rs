@@ -0,0 +1,201 @@
+use crate::config::{ConfigKey, PlanSettings};
+use crate::core::persist::{SaveFile, TypedSave};
+use crate::core::types::SaveFormat;
+use crate::errors::{Error, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use strum::IntoEnumIterator;
+
+/// Format version written by `save_state`. Bump it, and add the step that upgrades the previous
+/// version to `MIGRATIONS`, whenever a change to the command grammar, to how an `Arg` is
+/// displayed or to the typed fields would read files written by an older build differently.
+pub const SAVE_VERSION: u32 = 3;
+
+/// Top-level key holding the format version of a save file.
+const VERSION_KEY: &str = "version";
+/// Top-level key naming how the entities are written, `tokens` or `typed`.
+const FORMAT_KEY: &str = "format";
+/// Optional top-level key holding the plan settings written by `save --with-config`. Builds that
+/// predate it read the entities and ignore it, so adding it did not need a new version.
+const CONFIG_KEY: &str = "config";
+/// Files written before versions were recorded have no version key.
+const UNVERSIONED: u32 = 1;
+
+/// Upgrades the sections of a save written as version `from` to version `from + 1`.
+struct Migration {
+    from: u32,
+    upgrade: fn(&mut Map<String, Value>) -> Result<()>,
+}
+
+/// Every step from the first version to `SAVE_VERSION`, oldest first.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 1,
+        upgrade: upgrade_unversioned,
+    },
+    Migration {
+        from: 2,
+        upgrade: upgrade_untyped,
+    },
+];
+
+/// Version 1 covers every file written before versions were recorded. Its tokens are read as
+/// they are: until then the grammar only gained optional arguments (templates, estimates,
+/// markers, details, uids and checklist items), so older tokens still parse the same way.
+fn upgrade_unversioned(_sections: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+/// Version 3 added typed save files, which builds reading version 2 would fail on. Version 2
+/// files are all token lists and are read as they are.
+fn upgrade_untyped(_sections: &mut Map<String, Value>) -> Result<()> {
+    Ok(())
+}
+
+/// A save file read from disk, upgraded to the current version.
+#[derive(Debug)]
+pub struct UpgradedSave {
+    pub save: SaveFile,
+    /// Version the file was written as; below `SAVE_VERSION` if it was upgraded.
+    pub from_version: u32,
+    /// How the file wrote its entities. Typed files are converted to token lists when read.
+    pub format: SaveFormat,
+    /// Plan settings saved with the entities, if any.
+    pub config: Option<PlanSettings>,
+}
+
+#[derive(Serialize)]
+struct VersionedSave<'a, T> {
+    version: u32,
+    format: SaveFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<&'a PlanSettings>,
+    #[serde(flatten)]
+    save: &'a T,
+}
+
+/// Writes `save` as JSON stamped with `SAVE_VERSION`.
+pub fn encode(save: &SaveFile) -> Result<String> {
+    encode_as(SaveFormat::Tokens, save, None)
+}
+
+/// Writes `save` as a typed save file stamped with `SAVE_VERSION`.
+pub fn encode_typed(save: &TypedSave) -> Result<String> {
+    encode_as(SaveFormat::Typed, save, None)
+}
+
+/// Writes `save`, a `SaveFile` or `TypedSave` as `format` says, with `config` alongside it if
+/// given.
+pub fn encode_as<T: Serialize>(
+    format: SaveFormat,
+    save: &T,
+    config: Option<&PlanSettings>,
+) -> Result<String> {
+    let versioned = VersionedSave {
+        version: SAVE_VERSION,
+        format,
+        config,
+        save,
+    };
+    Ok(serde_json::to_string_pretty(&versioned)?)
+}
+
+/// Reads save file JSON of any version up to `SAVE_VERSION` and either format, running the
+/// migrations it needs.
+pub fn decode(contents: &str) -> Result<UpgradedSave> {
+    let Value::Object(mut sections) = serde_json::from_str(contents)? else {
+        return Err(Error::Domain(
+            "Invalid save file: expected an object with cards, templates, events and tasks.".into(),
+        ));
+    };
+    let from_version = match sections.remove(VERSION_KEY) {
+        None => UNVERSIONED,
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= UNVERSIONED)
+            .ok_or_else(|| {
+                Error::Domain(format!(
+                    "Invalid save file version: {value}. Expected a whole number from 1 to {SAVE_VERSION}."
+                ))
+            })?,
+    };
+    if from_version > SAVE_VERSION {
+        return Err(Error::Domain(format!(
+            "This save file uses format version {from_version}, but this build of planit reads versions up to {SAVE_VERSION}. Update planit to open it."
+        )));
+    }
+
+    let format = match sections.remove(FORMAT_KEY) {
+        None => detect_format(&sections),
+        Some(Value::String(name)) => SaveFormat::try_from(&name)?,
+        Some(value) => {
+            return Err(Error::Domain(format!(
+                "Invalid save file format: {value}. Expected \"tokens\" or \"typed\"."
+            )));
+        }
+    };
+
+    let config = sections.remove(CONFIG_KEY).map(plan_settings).transpose()?;
+
+    for step in MIGRATIONS.iter().filter(|step| step.from >= from_version) {
+        (step.upgrade)(&mut sections)?;
+    }
+    let save = match format {
+        SaveFormat::Tokens => serde_json::from_value(Value::Object(sections))?,
+        SaveFormat::Typed => {
+            serde_json::from_value::<TypedSave>(Value::Object(sections))?.to_save_file()?
+        }
+    };
+    Ok(UpgradedSave {
+        save,
+        from_version,
+        format,
+        config,
+    })
+}
+
+/// Checks that a saved config is an object of plan settings by key name. Whether the values are
+/// valid is up to the config they are applied to.
+fn plan_settings(value: Value) -> Result<PlanSettings> {
+    let invalid = |detail: String| Error::Domain(format!("Invalid save file config: {detail}"));
+    let Value::Object(entries) = value else {
+        return Err(invalid(format!("expected an object, got {value}.")));
+    };
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let known = key
+                .parse::<ConfigKey>()
+                .is_ok_and(|key| key.is_plan_setting());
+            if !known {
+                let keys = ConfigKey::iter()
+                    .filter(ConfigKey::is_plan_setting)
+                    .map(|key| key.to_string())
+                    .collect::<Vec<_>>();
+                return Err(invalid(format!(
+                    "'{key}' is not a plan setting. Plan settings: {}",
+                    keys.join(", ")
+                )));
+            }
+            match value {
+                Value::String(value) => Ok((key, value)),
+                other => Err(invalid(format!("{key} must be a string, got {other}."))),
+            }
+        })
+        .collect()
+}
+
+/// Files without a format key, e.g. written by hand or by another tool, are typed when their
+/// entities are objects rather than token lists.
+fn detect_format(sections: &Map<String, Value>) -> SaveFormat {
+    let first = sections
+        .values()
+        .filter_map(Value::as_array)
+        .flatten()
+        .next();
+    match first {
+        Some(Value::Object(_)) => SaveFormat::Typed,
+        _ => SaveFormat::Tokens,
+    }
+}
@@ -1,6 +1,13 @@
 pub mod aliases;
 pub mod cli;
 pub mod context;
+pub mod filter;
+pub mod history;
+pub mod import;
+pub mod integrity;
+pub mod journal;
+pub mod merge;
+pub mod migrations;
 pub mod models;
 pub mod persist;
 pub mod repository;
@@ -8,3 +15,7 @@ pub mod repository;
 mod tests;
 pub mod transaction;
 pub mod types;
+pub mod uid;
+pub mod versions;
+pub mod view;
+pub mod workspace;
@@ -1,12 +1,38 @@
-use crate::core::types::{CardColor, Date, DayOfWeek, TimeRange};
+use crate::core::types::{CardColor, Date, DayOfWeek, Estimate, TimeRange};
+use crate::core::uid::Uid;
+use crate::errors::{Error, Result};
 use crate::extensions::chrono::WeekdayExt;
 use chrono::Datelike;
 use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
 pub trait BaseEntity {
     fn id(&self) -> i32;
     fn set_id(&mut self, id: i32);
+    fn uid(&self) -> &Uid;
+    fn details(&self) -> &Details;
+}
+
+/// Free-text notes, links and `key=value` metadata that any entity can carry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Details {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
+}
+/// How the details are named when they are set, filtered on and shown.
+pub const DETAIL_FIELDS: &[&str] = &["note", "links", "meta.<key>"];
+
+impl Details {
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_none() && self.links.is_empty() && self.meta.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,27 +56,124 @@ impl fmt::Display for SubTask {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemplateOrigin {
+    pub template_id: i32,
+    pub occurrence: NaiveDate,
+}
+
+/// One step of a task's checklist. An estimate, when given, counts toward the task's hours.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hours: Option<f32>,
+    #[serde(default)]
+    pub done: bool,
+}
+impl ChecklistItem {
+    pub fn new(text: impl Into<String>, hours: Option<f32>) -> Self {
+        Self {
+            text: text.into(),
+            hours,
+            done: false,
+        }
+    }
+
+    /// Reads `[x] <text> ~<hours>`, the form it is written in.
+    pub fn try_from_str(s: &str) -> Result<Self> {
+        let (done, rest) = match s.trim().strip_prefix("[x]") {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        // Only a number after the last ` ~` is an estimate; any other ` ~` is part of the text.
+        let (text, hours) = match rest.rsplit_once(" ~") {
+            Some((text, hours)) if hours.trim().parse::<f32>().is_ok() => {
+                (text, Some(Self::hours_from_str(hours)?))
+            }
+            _ => (rest, None),
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(Error::Parse("Item must contain text.".into()));
+        }
+        let mut item = Self::new(text, hours);
+        item.done = done;
+        Ok(item)
+    }
+
+    fn hours_from_str(s: &str) -> Result<f32> {
+        match s.trim().parse::<f32>() {
+            Ok(h) if h > 0.0 && h.is_finite() => Ok(h),
+            _ => Err(Error::Parse(format!(
+                "Invalid item hours: '{}'. Expected a number greater than 0, e.g. ~0.5",
+                s.trim()
+            ))),
+        }
+    }
+}
+/// Written `[x] <text> ~<hours>`, the mark and estimate only when set.
+impl fmt::Display for ChecklistItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.done {
+            write!(f, "[x] ")?;
+        }
+        write!(f, "{}", self.text)?;
+        if let Some(hours) = self.hours {
+            write!(f, " ~{hours}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized with the fields a typed save file keeps: the planned subtasks are left out, as
+/// they are in every save, and are placed again by the next `schedule`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Task {
+    #[serde(default)]
     pub id: i32,
+    #[serde(default)]
+    pub uid: Uid,
     pub name: String,
     pub hours: f32,
     pub date: Date,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub card_id: Option<i32>,
+    #[serde(skip)]
     pub subtasks: Vec<SubTask>,
+    #[serde(skip)]
     pub remaining_hours: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<TemplateOrigin>,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub atomic: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    #[serde(default, skip_serializing_if = "Details::is_empty")]
+    pub details: Details,
 }
 impl Task {
     pub fn new(name: impl Into<String>, hours: f32, card_id: Option<i32>, date: Date) -> Self {
         let h = hours.max(0.0);
         Self {
             id: 1,
+            uid: Uid::new(),
             name: name.into(),
             hours: h,
             date,
             card_id,
             subtasks: Vec::new(),
             remaining_hours: h,
+            origin: None,
+            completed: false,
+            atomic: false,
+            estimate: None,
+            checklist: Vec::new(),
+            details: Details::default(),
         }
     }
 
@@ -66,7 +189,61 @@ impl Task {
         self.hours = h;
         self.date = date;
         self.card_id = card_id;
-        self.remaining_hours = h;
+        self.remaining_hours = self.open_hours();
+        self.subtasks.clear();
+        self
+    }
+
+    /// Drops the planned subtasks so the next `schedule` places the task again.
+    pub fn unschedule(&mut self) -> &Self {
+        self.remaining_hours = self.open_hours();
+        self.subtasks.clear();
+        self
+    }
+
+    /// Hours still to plan: none once completed, otherwise the hours less the estimates of the
+    /// checked items.
+    pub fn open_hours(&self) -> f32 {
+        if self.completed {
+            return 0.0;
+        }
+        let checked: f32 = self
+            .checklist
+            .iter()
+            .filter(|item| item.done)
+            .filter_map(|item| item.hours)
+            .sum();
+        (self.hours - checked).max(0.0)
+    }
+
+    /// The sum of the item estimates when any item has one; the task's hours then follow it.
+    pub fn checklist_hours(&self) -> Option<f32> {
+        self.checklist
+            .iter()
+            .any(|item| item.hours.is_some())
+            .then(|| self.checklist.iter().filter_map(|item| item.hours).sum())
+    }
+
+    /// Sets the hours to the sum of the item estimates, when any item has one, and unschedules.
+    pub fn roll_up_checklist(&mut self) -> &Self {
+        if let Some(hours) = self.checklist_hours() {
+            self.hours = hours;
+        }
+        self.unschedule()
+    }
+
+    /// Checked and total checklist items, if the task has a checklist.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        if self.checklist.is_empty() {
+            return None;
+        }
+        let done = self.checklist.iter().filter(|item| item.done).count();
+        Some((done, self.checklist.len()))
+    }
+
+    pub fn complete(&mut self) -> &Self {
+        self.completed = true;
+        self.remaining_hours = 0.0;
         self.subtasks.clear();
         self
     }
@@ -91,6 +268,12 @@ impl BaseEntity for Task {
     fn set_id(&mut self, id: i32) {
         self.id = id;
     }
+    fn uid(&self) -> &Uid {
+        &self.uid
+    }
+    fn details(&self) -> &Details {
+        &self.details
+    }
 }
 impl fmt::Display for Task {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -105,8 +288,139 @@ impl fmt::Display for Task {
         };
         write!(
             f,
-            "Task(id={}, name='{}', hours={}, date={}, card_id={:?}, subtasks={})",
+            "Task(id={}, name='{}', hours={}, date={}, card_id={:?}, subtasks={}",
             self.id, self.name, self.hours, self.date, self.card_id, subtasks
+        )?;
+        if let Some(origin) = &self.origin {
+            write!(
+                f,
+                ", template_id={}, occurrence={}",
+                origin.template_id,
+                origin.occurrence.format("%Y-%m-%d")
+            )?;
+        }
+        if let Some(estimate) = &self.estimate {
+            write!(f, ", estimate={}", estimate)?;
+        }
+        if self.atomic {
+            write!(f, ", atomic=true")?;
+        }
+        if self.completed {
+            write!(f, ", completed=true")?;
+        }
+        write!(f, ")")
+    }
+}
+
+// ============
+// TaskTemplate
+// ============
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskTemplate {
+    #[serde(default)]
+    pub id: i32,
+    #[serde(default)]
+    pub uid: Uid,
+    pub name: String,
+    pub hours: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card_id: Option<i32>,
+    pub days: Vec<DayOfWeek>,
+    /// Occurrences whose generated task was deleted, so they are not generated again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Details::is_empty")]
+    pub details: Details,
+}
+impl TaskTemplate {
+    pub fn new(
+        name: impl Into<String>,
+        hours: f32,
+        card_id: Option<i32>,
+        days: Vec<DayOfWeek>,
+    ) -> Self {
+        Self {
+            id: 1,
+            uid: Uid::new(),
+            name: name.into(),
+            hours: hours.max(0.0),
+            card_id,
+            days,
+            skipped: Vec::new(),
+            details: Details::default(),
+        }
+    }
+
+    pub fn modify(
+        &mut self,
+        name: impl Into<String>,
+        hours: f32,
+        card_id: Option<i32>,
+        days: Vec<DayOfWeek>,
+    ) -> &Self {
+        self.name = name.into();
+        self.hours = hours.max(0.0);
+        self.card_id = card_id;
+        self.days = days;
+        self
+    }
+
+    pub fn occurs_on(&self, target_date: NaiveDate) -> bool {
+        let day_of_week = target_date.weekday().to_day_of_week();
+        self.days.contains(&day_of_week) && !self.skipped.contains(&target_date)
+    }
+
+    /// Stops generating a task for `occurrence`, e.g. once its task was deleted.
+    pub fn skip(&mut self, occurrence: NaiveDate) {
+        if let Err(at) = self.skipped.binary_search(&occurrence) {
+            self.skipped.insert(at, occurrence);
+        }
+    }
+
+    /// Builds the concrete task due on `occurrence`, linked back to this template and carrying
+    /// its details.
+    pub fn instantiate(&self, occurrence: NaiveDate) -> Task {
+        let mut task = Task::new(
+            self.name.clone(),
+            self.hours,
+            self.card_id,
+            Date(occurrence),
+        );
+        task.details = self.details.clone();
+        task.origin = Some(TemplateOrigin {
+            template_id: self.id,
+            occurrence,
+        });
+        task
+    }
+}
+impl BaseEntity for TaskTemplate {
+    fn id(&self) -> i32 {
+        self.id
+    }
+    fn set_id(&mut self, id: i32) {
+        self.id = id;
+    }
+    fn uid(&self) -> &Uid {
+        &self.uid
+    }
+    fn details(&self) -> &Details {
+        &self.details
+    }
+}
+impl fmt::Display for TaskTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days_str = self
+            .days
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "TaskTemplate(id={}, name='{}', hours={}, days=[{}], card_id={:?})",
+            self.id, self.name, self.hours, days_str, self.card_id
         )
     }
 }
@@ -115,18 +429,25 @@ impl fmt::Display for Task {
 // Card
 // =====
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Card {
+    #[serde(default)]
     pub id: i32,
+    #[serde(default)]
+    pub uid: Uid,
     pub name: String,
     pub color: CardColor,
+    #[serde(default, skip_serializing_if = "Details::is_empty")]
+    pub details: Details,
 }
 impl Card {
     pub fn new(name: impl Into<String>, color: CardColor) -> Self {
         Self {
             id: 1,
+            uid: Uid::new(),
             name: name.into(),
             color,
+            details: Details::default(),
         }
     }
 
@@ -143,6 +464,12 @@ impl BaseEntity for Card {
     fn set_id(&mut self, id: i32) {
         self.id = id;
     }
+    fn uid(&self) -> &Uid {
+        &self.uid
+    }
+    fn details(&self) -> &Details {
+        &self.details
+    }
 }
 impl fmt::Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -154,14 +481,22 @@ impl fmt::Display for Card {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
+    #[serde(default)]
     pub id: i32,
+    #[serde(default)]
+    pub uid: Uid,
     pub name: String,
+    #[serde(default)]
     pub days: Vec<DayOfWeek>,
     pub time_range: TimeRange,
+    #[serde(default)]
     pub recurring: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub card_id: Option<i32>,
+    #[serde(default, skip_serializing_if = "Details::is_empty")]
+    pub details: Details,
 }
 
 impl Event {
@@ -174,11 +509,13 @@ impl Event {
     ) -> Self {
         Self {
             id: 1,
+            uid: Uid::new(),
             recurring,
             name: name.into(),
             days,
             time_range,
             card_id,
+            details: Details::default(),
         }
     }
 
@@ -218,6 +555,12 @@ impl BaseEntity for Event {
     fn set_id(&mut self, id: i32) {
         self.id = id;
     }
+    fn uid(&self) -> &Uid {
+        &self.uid
+    }
+    fn details(&self) -> &Details {
+        &self.details
+    }
 }
 
 impl fmt::Display for Event {
@@ -1,38 +1,154 @@
 use crate::arg::arg_emitter::{
     ArgEmitContext, ArgEmitter, CardArgEmitter, EventArgEmitter, NoRefEmitContext, SaveEmitContext,
-    TaskArgEmitter,
+    TaskArgEmitter, TemplateArgEmitter,
 };
 use crate::arg::arg_parser::ArgParser;
 use crate::arg::args::Arg;
 use crate::command::command_parser::CommandParser;
+use crate::config::PlanSettings;
 use crate::core::aliases::{IdLookup, TokenList, TokenMatrix};
 use crate::core::context::AppContext;
-use crate::core::models::{Card, Event, Task};
+use crate::core::migrations::{self, UpgradedSave};
+use crate::core::models::{BaseEntity, Card, Event, Task, TaskTemplate};
 use crate::core::repository::{Repository, Sort};
 use crate::core::transaction::CommandQueue;
-use crate::errors::Result;
+use crate::core::types::SaveFormat;
+use crate::core::uid::saved_uid;
+use crate::errors::{Error, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SaveFile {
     #[serde(default)]
     pub cards: TokenMatrix,
     #[serde(default)]
+    pub templates: TokenMatrix,
+    #[serde(default)]
     pub events: TokenMatrix,
     #[serde(default)]
     pub tasks: TokenMatrix,
 }
 
+/// The plan as entities with typed fields, the alternative to the token lists of `SaveFile`
+/// meant for other tools. Tasks, events and templates refer to cards and templates by the ids
+/// they have in the file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypedSave {
+    #[serde(default)]
+    pub cards: Vec<Card>,
+    #[serde(default)]
+    pub templates: Vec<TaskTemplate>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+}
+
+impl TypedSave {
+    pub fn from_repos(
+        tasks: &Repository<Task>,
+        events: &Repository<Event>,
+        cards: &Repository<Card>,
+        templates: &Repository<TaskTemplate>,
+    ) -> Self {
+        Self {
+            cards: cards.values(Sort::IdAsc).into_iter().cloned().collect(),
+            templates: templates.values(Sort::IdAsc).into_iter().cloned().collect(),
+            events: events.values(Sort::IdAsc).into_iter().cloned().collect(),
+            tasks: tasks.values(Sort::IdAsc).into_iter().cloned().collect(),
+        }
+    }
+
+    /// The same entities as token lists, which is how they are loaded. Hours the lists cannot
+    /// hold are refused rather than rounded.
+    pub fn to_save_file(&self) -> Result<SaveFile> {
+        for template in &self.templates {
+            whole_hours("template", &template.name, template.hours)?;
+        }
+        for task in &self.tasks {
+            // Estimated checklist items set the hours again on load, so any sum of them is kept.
+            if task.checklist_hours() != Some(task.hours) {
+                whole_hours("task", &task.name, task.hours)?;
+            }
+        }
+        let cards = unique_ids("card", &self.cards)?;
+        let templates = unique_ids("template", &self.templates)?;
+        let events = self.events.iter().collect::<Vec<_>>();
+        let tasks = self.tasks.iter().collect::<Vec<_>>();
+        tokens_for(&cards, &templates, &events, &tasks)
+    }
+}
+
+/// Refuses `hours` unless it is a whole number greater than 0, the only hours a token list holds.
+fn whole_hours(kind: &str, name: &str, hours: f32) -> Result<()> {
+    if hours >= 1.0 && hours.fract() == 0.0 {
+        return Ok(());
+    }
+    Err(Error::Parse(format!(
+        "Invalid save file: {kind} '{name}' has {hours} hours; expected a whole number greater \
+         than 0."
+    )))
+}
+
+/// `entities` by reference, refusing ids used twice since references to them would be ambiguous.
+fn unique_ids<'a, E: BaseEntity>(kind: &str, entities: &'a [E]) -> Result<Vec<&'a E>> {
+    let mut seen = HashSet::new();
+    for entity in entities {
+        if !seen.insert(entity.id()) {
+            return Err(Error::Parse(format!(
+                "Invalid save file: more than one {kind} has id {}.",
+                entity.id()
+            )));
+        }
+    }
+    Ok(entities.iter().collect())
+}
+
+/// Writes the plan to `path` as token lists, the format journal snapshots and the version
+/// store use.
 pub fn save_state(
     tasks: &Repository<Task>,
     events: &Repository<Event>,
     cards: &Repository<Card>,
+    templates: &Repository<TaskTemplate>,
     path: &Path,
 ) -> Result<PathBuf> {
-    let file = build_save_file(tasks, events, cards)?;
+    save_state_as(
+        SaveFormat::Tokens,
+        None,
+        tasks,
+        events,
+        cards,
+        templates,
+        path,
+    )
+}
+
+/// Writes the plan to `path` in `format`, with `config` alongside it if given.
+pub fn save_state_as(
+    format: SaveFormat,
+    config: Option<&PlanSettings>,
+    tasks: &Repository<Task>,
+    events: &Repository<Event>,
+    cards: &Repository<Card>,
+    templates: &Repository<TaskTemplate>,
+    path: &Path,
+) -> Result<PathBuf> {
+    let contents = match format {
+        SaveFormat::Tokens => migrations::encode_as(
+            format,
+            &build_save_file(tasks, events, cards, templates)?,
+            config,
+        )?,
+        SaveFormat::Typed => migrations::encode_as(
+            format,
+            &TypedSave::from_repos(tasks, events, cards, templates),
+            config,
+        )?,
+    };
 
     if let Some(parent) = path.parent() {
         if !parent.as_os_str().is_empty() {
@@ -40,29 +156,49 @@ pub fn save_state(
         }
     }
 
-    let contents = serde_json::to_string_pretty(&file)?;
     fs::write(path, contents)?;
     Ok(path.to_path_buf())
 }
 
 pub fn load_state(ctx: &mut AppContext, path: &Path) -> Result<()> {
-    let save_file = load_save_file(path)?;
+    let loaded = load_save_file(path)?;
+    apply_save_file(ctx, &loaded.save)
+}
+
+/// Replaces the entities in `ctx` with the ones in `save_file`, in one transaction. An error
+/// names the row that caused it, e.g. `tasks row 2`, and its tokens. Two rows of a section may
+/// not share a uid, since merge and history would take them for the same entity.
+pub fn apply_save_file(ctx: &mut AppContext, save_file: &SaveFile) -> Result<()> {
     let arg_parser = ArgParser::new();
     let command_parser = CommandParser::new();
+    let sections = [
+        ("cards", "card", &save_file.cards),
+        ("templates", "template", &save_file.templates),
+        ("events", "event", &save_file.events),
+        ("tasks", "task", &save_file.tasks),
+    ];
 
     let mut queue = CommandQueue::new();
-
-    for tokens in &save_file.cards {
-        let args = arg_parser.parse(tokens)?;
-        queue.push("card", args);
-    }
-    for tokens in &save_file.events {
-        let args = arg_parser.parse(tokens)?;
-        queue.push("event", args);
-    }
-    for tokens in &save_file.tasks {
-        let args = arg_parser.parse(tokens)?;
-        queue.push("task", args);
+    for (section, name, rows) in sections {
+        let mut uid_rows: HashMap<String, usize> = HashMap::new();
+        for (index, tokens) in rows.iter().enumerate() {
+            let label = format!(
+                "Invalid save file at {section} row {} ({})",
+                index + 1,
+                tokens.join(" ")
+            );
+            if let Some(uid) = tokens.iter().find_map(|t| saved_uid(t))
+                && let Some(row) = uid_rows.insert(uid.to_ascii_uppercase(), index + 1)
+            {
+                return Err(Error::Parse(format!(
+                    "{label}. uid {uid} is already used by {section} row {row}."
+                )));
+            }
+            match arg_parser.parse(tokens) {
+                Ok(args) => queue.push_labeled(&label, name, args),
+                Err(err) => return Err(Error::Parse(format!("{label}. {err}"))),
+            }
+        }
     }
 
     queue.execute(ctx, &command_parser, true)
@@ -77,60 +213,109 @@ fn emit_tokens<E>(
     Ok(args_to_tokens(&args))
 }
 
-fn serialize_cards_for_save(
-    cards: &[&Card],
-    emitter: &CardArgEmitter,
+/// Emits referenced entities in id order, renumbering them by position so the
+/// ids they are assigned on load are known up front.
+fn serialize_renumbered_for_save<E: BaseEntity>(
+    entities: &[&E],
+    emitter: &dyn ArgEmitter<E>,
+    ctx: &dyn ArgEmitContext,
 ) -> Result<(TokenMatrix, IdLookup)> {
     let mut saved_id_lookup: IdLookup = HashMap::new();
-    let mut card_tokens: TokenMatrix = Vec::new();
+    let mut entity_tokens: TokenMatrix = Vec::new();
 
-    for (idx, card) in cards.iter().enumerate() {
+    for (idx, entity) in entities.iter().enumerate() {
         let new_idx = idx + 1;
-        saved_id_lookup.insert(card.id, new_idx as i32);
-        let tokens = emit_tokens(emitter, card, &NoRefEmitContext)?;
-        card_tokens.push(tokens);
+        saved_id_lookup.insert(entity.id(), new_idx as i32);
+        let tokens = emit_tokens(emitter, *entity, ctx)?;
+        entity_tokens.push(tokens);
     }
 
-    Ok((card_tokens, saved_id_lookup))
+    Ok((entity_tokens, saved_id_lookup))
 }
 
-fn build_save_file(
+pub fn build_save_file(
     tasks: &Repository<Task>,
     events: &Repository<Event>,
     cards: &Repository<Card>,
+    templates: &Repository<TaskTemplate>,
+) -> Result<SaveFile> {
+    tokens_for(
+        &cards.values(Sort::IdAsc),
+        &templates.values(Sort::IdAsc),
+        &events.values(Sort::IdAsc),
+        &tasks.values(Sort::IdAsc),
+    )
+}
+
+fn tokens_for(
+    cards: &[&Card],
+    templates: &[&TaskTemplate],
+    events: &[&Event],
+    tasks: &[&Task],
 ) -> Result<SaveFile> {
     let card_emitter = CardArgEmitter::new();
+    let template_emitter = TemplateArgEmitter::new();
     let event_emitter = EventArgEmitter::new();
     let task_emitter = TaskArgEmitter::new();
-    let cards_sorted = cards.values(Sort::IdAsc);
-    let (card_tokens, card_id_map) = serialize_cards_for_save(&cards_sorted, &card_emitter)?;
+    let (card_tokens, card_id_map) =
+        serialize_renumbered_for_save(cards, &card_emitter, &NoRefEmitContext)?;
+
+    let no_templates = IdLookup::new();
+    let card_context = SaveEmitContext {
+        id_lookup: &card_id_map,
+        template_lookup: &no_templates,
+    };
+    let (template_tokens, template_id_map) =
+        serialize_renumbered_for_save(templates, &template_emitter, &card_context)?;
 
     let emit_context = SaveEmitContext {
         id_lookup: &card_id_map,
+        template_lookup: &template_id_map,
     };
 
     let events_tokens = events
-        .values(Sort::IdAsc)
-        .into_iter()
-        .map(|event| emit_tokens(&event_emitter, &event, &emit_context))
+        .iter()
+        .map(|event| emit_tokens(&event_emitter, *event, &emit_context))
         .collect::<Result<Vec<TokenList>>>()?;
 
     let tasks_tokens = tasks
-        .values(Sort::IdAsc)
-        .into_iter()
-        .map(|task| emit_tokens(&task_emitter, &task, &emit_context))
+        .iter()
+        .map(|task| emit_tokens(&task_emitter, *task, &emit_context))
         .collect::<Result<Vec<TokenList>>>()?;
 
     Ok(SaveFile {
         cards: card_tokens,
+        templates: template_tokens,
         events: events_tokens,
         tasks: tasks_tokens,
     })
 }
 
-fn load_save_file(path: &Path) -> Result<SaveFile> {
-    let contents = fs::read_to_string(path)?;
-    Ok(serde_json::from_str(&contents)?)
+/// Reads the save file at `path` in either format, upgrading it if an older build wrote it.
+pub fn load_save_file(path: &Path) -> Result<UpgradedSave> {
+    migrations::decode(&fs::read_to_string(path)?)
+}
+
+/// Rewrites the save file at `from` to `to` in the other format, after loading it into a scratch
+/// context so only a file `read` accepts is converted. Returns the format written.
+pub fn convert_save_file(ctx: &AppContext, from: &Path, to: &Path) -> Result<SaveFormat> {
+    let loaded = load_save_file(from)?;
+    let mut scratch = ctx.scratch();
+    apply_save_file(&mut scratch, &loaded.save)?;
+    let format = match loaded.format {
+        SaveFormat::Tokens => SaveFormat::Typed,
+        SaveFormat::Typed => SaveFormat::Tokens,
+    };
+    save_state_as(
+        format,
+        loaded.config.as_ref(),
+        &scratch.tasks,
+        &scratch.events,
+        &scratch.cards,
+        &scratch.templates,
+        to,
+    )?;
+    Ok(format)
 }
 
 fn args_to_tokens(args: &[Arg]) -> TokenList {
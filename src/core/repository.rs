@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::core::aliases::IdLookup;
 use crate::core::models::BaseEntity;
 use crate::errors::{Error, Result};
 
@@ -11,9 +12,10 @@ pub enum Sort {
     IdDesc,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct Staged<T: BaseEntity> {
     pending: Vec<T>,
+    items_before: HashMap<i32, T>,
     next_id_start: i32,
     cleared: bool,
 }
@@ -82,7 +84,7 @@ impl<'a, T: BaseEntity> FilterSorter<'a, T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Repository<T: BaseEntity> {
     items: HashMap<i32, T>,
     next_id: i32,
@@ -215,6 +217,32 @@ impl<T: BaseEntity> Repository<T> {
         v
     }
 
+    /// Gives the entities, taken in id order, the ids in `ids` and sets the id the next insert
+    /// gets. Returns each old id with its new one.
+    pub fn reassign(&mut self, ids: &[i32], next_id: i32) -> Result<IdLookup> {
+        let mut old_ids: Vec<i32> = self.items.keys().copied().collect();
+        let distinct: HashSet<&i32> = ids.iter().collect();
+        if old_ids.len() != ids.len() || distinct.len() != ids.len() {
+            return Err(Error::Parse(format!(
+                "Expected {} distinct ids, found {:?}.",
+                old_ids.len(),
+                ids
+            )));
+        }
+        old_ids.sort_unstable();
+        let mut lookup = IdLookup::new();
+        let mut items = HashMap::with_capacity(ids.len());
+        for (&new_id, old_id) in ids.iter().zip(old_ids) {
+            let mut entity = self.items.remove(&old_id).expect("id listed from items");
+            entity.set_id(new_id);
+            items.insert(new_id, entity);
+            lookup.insert(old_id, new_id);
+        }
+        self.items = items;
+        self.next_id = next_id;
+        Ok(lookup)
+    }
+
     pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.items.values_mut()
     }
@@ -227,7 +255,10 @@ impl<T: BaseEntity> Repository<T> {
         QueryMut::new(self)
     }
 
-    pub fn begin_stage(&mut self, clear_existing: bool) -> Result<()> {
+    pub fn begin_stage(&mut self, clear_existing: bool) -> Result<()>
+    where
+        T: Clone,
+    {
         if self.staged.is_some() {
             return Err(Error::Parse("Transaction already in progress.".into()));
         }
@@ -236,14 +267,17 @@ impl<T: BaseEntity> Repository<T> {
         }
         self.staged = Some(Staged {
             pending: Vec::new(),
+            items_before: self.items.clone(),
             next_id_start: self.next_id,
             cleared: clear_existing,
         });
         Ok(())
     }
 
+    /// Drops the staged inserts and undoes any change made to existing items since `begin_stage`.
     pub fn discard_stage(&mut self) {
         if let Some(staged) = self.staged.take() {
+            self.items = staged.items_before;
             self.next_id = staged.next_id_start;
         }
     }
@@ -1,13 +1,30 @@
 use super::{
     context::AppContext,
-    models::{BaseEntity, Card, Event, FreeTimeBlock, Task},
-    persist::{SaveFile, load_state, save_state},
+    filter::{Filter, Selection},
+    history::{self, Snapshot},
+    import::import_save_file,
+    integrity::{card_referencers, delete_card},
+    journal::{Journal, Journaling},
+    merge::{self, Side},
+    migrations::{self, SAVE_VERSION},
+    models::{
+        BaseEntity, Card, ChecklistItem, Event, FreeTimeBlock, Task, TaskTemplate, TemplateOrigin,
+    },
+    persist::{
+        SaveFile, TypedSave, apply_save_file, build_save_file, load_save_file, load_state,
+        save_state,
+    },
     repository::{Repository, Sort},
     types::{
-        Bool, CardColor, Date, DayOfWeek, EntityActionType, EntityType, GlobalCommand,
-        TaskOverflowPolicy, TaskSchedulingOrder, TimeRange,
+        Bool, CardColor, CardDeletePolicy, Date, DayOfWeek, EntityActionType, EntityType, Estimate,
+        GlobalCommand, SaveFormat, TaskConflictPolicy, TaskOverflowPolicy, TaskPackingMode,
+        TaskSchedulingOrder, TimeRange,
     },
+    uid::Uid,
+    versions::{Head, VersionStore, diff_saves},
+    workspace,
 };
+use crate::config::PlanSettings;
 use crate::core::cli::CliPaths;
 use crate::errors::Error;
 use chrono::{Datelike, NaiveDate, Timelike};
@@ -105,6 +122,12 @@ fn parses_bool_and_policy_enums() {
         TaskSchedulingOrder::LongestTaskFirst
     );
     assert!(TaskSchedulingOrder::DueOnly.help().contains("due"));
+
+    assert_eq!(
+        TaskPackingMode::try_from("GROUP-BY-CARD").unwrap(),
+        TaskPackingMode::GroupByCard
+    );
+    assert!(TaskPackingMode::try_from("x").is_err());
 }
 
 #[test]
@@ -374,6 +397,26 @@ fn repository_discard_stage_restores_next_id() {
     assert_eq!(repo.len(), 0);
 }
 
+#[test]
+fn repository_discard_stage_restores_changed_and_deleted_items() {
+    let mut repo = Repository::<Task>::new();
+    for name in ["a", "b"] {
+        repo.insert(Task::new(
+            name,
+            1.0,
+            None,
+            Date::try_from_str("2025-01-01").unwrap(),
+        ));
+    }
+    repo.begin_stage(false).unwrap();
+    repo.get_mut(1).unwrap().name = "changed".into();
+    repo.delete(2).unwrap();
+
+    repo.discard_stage();
+    assert_eq!(repo.get(1).unwrap().name, "a");
+    assert_eq!(repo.get(2).unwrap().name, "b");
+}
+
 #[test]
 fn repository_staged_effective_ids_returns_union_without_clear() {
     let mut repo = Repository::<Task>::new();
@@ -596,13 +639,14 @@ fn save_state_writes_expected_tokens() {
     let mut tasks = Repository::<Task>::new();
     let mut events = Repository::<Event>::new();
 
-    let card = cards.insert(Card::new("Focus", CardColor::Blue));
+    let card = cards.insert(Card::new("Focus", CardColor::Blue)).clone();
     let task = Task::new(
         "Deep Work",
         2.0,
         Some(card.id),
         Date::try_from_str("2099-01-02").unwrap(),
     );
+    let task_uid = format!("uid={}", task.uid);
     tasks.insert(task);
     let event = Event::new(
         true,
@@ -611,16 +655,21 @@ fn save_state_writes_expected_tokens() {
         vec![DayOfWeek::Mon, DayOfWeek::Wed],
         TimeRange::try_from_str("9:00AM-10:00AM").unwrap(),
     );
+    let event_uid = format!("uid={}", event.uid);
     events.insert(event);
 
     let path = temp_save_path("tokens");
-    let saved = save_state(&tasks, &events, &cards, &path).unwrap();
+    let saved = save_state(&tasks, &events, &cards, &Repository::new(), &path).unwrap();
     let contents = fs::read_to_string(saved).unwrap();
     let save_file: SaveFile = serde_json::from_str(&contents).unwrap();
 
     assert_eq!(
         save_file.cards,
-        vec![vec![String::from("\"Focus\""), String::from("BLUE")]]
+        vec![vec![
+            String::from("\"Focus\""),
+            String::from("BLUE"),
+            format!("uid={}", card.uid)
+        ]]
     );
     assert_eq!(
         save_file.tasks,
@@ -629,7 +678,8 @@ fn save_state_writes_expected_tokens() {
             String::from("2"),
             String::from("+C1"),
             String::from("@"),
-            String::from("2099-01-02")
+            String::from("2099-01-02"),
+            task_uid
         ]]
     );
     assert_eq!(
@@ -641,7 +691,8 @@ fn save_state_writes_expected_tokens() {
             String::from("@"),
             String::from("MON,"),
             String::from("WED"),
-            String::from("9:00AM-10:00AM")
+            String::from("9:00AM-10:00AM"),
+            event_uid
         ]]
     );
 }
@@ -651,6 +702,7 @@ fn load_state_inserts_cards() {
     let path = temp_save_path("cards");
     let save_file = SaveFile {
         cards: vec![vec!["\"Card\"".into(), "RED".into()]],
+        templates: Vec::new(),
         events: Vec::new(),
         tasks: Vec::new(),
     };
@@ -670,6 +722,7 @@ fn load_state_inserts_tasks_with_card_reference() {
     let path = temp_save_path("tasks");
     let save_file = SaveFile {
         cards: vec![vec!["\"Tag\"".into(), "GREEN".into()]],
+        templates: Vec::new(),
         events: Vec::new(),
         tasks: vec![vec![
             "\"Task\"".into(),
@@ -695,6 +748,7 @@ fn load_state_inserts_events_with_card_reference() {
     let path = temp_save_path("events");
     let save_file = SaveFile {
         cards: vec![vec!["\"Tag\"".into(), "RED".into()]],
+        templates: Vec::new(),
         events: vec![vec![
             "True".into(),
             "\"Event\"".into(),
@@ -716,11 +770,344 @@ fn load_state_inserts_events_with_card_reference() {
     assert_eq!(event.card_id, Some(1));
 }
 
+#[test]
+fn load_errors_name_the_row_that_failed_and_load_nothing() {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    ctx.cards.insert(Card::new("Kept", CardColor::Green));
+    let task = |hours: &str, card: &str| -> Vec<String> {
+        vec![
+            "\"Task\"".into(),
+            hours.into(),
+            card.into(),
+            "@".into(),
+            "2099-01-01".into(),
+        ]
+    };
+
+    let cases = [
+        (
+            task("1", "+C9"),
+            "tasks row 2 (\"Task\" 1 +C9 @ 2099-01-01)",
+        ),
+        (
+            task("x", "+C1"),
+            "tasks row 2 (\"Task\" x +C1 @ 2099-01-01)",
+        ),
+    ];
+    for (bad, row) in cases {
+        let save_file = SaveFile {
+            cards: vec![vec!["\"Tag\"".into(), "RED".into()]],
+            tasks: vec![task("1", "+C1"), bad],
+            ..SaveFile::default()
+        };
+        let err = apply_save_file(&mut ctx, &save_file).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Parse error: Invalid save file at {row}. ")),
+            "{err}"
+        );
+        assert_eq!(ctx.cards.get(1).unwrap().name, "Kept");
+        assert_eq!(ctx.tasks.len(), 0);
+    }
+}
+
+#[test]
+fn save_and_load_round_trip_templates_and_instances() {
+    let mut cards = Repository::<Card>::new();
+    let mut templates = Repository::<TaskTemplate>::new();
+    let mut tasks = Repository::<Task>::new();
+    cards.insert(Card::new("Unused", CardColor::Red));
+    let card = cards.insert(Card::new("Ops", CardColor::Blue)).id;
+    templates.insert(TaskTemplate::new("Old", 1.0, None, vec![DayOfWeek::Mon]));
+    templates.delete(1).unwrap();
+    let template = templates
+        .insert(TaskTemplate::new(
+            "Report",
+            2.0,
+            Some(card),
+            vec![DayOfWeek::Fri],
+        ))
+        .id;
+    let occurrence = NaiveDate::from_ymd_opt(2099, 1, 2).unwrap();
+    let mut instance = templates.get(template).unwrap().instantiate(occurrence);
+    instance.date = Date::try_from_str("2099-01-03").unwrap();
+    instance.complete();
+    tasks.insert(instance);
+
+    let path = temp_save_path("templates");
+    save_state(&tasks, &Repository::new(), &cards, &templates, &path).unwrap();
+
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    load_state(&mut ctx, &path).unwrap();
+
+    let loaded_template = ctx.templates.get(1).unwrap();
+    assert_eq!(loaded_template.name, "Report");
+    assert_eq!(loaded_template.card_id, Some(2));
+    let loaded_task = ctx.tasks.get(1).unwrap();
+    assert_eq!(
+        loaded_task.origin,
+        Some(TemplateOrigin {
+            template_id: 1,
+            occurrence,
+        })
+    );
+    assert!(loaded_task.completed);
+    assert_eq!(loaded_task.date, Date::try_from_str("2099-01-03").unwrap());
+}
+
+#[test]
+fn save_and_load_round_trip_details() {
+    let mut cards = Repository::<Card>::new();
+    let mut tasks = Repository::<Task>::new();
+    let mut card = Card::new("Study", CardColor::Blue);
+    card.details.meta.insert("term".into(), "fall".into());
+    // The word none is text here, not a request to clear.
+    card.details.notes = Some("none".into());
+    card.details.meta.insert("room".into(), "None".into());
+    cards.insert(card);
+    let mut task = Task::new(
+        "Essay",
+        3.0,
+        Some(1),
+        Date::try_from_str("2099-01-02").unwrap(),
+    );
+    task.details.notes = Some("Chapters  3-4, \"intro\" first".into());
+    task.details.links = vec!["https://a.org".into(), "https://b.org/?q=1".into()];
+    task.details.meta.insert("course".into(), "CS 101".into());
+    tasks.insert(task.clone());
+
+    let path = temp_save_path("details");
+    save_state(
+        &tasks,
+        &Repository::new(),
+        &cards,
+        &Repository::new(),
+        &path,
+    )
+    .unwrap();
+
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    load_state(&mut ctx, &path).unwrap();
+
+    assert_eq!(ctx.tasks.get(1).unwrap().details, task.details);
+    assert_eq!(
+        ctx.cards.get(1).unwrap().details,
+        cards.get(1).unwrap().details
+    );
+}
+
+#[test]
+fn checked_checklist_items_reduce_open_hours() {
+    let mut task = Task::new(
+        "Essay",
+        5.0,
+        None,
+        Date::try_from_str("2099-01-02").unwrap(),
+    );
+    task.checklist.push(ChecklistItem::new("Read", None));
+    task.roll_up_checklist();
+    assert_eq!(task.hours, 5.0, "items without estimates keep the hours");
+
+    task.checklist
+        .push(ChecklistItem::new("Outline", Some(1.5)));
+    task.checklist.push(ChecklistItem::new("Draft", Some(2.0)));
+    task.roll_up_checklist();
+    assert_eq!(task.hours, 3.5);
+    assert_eq!(task.remaining_hours, 3.5);
+
+    task.checklist[2].done = true;
+    task.checklist[0].done = true;
+    assert_eq!(task.open_hours(), 1.5);
+    assert_eq!(task.progress(), Some((2, 3)));
+    task.complete();
+    assert_eq!(task.open_hours(), 0.0);
+
+    let item = ChecklistItem::try_from_str(" [x] Proofread  ~0.25").unwrap();
+    assert_eq!(
+        (item.text.as_str(), item.hours, item.done),
+        ("Proofread", Some(0.25), true)
+    );
+    assert!(ChecklistItem::try_from_str("[x] ~1").is_err());
+    assert!(ChecklistItem::try_from_str("Slides ~0").is_err());
+    // A ` ~` not followed by a number is part of the text.
+    let item = ChecklistItem::try_from_str("Compare ~approx. and ~exact results ~2").unwrap();
+    assert_eq!(
+        (item.text.as_str(), item.hours),
+        ("Compare ~approx. and ~exact results", Some(2.0))
+    );
+    let item = ChecklistItem::try_from_str("Slides ~soon").unwrap();
+    assert_eq!((item.text.as_str(), item.hours), ("Slides ~soon", None));
+}
+
+#[test]
+fn save_and_load_round_trip_checklist() {
+    let mut tasks = Repository::<Task>::new();
+    let mut task = Task::new(
+        "Essay",
+        1.0,
+        None,
+        Date::try_from_str("2099-01-02").unwrap(),
+    );
+    task.checklist
+        .push(ChecklistItem::new("Ask \"Sam\" for  notes", None));
+    let mut done = ChecklistItem::new("Outline", Some(0.25));
+    done.done = true;
+    task.checklist.push(done);
+    task.checklist.push(ChecklistItem::new("Draft", Some(0.5)));
+    task.roll_up_checklist();
+    tasks.insert(task.clone());
+
+    let path = temp_save_path("checklist");
+    save_state(
+        &tasks,
+        &Repository::new(),
+        &Repository::new(),
+        &Repository::new(),
+        &path,
+    )
+    .unwrap();
+
+    let saved = load_save_file(&path).unwrap().save;
+    assert_eq!(saved.tasks[0][1], "0.75", "hours are saved as they are");
+
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    load_state(&mut ctx, &path).unwrap();
+
+    let loaded = ctx.tasks.get(1).unwrap();
+    assert_eq!(loaded.checklist, task.checklist);
+    assert_eq!(loaded.hours, 0.75);
+    assert_eq!(loaded.open_hours(), 0.5);
+}
+
+#[test]
+fn uids_are_unique_sortable_and_parse_back() {
+    let first = Uid::new();
+    let second = Uid::new();
+    assert_ne!(first, second);
+    assert_eq!(first.as_str().len(), 26);
+    assert!(
+        first.as_str()[..10] <= second.as_str()[..10],
+        "time prefix sorts"
+    );
+    assert_eq!(
+        Uid::try_from_str(&first.as_str().to_lowercase()).unwrap(),
+        first
+    );
+    for bad in [
+        "",
+        "01J9Z8Q4C6X5W2R7T3M1N0P8K",
+        "01J9Z8Q4C6X5W2R7T3M1N0P8KU",
+        "81J9Z8Q4C6X5W2R7T3M1N0P8KD",
+    ] {
+        assert!(Uid::try_from_str(bad).is_err(), "{bad} should be rejected");
+    }
+}
+
+#[test]
+fn save_and_load_keep_uids_while_ids_are_reassigned() {
+    let mut cards = Repository::<Card>::new();
+    let mut tasks = Repository::<Task>::new();
+    cards.insert(Card::new("Old", CardColor::Red));
+    let card = cards.insert(Card::new("Work", CardColor::Blue)).clone();
+    cards.delete(1).unwrap();
+    let task = Task::new(
+        "Essay",
+        2.0,
+        Some(card.id),
+        Date::try_from_str("2099-01-02").unwrap(),
+    );
+    tasks.insert(task.clone());
+
+    let path = temp_save_path("uids");
+    save_state(
+        &tasks,
+        &Repository::new(),
+        &cards,
+        &Repository::new(),
+        &path,
+    )
+    .unwrap();
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    load_state(&mut ctx, &path).unwrap();
+
+    let loaded_card = ctx.cards.get(1).unwrap();
+    assert_eq!(loaded_card.uid, card.uid, "card 2 is card 1 after load");
+    let loaded_task = ctx.tasks.get(1).unwrap();
+    assert_eq!(loaded_task.uid, task.uid);
+    assert_eq!(loaded_task.card_id, Some(1));
+
+    let filter = Filter::parse::<Task>(
+        &[format!("uid={}", task.uid.as_str().to_lowercase())],
+        NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+    )
+    .unwrap();
+    assert!(filter.matches(loaded_task));
+}
+
+#[test]
+fn load_state_rejects_uids_used_twice() {
+    let uid = Uid::new();
+    let row = |name: &str| -> Vec<String> {
+        vec![
+            format!("\"{name}\""),
+            "1".into(),
+            "@".into(),
+            "2099-01-01".into(),
+            format!("uid={}", uid.as_str()),
+        ]
+    };
+    let save_file = SaveFile {
+        tasks: vec![row("First"), row("Second")],
+        ..SaveFile::default()
+    };
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+
+    let err = apply_save_file(&mut ctx, &save_file).unwrap_err();
+    assert!(
+        err.to_string().contains(&format!(
+            "uid {} is already used by tasks row 1",
+            uid.as_str()
+        )),
+        "{err}"
+    );
+    assert_eq!(ctx.tasks.len(), 0);
+}
+
+#[test]
+fn load_state_rejects_task_with_missing_template() {
+    let path = temp_save_path("missing-template");
+    let save_file = SaveFile {
+        cards: Vec::new(),
+        templates: Vec::new(),
+        events: Vec::new(),
+        tasks: vec![vec![
+            "\"Task\"".into(),
+            "1".into(),
+            "+T1".into(),
+            "@".into(),
+            "2099-01-01".into(),
+        ]],
+    };
+    write_save_file(&path, &save_file);
+
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    assert!(load_state(&mut ctx, &path).is_err());
+    assert_eq!(ctx.tasks.len(), 0);
+}
+
 #[test]
 fn load_state_rolls_back_on_error() {
     let path = temp_save_path("rollback");
     let save_file = SaveFile {
         cards: Vec::new(),
+        templates: Vec::new(),
         events: vec![vec![
             "True".into(),
             "\"Event\"".into(),
@@ -780,3 +1167,1029 @@ fn cli_paths_errors_on_missing_value() {
     let err = CliPaths::from_args(args.into_iter()).unwrap_err();
     assert_eq!(err, "Missing value for --config");
 }
+
+#[test]
+fn estimate_parses_three_points_and_samples_within_bounds() {
+    let e = Estimate::try_from_str("~2/3/6.5").unwrap();
+    assert_eq!(e.to_string(), "~2/3/6.5");
+    assert_eq!(e.sample(0.0), 2.0);
+    assert!((e.sample(0.999_999) - 6.5).abs() < 0.01);
+    // u equal to the mode's CDF value lands on the mode
+    assert!((e.sample(1.0 / 4.5) - 3.0).abs() < 1e-4);
+    assert_eq!(Estimate::try_from_str("~3/3/3").unwrap().sample(0.7), 3.0);
+
+    for bad in ["2/3/6", "~3/2/6", "~2/3", "~a/b/c", "~-1/2/3"] {
+        match Estimate::try_from_str(bad).unwrap_err() {
+            Error::Parse(msg) => assert!(msg.contains("Invalid estimate")),
+            other => panic!("expected parse error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn history_undo_and_redo_restore_entities() {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    let date = Date(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap());
+
+    let before = Snapshot::capture(&ctx);
+    ctx.tasks
+        .insert(Task::new("essay", 2.0, None, date.clone()));
+    history::record(&mut ctx, "task \"essay\" 2 @ 2099-01-01", before);
+    let before = Snapshot::capture(&ctx);
+    ctx.tasks.get_mut(1).unwrap().hours = 5.0;
+    history::record(&mut ctx, "mod task 1", before);
+
+    let step = history::undo(&mut ctx).unwrap();
+    assert_eq!(step.label, "mod task 1");
+    assert_eq!(step.changes.len(), 1);
+    assert!(step.changes[0].starts_with("changed task 1: "));
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 2.0);
+
+    let step = history::undo(&mut ctx).unwrap();
+    assert_eq!(step.changes, vec!["removed task 1".to_string()]);
+    assert_eq!(ctx.tasks.len(), 0);
+    assert!(history::undo(&mut ctx).is_err());
+
+    history::redo(&mut ctx).unwrap();
+    history::redo(&mut ctx).unwrap();
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 5.0);
+    match history::redo(&mut ctx).unwrap_err() {
+        Error::Parse(msg) => assert_eq!(msg, "Nothing to redo."),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn history_skips_no_op_commands_and_new_changes_clear_redo() {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    let date = Date(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap());
+
+    let before = Snapshot::capture(&ctx);
+    history::record(&mut ctx, "mod task --help", before);
+    assert!(history::undo(&mut ctx).is_err());
+
+    let before = Snapshot::capture(&ctx);
+    ctx.cards.insert(Card::new("Tag", CardColor::Green));
+    history::record(&mut ctx, "card", before);
+    history::undo(&mut ctx).unwrap();
+
+    let before = Snapshot::capture(&ctx);
+    ctx.tasks.insert(Task::new("essay", 2.0, None, date));
+    history::record(&mut ctx, "task", before);
+    assert!(history::redo(&mut ctx).is_err());
+}
+
+// ---------- migrations.rs ----------
+#[test]
+fn encode_stamps_the_current_version_and_decodes_back() {
+    let save = SaveFile {
+        cards: vec![vec!["\"Card\"".into(), "RED".into()]],
+        templates: Vec::new(),
+        events: Vec::new(),
+        tasks: Vec::new(),
+    };
+    let contents = migrations::encode(&save).unwrap();
+    assert!(contents.starts_with(&format!("{{\n  \"version\": {SAVE_VERSION},")));
+
+    let decoded = migrations::decode(&contents).unwrap();
+    assert_eq!(decoded.from_version, SAVE_VERSION);
+    assert_eq!(decoded.save, save);
+}
+
+#[test]
+fn decode_treats_files_without_a_version_as_version_one() {
+    let decoded =
+        migrations::decode(r#"{"cards": [], "templates": [], "events": [], "tasks": []}"#).unwrap();
+    assert_eq!(decoded.from_version, 1);
+    assert!(decoded.save.cards.is_empty());
+}
+
+#[test]
+fn decode_rejects_invalid_and_newer_versions() {
+    let sections = r#""cards": [], "templates": [], "events": [], "tasks": []"#;
+    for version in ["0", "-1", "1.5", "\"2\""] {
+        let err =
+            migrations::decode(&format!(r#"{{"version": {version}, {sections}}}"#)).unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid save file version"),
+            "{version}: {err}"
+        );
+    }
+
+    let newer = SAVE_VERSION + 1;
+    let err = migrations::decode(&format!(r#"{{"version": {newer}, {sections}}}"#)).unwrap_err();
+    assert!(matches!(err, Error::Domain(_)));
+    assert!(err.to_string().contains(&format!(
+        "uses format version {newer}, but this build of planit reads versions up to {SAVE_VERSION}"
+    )));
+
+    assert!(migrations::decode("[]").is_err());
+}
+
+#[test]
+fn typed_saves_decode_to_the_token_lists_of_the_same_plan() {
+    let mut cards = Repository::<Card>::new();
+    let mut tasks = Repository::<Task>::new();
+    let mut templates = Repository::<TaskTemplate>::new();
+    cards.insert(Card::new("Gone", CardColor::Red));
+    cards.insert(Card::new("Focus", CardColor::LightBlue));
+    cards.delete(1).unwrap();
+    templates.insert(TaskTemplate::new(
+        "Report",
+        2.0,
+        Some(2),
+        vec![DayOfWeek::Fri],
+    ));
+    let template = templates.get(1).unwrap().clone();
+    let mut task = template.instantiate(NaiveDate::from_ymd_opt(2099, 1, 9).unwrap());
+    task.estimate = Some(Estimate::try_from_str("~1/2/4").unwrap());
+    task.checklist
+        .push(ChecklistItem::new("Gather numbers", Some(2.0)));
+    task.details.notes = Some("Quarterly".into());
+    tasks.insert(task);
+    let events = Repository::<Event>::new();
+
+    let typed = TypedSave::from_repos(&tasks, &events, &cards, &templates);
+    let contents = migrations::encode_typed(&typed).unwrap();
+    assert!(contents.contains("\"format\": \"typed\""));
+    assert!(contents.contains("\"color\": \"LIGHT_BLUE\""));
+
+    let decoded = migrations::decode(&contents).unwrap();
+    assert_eq!(decoded.format, SaveFormat::Typed);
+    assert_eq!(
+        decoded.save,
+        build_save_file(&tasks, &events, &cards, &templates).unwrap()
+    );
+}
+
+#[test]
+fn typed_saves_refuse_hours_token_lists_cannot_hold() {
+    for (section, entity, hours) in [
+        ("tasks", r#""date": "2099-01-05""#, "2.5"),
+        ("tasks", r#""date": "2099-01-05""#, "0"),
+        ("templates", r#""days": ["MON"]"#, "1.5"),
+    ] {
+        let err = migrations::decode(&format!(
+            r#"{{"{section}": [{{"name": "Essay", "hours": {hours}, {entity}}}]}}"#
+        ))
+        .unwrap_err();
+        assert!(
+            err.to_string().contains(&format!(
+                "'Essay' has {hours} hours; expected a whole number"
+            )),
+            "{err}"
+        );
+    }
+
+    let decoded = migrations::decode(
+        r#"{"tasks": [{"name": "Essay", "hours": 2.5, "date": "2099-01-05",
+            "checklist": [{"text": "Draft", "hours": 2.5}]}]}"#,
+    )
+    .unwrap();
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    apply_save_file(&mut ctx, &decoded.save).unwrap();
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 2.5);
+}
+
+#[test]
+fn plan_settings_are_written_alongside_the_entities_and_checked_on_decode() {
+    let settings = PlanSettings::from([
+        ("RANGE".to_string(), "9:00AM-5:00PM".to_string()),
+        ("TASK_OVERFLOW_POLICY".to_string(), "block".to_string()),
+    ]);
+    let contents =
+        migrations::encode_as(SaveFormat::Tokens, &SaveFile::default(), Some(&settings)).unwrap();
+    let decoded = migrations::decode(&contents).unwrap();
+    assert_eq!(decoded.config, Some(settings));
+    assert_eq!(
+        migrations::decode(&migrations::encode(&SaveFile::default()).unwrap())
+            .unwrap()
+            .config,
+        None
+    );
+
+    for (config, error) in [
+        (r#""8-5""#, "expected an object"),
+        (
+            r#"{"WORKSPACE": "main"}"#,
+            "'WORKSPACE' is not a plan setting",
+        ),
+        (r#"{"BOGUS": "1"}"#, "'BOGUS' is not a plan setting"),
+        (r#"{"RANGE": 8}"#, "RANGE must be a string"),
+    ] {
+        let err =
+            migrations::decode(&format!(r#"{{"config": {config}, "cards": []}}"#)).unwrap_err();
+        assert!(matches!(err, Error::Domain(_)));
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Invalid save file config: {error}")),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn decode_detects_typed_entities_and_checks_their_references() {
+    let decoded = migrations::decode(
+        r#"{"cards": [{"id": 7, "name": "Work", "color": "BLUE"}],
+            "tasks": [{"name": "Essay", "hours": 2, "date": "2099-01-05", "card_id": 7}]}"#,
+    )
+    .unwrap();
+    assert_eq!(decoded.format, SaveFormat::Typed);
+    assert_eq!(decoded.save.tasks[0][..3], ["\"Essay\"", "2", "+C1"]);
+
+    let missing = migrations::decode(
+        r#"{"tasks": [{"name": "Essay", "hours": 2, "date": "2099-01-05", "card_id": 7}]}"#,
+    )
+    .unwrap_err();
+    assert!(
+        missing.to_string().contains("missing card id 7"),
+        "{missing}"
+    );
+
+    let duplicate = migrations::decode(
+        r#"{"format": "typed", "cards": [{"id": 1, "name": "A", "color": "RED"},
+                                         {"id": 1, "name": "B", "color": "RED"}]}"#,
+    )
+    .unwrap_err();
+    assert!(
+        duplicate
+            .to_string()
+            .contains("more than one card has id 1"),
+        "{duplicate}"
+    );
+
+    assert!(migrations::decode(r#"{"format": "yaml", "cards": []}"#).is_err());
+}
+
+// ---------- journal.rs ----------
+fn journal_ctx(name: &str) -> AppContext {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    ctx.schedules_dir = temp_schedules_dir(&format!("journal-{name}"));
+    ctx
+}
+
+fn lines(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|line| line.to_string()).collect()
+}
+
+#[test]
+fn journal_appends_lines_and_keeps_snapshots_of_replaced_plans() {
+    let mut ctx = journal_ctx("record");
+    let journal = Journal::open(&ctx.schedules_dir);
+    assert!(journal.entries().unwrap().is_empty());
+
+    journal
+        .record(&ctx, "task \"Essay\" 2 @ 2099-01-01", Journaling::Line)
+        .unwrap();
+    journal.record(&ctx, "config", Journaling::Skip).unwrap();
+    ctx.tasks.insert(Task::new(
+        "Lab",
+        3.0,
+        None,
+        Date::try_from_str("2099-01-02").unwrap(),
+    ));
+    journal
+        .record(&ctx, "checkout main", Journaling::Snapshot)
+        .unwrap();
+
+    let entries = journal.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0], "task \"Essay\" 2 @ 2099-01-01");
+    assert!(entries[1].starts_with("read \""));
+    assert!(entries[1].ends_with("snapshot-2.json\""));
+
+    let mut recovered = journal_ctx("record-replay");
+    let (applied, result) = journal.replay(&mut recovered, &entries);
+    assert!(result.is_ok());
+    assert_eq!(applied, 2);
+    assert_eq!(recovered.tasks.get(1).unwrap().name, "Lab");
+
+    journal.truncate(1).unwrap();
+    assert_eq!(journal.entries().unwrap().len(), 1);
+    journal.clear().unwrap();
+    assert!(journal.entries().unwrap().is_empty());
+    assert!(!ctx.schedules_dir.join(".journal").exists());
+}
+
+#[test]
+fn replaying_a_journal_restores_the_plan_and_its_undo_history() {
+    let mut ctx = journal_ctx("replay");
+    let entries = lines(&[
+        "task \"Essay\" 2 @ 2099-01-01",
+        "task \"Lab\" 3 @ 2099-01-02",
+        "del task 1",
+        "undo",
+        "done task 2",
+    ]);
+
+    let (applied, result) = Journal::open(&ctx.schedules_dir).replay(&mut ctx, &entries);
+    assert!(result.is_ok());
+    assert_eq!(applied, 5);
+    assert_eq!(ctx.tasks.len(), 2);
+    assert!(ctx.tasks.get(2).unwrap().completed);
+    assert_eq!(history::undo(&mut ctx).unwrap().label, "done task 2");
+}
+
+#[test]
+fn replay_stops_at_the_first_line_that_fails() {
+    let mut ctx = journal_ctx("replay-fails");
+    let entries = lines(&[
+        "task \"Essay\" 2 @ 2099-01-01",
+        "mod task 9 hours=4",
+        "task \"Lab\" 3 @ 2099-01-02",
+    ]);
+
+    let (applied, result) = Journal::open(&ctx.schedules_dir).replay(&mut ctx, &entries);
+    assert_eq!(applied, 1);
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Replay stopped at 'mod task 9 hours=4'.")
+    );
+    assert_eq!(ctx.tasks.len(), 1);
+}
+
+// ---------- workspace.rs ----------
+/// A context with its own config file, so setting keys leaves the repository's config alone.
+fn workspace_ctx(name: &str, workspace: &str, interval: &str) -> AppContext {
+    let dir = temp_schedules_dir(&format!("workspace-{name}"));
+    fs::create_dir_all(&dir).unwrap();
+    let config_path = dir.join("config.json");
+    fs::copy("config.json", &config_path).unwrap();
+    let mut ctx =
+        AppContext::new_with_paths(config_path, dir.join("schedules"), dir.join("logs")).unwrap();
+    ctx.logger.set_file_logging_enabled(false);
+    ctx.config
+        .set_many([("WORKSPACE", workspace), ("AUTOSAVE_INTERVAL", interval)])
+        .unwrap();
+    ctx
+}
+
+fn add_task(ctx: &mut AppContext, name: &str) {
+    ctx.tasks.insert(Task::new(
+        name,
+        1.0,
+        None,
+        Date::try_from_str("2099-01-01").unwrap(),
+    ));
+}
+
+#[test]
+fn autosave_writes_the_workspace_every_interval_and_clears_the_journal() {
+    let mut ctx = workspace_ctx("interval", "main", "2");
+    let path = workspace::workspace_path(&ctx).unwrap();
+    assert!(path.ends_with("schedules/main.json"));
+    let journal = Journal::open(&ctx.schedules_dir);
+
+    add_task(&mut ctx, "Essay");
+    journal.record(&ctx, "task", Journaling::Line).unwrap();
+    assert_eq!(workspace::record_change(&mut ctx).unwrap(), None);
+    assert_eq!(
+        workspace::status_line(&ctx).unwrap(),
+        "Workspace main.json: 1 unsaved change(s), autosave every 2 changes"
+    );
+
+    add_task(&mut ctx, "Lab");
+    journal.record(&ctx, "task", Journaling::Line).unwrap();
+    assert_eq!(
+        workspace::record_change(&mut ctx).unwrap(),
+        Some(path.clone())
+    );
+    assert_eq!(ctx.workspace.unsaved(), 0);
+    assert!(journal.entries().unwrap().is_empty());
+
+    let mut reopened = workspace_ctx("interval-reopened", "main", "2");
+    reopened.schedules_dir = ctx.schedules_dir.clone();
+    assert_eq!(workspace::load(&mut reopened).unwrap(), Some(path));
+    assert_eq!(reopened.tasks.len(), 2);
+}
+
+#[test]
+fn flush_saves_pending_changes_unless_autosave_is_off() {
+    let mut ctx = workspace_ctx("flush", "main", "0");
+    add_task(&mut ctx, "Essay");
+    workspace::record_change(&mut ctx).unwrap();
+    assert_eq!(workspace::flush(&mut ctx).unwrap(), None);
+    assert_eq!(
+        workspace::status_line(&ctx).unwrap(),
+        "Workspace main.json: 1 unsaved change(s), autosave off"
+    );
+
+    ctx.config.set("AUTOSAVE_INTERVAL", "10").unwrap();
+    assert!(workspace::flush(&mut ctx).unwrap().is_some());
+    assert_eq!(workspace::flush(&mut ctx).unwrap(), None);
+}
+
+#[test]
+fn a_workspace_that_fails_to_load_pauses_autosave() {
+    let mut ctx = workspace_ctx("broken", "main", "1");
+    let path = workspace::workspace_path(&ctx).unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "not json").unwrap();
+
+    assert!(workspace::load(&mut ctx).is_err());
+    add_task(&mut ctx, "Essay");
+    assert_eq!(workspace::record_change(&mut ctx).unwrap(), None);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "not json");
+    assert!(
+        workspace::status_line(&ctx)
+            .unwrap()
+            .ends_with("autosave paused")
+    );
+}
+
+#[test]
+fn without_a_workspace_any_save_counts_and_nothing_is_autosaved() {
+    let mut ctx = workspace_ctx("none", "-", "1");
+    assert_eq!(workspace::status_line(&ctx), None);
+    add_task(&mut ctx, "Essay");
+    assert_eq!(workspace::record_change(&mut ctx).unwrap(), None);
+    assert_eq!(workspace::status_line(&ctx).unwrap(), "1 unsaved change(s)");
+    let other = ctx.schedules_dir.join("other.json");
+    workspace::mark_saved(&mut ctx, &other).unwrap();
+    assert_eq!(workspace::status_line(&ctx), None);
+}
+
+// ---------- versions.rs ----------
+fn temp_schedules_dir(name: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    std::env::temp_dir().join(format!("planit-versions-{name}-{nanos}"))
+}
+
+fn save_with_tasks(tasks: &[&str]) -> SaveFile {
+    SaveFile {
+        cards: Vec::new(),
+        templates: Vec::new(),
+        events: Vec::new(),
+        tasks: tasks
+            .iter()
+            .map(|name| {
+                vec![
+                    format!("\"{name}\""),
+                    "2".into(),
+                    "@".into(),
+                    "2099-01-01".into(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn version_store_commits_chain_and_resolves_revisions() {
+    let store = VersionStore::open(&temp_schedules_dir("chain"));
+    assert!(store.log().unwrap().is_empty());
+    assert!(store.resolve("HEAD").is_err());
+
+    let first = store.commit(&save_with_tasks(&["a"]), "first").unwrap();
+    let second = store
+        .commit(&save_with_tasks(&["a", "b"]), "second")
+        .unwrap();
+    assert_eq!(second.parent.as_deref(), Some(first.id.as_str()));
+    assert_eq!(store.head().unwrap(), Some(second.id.clone()));
+
+    let log = store.log().unwrap();
+    let messages: Vec<&str> = log.iter().map(|c| c.message.as_str()).collect();
+    assert_eq!(messages, vec!["second", "first"]);
+
+    assert_eq!(store.resolve("head").unwrap(), second);
+    assert_eq!(store.resolve("HEAD~1").unwrap(), first);
+    assert_eq!(store.resolve(first.short_id()).unwrap(), first);
+    assert!(store.resolve("HEAD~2").is_err());
+    assert!(store.resolve("abc").is_err());
+    assert_eq!(store.snapshot(&first).unwrap(), save_with_tasks(&["a"]));
+}
+
+#[test]
+fn version_store_refuses_unchanged_commit_and_shares_snapshots() {
+    let store = VersionStore::open(&temp_schedules_dir("unchanged"));
+    let first = store.commit(&save_with_tasks(&["a"]), "first").unwrap();
+    match store.commit(&save_with_tasks(&["a"]), "again").unwrap_err() {
+        Error::Parse(msg) => assert!(msg.contains("Nothing to commit")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+
+    store.commit(&save_with_tasks(&["b"]), "second").unwrap();
+    let back = store.commit(&save_with_tasks(&["a"]), "back").unwrap();
+    assert_eq!(back.snapshot, first.snapshot);
+    assert_ne!(back.id, first.id);
+}
+
+#[test]
+fn diff_saves_reports_removed_and_added_entities() {
+    let old = save_with_tasks(&["a", "b", "b"]);
+    let new = save_with_tasks(&["b", "c"]);
+    assert_eq!(
+        diff_saves(&old, &new),
+        vec![
+            "- task \"a\" 2 @ 2099-01-01".to_string(),
+            "- task \"b\" 2 @ 2099-01-01".to_string(),
+            "+ task \"c\" 2 @ 2099-01-01".to_string(),
+        ]
+    );
+    assert!(diff_saves(&new, &new).is_empty());
+}
+
+#[test]
+fn version_store_branches_track_commits_and_find_merge_base() {
+    let store = VersionStore::open(&temp_schedules_dir("branches"));
+    assert_eq!(store.head_ref().unwrap(), Head::Branch("main".into()));
+    assert!(store.create_branch("feature").is_err());
+
+    let root = store.commit(&save_with_tasks(&["a"]), "root").unwrap();
+    store.create_branch("feature").unwrap();
+    assert!(store.create_branch("feature").is_err());
+    assert!(store.create_branch("../escape").is_err());
+    let ours = store.commit(&save_with_tasks(&["a", "b"]), "ours").unwrap();
+
+    store.switch_branch("feature").unwrap();
+    assert_eq!(store.head().unwrap(), Some(root.id.clone()));
+    let theirs = store
+        .commit(&save_with_tasks(&["a", "c"]), "theirs")
+        .unwrap();
+    assert_eq!(store.resolve("feature").unwrap(), theirs);
+    assert_eq!(store.resolve("main").unwrap(), ours);
+
+    let names: Vec<String> = store.branches().unwrap().into_iter().map(|b| b.0).collect();
+    assert_eq!(names, vec!["feature".to_string(), "main".to_string()]);
+    assert_eq!(
+        store.merge_base(&ours, &theirs).unwrap(),
+        Some(root.clone())
+    );
+
+    store.switch_branch("main").unwrap();
+    let merged = store
+        .commit_merge(&save_with_tasks(&["a", "b", "c"]), "merge", &theirs.id)
+        .unwrap();
+    assert_eq!(store.merge_base(&merged, &theirs).unwrap(), Some(theirs));
+
+    store.detach_head(&root.id).unwrap();
+    assert_eq!(store.head_ref().unwrap(), Head::Detached(root.id));
+}
+
+#[test]
+fn three_way_merge_combines_separate_changes_and_keeps_references() {
+    let task = |name: &str, hours: &str, card: &str| {
+        vec![
+            format!("\"{name}\""),
+            hours.to_string(),
+            card.to_string(),
+            "@".into(),
+            "2099-01-01".into(),
+        ]
+    };
+    let base = SaveFile {
+        cards: vec![vec!["\"Work\"".into(), "RED".into()]],
+        tasks: vec![task("a", "2", "+C1")],
+        ..Default::default()
+    };
+    // Ours adds a card before Work, so Work moves to position 2.
+    let ours = SaveFile {
+        cards: vec![
+            vec!["\"Home\"".into(), "BLUE".into()],
+            vec!["\"Work\"".into(), "RED".into()],
+        ],
+        tasks: vec![task("a", "2", "+C2"), task("b", "1", "+C1")],
+        ..Default::default()
+    };
+    let theirs = SaveFile {
+        cards: vec![vec!["\"Work\"".into(), "RED".into()]],
+        tasks: vec![task("a", "5", "+C1")],
+        ..Default::default()
+    };
+
+    let plan = merge::three_way(&base, &ours, &theirs);
+    assert!(plan.conflicts.is_empty());
+    let merged = plan.resolve(&[]).unwrap();
+    assert_eq!(merged.cards, ours.cards);
+    assert_eq!(
+        merged.tasks,
+        vec![task("a", "5", "+C2"), task("b", "1", "+C1")]
+    );
+}
+
+#[test]
+fn three_way_merge_matches_entities_by_saved_uid() {
+    let uid = "01J9Z8Q4C6X5W2R7T3M1N0P8KD";
+    let task = |name: &str, hours: &str| {
+        vec![
+            format!("\"{name}\""),
+            hours.to_string(),
+            "@".into(),
+            "2099-01-01".into(),
+            format!("uid={uid}"),
+        ]
+    };
+    let base = SaveFile {
+        tasks: vec![task("Essay", "2")],
+        ..Default::default()
+    };
+    let ours = SaveFile {
+        tasks: vec![task("Final essay", "2")],
+        ..Default::default()
+    };
+    let theirs = SaveFile {
+        tasks: vec![task("Essay", "4")],
+        ..Default::default()
+    };
+
+    // A rename is a change to the same task, not a delete and an add.
+    let plan = merge::three_way(&base, &ours, &theirs);
+    assert_eq!(plan.conflicts.len(), 1);
+    assert_eq!(plan.conflicts[0].name, "\"Final essay\"");
+    assert_eq!(
+        plan.resolve(&[Side::Theirs]).unwrap().tasks,
+        vec![task("Essay", "4")]
+    );
+}
+
+#[test]
+fn three_way_merge_reports_conflicts_and_applies_choices() {
+    let base = save_with_tasks(&["a", "b"]);
+    let mut ours = save_with_tasks(&["a"]);
+    ours.tasks[0][1] = "3".into();
+    let mut theirs = save_with_tasks(&["a", "b"]);
+    theirs.tasks[0][1] = "4".into();
+
+    let plan = merge::three_way(&base, &ours, &theirs);
+    assert_eq!(plan.conflicts.len(), 1);
+    assert_eq!(plan.conflicts[0].kind, "task");
+    assert_eq!(plan.conflicts[0].name, "\"a\"");
+    match plan.resolve(&[]).unwrap_err() {
+        Error::Parse(msg) => assert!(msg.contains("1 conflict(s) left unresolved")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+
+    assert_eq!(plan.resolve(&[Side::Ours]).unwrap().tasks, ours.tasks);
+    assert_eq!(
+        plan.resolve(&[Side::Theirs]).unwrap().tasks,
+        vec![theirs.tasks[0].clone()]
+    );
+}
+
+// ---------- integrity.rs ----------
+fn ctx_with_card_users() -> AppContext {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    let date = Date(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap());
+    ctx.cards.insert(Card::new("work", CardColor::Red));
+    ctx.cards.insert(Card::new("home", CardColor::Blue));
+    ctx.templates.insert(TaskTemplate::new(
+        "report",
+        1.0,
+        Some(1),
+        vec![DayOfWeek::Fri],
+    ));
+    ctx.tasks
+        .insert(Task::new("essay", 2.0, Some(1), date.clone()));
+    ctx.tasks.insert(Task::new("chores", 1.0, Some(2), date));
+    ctx
+}
+
+#[test]
+fn delete_card_restrict_refuses_and_lists_referencers() {
+    let mut ctx = ctx_with_card_users();
+    assert_eq!(
+        card_referencers(&ctx, 1),
+        vec![(EntityType::Template, 1), (EntityType::Task, 1)]
+    );
+
+    match delete_card(&mut ctx, 1, CardDeletePolicy::Restrict).unwrap_err() {
+        Error::Parse(msg) => assert!(msg.starts_with("Card 1 is used by template 1, task 1.")),
+        other => panic!("expected parse error, got {other:?}"),
+    }
+    assert!(ctx.cards.get(1).is_ok());
+
+    ctx.tasks.get_mut(2).unwrap().card_id = None;
+    assert!(
+        delete_card(&mut ctx, 2, CardDeletePolicy::Restrict)
+            .unwrap()
+            .is_empty()
+    );
+    assert!(ctx.cards.get(2).is_err());
+}
+
+#[test]
+fn delete_card_cascade_and_set_null_handle_referencers() {
+    let mut ctx = ctx_with_card_users();
+    let changes = delete_card(&mut ctx, 1, CardDeletePolicy::SetNull).unwrap();
+    assert_eq!(
+        changes,
+        vec![
+            "cleared card on template 1".to_string(),
+            "cleared card on task 1".to_string()
+        ]
+    );
+    assert_eq!(ctx.tasks.get(1).unwrap().card_id, None);
+    assert_eq!(ctx.templates.get(1).unwrap().card_id, None);
+
+    let mut ctx = ctx_with_card_users();
+    let changes = delete_card(&mut ctx, 1, CardDeletePolicy::Cascade).unwrap();
+    assert_eq!(
+        changes,
+        vec![
+            "deleted template 1".to_string(),
+            "deleted task 1".to_string()
+        ]
+    );
+    assert_eq!(ctx.templates.len(), 0);
+    assert_eq!(ctx.tasks.len(), 1);
+    assert!(ctx.cards.get(1).is_err());
+    assert!(delete_card(&mut ctx, 1, CardDeletePolicy::Cascade).is_err());
+}
+
+fn filter_tasks() -> Repository<Task> {
+    let due = |d: u32| Date(NaiveDate::from_ymd_opt(2026, 10, d).unwrap());
+    let mut tasks = Repository::new();
+    tasks.insert(Task::new("Weekly report", 2.0, Some(2), due(15)));
+    tasks.insert(Task::new("Report draft", 5.0, Some(1), due(12)));
+    let mut done = Task::new("Old report", 1.0, Some(2), due(10));
+    done.complete();
+    tasks.insert(done);
+    tasks.insert(Task::new("Groceries", 1.0, None, due(20)));
+    tasks
+}
+
+fn find_ids(tasks: &Repository<Task>, exprs: &[&str]) -> Vec<i32> {
+    // 2026-10-14 is a Wednesday.
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    let exprs: Vec<String> = exprs.iter().map(|e| e.to_string()).collect();
+    let filter = Filter::parse::<Task>(&exprs, today).unwrap();
+    filter
+        .apply(tasks)
+        .values(Sort::IdAsc)
+        .iter()
+        .map(|t| t.id)
+        .collect()
+}
+
+#[test]
+fn filter_matches_task_fields() {
+    let tasks = filter_tasks();
+    assert_eq!(
+        find_ids(&tasks, &["due<fri", "card=+C2", "name~\"report\""]),
+        vec![1, 3]
+    );
+    assert_eq!(find_ids(&tasks, &["due<=2026-10-12", "!done"]), vec![2]);
+    assert_eq!(find_ids(&tasks, &["overdue"]), vec![2]);
+    assert_eq!(find_ids(&tasks, &["hours>=2", "card!=1"]), vec![1]);
+    assert_eq!(find_ids(&tasks, &["card=none"]), vec![4]);
+    assert_eq!(find_ids(&tasks, &["name~/^(weekly|old) /"]), vec![1, 3]);
+    assert_eq!(
+        find_ids(&tasks, &["name=groceries", "unscheduled"]),
+        vec![4]
+    );
+    assert_eq!(find_ids(&tasks, &[]), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn filter_matches_event_and_card_fields() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    let range = TimeRange::try_from_str("9:00AM-10:00AM").unwrap();
+    let mut events = Repository::new();
+    events.insert(Event::new(
+        true,
+        "Gym",
+        None,
+        vec![DayOfWeek::Mon],
+        range.clone(),
+    ));
+    events.insert(Event::new(
+        false,
+        "Dentist",
+        Some(1),
+        vec![DayOfWeek::Tue],
+        range,
+    ));
+    let exprs = vec!["recurring".to_string(), "day=mon".to_string()];
+    let filter = Filter::parse::<Event>(&exprs, today).unwrap();
+    assert!(filter.matches(events.get(1).unwrap()));
+    assert!(!filter.matches(events.get(2).unwrap()));
+
+    let card = Card::new("Work", CardColor::Red);
+    let filter = Filter::parse::<Card>(&["color!=red".to_string()], today).unwrap();
+    assert!(!filter.matches(&card));
+}
+
+#[test]
+fn filter_rejects_unknown_fields_and_operators() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    let parse = |e: &str| Filter::parse::<Task>(&[e.to_string()], today);
+    match parse("priority=1") {
+        Err(Error::Parse(msg)) => assert!(msg.contains("Valid fields: name, hours, due")),
+        other => panic!("expected unknown field error, got {other:?}"),
+    }
+    assert!(parse("name<abc").is_err());
+    assert!(parse("done=yes").is_err());
+    assert!(parse("hours").is_err());
+    assert!(parse("hours>lots").is_err());
+    assert!(parse("due<someday").is_err());
+    assert!(parse("name~/(/").is_err());
+}
+
+#[test]
+fn filter_matches_notes_links_and_metadata() {
+    let mut tasks = filter_tasks();
+    let details = &mut tasks.get_mut(1).unwrap().details;
+    details.notes = Some("Ask about the rubric".into());
+    details.links = vec!["https://a.org".into(), "https://docs.b.org".into()];
+    details.meta.insert("course".into(), "CS101".into());
+    tasks
+        .get_mut(2)
+        .unwrap()
+        .details
+        .meta
+        .insert("course".into(), "MA201".into());
+
+    assert_eq!(find_ids(&tasks, &["meta.course=cs101"]), vec![1]);
+    assert_eq!(find_ids(&tasks, &["meta.course"]), vec![1, 2]);
+    assert_eq!(find_ids(&tasks, &["!meta.course"]), vec![3, 4]);
+    assert_eq!(find_ids(&tasks, &["meta.course!=CS101"]), vec![2, 3, 4]);
+    assert_eq!(find_ids(&tasks, &["note~rubric"]), vec![1]);
+    assert_eq!(find_ids(&tasks, &["!note"]), vec![2, 3, 4]);
+    assert_eq!(find_ids(&tasks, &["links~docs"]), vec![1]);
+    assert_eq!(find_ids(&tasks, &["links!=https://a.org"]), vec![2, 3, 4]);
+
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+    assert!(Filter::parse::<Task>(&["note<b".to_string()], today).is_err());
+    assert!(Filter::parse::<Card>(&["meta.".to_string()], today).is_err());
+}
+
+#[test]
+fn selection_picks_ids_ranges_and_filter_matches() {
+    let tasks = filter_tasks();
+    let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+
+    let ids = Selection::ids_from_str("1,3-9").unwrap();
+    assert_eq!(ids.to_string(), "1,3-9");
+    assert_eq!(ids.select(&tasks, today).unwrap(), vec![1, 3, 4]);
+    assert!(
+        Selection::ids_from_str("2,9")
+            .unwrap()
+            .select(&tasks, today)
+            .is_err(),
+        "a single id must exist"
+    );
+    assert!(Selection::ids_from_str("5-2").is_err());
+    assert!(Selection::ids_from_str("1,,2").is_err());
+
+    let filtered = Selection::Where(vec!["card=+C2".into(), "!done".into()]);
+    assert_eq!(filtered.select(&tasks, today).unwrap(), vec![1]);
+    assert_eq!(
+        Selection::of_ids(&[1, 2, 3, 5, 7, 8]).to_string(),
+        "1-3,5,7-8"
+    );
+}
+
+// ---------- import.rs ----------
+/// A plan with a `Work` card, a template on it and a `Report` task made from that template, saved
+/// as tokens.
+fn import_source() -> SaveFile {
+    let mut source = AppContext::new();
+    source.cards.insert(Card::new("Unused", CardColor::Green));
+    source.cards.insert(Card::new("Work", CardColor::Red));
+    source.templates.insert(TaskTemplate::new(
+        "Standup",
+        1.0,
+        Some(2),
+        vec![DayOfWeek::Mon],
+    ));
+    let mut task = Task::new(
+        "Report",
+        2.0,
+        Some(2),
+        Date::try_from_str("2099-01-05").unwrap(),
+    );
+    task.origin = Some(TemplateOrigin {
+        template_id: 1,
+        occurrence: NaiveDate::from_ymd_opt(2099, 1, 5).unwrap(),
+    });
+    source.tasks.insert(task);
+    build_save_file(
+        &source.tasks,
+        &source.events,
+        &source.cards,
+        &source.templates,
+    )
+    .unwrap()
+}
+
+/// The plan the source is imported into: a `Home` card, the same `Work` card under another id
+/// and a `Report` task of its own.
+fn import_target() -> AppContext {
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    ctx.cards.insert(Card::new("Home", CardColor::Blue));
+    ctx.cards.insert(Card::new("Work", CardColor::Red));
+    ctx.tasks.insert(Task::new(
+        "Report",
+        5.0,
+        None,
+        Date::try_from_str("2099-02-01").unwrap(),
+    ));
+    ctx
+}
+
+#[test]
+fn import_reuses_matching_cards_and_remaps_references() {
+    let mut ctx = import_target();
+    let report = import_save_file(&mut ctx, &import_source(), TaskConflictPolicy::Rename).unwrap();
+
+    assert_eq!(report.cards_added, 1);
+    assert_eq!(report.cards_matched, 1);
+    assert_eq!(report.templates_added, 1);
+    assert_eq!(report.tasks_added, 1);
+    assert_eq!(report.tasks_renamed, 1);
+
+    let names = |ctx: &AppContext| {
+        ctx.cards
+            .values(Sort::IdAsc)
+            .iter()
+            .map(|c| c.name.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&ctx), ["Home", "Work", "Unused"]);
+    let template = ctx.templates.get(1).unwrap();
+    assert_eq!(template.card_id, Some(2));
+    let imported = ctx.tasks.get(2).unwrap();
+    assert_eq!(imported.name, "Report (2)");
+    assert_eq!(imported.card_id, Some(2));
+    assert_eq!(imported.origin.as_ref().map(|o| o.template_id), Some(1));
+    assert_eq!(ctx.tasks.get(1).unwrap().name, "Report");
+
+    import_save_file(&mut ctx, &import_source(), TaskConflictPolicy::Rename).unwrap();
+    assert_eq!(ctx.tasks.get(3).unwrap().name, "Report (3)");
+    assert_eq!(ctx.cards.len(), 3, "cards added by the first import match");
+}
+
+#[test]
+fn import_skips_or_overwrites_tasks_with_existing_names() {
+    let mut ctx = import_target();
+    let report = import_save_file(&mut ctx, &import_source(), TaskConflictPolicy::Skip).unwrap();
+    assert_eq!((report.tasks_added, report.tasks_skipped), (0, 1));
+    assert_eq!(ctx.tasks.len(), 1);
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 5.0);
+
+    let mut ctx = import_target();
+    let report =
+        import_save_file(&mut ctx, &import_source(), TaskConflictPolicy::Overwrite).unwrap();
+    assert_eq!((report.tasks_added, report.tasks_overwritten), (1, 1));
+    assert!(ctx.tasks.get(1).is_err());
+    let task = ctx.tasks.get(2).unwrap();
+    assert_eq!((task.name.as_str(), task.hours), ("Report", 2.0));
+}
+
+#[test]
+fn import_gives_taken_uids_fresh_ones() {
+    let save = import_source();
+    let mut ctx = AppContext::new();
+    ctx.logger.set_file_logging_enabled(false);
+    import_save_file(&mut ctx, &save, TaskConflictPolicy::Rename).unwrap();
+    let first = ctx.tasks.get(1).unwrap().uid.clone();
+
+    import_save_file(&mut ctx, &save, TaskConflictPolicy::Rename).unwrap();
+    assert_ne!(ctx.tasks.get(2).unwrap().uid, first);
+    assert_eq!(ctx.cards.len(), 2, "both cards match by name and color");
+}
+
+#[test]
+fn failed_import_leaves_the_plan_as_it_was() {
+    let mut ctx = import_target();
+    let broken = SaveFile {
+        tasks: vec![
+            vec![
+                "\"Report\"".into(),
+                "1".into(),
+                "@".into(),
+                "2099-01-01".into(),
+            ],
+            vec![
+                "\"Orphan\"".into(),
+                "1".into(),
+                "+C9".into(),
+                "@".into(),
+                "2099-01-01".into(),
+            ],
+        ],
+        ..SaveFile::default()
+    };
+    assert!(import_save_file(&mut ctx, &broken, TaskConflictPolicy::Overwrite).is_err());
+    assert_eq!(ctx.cards.len(), 2);
+    assert_eq!(ctx.tasks.len(), 1);
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 5.0);
+
+    let mut good = broken.clone();
+    good.tasks.pop();
+    import_save_file(&mut ctx, &good, TaskConflictPolicy::Overwrite).unwrap();
+    assert_eq!(ctx.tasks.len(), 1);
+    assert_eq!(ctx.tasks.get(2).unwrap().hours, 1.0);
+}
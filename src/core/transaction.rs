@@ -1,32 +1,39 @@
 use crate::arg::args::Arg;
 use crate::command::command_parser::CommandParser;
 use crate::core::context::AppContext;
-use crate::core::models::{BaseEntity, Card, Event, Task};
+use crate::core::history::{self, Snapshot};
+use crate::core::models::{BaseEntity, Card, Event, Task, TaskTemplate};
 use crate::core::repository::{PreparedRepo, Repository};
+use crate::core::types::EntityType;
 use crate::errors::{Error, Result};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+pub type EntityRef = (EntityType, i32);
 
 pub trait ParticipantOps {
     fn begin_stage(&mut self, ctx: &mut AppContext, clear_existing: bool) -> Result<()>;
-    fn id_pool(&mut self, ctx: &mut AppContext) -> Result<HashSet<i32>>;
-    fn references(&mut self, ctx: &mut AppContext) -> Result<Vec<i32>>;
+    fn id_pool(&mut self, ctx: &mut AppContext) -> Result<(EntityType, HashSet<i32>)>;
+    fn references(&mut self, ctx: &mut AppContext) -> Result<Vec<EntityRef>>;
     fn prepare_commit(&mut self, ctx: &mut AppContext) -> Result<()>;
     fn apply_prepared(&mut self, ctx: &mut AppContext) -> Result<()>;
     fn discard_stage(&mut self, ctx: &mut AppContext);
 }
 
 struct RepoParticipant<T: BaseEntity + Clone> {
+    kind: EntityType,
     accessor: fn(&mut AppContext) -> &mut Repository<T>,
-    ref_extractor: Option<fn(&T) -> Option<i32>>,
+    ref_extractor: Option<fn(&T) -> Vec<EntityRef>>,
     prepared: Option<PreparedRepo<T>>,
 }
 
 impl<T: BaseEntity + Clone> RepoParticipant<T> {
     fn new(
+        kind: EntityType,
         accessor: fn(&mut AppContext) -> &mut Repository<T>,
-        ref_extractor: Option<fn(&T) -> Option<i32>>,
+        ref_extractor: Option<fn(&T) -> Vec<EntityRef>>,
     ) -> Self {
         Self {
+            kind,
             accessor,
             ref_extractor,
             prepared: None,
@@ -39,15 +46,11 @@ impl<T: BaseEntity + Clone + 'static> ParticipantOps for RepoParticipant<T> {
         (self.accessor)(ctx).begin_stage(clear_existing)
     }
 
-    fn id_pool(&mut self, ctx: &mut AppContext) -> Result<HashSet<i32>> {
-        if self.ref_extractor.is_none() {
-            (self.accessor)(ctx).staged_effective_ids()
-        } else {
-            Ok(HashSet::new())
-        }
+    fn id_pool(&mut self, ctx: &mut AppContext) -> Result<(EntityType, HashSet<i32>)> {
+        Ok((self.kind, (self.accessor)(ctx).staged_effective_ids()?))
     }
 
-    fn references(&mut self, ctx: &mut AppContext) -> Result<Vec<i32>> {
+    fn references(&mut self, ctx: &mut AppContext) -> Result<Vec<EntityRef>> {
         let Some(extractor) = self.ref_extractor else {
             return Ok(Vec::new());
         };
@@ -56,9 +59,7 @@ impl<T: BaseEntity + Clone + 'static> ParticipantOps for RepoParticipant<T> {
         let mut ids = Vec::new();
         if let Some(pending) = repo.staged_pending() {
             for entity in pending {
-                if let Some(cid) = extractor(entity) {
-                    ids.push(cid);
-                }
+                ids.extend(extractor(entity));
             }
         }
         Ok(ids)
@@ -92,14 +93,31 @@ pub struct Transaction {
 impl Transaction {
     pub fn new() -> Self {
         let mut participants: Vec<Box<dyn ParticipantOps>> = Vec::new();
-        participants.push(Box::new(RepoParticipant::new(AppContext::cards_repo, None)));
         participants.push(Box::new(RepoParticipant::new(
+            EntityType::Card,
+            AppContext::cards_repo,
+            None,
+        )));
+        participants.push(Box::new(RepoParticipant::new(
+            EntityType::Template,
+            AppContext::templates_repo,
+            Some(|t: &TaskTemplate| card_ref(t.card_id)),
+        )));
+        participants.push(Box::new(RepoParticipant::new(
+            EntityType::Event,
             AppContext::events_repo,
-            Some(|e: &Event| e.card_id),
+            Some(|e: &Event| card_ref(e.card_id)),
         )));
         participants.push(Box::new(RepoParticipant::new(
+            EntityType::Task,
             AppContext::tasks_repo,
-            Some(|t: &Task| t.card_id),
+            Some(|t: &Task| {
+                let mut refs = card_ref(t.card_id);
+                if let Some(origin) = &t.origin {
+                    refs.push((EntityType::Template, origin.template_id));
+                }
+                refs
+            }),
         )));
         Self { participants }
     }
@@ -132,17 +150,19 @@ impl Transaction {
     }
 
     fn validate_associations(&mut self, ctx: &mut AppContext) -> Result<()> {
-        let mut id_pool = HashSet::new();
+        let mut id_pools: HashMap<EntityType, HashSet<i32>> = HashMap::new();
         for p in self.participants.iter_mut() {
-            id_pool.extend(p.id_pool(ctx)?);
+            let (kind, ids) = p.id_pool(ctx)?;
+            id_pools.entry(kind).or_default().extend(ids);
         }
 
         for p in self.participants.iter_mut() {
-            for cid in p.references(ctx)? {
-                if !id_pool.contains(&cid) {
+            for (kind, id) in p.references(ctx)? {
+                let present = id_pools.get(&kind).is_some_and(|ids| ids.contains(&id));
+                if !present {
                     return Err(Error::Parse(format!(
-                        "Referenced id {} not present in transaction.",
-                        cid
+                        "Referenced {} id {} not present in transaction.",
+                        kind, id
                     )));
                 }
             }
@@ -175,6 +195,9 @@ impl AppContext {
     fn cards_repo(&mut self) -> &mut Repository<Card> {
         &mut self.cards
     }
+    fn templates_repo(&mut self) -> &mut Repository<TaskTemplate> {
+        &mut self.templates
+    }
     fn events_repo(&mut self) -> &mut Repository<Event> {
         &mut self.events
     }
@@ -183,8 +206,15 @@ impl AppContext {
     }
 }
 
+fn card_ref(card_id: Option<i32>) -> Vec<EntityRef> {
+    card_id
+        .map(|id| vec![(EntityType::Card, id)])
+        .unwrap_or_default()
+}
+
 #[derive(Debug)]
 struct CommandOp {
+    label: String,
     name: String,
     args: Vec<Arg>,
 }
@@ -200,12 +230,21 @@ impl CommandQueue {
     }
 
     pub fn push(&mut self, name: &str, args: Vec<Arg>) {
+        self.push_labeled(name, name, args);
+    }
+
+    /// Queues a command that errors, undo and redo will refer to as `label`, e.g. its command
+    /// line.
+    pub fn push_labeled(&mut self, label: &str, name: &str, args: Vec<Arg>) {
         self.ops.push(CommandOp {
+            label: label.to_string(),
             name: name.to_string(),
             args,
         });
     }
 
+    /// Runs every command in one transaction, so either all of them apply or none do. The error
+    /// of a command that fails follows its label.
     pub fn execute(
         self,
         ctx: &mut AppContext,
@@ -215,10 +254,38 @@ impl CommandQueue {
         let mut tx = Transaction::new();
         tx.run(ctx, clear_existing, |ctx| {
             for op in &self.ops {
-                let cmd = parser.parse(&op.name, &op.args)?;
-                cmd.execute(ctx)?;
+                parser
+                    .parse(&op.name, &op.args)
+                    .and_then(|cmd| cmd.execute(ctx))
+                    .map_err(|err| Error::Parse(format!("{}. {err}", op.label)))?;
             }
             Ok(())
         })
     }
+
+    /// Runs each command on its own, as if it had just been typed, instead of in one
+    /// transaction: commands that replace the whole plan can take part, and every change is
+    /// recorded for undo. `after` runs once each command succeeds, as part of its undo step,
+    /// and is given its index. Returns how many commands ran before the first failure, if any.
+    pub fn replay(
+        self,
+        ctx: &mut AppContext,
+        parser: &CommandParser,
+        mut after: impl FnMut(usize, &mut AppContext) -> Result<()>,
+    ) -> (usize, Result<()>) {
+        for (applied, op) in self.ops.iter().enumerate() {
+            let result = parser.parse(&op.name, &op.args).and_then(|cmd| {
+                let before = cmd.changes_state().then(|| Snapshot::capture(ctx));
+                let result = cmd.execute(ctx).and_then(|()| after(applied, ctx));
+                if let Some(before) = before {
+                    history::record(ctx, &op.label, before);
+                }
+                result
+            });
+            if let Err(err) = result {
+                return (applied, Err(err));
+            }
+        }
+        (self.ops.len(), Ok(()))
+    }
 }
@@ -15,14 +15,42 @@ pub enum GlobalCommand {
     Config,
     #[strum(serialize = "schedule", to_string = "schedule")]
     Schedule,
+    #[strum(serialize = "forecast", to_string = "forecast")]
+    Forecast,
     #[strum(serialize = "log", to_string = "log")]
     Log,
     #[strum(serialize = "save", to_string = "save")]
     Save,
     #[strum(serialize = "read", to_string = "read")]
     Read,
+    #[strum(serialize = "convert", to_string = "convert")]
+    Convert,
+    #[strum(serialize = "import", to_string = "import")]
+    Import,
     #[strum(serialize = "man", to_string = "man")]
     Man,
+    #[strum(serialize = "undo", to_string = "undo")]
+    Undo,
+    #[strum(serialize = "redo", to_string = "redo")]
+    Redo,
+    #[strum(serialize = "commit", to_string = "commit")]
+    Commit,
+    #[strum(serialize = "history", to_string = "history")]
+    History,
+    #[strum(serialize = "checkout", to_string = "checkout")]
+    Checkout,
+    #[strum(serialize = "diff", to_string = "diff")]
+    Diff,
+    #[strum(serialize = "branch", to_string = "branch")]
+    Branch,
+    #[strum(serialize = "merge", to_string = "merge")]
+    Merge,
+    #[strum(serialize = "find", to_string = "find")]
+    Find,
+    #[strum(serialize = "show", to_string = "show")]
+    Show,
+    #[strum(serialize = "item", to_string = "item")]
+    Item,
 }
 
 impl GlobalCommand {
@@ -37,6 +65,34 @@ impl GlobalCommand {
     }
 }
 
+/// What `item <action> <taskId> ...` does to a task's checklist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum ChecklistAction {
+    #[strum(serialize = "add", to_string = "add")]
+    Add,
+    #[strum(serialize = "check", to_string = "check")]
+    Check,
+    #[strum(serialize = "uncheck", to_string = "uncheck")]
+    Uncheck,
+    #[strum(serialize = "move", to_string = "move")]
+    Move,
+    #[strum(serialize = "del", to_string = "del")]
+    Del,
+}
+
+impl ChecklistAction {
+    pub fn try_from(s: &str) -> Result<Self> {
+        Self::from_str(s).map_err(|_| {
+            Error::Parse(format!(
+                "Unsupported item action: '{}'. Valid item actions: {}",
+                s.trim(),
+                valid_csv::<ChecklistAction>()
+            ))
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
 #[strum(ascii_case_insensitive, serialize_all = "lowercase")]
 pub enum TypeHelpCommand {
@@ -68,7 +124,9 @@ impl TypeHelpCommand {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, Display, AsRefStr, EnumIterDerive,
+)]
 #[strum(ascii_case_insensitive, serialize_all = "lowercase")]
 pub enum EntityType {
     #[strum(serialize = "task", to_string = "task")]
@@ -77,6 +135,8 @@ pub enum EntityType {
     Event,
     #[strum(serialize = "card", to_string = "card")]
     Card,
+    #[strum(serialize = "template", to_string = "template")]
+    Template,
 }
 impl EntityType {
     pub fn try_from(s: &str) -> Result<Self> {
@@ -99,6 +159,8 @@ pub enum EntityActionType {
     Modify,
     #[strum(serialize = "del")]
     Delete,
+    #[strum(serialize = "done")]
+    Complete,
 }
 impl EntityActionType {
     pub fn try_from(s: &str) -> Result<Self> {
@@ -112,8 +174,22 @@ impl EntityActionType {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    Display,
+    AsRefStr,
+    EnumIterDerive,
+    Serialize,
+    Deserialize,
+)]
 #[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "UPPERCASE")]
 pub enum DayOfWeek {
     #[strum(
         serialize = "mon",
@@ -185,7 +261,8 @@ impl DayOfWeek {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Date(pub NaiveDate);
 
 #[derive(Copy, Clone, Debug, EnumIterDerive, AsRefStr, EnumString)]
@@ -264,6 +341,71 @@ impl fmt::Display for Date {
     }
 }
 
+/// Three-point hour estimate, written `~<optimistic>/<likely>/<pessimistic>` (e.g. `~2/3/6`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub optimistic: f32,
+    pub likely: f32,
+    pub pessimistic: f32,
+}
+
+impl Estimate {
+    pub fn try_from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::Parse(format!(
+                "Invalid estimate: '{}'. Expected '~<optimistic>/<likely>/<pessimistic>' hours with optimistic <= likely <= pessimistic (e.g., ~2/3/6).",
+                s.trim()
+            ))
+        };
+        let parts: Vec<f32> = s
+            .trim()
+            .strip_prefix('~')
+            .ok_or_else(invalid)?
+            .split('/')
+            .map(|p| p.parse::<f32>().map_err(|_| invalid()))
+            .collect::<Result<_>>()?;
+        let [optimistic, likely, pessimistic] = parts[..] else {
+            return Err(invalid());
+        };
+        if !(0.0 <= optimistic
+            && optimistic <= likely
+            && likely <= pessimistic
+            && pessimistic.is_finite())
+        {
+            return Err(invalid());
+        }
+        Ok(Self {
+            optimistic,
+            likely,
+            pessimistic,
+        })
+    }
+
+    /// Maps `u` in [0, 1) onto the triangular distribution spanned by the estimate.
+    pub fn sample(&self, u: f32) -> f32 {
+        let (a, c, b) = (self.optimistic, self.likely, self.pessimistic);
+        if b - a <= f32::EPSILON {
+            return c;
+        }
+        let split = (c - a) / (b - a);
+        if u < split {
+            a + (u * (b - a) * (c - a)).sqrt()
+        } else {
+            b - ((1.0 - u) * (b - a) * (b - c)).sqrt()
+        }
+    }
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "~{}/{}/{}",
+            self.optimistic, self.likely, self.pessimistic
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TimeRange {
     pub start: NaiveTime,
@@ -526,6 +668,204 @@ impl TaskSchedulingOrder {
     }
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    AsRefStr,
+    EnumIterDerive,
+    Serialize,
+    Deserialize,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "kebab-case")]
+pub enum TaskPackingMode {
+    #[default]
+    #[strum(serialize = "greedy", to_string = "greedy")]
+    Greedy,
+    #[strum(serialize = "group-by-card", to_string = "group-by-card")]
+    GroupByCard,
+}
+impl TaskPackingMode {
+    pub fn help(&self) -> &'static str {
+        match self {
+            TaskPackingMode::Greedy => "Place tasks in scheduling order as they come.",
+            TaskPackingMode::GroupByCard => {
+                "Group tasks sharing a card into contiguous runs to minimise context switches."
+            }
+        }
+    }
+
+    pub fn try_from(s: &str) -> Result<Self> {
+        Self::from_str(s).map_err(|_| {
+            Error::Parse(format!(
+                "Invalid task packing mode: '{}'. Allowed packing modes: {}",
+                s.trim(),
+                valid_csv::<TaskPackingMode>()
+            ))
+        })
+    }
+}
+
+/// What happens to tasks, events and templates that use a card being deleted.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    AsRefStr,
+    EnumIterDerive,
+    Serialize,
+    Deserialize,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "kebab-case")]
+pub enum CardDeletePolicy {
+    #[default]
+    #[strum(serialize = "restrict", to_string = "restrict")]
+    Restrict,
+    #[strum(serialize = "cascade", to_string = "cascade")]
+    Cascade,
+    #[strum(serialize = "set-null", to_string = "set-null")]
+    SetNull,
+}
+
+impl CardDeletePolicy {
+    pub fn help(&self) -> &'static str {
+        match self {
+            CardDeletePolicy::Restrict => "Refuse to delete a card that is still in use.",
+            CardDeletePolicy::Cascade => {
+                "Delete the tasks, events and templates that use the card along with it."
+            }
+            CardDeletePolicy::SetNull => "Keep entities that use the card, without a card.",
+        }
+    }
+
+    pub fn from_flag(flag: Flag) -> Option<Self> {
+        match flag {
+            Flag::Restrict => Some(CardDeletePolicy::Restrict),
+            Flag::Cascade => Some(CardDeletePolicy::Cascade),
+            Flag::SetNull => Some(CardDeletePolicy::SetNull),
+            Flag::Help
+            | Flag::Sort
+            | Flag::Columns
+            | Flag::Yes
+            | Flag::Skip
+            | Flag::Rename
+            | Flag::Overwrite
+            | Flag::Preview
+            | Flag::WithConfig => None,
+        }
+    }
+
+    pub fn try_from(s: &str) -> Result<Self> {
+        Self::from_str(s).map_err(|_| {
+            Error::Parse(format!(
+                "Invalid card delete policy: '{}'. Allowed policies: {}",
+                s.trim(),
+                valid_csv::<CardDeletePolicy>()
+            ))
+        })
+    }
+}
+
+/// What `import` does with an imported task named like a task already in the plan.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, EnumString, Display, AsRefStr, EnumIterDerive,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum TaskConflictPolicy {
+    #[default]
+    #[strum(serialize = "skip", to_string = "skip")]
+    Skip,
+    #[strum(serialize = "rename", to_string = "rename")]
+    Rename,
+    #[strum(serialize = "overwrite", to_string = "overwrite")]
+    Overwrite,
+}
+
+impl TaskConflictPolicy {
+    pub fn help(&self) -> &'static str {
+        match self {
+            TaskConflictPolicy::Skip => "Keep the existing task and leave the imported one out.",
+            TaskConflictPolicy::Rename => "Import the task as \"<name> (2)\", \"<name> (3)\", ...",
+            TaskConflictPolicy::Overwrite => "Replace the existing task with the imported one.",
+        }
+    }
+
+    pub fn from_flag(flag: Flag) -> Option<Self> {
+        match flag {
+            Flag::Skip => Some(TaskConflictPolicy::Skip),
+            Flag::Rename => Some(TaskConflictPolicy::Rename),
+            Flag::Overwrite => Some(TaskConflictPolicy::Overwrite),
+            Flag::Help
+            | Flag::Restrict
+            | Flag::Cascade
+            | Flag::SetNull
+            | Flag::Sort
+            | Flag::Columns
+            | Flag::Yes
+            | Flag::Preview
+            | Flag::WithConfig => None,
+        }
+    }
+}
+
+/// How `save` and the workspace write the plan. `read` accepts either.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    AsRefStr,
+    EnumIterDerive,
+    Serialize,
+    Deserialize,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "kebab-case")]
+pub enum SaveFormat {
+    #[default]
+    #[strum(serialize = "tokens", to_string = "tokens")]
+    Tokens,
+    #[strum(serialize = "typed", to_string = "typed")]
+    Typed,
+}
+
+impl SaveFormat {
+    pub fn help(&self) -> &'static str {
+        match self {
+            SaveFormat::Tokens => "Write each entity as the arguments of the command that adds it.",
+            SaveFormat::Typed => {
+                "Write each entity as an object with typed fields, for other tools."
+            }
+        }
+    }
+
+    pub fn try_from(s: &str) -> Result<Self> {
+        Self::from_str(s).map_err(|_| {
+            Error::Parse(format!(
+                "Invalid save format: '{}'. Allowed formats: {}",
+                s.trim(),
+                valid_csv::<SaveFormat>()
+            ))
+        })
+    }
+}
+
 #[derive(
     Debug,
     Clone,
@@ -591,8 +931,22 @@ impl fmt::Display for ScheduledTime {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    EnumString,
+    Display,
+    AsRefStr,
+    EnumIterDerive,
+    Serialize,
+    Deserialize,
+)]
 #[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum CardColor {
     #[strum(serialize = "red", to_string = "RED")]
     Red,
@@ -673,4 +1027,47 @@ impl CardColor {
 pub enum Flag {
     #[strum(serialize = "-h", serialize = "-help", to_string = "-h")]
     Help,
+    #[strum(serialize = "--restrict", to_string = "--restrict")]
+    Restrict,
+    #[strum(serialize = "--cascade", to_string = "--cascade")]
+    Cascade,
+    #[strum(serialize = "--set-null", to_string = "--set-null")]
+    SetNull,
+    #[strum(serialize = "--sort", to_string = "--sort")]
+    Sort,
+    #[strum(serialize = "--columns", to_string = "--columns")]
+    Columns,
+    #[strum(serialize = "--yes", serialize = "-y", to_string = "--yes")]
+    Yes,
+    #[strum(serialize = "--skip", to_string = "--skip")]
+    Skip,
+    #[strum(serialize = "--rename", to_string = "--rename")]
+    Rename,
+    #[strum(serialize = "--overwrite", to_string = "--overwrite")]
+    Overwrite,
+    #[strum(serialize = "--preview", to_string = "--preview")]
+    Preview,
+    #[strum(serialize = "--with-config", to_string = "--with-config")]
+    WithConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, AsRefStr, EnumIterDerive)]
+#[strum(ascii_case_insensitive)]
+pub enum TaskMarker {
+    #[strum(serialize = "!atomic", to_string = "!atomic")]
+    Atomic,
+    #[strum(serialize = "!done", to_string = "!done")]
+    Done,
+}
+
+impl TaskMarker {
+    pub fn try_from(s: &str) -> Result<Self> {
+        Self::from_str(s).map_err(|_| {
+            Error::Parse(format!(
+                "Invalid task marker: '{}'. Valid markers: {}",
+                s.trim(),
+                valid_csv::<TaskMarker>()
+            ))
+        })
+    }
 }
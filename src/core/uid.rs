@@ -0,0 +1,95 @@
+use crate::errors::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Field that carries a saved entity's uid at the end of its add arguments. It is read on load
+/// but never modified.
+pub const UID_FIELD: &str = "uid";
+
+/// The uid carried by a saved `uid=<uid>` token.
+pub fn saved_uid(token: &str) -> Option<&str> {
+    token.strip_prefix(UID_FIELD)?.strip_prefix('=')
+}
+
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const LEN: usize = 26;
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Identity of an entity that survives save and load, unlike its numeric id, which is only
+/// assigned for the session. Written as a ULID: 26 Crockford base32 characters, a millisecond
+/// timestamp followed by 80 random bits, so uids sort by creation time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Uid(String);
+
+impl Uid {
+    pub fn new() -> Self {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let random = ((random_u64() as u128) << 16) | (random_u64() as u128 & 0xFFFF);
+        Self::encode(((millis & 0xFFFF_FFFF_FFFF) << 80) | random)
+    }
+
+    pub fn try_from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_uppercase();
+        let valid =
+            s.len() == LEN && s.bytes().all(|b| ALPHABET.contains(&b)) && s.as_bytes()[0] <= b'7';
+        if !valid {
+            return Err(Error::Parse(format!(
+                "Invalid uid: '{s}'. Expected 26 characters of 0-9 and A-Z without I, L, O and U."
+            )));
+        }
+        Ok(Self(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn encode(value: u128) -> Self {
+        let text = (0..LEN)
+            .rev()
+            .map(|i| ALPHABET[((value >> (i * 5)) & 0x1F) as usize] as char)
+            .collect();
+        Self(text)
+    }
+}
+
+impl Default for Uid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Uid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<String> for Uid {
+    type Error = Error;
+    fn try_from(s: String) -> Result<Self> {
+        Self::try_from_str(&s)
+    }
+}
+
+impl From<Uid> for String {
+    fn from(uid: Uid) -> Self {
+        uid.0
+    }
+}
+
+/// `RandomState` is keyed randomly per process; hashing a counter gives a fresh value each call.
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
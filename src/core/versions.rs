@@ -0,0 +1,441 @@
+use crate::core::aliases::{TokenList, TokenMatrix};
+use crate::core::migrations;
+use crate::core::persist::SaveFile;
+use crate::core::uid::saved_uid;
+use crate::errors::{Error, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory under the schedules directory that holds snapshots, commits, branches and `HEAD`.
+const STORE_DIR: &str = ".planit";
+/// Branch a fresh history starts on.
+pub const DEFAULT_BRANCH: &str = "main";
+/// `HEAD` names a branch with this prefix; otherwise it holds a commit hash (detached).
+const HEAD_REF_PREFIX: &str = "ref: ";
+/// Shortest hash prefix accepted as a revision.
+const MIN_PREFIX_LEN: usize = 4;
+/// Length of the abbreviated hash shown to users.
+const SHORT_HASH_LEN: usize = 8;
+
+/// One point in the history of the plan. The snapshot is stored separately, keyed by the hash
+/// of its contents, so commits of an unchanged plan share it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Commit {
+    /// Hash of the commit file; derived from the file name rather than stored in it.
+    #[serde(skip)]
+    pub id: String,
+    pub parent: Option<String>,
+    /// Second parent of a merge commit: the tip that was merged in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged: Option<String>,
+    pub snapshot: String,
+    pub author: String,
+    pub timestamp: DateTime<Local>,
+    pub message: String,
+}
+
+impl Commit {
+    pub fn short_id(&self) -> &str {
+        &self.id[..SHORT_HASH_LEN.min(self.id.len())]
+    }
+
+    fn parents(&self) -> impl Iterator<Item = &String> {
+        self.parent.iter().chain(self.merged.iter())
+    }
+}
+
+/// What `HEAD` points at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Head {
+    /// On a branch; new commits move the branch.
+    Branch(String),
+    /// On a specific commit, outside any branch.
+    Detached(String),
+}
+
+/// Content-addressed store of `SaveFile` snapshots and the commits that point at them.
+///
+/// Layout: `objects/<hash>.json` (snapshots), `commits/<hash>.json`, `refs/<branch>` and `HEAD`.
+pub struct VersionStore {
+    root: PathBuf,
+}
+
+impl VersionStore {
+    pub fn open(schedules_dir: &Path) -> Self {
+        Self {
+            root: schedules_dir.join(STORE_DIR),
+        }
+    }
+
+    fn objects_dir(&self) -> PathBuf {
+        self.root.join("objects")
+    }
+
+    fn commits_dir(&self) -> PathBuf {
+        self.root.join("commits")
+    }
+
+    fn refs_dir(&self) -> PathBuf {
+        self.root.join("refs")
+    }
+
+    fn head_path(&self) -> PathBuf {
+        self.root.join("HEAD")
+    }
+
+    /// Where `HEAD` points; a store without one starts on the default branch.
+    pub fn head_ref(&self) -> Result<Head> {
+        let Some(contents) = read_optional(&self.head_path())? else {
+            return Ok(Head::Branch(DEFAULT_BRANCH.to_string()));
+        };
+        Ok(match contents.strip_prefix(HEAD_REF_PREFIX) {
+            Some(branch) => Head::Branch(branch.trim().to_string()),
+            None => Head::Detached(contents),
+        })
+    }
+
+    /// The commit `HEAD` resolves to, if any has been made.
+    pub fn head(&self) -> Result<Option<String>> {
+        match self.head_ref()? {
+            Head::Branch(name) => self.branch_tip(&name),
+            Head::Detached(id) => Ok(Some(id)),
+        }
+    }
+
+    fn write_head(&self, head: &Head) -> Result<()> {
+        fs::create_dir_all(&self.root)?;
+        let contents = match head {
+            Head::Branch(name) => format!("{HEAD_REF_PREFIX}{name}\n"),
+            Head::Detached(id) => format!("{id}\n"),
+        };
+        fs::write(self.head_path(), contents)?;
+        Ok(())
+    }
+
+    /// Moves whatever `HEAD` points at (the current branch, or `HEAD` itself) to `id`.
+    pub fn advance_head(&self, id: &str) -> Result<()> {
+        match self.head_ref()? {
+            Head::Branch(name) => self.write_branch(&name, id),
+            Head::Detached(_) => self.write_head(&Head::Detached(id.to_string())),
+        }
+    }
+
+    /// Points `HEAD` at `id` outside any branch.
+    pub fn detach_head(&self, id: &str) -> Result<()> {
+        self.write_head(&Head::Detached(id.to_string()))
+    }
+
+    /// Points `HEAD` at the branch `name`, which must exist.
+    pub fn switch_branch(&self, name: &str) -> Result<Commit> {
+        let tip = self
+            .branch_tip(name)?
+            .ok_or_else(|| Error::Parse(format!("Unknown branch: '{name}'.")))?;
+        self.write_head(&Head::Branch(name.to_string()))?;
+        self.read_commit(&tip)
+    }
+
+    pub fn has_branch(&self, name: &str) -> Result<bool> {
+        Ok(self.branch_tip(name)?.is_some())
+    }
+
+    fn branch_tip(&self, name: &str) -> Result<Option<String>> {
+        if !is_branch_name(name) {
+            return Ok(None);
+        }
+        read_optional(&self.refs_dir().join(name))
+    }
+
+    fn write_branch(&self, name: &str, id: &str) -> Result<()> {
+        fs::create_dir_all(self.refs_dir())?;
+        fs::write(self.refs_dir().join(name), format!("{id}\n"))?;
+        Ok(())
+    }
+
+    /// Starts branch `name` at the `HEAD` commit without switching to it.
+    pub fn create_branch(&self, name: &str) -> Result<Commit> {
+        if !is_branch_name(name) {
+            return Err(Error::Parse(format!(
+                "Invalid branch name: '{name}'. Use letters, digits, '-', '_' or '.'."
+            )));
+        }
+        if self.branch_tip(name)?.is_some() {
+            return Err(Error::Parse(format!("Branch '{name}' already exists.")));
+        }
+        let head = self.head()?.ok_or_else(no_commits)?;
+        self.write_branch(name, &head)?;
+        self.read_commit(&head)
+    }
+
+    /// Every branch with the commit it points at, by name.
+    pub fn branches(&self) -> Result<Vec<(String, Commit)>> {
+        let mut branches = Vec::new();
+        if let Ok(entries) = fs::read_dir(self.refs_dir()) {
+            for entry in entries {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if let Some(tip) = self.branch_tip(&name)? {
+                    branches.push((name, self.read_commit(&tip)?));
+                }
+            }
+        }
+        branches.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(branches)
+    }
+
+    /// Stores `save` and a commit on top of `HEAD`, then moves `HEAD` to it.
+    pub fn commit(&self, save: &SaveFile, message: &str) -> Result<Commit> {
+        let parent = self.head()?;
+        let snapshot = self.write_object(save)?;
+        if let Some(parent_id) = &parent
+            && self.read_commit(parent_id)?.snapshot == snapshot
+        {
+            return Err(Error::Parse(
+                "Nothing to commit: the plan is unchanged since HEAD.".into(),
+            ));
+        }
+        self.write_commit(parent, None, snapshot, message)
+    }
+
+    /// Records the result of merging `merged` into `HEAD` as a commit with both as parents.
+    pub fn commit_merge(&self, save: &SaveFile, message: &str, merged: &str) -> Result<Commit> {
+        let parent = self.head()?;
+        let snapshot = self.write_object(save)?;
+        self.write_commit(parent, Some(merged.to_string()), snapshot, message)
+    }
+
+    fn write_commit(
+        &self,
+        parent: Option<String>,
+        merged: Option<String>,
+        snapshot: String,
+        message: &str,
+    ) -> Result<Commit> {
+        let mut commit = Commit {
+            id: String::new(),
+            parent,
+            merged,
+            snapshot,
+            author: current_author(),
+            timestamp: Local::now(),
+            message: message.to_string(),
+        };
+        let contents = serde_json::to_string_pretty(&commit)?;
+        commit.id = hash_hex(contents.as_bytes());
+        fs::create_dir_all(self.commits_dir())?;
+        fs::write(self.commit_path(&commit.id), contents)?;
+        self.advance_head(&commit.id)?;
+        Ok(commit)
+    }
+
+    /// Commits reachable from `HEAD` through first parents, newest first.
+    pub fn log(&self) -> Result<Vec<Commit>> {
+        let mut commits = Vec::new();
+        let mut next = self.head()?;
+        while let Some(id) = next {
+            let commit = self.read_commit(&id)?;
+            next = commit.parent.clone();
+            commits.push(commit);
+        }
+        Ok(commits)
+    }
+
+    /// Nearest commit both `a` and `b` descend from, following merge parents as well.
+    pub fn merge_base(&self, a: &Commit, b: &Commit) -> Result<Option<Commit>> {
+        let mut ancestors_of_a = HashSet::new();
+        let mut queue = VecDeque::from([a.id.clone()]);
+        while let Some(id) = queue.pop_front() {
+            if ancestors_of_a.insert(id.clone()) {
+                queue.extend(self.read_commit(&id)?.parents().cloned());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([b.id.clone()]);
+        while let Some(id) = queue.pop_front() {
+            if ancestors_of_a.contains(&id) {
+                return self.read_commit(&id).map(Some);
+            }
+            if seen.insert(id.clone()) {
+                queue.extend(self.read_commit(&id)?.parents().cloned());
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolves a branch name, `HEAD`, `HEAD~<n>` or a unique prefix (at least 4 characters) of
+    /// a commit hash.
+    pub fn resolve(&self, rev: &str) -> Result<Commit> {
+        let rev = rev.trim();
+        if let Some(tip) = self.branch_tip(rev)? {
+            return self.read_commit(&tip);
+        }
+        let upper = rev.to_ascii_uppercase();
+        if let Some(rest) = upper.strip_prefix("HEAD") {
+            let steps = if rest.is_empty() {
+                0
+            } else {
+                rest.strip_prefix('~')
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .ok_or_else(|| unknown_revision(rev))?
+            };
+            return self
+                .log()?
+                .into_iter()
+                .nth(steps)
+                .ok_or_else(|| match self.head() {
+                    Ok(None) => no_commits(),
+                    _ => unknown_revision(rev),
+                });
+        }
+
+        let prefix = rev.to_ascii_lowercase();
+        if prefix.len() < MIN_PREFIX_LEN || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(unknown_revision(rev));
+        }
+        let mut matches = Vec::new();
+        if let Ok(entries) = fs::read_dir(self.commits_dir()) {
+            for entry in entries {
+                let name = entry?.file_name().to_string_lossy().to_string();
+                if let Some(id) = name.strip_suffix(".json")
+                    && id.starts_with(&prefix)
+                {
+                    matches.push(id.to_string());
+                }
+            }
+        }
+        match matches.as_slice() {
+            [id] => self.read_commit(id),
+            [] => Err(unknown_revision(rev)),
+            _ => Err(Error::Parse(format!(
+                "Ambiguous revision '{}': it matches {} commits. Use more characters.",
+                rev,
+                matches.len()
+            ))),
+        }
+    }
+
+    /// The plan as it was at `commit`.
+    pub fn snapshot(&self, commit: &Commit) -> Result<SaveFile> {
+        let path = self.objects_dir().join(format!("{}.json", commit.snapshot));
+        Ok(migrations::decode(&fs::read_to_string(path)?)?.save)
+    }
+
+    fn commit_path(&self, id: &str) -> PathBuf {
+        self.commits_dir().join(format!("{id}.json"))
+    }
+
+    fn read_commit(&self, id: &str) -> Result<Commit> {
+        let contents = fs::read_to_string(self.commit_path(id))
+            .map_err(|e| Error::Domain(format!("Could not read commit {id}: {e}")))?;
+        let mut commit: Commit = serde_json::from_str(&contents)?;
+        commit.id = id.to_string();
+        Ok(commit)
+    }
+
+    fn write_object(&self, save: &SaveFile) -> Result<String> {
+        let contents = migrations::encode(save)?;
+        let id = hash_hex(contents.as_bytes());
+        let path = self.objects_dir().join(format!("{id}.json"));
+        if !path.exists() {
+            fs::create_dir_all(self.objects_dir())?;
+            fs::write(path, contents)?;
+        }
+        Ok(id)
+    }
+}
+
+fn is_branch_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.eq_ignore_ascii_case("HEAD")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(contents) if !contents.trim().is_empty() => Ok(Some(contents.trim().to_string())),
+        Ok(_) => Ok(None),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn hash_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn current_author() -> String {
+    ["USER", "USERNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.trim().is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn unknown_revision(rev: &str) -> Error {
+    Error::Parse(format!(
+        "Unknown revision: '{}'. Use a branch, HEAD, HEAD~<n> or a commit hash from 'history'.",
+        rev
+    ))
+}
+
+fn no_commits() -> Error {
+    Error::Parse("No commits yet. Run 'commit \"<message>\"' first.".into())
+}
+
+/// Line-level changes from `old` to `new`: one `- <kind> <tokens>` or `+ <kind> <tokens>` line
+/// per saved entity that only appears on one side. A modified entity shows as both.
+/// Saved uids are left out of the lines; they identify an entity rather than describe it.
+pub fn diff_saves(old: &SaveFile, new: &SaveFile) -> Vec<String> {
+    let mut lines = Vec::new();
+    diff_section("card", &old.cards, &new.cards, &mut lines);
+    diff_section("template", &old.templates, &new.templates, &mut lines);
+    diff_section("event", &old.events, &new.events, &mut lines);
+    diff_section("task", &old.tasks, &new.tasks, &mut lines);
+    lines
+}
+
+fn diff_section(kind: &str, old: &TokenMatrix, new: &TokenMatrix, out: &mut Vec<String>) {
+    let old: Vec<String> = old.iter().map(described).collect();
+    let new: Vec<String> = new.iter().map(described).collect();
+    for line in only_in(&old, &new) {
+        out.push(format!("- {kind} {line}"));
+    }
+    for line in only_in(&new, &old) {
+        out.push(format!("+ {kind} {line}"));
+    }
+}
+
+fn described(tokens: &TokenList) -> String {
+    tokens
+        .iter()
+        .filter(|t| saved_uid(t).is_none())
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Lines of `side` left over once each line of `other` has cancelled one equal line.
+fn only_in<'a>(side: &'a [String], other: &[String]) -> Vec<&'a str> {
+    let mut available: HashMap<&str, usize> = HashMap::new();
+    for line in other {
+        *available.entry(line.as_str()).or_default() += 1;
+    }
+    side.iter()
+        .map(String::as_str)
+        .filter(|line| match available.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
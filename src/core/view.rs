@@ -0,0 +1,263 @@
+use crate::arg::args::Arg;
+use crate::config::Config;
+use crate::core::types::{EntityType, Flag};
+use crate::errors::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Columns each entity table can show, the built-in default columns first.
+pub fn columns(kind: EntityType) -> &'static [&'static str] {
+    match kind {
+        EntityType::Task => &["id", "name", "tag", "hours", "due", "remaining", "estimate"],
+        EntityType::Event => &["id", "name", "tag", "time", "days", "recurring", "hours"],
+        EntityType::Card => &["id", "name", "color"],
+        EntityType::Template => &["id", "name", "tag", "hours", "days"],
+    }
+}
+
+fn default_column_count(kind: EntityType) -> usize {
+    match kind {
+        EntityType::Task | EntityType::Template => 5,
+        EntityType::Event => 6,
+        EntityType::Card => 3,
+    }
+}
+
+fn check_column(kind: EntityType, column: &str) -> Result<String> {
+    let column = column.trim().to_ascii_lowercase();
+    if columns(kind).contains(&column.as_str()) {
+        Ok(column)
+    } else {
+        Err(Error::Parse(format!(
+            "Unknown {} column: '{}'. Valid columns: {}",
+            kind,
+            column,
+            columns(kind).join(", ")
+        )))
+    }
+}
+
+/// One sort column; written with a leading `-` it sorts descending.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.descending { "-" } else { "" };
+        write!(f, "{sign}{}", self.column)
+    }
+}
+
+/// An item of a per-entity list: a sort key or a column name.
+pub trait ViewItem: Sized + Clone + fmt::Display {
+    fn parse(kind: Option<EntityType>, tok: &str) -> Result<Self>;
+}
+
+impl ViewItem for SortKey {
+    fn parse(kind: Option<EntityType>, tok: &str) -> Result<Self> {
+        let tok = tok.trim();
+        let (descending, column) = match tok.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, tok),
+        };
+        let column = match kind {
+            Some(kind) => check_column(kind, column)?,
+            None => column.to_ascii_lowercase(),
+        };
+        Ok(SortKey { column, descending })
+    }
+}
+
+impl ViewItem for String {
+    fn parse(kind: Option<EntityType>, tok: &str) -> Result<Self> {
+        match kind {
+            Some(kind) => check_column(kind, tok),
+            None => Ok(tok.trim().to_ascii_lowercase()),
+        }
+    }
+}
+
+fn parse_list<T: ViewItem>(kind: Option<EntityType>, value: &str) -> Result<Vec<T>> {
+    if value.split(',').any(|s| s.trim().is_empty()) {
+        return Err(Error::Parse(format!(
+            "Invalid column list: '{value}'. Expected comma-separated columns, e.g. due,-hours"
+        )));
+    }
+    value.split(',').map(|tok| T::parse(kind, tok)).collect()
+}
+
+/// `--sort <keys>` or `--columns <names>` given to an entity listing. Columns are checked
+/// against the entity type later, by `TableOptionsFor`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableOption {
+    Sort(Vec<SortKey>),
+    Columns(Vec<String>),
+}
+
+impl TableOption {
+    pub fn try_from_parts(flag: Flag, value: &str) -> Result<Self> {
+        match flag {
+            Flag::Sort => Ok(TableOption::Sort(parse_list(None, value)?)),
+            Flag::Columns => Ok(TableOption::Columns(parse_list(None, value)?)),
+            other => Err(Error::Parse(format!("Flag {other} does not take a value."))),
+        }
+    }
+
+    fn check(&self, kind: EntityType) -> Result<()> {
+        match self {
+            TableOption::Sort(keys) => keys
+                .iter()
+                .try_for_each(|k| check_column(kind, &k.column).map(|_| ())),
+            TableOption::Columns(cols) => cols
+                .iter()
+                .try_for_each(|c| check_column(kind, c).map(|_| ())),
+        }
+    }
+}
+
+impl fmt::Display for TableOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (flag, items) = match self {
+            TableOption::Sort(keys) => (Flag::Sort, join(keys)),
+            TableOption::Columns(cols) => (Flag::Columns, join(cols)),
+        };
+        write!(f, "{flag} {items}")
+    }
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Which columns an entity table shows and the order of its rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableView {
+    pub columns: Vec<String>,
+    pub sort: Vec<SortKey>,
+}
+
+impl TableView {
+    /// Built-in view: the default columns, rows by id.
+    pub fn default_for(kind: EntityType) -> Self {
+        Self {
+            columns: columns(kind)[..default_column_count(kind)]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            sort: Vec::new(),
+        }
+    }
+
+    /// Checks every `--sort`/`--columns` option in `args` against the columns of `kind`.
+    pub fn check_args(kind: EntityType, args: &[Arg]) -> Result<()> {
+        args.iter().try_for_each(|a| match a {
+            Arg::TableOption(opt) => opt.check(kind),
+            _ => Ok(()),
+        })
+    }
+
+    /// The built-in view, overridden by the configured defaults for `kind`, then by the
+    /// options in `args`.
+    pub fn resolve(kind: EntityType, config: &Config, args: &[Arg]) -> Result<Self> {
+        Self::check_args(kind, args)?;
+        let mut view = Self::default_for(kind);
+        if let Some(cols) = config.default_columns().get(kind) {
+            view.columns = cols.to_vec();
+        }
+        if let Some(keys) = config.default_sort().get(kind) {
+            view.sort = keys.to_vec();
+        }
+        for arg in args {
+            match arg {
+                Arg::TableOption(TableOption::Columns(cols)) => view.columns = cols.clone(),
+                Arg::TableOption(TableOption::Sort(keys)) => view.sort = keys.clone(),
+                _ => {}
+            }
+        }
+        Ok(view)
+    }
+
+    pub fn headers(&self) -> Vec<String> {
+        self.columns.iter().map(|c| c.to_uppercase()).collect()
+    }
+}
+
+/// Lists per entity type as stored in config, written `task=due,-hours; event=time`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String", bound = "")]
+pub struct PerEntity<T: ViewItem>(Vec<(EntityType, Vec<T>)>);
+
+pub type SortDefaults = PerEntity<SortKey>;
+pub type ColumnDefaults = PerEntity<String>;
+
+impl<T: ViewItem> Default for PerEntity<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: ViewItem> PerEntity<T> {
+    pub fn try_from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() || s == "-" {
+            return Ok(Self::default());
+        }
+        let mut entries: Vec<(EntityType, Vec<T>)> = Vec::new();
+        for part in s.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (kind, list) = part.split_once('=').ok_or_else(|| {
+                Error::Parse(format!(
+                    "Invalid entry: '{part}'. Expected <entity>=<columns>, e.g. task=due,-hours"
+                ))
+            })?;
+            let kind = EntityType::try_from(kind)?;
+            let items = parse_list(Some(kind), list)?;
+            entries.retain(|(k, _)| *k != kind);
+            entries.push((kind, items));
+        }
+        Ok(Self(entries))
+    }
+
+    pub fn get(&self, kind: EntityType) -> Option<&[T]> {
+        self.0
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, items)| items.as_slice())
+    }
+}
+
+impl<T: ViewItem> fmt::Display for PerEntity<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "-");
+        }
+        let parts: Vec<String> = self
+            .0
+            .iter()
+            .map(|(kind, items)| format!("{kind}={}", join(items)))
+            .collect();
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+impl<T: ViewItem> TryFrom<String> for PerEntity<T> {
+    type Error = Error;
+    fn try_from(s: String) -> Result<Self> {
+        Self::try_from_str(&s)
+    }
+}
+
+impl<T: ViewItem> From<PerEntity<T>> for String {
+    fn from(value: PerEntity<T>) -> Self {
+        match value.to_string().as_str() {
+            "-" => String::new(),
+            s => s.to_string(),
+        }
+    }
+}
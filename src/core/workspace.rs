@@ -0,0 +1,115 @@
+use crate::core::context::AppContext;
+use crate::core::journal::Journal;
+use crate::core::persist::{load_state, save_state_as};
+use crate::errors::Result;
+use std::path::{Path, PathBuf};
+
+/// Changes made since the plan was last saved, counted per command.
+#[derive(Debug, Default)]
+pub struct WorkspaceStatus {
+    unsaved: usize,
+    /// Set when the workspace could not be loaded, so autosave does not overwrite it with a
+    /// plan that is missing its contents.
+    paused: bool,
+}
+
+impl WorkspaceStatus {
+    pub fn unsaved(&self) -> usize {
+        self.unsaved
+    }
+}
+
+/// `schedules/<WORKSPACE>.json`, or `None` when no workspace is set.
+pub fn workspace_path(ctx: &AppContext) -> Option<PathBuf> {
+    let mut path = ctx.schedules_dir.join(ctx.config.workspace()?);
+    if path.extension().is_none() {
+        path.set_extension("json");
+    }
+    Some(path)
+}
+
+/// Loads the workspace into `ctx` if one is set and has been saved before. A workspace that
+/// fails to load pauses autosave for the session.
+pub fn load(ctx: &mut AppContext) -> Result<Option<PathBuf>> {
+    let Some(path) = workspace_path(ctx).filter(|path| path.exists()) else {
+        return Ok(None);
+    };
+    if let Err(err) = load_state(ctx, &path) {
+        ctx.workspace.paused = true;
+        return Err(err);
+    }
+    ctx.workspace = WorkspaceStatus::default();
+    Ok(Some(path))
+}
+
+/// Counts one change and autosaves when `AUTOSAVE_INTERVAL` changes have built up. Returns the
+/// path written to, if any.
+pub fn record_change(ctx: &mut AppContext) -> Result<Option<PathBuf>> {
+    ctx.workspace.unsaved += 1;
+    if ctx.workspace.unsaved < ctx.config.autosave_interval() {
+        return Ok(None);
+    }
+    flush(ctx)
+}
+
+/// Autosaves whatever has not been saved yet, e.g. on exit. Does nothing when autosave is off
+/// or paused, or there is no workspace.
+pub fn flush(ctx: &mut AppContext) -> Result<Option<PathBuf>> {
+    if ctx.workspace.unsaved == 0 || ctx.workspace.paused || ctx.config.autosave_interval() == 0 {
+        return Ok(None);
+    }
+    let Some(path) = workspace_path(ctx) else {
+        return Ok(None);
+    };
+    save_state_as(
+        ctx.config.save_format(),
+        None,
+        &ctx.tasks,
+        &ctx.events,
+        &ctx.cards,
+        &ctx.templates,
+        &path,
+    )?;
+    mark_saved(ctx, &path)?;
+    Ok(Some(path))
+}
+
+/// Notes that the plan was written to `path`. Saving to the workspace also restarts the journal,
+/// since the workspace is loaded at startup and already holds those changes. Without a
+/// workspace, any save counts.
+pub fn mark_saved(ctx: &mut AppContext, path: &Path) -> Result<()> {
+    match workspace_path(ctx) {
+        Some(workspace) if workspace == path => {
+            ctx.workspace = WorkspaceStatus::default();
+            Journal::open(&ctx.schedules_dir).restart(ctx)
+        }
+        Some(_) => Ok(()),
+        None => {
+            ctx.workspace.unsaved = 0;
+            Ok(())
+        }
+    }
+}
+
+/// One line on where the plan is saved and how much is not, shown above the prompt. `None`
+/// when there is no workspace and nothing to save.
+pub fn status_line(ctx: &AppContext) -> Option<String> {
+    let unsaved = match ctx.workspace.unsaved {
+        0 => "all changes saved".to_string(),
+        n => format!("{n} unsaved change(s)"),
+    };
+    let Some(path) = workspace_path(ctx) else {
+        return (ctx.workspace.unsaved > 0).then_some(unsaved);
+    };
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let autosave = if ctx.workspace.paused {
+        "autosave paused".to_string()
+    } else {
+        match ctx.config.autosave_interval() {
+            0 => "autosave off".to_string(),
+            1 => "autosave on".to_string(),
+            n => format!("autosave every {n} changes"),
+        }
+    };
+    Some(format!("Workspace {name}: {unsaved}, {autosave}"))
+}
@@ -6,7 +6,7 @@ pub mod errors;
 pub mod extensions;
 pub mod logging;
 pub mod prompter;
-mod scheduler;
+pub mod scheduler;
 pub mod ui;
 
 // Re-export main entry helpers if needed in future integration tests.
@@ -130,6 +130,16 @@ impl Logger {
         }
     }
 
+    /// A logger that drops every message, for work the user should not see, such as loading a
+    /// file into a scratch context.
+    pub fn silent() -> Self {
+        Self {
+            console_sinks: Arc::new(Vec::new()),
+            file_state: Arc::new(Mutex::new(FileState::default())),
+            file_enabled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
     fn ensure_file_sink(&self) -> Option<Arc<dyn LogSink>> {
         let mut state = self.file_state.lock().ok()?;
         if state.attempted {
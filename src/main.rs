@@ -1,5 +1,7 @@
 use crate::core::cli::CliPaths;
 use crate::core::context::AppContext;
+use crate::core::journal::Journal;
+use crate::core::workspace;
 use crate::logging::LogTarget;
 use crate::prompter::flows::main_flow::MainFlow;
 use crate::prompter::prompter::Prompter;
@@ -11,7 +13,7 @@ pub mod core;
 pub mod errors;
 pub mod extensions;
 pub mod logging;
-mod scheduler;
+pub mod scheduler;
 pub mod ui;
 
 pub mod prompter;
@@ -35,8 +37,25 @@ fn main() {
     let prompter = Prompter::new();
     let flow = MainFlow::new(&mut ctx);
 
-    if let Err(err) = prompter.run(flow, false) {
-        ctx.logger
-            .error(format!("{err}"), LogTarget::ConsoleAndFile);
+    match prompter.run(flow, false) {
+        // A clean exit leaves nothing to recover.
+        Ok(()) => {
+            if let Err(err) = workspace::flush(&mut ctx) {
+                ctx.logger.error(
+                    format!("Could not save the workspace on exit. {err}"),
+                    LogTarget::ConsoleAndFile,
+                );
+                return;
+            }
+            if let Err(err) = Journal::open(&ctx.schedules_dir).clear() {
+                ctx.logger.error(
+                    format!("Could not clear the journal. {err}"),
+                    LogTarget::ConsoleAndFile,
+                );
+            }
+        }
+        Err(err) => ctx
+            .logger
+            .error(format!("{err}"), LogTarget::ConsoleAndFile),
     }
 }
@@ -1,6 +1,10 @@
 use crate::config::{ConfigKey, ConfigRows};
 use crate::core::context::AppContext;
-use crate::core::types::{TaskOverflowPolicy, TaskSchedulingOrder};
+use crate::core::types::{
+    CardDeletePolicy, EntityType, SaveFormat, TaskOverflowPolicy, TaskPackingMode,
+    TaskSchedulingOrder,
+};
+use crate::core::view;
 use crate::errors::Result;
 use crate::logging::LogTarget;
 use crate::prompter::models::{ConfigState, Flow, FlowCtrl};
@@ -77,6 +81,12 @@ impl<'a> ConfigEditFlow<'a> {
                     .collect::<Vec<_>>()
                     .join("\n"),
             ),
+            ConfigKey::TaskPackingMode => Some(
+                TaskPackingMode::iter()
+                    .map(|p| format!("{}: {}", p, p.help()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
             ConfigKey::FileLoggingEnabled => Some(
                 vec![
                     "True: enable writing log messages to the session file",
@@ -84,8 +94,50 @@ impl<'a> ConfigEditFlow<'a> {
                 ]
                 .join("\n"),
             ),
+            ConfigKey::StableRescheduling => Some(
+                [
+                    "True: keep previously scheduled slices where still feasible",
+                    "False: rebuild the schedule from scratch on every run",
+                ]
+                .join("\n"),
+            ),
+            ConfigKey::CardDeletePolicy => Some(
+                CardDeletePolicy::iter()
+                    .map(|p| format!("{}: {}", p, p.help()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            ConfigKey::DefaultSort => Some(format!(
+                "<entity>=<column>[,-<column>...]; ... (- sorts descending; '-' clears)\n{}",
+                view_columns_help()
+            )),
+            ConfigKey::DefaultColumns => Some(format!(
+                "<entity>=<column>[,<column>...]; ... ('-' clears)\n{}",
+                view_columns_help()
+            )),
+            ConfigKey::Workspace => Some(
+                [
+                    "<name>: load schedules/<name>.json at startup and autosave to it",
+                    "-: no workspace; save by hand with save \"<name>\"",
+                ]
+                .join("\n"),
+            ),
+            ConfigKey::AutosaveInterval => Some(
+                [
+                    "<n>: save to the workspace after every n changes (1 saves after each one)",
+                    "0: only save when running save",
+                ]
+                .join("\n"),
+            ),
+            ConfigKey::SaveFormat => Some(
+                SaveFormat::iter()
+                    .map(|p| format!("{}: {}", p, p.help()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
             ConfigKey::Range => None,
             ConfigKey::ScheduleStartDate => None,
+            ConfigKey::HistoryDepth => None,
         }
     }
 
@@ -279,3 +331,10 @@ impl<'a> ConfigEditFlow<'a> {
         }
     }
 }
+
+fn view_columns_help() -> String {
+    EntityType::iter()
+        .map(|kind| format!("{}: {}", kind, view::columns(kind).join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,41 @@
+use crate::errors::Result;
+use crate::prompter::models::{Flow, FlowCtrl};
+use std::io::Write;
+
+/// Asks a yes/no question; anything but an explicit yes, including end of input, declines.
+pub struct ConfirmFlow<'a> {
+    question: String,
+    confirmed: &'a mut bool,
+}
+
+impl<'a> ConfirmFlow<'a> {
+    pub fn new(question: impl Into<String>, confirmed: &'a mut bool) -> Self {
+        *confirmed = false;
+        Self {
+            question: question.into(),
+            confirmed,
+        }
+    }
+}
+
+impl<'a> Flow for ConfirmFlow<'a> {
+    fn render(&mut self) -> Result<()> {
+        print!("{} [y/N] > ", self.question);
+        let _ = std::io::stdout().flush();
+        Ok(())
+    }
+
+    fn handle_input(&mut self, input: &str) -> Result<FlowCtrl> {
+        match input.to_ascii_lowercase().as_str() {
+            "y" | "yes" => {
+                *self.confirmed = true;
+                Ok(FlowCtrl::Finish)
+            }
+            "" | "n" | "no" => Ok(FlowCtrl::Abort),
+            _ => {
+                println!("Please answer 'y' (yes) or 'n' (no).");
+                Ok(FlowCtrl::Continue)
+            }
+        }
+    }
+}
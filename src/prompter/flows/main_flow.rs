@@ -6,10 +6,15 @@ use crate::command::command_parser::CommandParser;
 use crate::command::commands::CommandDyn;
 use crate::command::manual::ManualCatalog;
 use crate::core::context::AppContext;
+use crate::core::history::{self, Snapshot};
+use crate::core::journal::{Journal, Journaling};
+use crate::core::workspace;
 use crate::errors::Result;
 use crate::logging::{LogTarget, Logger};
+use crate::prompter::flows::confirm::ConfirmFlow;
 use crate::prompter::models::{Flow, FlowCtrl};
-use crate::ui::ansi::STYLE_RESET;
+use crate::prompter::prompter::Prompter;
+use crate::ui::ansi::{FG_LIGHT_GRAY, STYLE_RESET};
 use crate::ui::chrome::UiChrome;
 
 pub struct MainFlow<'a> {
@@ -34,6 +39,7 @@ impl<'a> MainFlow<'a> {
 impl<'a> Flow for MainFlow<'a> {
     fn render(&mut self) -> Result<()> {
         self.print_startup();
+        self.print_status();
         self.print_prompt();
         Ok(())
     }
@@ -59,7 +65,7 @@ impl<'a> Flow for MainFlow<'a> {
 
         self.log_command_run(&raw_command, line);
 
-        self.execute_command(&raw_command, cmd);
+        self.execute_command(&raw_command, line, cmd);
 
         Ok(FlowCtrl::Continue)
     }
@@ -82,6 +88,14 @@ impl<'a> MainFlow<'a> {
         println!("Logs path: {}", self.ctx.logs_dir.display());
         println!();
         self.ctx.startup_displayed = true;
+        self.load_workspace();
+        self.offer_recovery();
+    }
+
+    fn print_status(&self) {
+        if let Some(status) = workspace::status_line(self.ctx) {
+            println!("{FG_LIGHT_GRAY}{status}{STYLE_RESET}");
+        }
     }
 
     fn print_prompt(&self) {
@@ -145,10 +159,125 @@ impl<'a> MainFlow<'a> {
         }
     }
 
-    fn execute_command(&mut self, raw_command: &str, cmd: CommandDyn<'_>) {
-        if let Err(err) = cmd.execute(self.ctx) {
-            self.handle_command_error(raw_command, err.to_string());
+    fn execute_command(&mut self, raw_command: &str, line: &str, cmd: CommandDyn<'_>) {
+        let before = cmd.changes_state().then(|| Snapshot::capture(self.ctx));
+        let journal_line = cmd.journal_line(self.ctx, line);
+        let result = cmd.execute(self.ctx);
+        // Recorded even on error: whatever did change should still be undoable.
+        let changed = before.map(|before| history::record(self.ctx, line, before));
+        match result {
+            // Listings and no-op changes leave nothing to replay.
+            Ok(()) if changed != Some(false) => self.record_change(&journal_line, cmd.journaling()),
+            Ok(()) => {}
+            Err(err) => self.handle_command_error(raw_command, err.to_string()),
+        }
+    }
+
+    fn record_change(&mut self, line: &str, journaling: Journaling) {
+        if journaling == Journaling::Skip {
+            return;
+        }
+        let journal = Journal::open(&self.ctx.schedules_dir);
+        if let Err(err) = journal.record(self.ctx, line, journaling) {
+            self.logger.error(
+                format!(
+                    "Could not journal '{line}'; it will not be recovered after a crash. {err}"
+                ),
+                LogTarget::ConsoleAndFile,
+            );
+        }
+        match workspace::record_change(self.ctx) {
+            Ok(Some(path)) => self.logger.info(
+                format!("Autosaved to {}", path.display()),
+                LogTarget::FileOnly,
+            ),
+            Ok(None) => {}
+            Err(err) => self.logger.error(
+                format!("Autosave failed; run save to keep your changes. {err}"),
+                LogTarget::ConsoleAndFile,
+            ),
+        }
+    }
+
+    fn load_workspace(&mut self) {
+        match workspace::load(self.ctx) {
+            Ok(Some(path)) => self.logger.info(
+                format!("Loaded workspace from {}", path.display()),
+                LogTarget::ConsoleAndFile,
+            ),
+            Ok(None) => {}
+            Err(err) => self.logger.error(
+                format!(
+                    "Could not load the workspace. {err} Autosave is paused so the file is not overwritten."
+                ),
+                LogTarget::ConsoleAndFile,
+            ),
+        }
+    }
+
+    /// A journal left from the last session means it ended without exiting; offer to replay it.
+    fn offer_recovery(&mut self) {
+        let journal = Journal::open(&self.ctx.schedules_dir);
+        let entries = match journal.entries() {
+            Ok(entries) if !entries.is_empty() => entries,
+            Ok(_) => return,
+            Err(err) => {
+                self.logger.error(
+                    format!("Could not read the journal. {err}"),
+                    LogTarget::ConsoleAndFile,
+                );
+                return;
+            }
+        };
+
+        println!(
+            "The last session did not exit cleanly. {} change(s) were journaled:",
+            entries.len()
+        );
+        for line in &entries {
+            println!("  {line}");
+        }
+        let mut confirmed = false;
+        let question = ConfirmFlow::new("Replay them?", &mut confirmed);
+        if let Err(err) = Prompter::new().run(question, false) {
+            self.logger.error(format!("{err}"), LogTarget::ConsoleOnly);
         }
+        if !confirmed {
+            if let Err(err) = journal.clear() {
+                self.logger.error(
+                    format!("Could not discard the journal. {err}"),
+                    LogTarget::ConsoleAndFile,
+                );
+            }
+            println!("Discarded the journal.");
+            println!();
+            return;
+        }
+
+        let (applied, result) = journal.replay(self.ctx, &entries);
+        match result {
+            Ok(()) => self.logger.info(
+                format!("Recovered {applied} journaled change(s)."),
+                LogTarget::ConsoleAndFile,
+            ),
+            Err(err) => {
+                self.logger.error(
+                    format!(
+                        "{err} Recovered {applied} of {} journaled change(s).",
+                        entries.len()
+                    ),
+                    LogTarget::ConsoleAndFile,
+                );
+                // Keep the journal in step with what was actually recovered.
+                if let Err(err) = journal.truncate(applied) {
+                    self.logger.error(
+                        format!("Could not update the journal. {err}"),
+                        LogTarget::ConsoleAndFile,
+                    );
+                }
+            }
+        }
+        println!();
     }
 
     fn handle_command_error(&self, raw_command: &str, err_text: String) {
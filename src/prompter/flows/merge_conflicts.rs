@@ -0,0 +1,62 @@
+use crate::core::aliases::TokenList;
+use crate::core::merge::{Conflict, Side};
+use crate::errors::Result;
+use crate::prompter::models::{Flow, FlowCtrl};
+use std::io::Write;
+
+/// Asks, one conflict at a time, whether to keep our or their version of an entity.
+pub struct MergeConflictFlow<'a> {
+    conflicts: &'a [Conflict],
+    choices: &'a mut Vec<Side>,
+}
+
+impl<'a> MergeConflictFlow<'a> {
+    pub fn new(conflicts: &'a [Conflict], choices: &'a mut Vec<Side>) -> Self {
+        Self { conflicts, choices }
+    }
+}
+
+fn describe(tokens: &Option<TokenList>) -> String {
+    tokens
+        .as_ref()
+        .map(|t| t.join(" "))
+        .unwrap_or_else(|| "(deleted)".to_string())
+}
+
+impl<'a> Flow for MergeConflictFlow<'a> {
+    fn render(&mut self) -> Result<()> {
+        let idx = self.choices.len();
+        let Some(conflict) = self.conflicts.get(idx) else {
+            return Ok(());
+        };
+        println!(
+            "Conflict {}/{}: {} {}",
+            idx + 1,
+            self.conflicts.len(),
+            conflict.kind,
+            conflict.name
+        );
+        println!("  base:   {}", describe(&conflict.base));
+        println!("  ours:   {}", describe(&conflict.ours));
+        println!("  theirs: {}", describe(&conflict.theirs));
+        print!("Keep (o)urs or (t)heirs? > ");
+        let _ = std::io::stdout().flush();
+        Ok(())
+    }
+
+    fn handle_input(&mut self, input: &str) -> Result<FlowCtrl> {
+        match input.to_ascii_lowercase().as_str() {
+            "o" | "ours" => self.choices.push(Side::Ours),
+            "t" | "theirs" => self.choices.push(Side::Theirs),
+            _ => {
+                println!("Please answer 'o' (ours) or 't' (theirs).");
+                return Ok(FlowCtrl::Continue);
+            }
+        }
+        if self.choices.len() == self.conflicts.len() {
+            Ok(FlowCtrl::Finish)
+        } else {
+            Ok(FlowCtrl::Continue)
+        }
+    }
+}
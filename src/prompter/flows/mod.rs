@@ -1,4 +1,6 @@
 pub mod config_edit;
+pub mod confirm;
 pub mod main_flow;
+pub mod merge_conflicts;
 #[cfg(test)]
 mod tests;
@@ -1,13 +1,21 @@
-use super::{config_edit::ConfigEditFlow, main_flow::MainFlow};
+use super::{config_edit::ConfigEditFlow, main_flow::MainFlow, merge_conflicts::MergeConflictFlow};
+use crate::arg::arg_parse_strategy::CommandArgParser;
+use crate::command::command_parser::CommandParser;
 use crate::config::Config;
+use crate::core::journal::Journal;
+use crate::core::merge::{Conflict, Side};
 use crate::core::{
     context::AppContext,
-    repository::Repository,
+    history::{self, Snapshot},
+    repository::{Repository, Sort},
     types::{TaskOverflowPolicy, TimeRange},
+    workspace,
 };
 use crate::logging::Logger;
+use crate::prompter::prompter::Prompter;
 use crate::{core::models::Task, prompter::models::Flow};
 use std::fs;
+use std::io::Cursor;
 use std::path::PathBuf;
 
 fn temp_config_path() -> PathBuf {
@@ -34,7 +42,8 @@ fn make_ctx() -> AppContext {
     write_sample_config(&path);
     let config = Config::load_from(&path).unwrap();
     let logger = Logger::new();
-    let schedules_dir = std::env::temp_dir().join("planit-input-schedules");
+    // Unique per test so a journal left by one test is never offered to another.
+    let schedules_dir = path.with_extension("schedules");
     let logs_dir = std::env::temp_dir().join("planit-input-logs");
     logger.set_log_dir(&logs_dir);
     logger.set_file_logging_enabled(config.file_logging_enabled());
@@ -43,7 +52,10 @@ fn make_ctx() -> AppContext {
         tasks: Repository::<Task>::new(),
         events: Repository::new(),
         cards: Repository::new(),
+        templates: Repository::new(),
         logger,
+        history: Default::default(),
+        workspace: Default::default(),
         startup_displayed: false,
         config_path: path,
         schedules_dir,
@@ -81,6 +93,225 @@ fn main_flow_parses_and_executes_task_add() {
     assert_eq!(ctx.tasks.len(), 1);
 }
 
+#[test]
+fn main_flow_journals_commands_that_changed_the_plan() {
+    let mut ctx = make_ctx();
+    let mut flow = MainFlow::new(&mut ctx);
+    flow.handle_input(r#"task "Test" 2 @ 2099-01-01"#).unwrap();
+    flow.handle_input("task").unwrap();
+    flow.handle_input("mod task 9 hours=3").unwrap();
+    flow.handle_input("done task 1").unwrap();
+
+    let journal = Journal::open(&ctx.schedules_dir);
+    assert_eq!(
+        journal.entries().unwrap(),
+        vec![r#"task "Test" 2 @ 2099-01-01"#, "done task 1"]
+    );
+    journal.clear().unwrap();
+}
+
+#[test]
+fn main_flow_journals_selections_and_dates_as_they_resolved() {
+    let mut ctx = make_ctx();
+    let year = chrono::Local::now().format("%Y");
+    let mut flow = MainFlow::new(&mut ctx);
+    flow.handle_input(r#"task "Test" 2 @ 12-30"#).unwrap();
+    flow.handle_input(r#"task "Other" 2 @ 2099-01-05"#).unwrap();
+    flow.handle_input("mod task where due<2099-01-01 set due=12-31 --yes")
+        .unwrap();
+
+    let journal = Journal::open(&ctx.schedules_dir);
+    assert_eq!(
+        journal.entries().unwrap(),
+        vec![
+            format!(r#"task "Test" 2 @ {year}-12-30"#),
+            r#"task "Other" 2 @ 2099-01-05"#.to_string(),
+            format!("mod task 1 due={year}-12-31 --yes"),
+        ]
+    );
+    journal.clear().unwrap();
+
+    // A selection run without --yes asks first, which a replay could not answer.
+    let line = "del task where name~Other";
+    let raw: Vec<String> = line.split_whitespace().skip(1).map(String::from).collect();
+    let args = CommandArgParser::new().parse("del", &raw).unwrap();
+    let cmd = CommandParser::new().parse("del", &args).unwrap();
+    assert_eq!(cmd.journal_line(&ctx, line), "del task 2 --yes");
+}
+
+/// Each entity by id, with the ids it refers to, to compare a recovered plan with the session.
+fn plan_ids(ctx: &AppContext) -> Vec<String> {
+    let mut rows = Vec::new();
+    for card in ctx.cards.values(Sort::IdAsc) {
+        rows.push(format!("card {} {}", card.id, card.name));
+    }
+    for template in ctx.templates.values(Sort::IdAsc) {
+        rows.push(format!(
+            "template {} {} {:?}",
+            template.id, template.name, template.card_id
+        ));
+    }
+    for event in ctx.events.values(Sort::IdAsc) {
+        rows.push(format!(
+            "event {} {} {:?}",
+            event.id, event.name, event.card_id
+        ));
+    }
+    for task in ctx.tasks.values(Sort::IdAsc) {
+        let template = task.origin.as_ref().map(|origin| origin.template_id);
+        rows.push(format!(
+            "task {} {} {:?} {:?}",
+            task.id, task.name, task.card_id, template
+        ));
+    }
+    rows
+}
+
+#[test]
+fn commands_journaled_after_an_autosave_replay_onto_the_same_entities() {
+    let workspace_settings = [("WORKSPACE", "main"), ("AUTOSAVE_INTERVAL", "100")];
+    let mut ctx = make_ctx();
+    ctx.config.set_many(workspace_settings).unwrap();
+    let mut flow = MainFlow::new(&mut ctx);
+    for name in ["A", "B", "C"] {
+        for line in [
+            format!(r#"card "{name}" blue"#),
+            format!(r#"template "{name}" 1 +C1 @ fri"#),
+            format!(r#"event true "{name}" +C1 @ mon 9:00AM-10:00AM"#),
+            format!(r#"task "{name}" 1 +C1 @ 2099-01-01"#),
+        ] {
+            flow.handle_input(&line).unwrap();
+        }
+    }
+    for line in [
+        "mod template 3 card=+C3",
+        "mod event 3 card=+C3",
+        "mod task 3 card=+C3",
+        "del task 2",
+        "del event 2",
+        "del template 2",
+        "del card 2",
+        "save",
+    ] {
+        flow.handle_input(line).unwrap();
+    }
+    // Saving keeps the gaps, so the ids typed next still name what the listings showed.
+    for line in [
+        r#"card "D" red"#,
+        r#"task "D" 1 +C4 +T3 @ 2099-01-02"#,
+        r#"mod card 3 name="C2""#,
+        r#"mod template 3 name="C2""#,
+        r#"mod event 3 name="C2""#,
+        r#"mod task 3 name="C2""#,
+        "del task 1",
+    ] {
+        flow.handle_input(line).unwrap();
+    }
+    let session = plan_ids(&ctx);
+    assert!(session.contains(&"task 3 C2 Some(3) None".to_string()));
+    assert!(session.contains(&"task 4 D Some(4) Some(3)".to_string()));
+
+    // The session ends without saving again, as if it crashed.
+    let mut recovered = make_ctx();
+    recovered.config.set_many(workspace_settings).unwrap();
+    recovered.schedules_dir = ctx.schedules_dir.clone();
+    workspace::load(&mut recovered).unwrap();
+    let journal = Journal::open(&recovered.schedules_dir);
+    let entries = journal.entries().unwrap();
+    assert_eq!(entries.len(), 7);
+    assert!(journal.replay(&mut recovered, &entries).1.is_ok());
+    assert_eq!(plan_ids(&recovered), session);
+
+    // New entities continue from the same ids too.
+    for plan in [&mut ctx, &mut recovered] {
+        let mut flow = MainFlow::new(plan);
+        flow.handle_input(r#"task "E" 1 @ 2099-01-03"#).unwrap();
+        flow.handle_input("mod task 5 hours=2").unwrap();
+    }
+    assert_eq!(plan_ids(&recovered), plan_ids(&ctx));
+    assert_eq!(recovered.tasks.get(5).unwrap().hours, 2.0);
+    journal.clear().unwrap();
+}
+
+#[test]
+fn undo_and_redo_replay_as_the_plan_they_left() {
+    let task_names = |ctx: &AppContext| -> Vec<String> {
+        let tasks = ctx.tasks.values(Sort::IdAsc);
+        tasks.iter().map(|t| t.name.clone()).collect()
+    };
+
+    // Undoing a config edit, which the journal does not hold, leaves both tasks in place.
+    let mut ctx = make_ctx();
+    let mut flow = MainFlow::new(&mut ctx);
+    flow.handle_input(r#"task "A" 1 @ 2099-01-01"#).unwrap();
+    flow.handle_input(r#"task "B" 1 @ 2099-01-01"#).unwrap();
+    let before = Snapshot::capture(&ctx);
+    ctx.config.set_many([("HISTORY_DEPTH", "20")]).unwrap();
+    history::record(&mut ctx, "config", before);
+    MainFlow::new(&mut ctx).handle_input("undo").unwrap();
+
+    let journal = Journal::open(&ctx.schedules_dir);
+    let entries = journal.entries().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(entries[2].starts_with("read \""));
+    let mut recovered = make_ctx();
+    assert!(journal.replay(&mut recovered, &entries).1.is_ok());
+    assert_eq!(task_names(&recovered), ["A", "B"]);
+    journal.clear().unwrap();
+
+    // An undo after an autosave reverts a change the journal no longer holds.
+    let workspace_settings = [("WORKSPACE", "main"), ("AUTOSAVE_INTERVAL", "3")];
+    let mut ctx = make_ctx();
+    ctx.config.set_many(workspace_settings).unwrap();
+    let mut flow = MainFlow::new(&mut ctx);
+    flow.handle_input(r#"task "A" 1 @ 2099-01-01"#).unwrap();
+    flow.handle_input(r#"task "B" 1 @ 2099-01-01"#).unwrap();
+    flow.handle_input("undo").unwrap(); // autosaved here
+    flow.handle_input("undo").unwrap();
+    flow.handle_input("redo").unwrap();
+    assert_eq!(task_names(&ctx), ["A"]);
+
+    let mut recovered = make_ctx();
+    recovered.config.set_many(workspace_settings).unwrap();
+    recovered.schedules_dir = ctx.schedules_dir.clone();
+    workspace::load(&mut recovered).unwrap();
+    let journal = Journal::open(&recovered.schedules_dir);
+    let entries = journal.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(journal.replay(&mut recovered, &entries).1.is_ok());
+    assert_eq!(task_names(&recovered), ["A"]);
+    journal.clear().unwrap();
+}
+
+#[test]
+fn schedule_is_an_undo_step_of_its_own() {
+    let mut ctx = make_ctx();
+    let mut flow = MainFlow::new(&mut ctx);
+    flow.handle_input(r#"task "A" 2 @ 2099-01-01"#).unwrap();
+    flow.handle_input(r#"template "Status" 1 @ fri"#).unwrap();
+    flow.handle_input("schedule").unwrap();
+    flow.handle_input(r#"task "B" 1 @ 2099-01-01"#).unwrap();
+    let scheduled = ctx.tasks.len();
+    assert!(scheduled > 2);
+    assert!(!ctx.tasks.get(1).unwrap().subtasks.is_empty());
+
+    // Undoing a later change keeps what the schedule generated.
+    history::undo(&mut ctx).unwrap();
+    assert_eq!(ctx.tasks.len(), scheduled - 1);
+    assert!(!ctx.tasks.get(1).unwrap().subtasks.is_empty());
+
+    let step = history::undo(&mut ctx).unwrap();
+    assert_eq!(step.label, "schedule");
+    assert_eq!(ctx.tasks.len(), 1);
+    assert!(ctx.tasks.get(1).unwrap().subtasks.is_empty());
+
+    let journal = Journal::open(&ctx.schedules_dir);
+    let entries = journal.entries().unwrap();
+    assert!(entries[2].starts_with("read \""));
+    assert_eq!(ctx.workspace.unsaved(), 4);
+    journal.clear().unwrap();
+}
+
 #[test]
 fn config_edit_flow_walks_states_and_updates_value() {
     let mut ctx = make_ctx();
@@ -169,3 +400,23 @@ fn config_edit_flow_updates_file_logging_setting_in_logger() {
     assert!(!ctx.config.file_logging_enabled());
     assert!(!ctx.logger.file_logging_enabled());
 }
+
+#[test]
+fn merge_conflict_flow_records_one_choice_per_conflict() {
+    let conflict = |name: &str| Conflict {
+        kind: "task",
+        name: format!("\"{name}\""),
+        base: Some(vec![format!("\"{name}\""), "2".into()]),
+        ours: Some(vec![format!("\"{name}\""), "3".into()]),
+        theirs: None,
+    };
+    let conflicts = vec![conflict("a"), conflict("b")];
+    let mut choices = Vec::new();
+
+    let flow = MergeConflictFlow::new(&conflicts, &mut choices);
+    Prompter::new()
+        .run_with_reader(flow, false, Cursor::new("maybe\nt\nours\n"))
+        .unwrap();
+
+    assert_eq!(choices, vec![Side::Theirs, Side::Ours]);
+}
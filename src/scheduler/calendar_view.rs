@@ -1,6 +1,4 @@
-use crate::core::context::AppContext;
 use crate::core::models::FreeTimeBlock;
-use crate::core::repository::Sort;
 use crate::core::types::TimeRange;
 use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
 
@@ -36,42 +34,9 @@ impl CalendarView {
             .collect()
     }
 
-    /// Build free blocks for `date` from day window minus events and existing subtasks.
-    pub fn free_blocks_for_date(
-        ctx: &AppContext,
-        date: NaiveDate,
-        day_range: &TimeRange,
-    ) -> Vec<FreeTimeBlock> {
-        let mut free = vec![FreeTimeBlock::new(
-            NaiveDateTime::new(date, day_range.start),
-            NaiveDateTime::new(date, day_range.end),
-        )];
-
-        // subtract events
-        let events = ctx
-            .events
-            .query()
-            .r#where(|event| event.is_active_on_date(date))
-            .collect();
-        for event in events {
-            free = Self::subtract_busy_from_free(date, &free, &event.time_range);
-        }
-
-        // subtract already scheduled subtasks
-        for t in ctx.tasks.values(Sort::Unordered) {
-            for st in &t.subtasks {
-                if st.date == date {
-                    free = Self::subtract_busy_from_free(date, &free, &st.time_range);
-                }
-            }
-        }
-
-        Self::coalesce_free_blocks(free)
-    }
-
     // -------- internals (unchanged helpers) --------
 
-    fn subtract_busy_from_free(
+    pub(crate) fn subtract_busy_from_free(
         date: NaiveDate,
         free: &[FreeTimeBlock],
         busy: &TimeRange,
@@ -94,7 +59,6 @@ impl CalendarView {
         out
     }
 
-    #[cfg_attr(test, allow(dead_code))]
     pub(crate) fn coalesce_free_blocks(mut v: Vec<FreeTimeBlock>) -> Vec<FreeTimeBlock> {
         if v.is_empty() {
             return v;
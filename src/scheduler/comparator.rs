@@ -58,10 +58,21 @@ impl TaskOrderComparator for DueDateOnlyComparator {
     }
 }
 
+// Atomic tasks first so they claim contiguous blocks before others fragment the day
+pub struct AtomicFirstComparator {
+    inner: Box<dyn TaskOrderComparator>,
+}
+impl TaskOrderComparator for AtomicFirstComparator {
+    fn cmp(&self, a: &Task, b: &Task) -> Ordering {
+        b.atomic.cmp(&a.atomic).then_with(|| self.inner.cmp(a, b))
+    }
+}
+
 pub fn make_task_order_comparator(kind: TaskSchedulingOrder) -> Box<dyn TaskOrderComparator> {
-    match kind {
+    let inner: Box<dyn TaskOrderComparator> = match kind {
         TaskSchedulingOrder::ShortestTaskFirst => Box::new(ShortestTaskOrderComparator),
         TaskSchedulingOrder::LongestTaskFirst => Box::new(LongestTaskOrderComparator),
         TaskSchedulingOrder::DueOnly => Box::new(DueDateOnlyComparator),
-    }
+    };
+    Box::new(AtomicFirstComparator { inner })
 }
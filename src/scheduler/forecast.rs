@@ -0,0 +1,171 @@
+use crate::core::context::AppContext;
+use crate::core::models::{Event, Task};
+use crate::core::repository::{Repository, Sort};
+use crate::errors::{Error, Result};
+use crate::logging::Logger;
+use crate::scheduler::LoggerObserver;
+use crate::scheduler::occupancy::OccupancyIndex;
+use crate::scheduler::packer::{BlockPacker, FirstFitPacker};
+use crate::scheduler::planner::{Schedule, ScheduleOptions, ScheduleSnapshot, plan_schedule};
+use chrono::{Days, NaiveDate};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_FORECAST_RUNS: usize = 500;
+const FORECAST_SEED: u64 = 0x005E_EDF0_CA57;
+/// How many days past its due date a late task is carried before a run gives up on it.
+const LATE_HORIZON_DAYS: u64 = 365;
+
+/// Per-task result of a forecast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskForecast {
+    pub task_id: i32,
+    pub name: String,
+    pub card_id: Option<i32>,
+    pub due: NaiveDate,
+    /// Share of runs in which the task was fully scheduled by its due date.
+    pub on_time: f32,
+    /// Completion date percentiles, past the due date when that share of runs finishes late;
+    /// `None` when it does not finish within `LATE_HORIZON_DAYS` of the due date.
+    pub p50: Option<NaiveDate>,
+    pub p90: Option<NaiveDate>,
+}
+
+/// Runs the regular planner many times with hours drawn from each task's estimate, then places
+/// the hours each run could not fit by the due date on the days after it. The generator is
+/// seeded with a constant, so the same plan always gives the same forecast.
+pub struct Forecaster {
+    runs: usize,
+}
+
+impl Forecaster {
+    pub fn new(runs: usize) -> Self {
+        Self { runs }
+    }
+
+    /// Forecasts every open task; `ctx` itself is left untouched.
+    pub fn run(&self, ctx: &AppContext) -> Result<Vec<TaskForecast>> {
+        if self.runs == 0 {
+            return Err(Error::Parse("Forecast needs at least one run.".into()));
+        }
+
+        let options = ScheduleOptions::from_config(&ctx.config);
+        let mut rng = SplitMix64(FORECAST_SEED);
+        let mut finishes: BTreeMap<i32, Vec<Option<NaiveDate>>> = BTreeMap::new();
+        let mut last_run = None;
+
+        for _ in 0..self.runs {
+            let mut tasks = ctx.tasks.clone();
+            for task in tasks.values_mut() {
+                // Baseline slices from an earlier `schedule` must not pin sampled runs.
+                task.subtasks.clear();
+                if let Some(estimate) = task.estimate {
+                    task.hours = estimate.sample(rng.next_unit());
+                }
+            }
+            let schedule = plan_schedule(
+                ScheduleSnapshot {
+                    tasks: &tasks,
+                    ..ScheduleSnapshot::of(ctx)
+                },
+                &options,
+            )?;
+
+            let late = finish_late(&schedule, &ctx.events, &options);
+            for task in schedule.tasks.values(Sort::IdAsc) {
+                if task.completed {
+                    continue;
+                }
+                let finished = if task.remaining_hours <= 0.0 {
+                    task.subtasks.iter().map(|st| st.date).max()
+                } else {
+                    late.get(&task.id).copied().flatten()
+                };
+                finishes.entry(task.id).or_default().push(finished);
+            }
+            last_run = Some(schedule);
+        }
+
+        let Some(sample) = last_run else {
+            return Ok(Vec::new());
+        };
+        Ok(finishes
+            .into_iter()
+            .filter_map(|(id, mut runs)| {
+                let task = sample.tasks.get(id).ok()?;
+                // `None` (never finished) sorts after every date.
+                runs.sort_by_key(|d| (d.is_none(), *d));
+                let on_time = runs
+                    .iter()
+                    .filter(|d| d.is_some_and(|d| d <= task.date.0))
+                    .count() as f32
+                    / runs.len() as f32;
+                Some(TaskForecast {
+                    task_id: id,
+                    name: task.name.clone(),
+                    card_id: task.card_id,
+                    due: task.date.0,
+                    on_time,
+                    p50: percentile(&runs, 0.5),
+                    p90: percentile(&runs, 0.9),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Keeps placing the open hours of tasks `schedule` could not finish, earliest due first, in the
+/// time it left free from the day after each due date (or the first planned day, if later).
+/// Returns the day each one finishes, or `None` when it does not within `LATE_HORIZON_DAYS`.
+fn finish_late(
+    schedule: &Schedule,
+    events: &Repository<Event>,
+    options: &ScheduleOptions,
+) -> BTreeMap<i32, Option<NaiveDate>> {
+    let mut occupancy = OccupancyIndex::new(events, &schedule.tasks);
+    let mut packer = FirstFitPacker;
+    let logger = Logger::new();
+    logger.set_file_logging_enabled(false);
+    let observer = LoggerObserver { logger };
+
+    let mut late: Vec<Task> = schedule.unscheduled().into_iter().cloned().collect();
+    late.sort_by_key(|t| (t.date.0, t.id));
+    let mut finishes = BTreeMap::new();
+    for mut task in late {
+        task.subtasks.clear();
+        let first = (task.date.0 + Days::new(1)).max(options.start_date);
+        let mut finished = None;
+        for date in (0..LATE_HORIZON_DAYS).filter_map(|n| first.checked_add_days(Days::new(n))) {
+            let mut free = occupancy.free_blocks(date, &options.day_window);
+            packer.pack(&mut task, date, &mut free, &observer);
+            for st in task.subtasks.iter().filter(|st| st.date == date) {
+                occupancy.occupy(st);
+            }
+            if task.remaining_hours <= 0.0 {
+                finished = Some(date);
+                break;
+            }
+        }
+        finishes.insert(task.id, finished);
+    }
+    finishes
+}
+
+fn percentile(sorted: &[Option<NaiveDate>], p: f32) -> Option<NaiveDate> {
+    let rank = ((sorted.len() as f32 * p).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+/// Small deterministic generator; avoids pulling in a dependency for one sampler.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Uniform value in [0, 1).
+    fn next_unit(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
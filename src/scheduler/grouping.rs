@@ -0,0 +1,129 @@
+use crate::core::models::{FreeTimeBlock, SubTask, Task};
+use crate::core::types::TimeRange;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::collections::HashMap;
+
+/// What a slice is "about": its card when tagged, otherwise the task itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SwitchContext {
+    Card(i32),
+    Task(i32),
+}
+
+fn context_of(task: &Task) -> SwitchContext {
+    match task.card_id {
+        Some(card_id) => SwitchContext::Card(card_id),
+        None => SwitchContext::Task(task.id),
+    }
+}
+
+/// Counts how often consecutive task slices on `date` change context.
+pub fn context_switches<'t>(tasks: impl IntoIterator<Item = &'t Task>, date: NaiveDate) -> usize {
+    let mut slices: Vec<(NaiveTime, SwitchContext)> = tasks
+        .into_iter()
+        .flat_map(|t| {
+            t.subtasks
+                .iter()
+                .filter(move |st| st.date == date)
+                .map(move |st| (st.time_range.start, context_of(t)))
+        })
+        .collect();
+    slices.sort_by_key(|(start, _)| *start);
+    slices.windows(2).filter(|w| w[0].1 != w[1].1).count()
+}
+
+struct Run<'t> {
+    task: &'t mut Task,
+    first_start: NaiveTime,
+    seconds: i64,
+    overflow: bool,
+}
+
+/// Re-lays one day's slices so tasks sharing a card run back to back.
+pub struct CardGrouper;
+
+impl CardGrouper {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// `free` is the time the non-atomic slices of `date` were packed into. Each task keeps
+    /// the hours it was given that day, so due dates are met exactly as before; atomic tasks
+    /// keep their block.
+    pub fn regroup<'t>(
+        &self,
+        tasks: impl IntoIterator<Item = &'t mut Task>,
+        date: NaiveDate,
+        free: &[FreeTimeBlock],
+    ) {
+        let mut runs: Vec<Run<'t>> = Vec::new();
+        for task in tasks {
+            if task.atomic {
+                continue;
+            }
+            let (today, earlier): (Vec<SubTask>, Vec<SubTask>) = std::mem::take(&mut task.subtasks)
+                .into_iter()
+                .partition(|st| st.date == date);
+            task.subtasks = earlier;
+            let Some(first_start) = today.iter().map(|st| st.time_range.start).min() else {
+                continue;
+            };
+            runs.push(Run {
+                first_start,
+                seconds: today
+                    .iter()
+                    .map(|st| (st.time_range.end - st.time_range.start).num_seconds())
+                    .sum(),
+                overflow: today.iter().any(|st| st.overflow),
+                task,
+            });
+        }
+
+        // A group sits where its earliest slice was; tasks inside it keep their relative order.
+        let mut group_start: HashMap<SwitchContext, NaiveTime> = HashMap::new();
+        for run in &runs {
+            group_start
+                .entry(context_of(run.task))
+                .and_modify(|s| *s = (*s).min(run.first_start))
+                .or_insert(run.first_start);
+        }
+        runs.sort_by_key(|r| (group_start[&context_of(r.task)], r.first_start, r.task.id));
+
+        let mut blocks: Vec<_> = free.iter().map(|b| (b.start_time, b.end_time)).collect();
+        blocks.sort_by_key(|(start, _)| *start);
+        let mut blocks = blocks.into_iter().peekable();
+
+        for run in runs {
+            let mut need = run.seconds;
+            while need > 0 {
+                let Some((start, end)) = blocks.peek_mut() else {
+                    break;
+                };
+                let cap = (*end - *start).num_seconds();
+                if cap <= 0 {
+                    blocks.next();
+                    continue;
+                }
+                let take = need.min(cap);
+                let stop = *start + Duration::seconds(take);
+                run.task.subtasks.push(SubTask {
+                    task_id: run.task.id,
+                    date,
+                    time_range: TimeRange {
+                        start: start.time(),
+                        end: stop.time(),
+                    },
+                    overflow: false,
+                });
+                *start = stop;
+                need -= take;
+            }
+            if run.overflow
+                && let Some(last) = run.task.subtasks.last_mut()
+                && last.date == date
+            {
+                last.overflow = true;
+            }
+        }
+    }
+}
@@ -1,22 +1,27 @@
 use crate::core::context::AppContext;
-use crate::core::repository::Sort;
-use crate::core::types::{TaskOverflowPolicy, TaskSchedulingOrder, TimeRange};
 use crate::errors::Result;
 use crate::logging::{LogTarget, Logger};
-use crate::scheduler::calendar_view::CalendarView;
-use crate::scheduler::comparator::make_task_order_comparator;
-use crate::scheduler::overflow::make_overflow_handler;
-use crate::scheduler::packer::{BlockPacker, FirstFitPacker, PackOutcome, ScheduleObserver};
+use crate::scheduler::packer::{PackOutcome, ScheduleObserver};
+use crate::scheduler::planner::plan_with_logger;
 use crate::ui::display_manager::DisplayManager;
-use chrono::{Local, NaiveDate};
+use chrono::NaiveDate;
 
 mod calendar_view;
 mod comparator;
+mod forecast;
+mod grouping;
+mod occupancy;
 mod overflow;
 mod packer;
+mod planner;
+mod stability;
+mod template_expander;
 #[cfg(test)]
 mod tests;
 
+pub use forecast::{DEFAULT_FORECAST_RUNS, Forecaster, TaskForecast};
+pub use planner::{Schedule, ScheduleOptions, ScheduleSnapshot, ScheduleStats, plan_schedule};
+
 struct LoggerObserver {
     logger: Logger,
 }
@@ -62,106 +67,72 @@ impl ScheduleObserver for LoggerObserver {
             PackOutcome::None => { /* nothing placed */ }
         }
     }
+
+    fn task_unplaceable(&self, task_id: i32, date: NaiveDate, needed_hours: f32) {
+        self.logger.info(
+            format!(
+                "Atomic task with id {} needs one free block of {} hours; none available on date {}",
+                task_id, needed_hours, date
+            ),
+            LogTarget::FileOnly,
+        );
+    }
 }
 
 pub struct ScheduleManager<'a> {
     ctx: &'a mut AppContext,
-
-    // Precomputed / configured at construction
-    daywin: TimeRange,
-    order: TaskSchedulingOrder,
-    policy: TaskOverflowPolicy,
-    packer: Box<dyn BlockPacker>,
-    days_to_plan: u32,
-    observer: LoggerObserver,
+    options: ScheduleOptions,
 }
 
 impl<'a> ScheduleManager<'a> {
     pub fn new(ctx: &'a mut AppContext) -> Self {
         // Read once from config
-        let daywin = ctx.config.range().clone();
-        let order = *ctx.config.task_scheduling_order();
-        let policy = *ctx.config.task_overflow_policy();
-
-        // Choose default packer; you can make this configurable, too.
-        let packer: Box<dyn BlockPacker> = Box::new(FirstFitPacker);
-
-        // Decide planning window length here (or read from config)
-        let days_to_plan = 7;
-
-        let observer = LoggerObserver {
-            logger: ctx.logger.clone(),
-        };
-
-        Self {
-            ctx,
-            daywin,
-            order,
-            policy,
-            packer,
-            days_to_plan,
-            observer,
-        }
+        let options = ScheduleOptions::from_config(&ctx.config);
+        Self { ctx, options }
     }
 
-    /// Template Method: reset → iterate days → schedule tasks → apply overflow policy
+    /// Plans the schedule, then displays it with a summary.
     pub fn compute_schedule(&mut self) -> Result<()> {
-        self.ctx
-            .logger
-            .info("Starting scheduling...", LogTarget::FileOnly);
-        self.reset_tasks();
+        let schedule = self.plan()?;
 
-        // These are cheap to build each run and don't borrow self.ctx
-        let cmp = make_task_order_comparator(self.order);
-        let overflow = make_overflow_handler(self.policy);
-
-        let start_date = self
-            .ctx
-            .config
-            .schedule_start_date()
-            .unwrap_or_else(|| Local::now().date_naive());
-
-        // Precompute planning days once.
-        let days: Vec<_> = CalendarView::new()
-            .with_start_date(start_date)
-            .with_days(self.days_to_plan)
-            .days();
-
-        for date in &days {
-            let mut free_blocks =
-                CalendarView::free_blocks_for_date(&*self.ctx, *date, &self.daywin);
-
-            for event in self.ctx.events.values(Sort::Unordered) {
-                if event.is_active_on_date(*date) {
-                    let msg = format!("Event with id {} scheduled on date {}", event.id, date);
-                    self.ctx.logger.info(msg, LogTarget::FileOnly);
-                }
+        for task in schedule.unscheduled() {
+            if task.atomic {
+                let msg = format!(
+                    "Atomic task with id {} could not be scheduled in one sitting before {}",
+                    task.id, task.date
+                );
+                self.ctx.logger.warn(msg, LogTarget::ConsoleAndFile);
             }
-
-            self.ctx
-                .tasks
-                .query_mut()
-                .r#where(|t| *date <= t.date.0 && t.remaining_hours > 0.0)
-                .order_with(|a, b| cmp.cmp(a, b))
-                .for_each_mut(|task| {
-                    let outcome = if task.remaining_hours <= 0.0 {
-                        PackOutcome::None
-                    } else {
-                        self.packer
-                            .pack(task, *date, &mut free_blocks, &self.observer)
-                    };
-
-                    match outcome {
-                        PackOutcome::None => { /* nothing placed */ }
-                        PackOutcome::Partial | PackOutcome::Full => {
-                            let _ = overflow.handle(task, true);
-                        }
-                    }
-                });
         }
 
         let dm = DisplayManager::new();
-        dm.display_schedule_for_days(&days, &self.ctx.tasks, &self.ctx.events, &self.ctx.cards);
+        dm.display_schedule_for_days(
+            &schedule.days,
+            &schedule.tasks,
+            &self.ctx.events,
+            &self.ctx.cards,
+        );
+        let stats = &schedule.stats;
+        self.ctx.logger.info(
+            format!(
+                "Scheduled {} hours ({} could not be placed by their due dates)",
+                stats.scheduled_hours, stats.unplaced_hours
+            ),
+            LogTarget::FileOnly,
+        );
+        self.ctx.logger.info(
+            format!(
+                "Context switches across schedule: {}",
+                stats.context_switches
+            ),
+            LogTarget::ConsoleAndFile,
+        );
+        if let Some((moved, total)) = stats.moved {
+            self.ctx.logger.info(
+                format!("Moved {} of {} previously scheduled slices", moved, total),
+                LogTarget::ConsoleAndFile,
+            );
+        }
         self.ctx
             .logger
             .info("Finished scheduling.", LogTarget::ConsoleAndFile);
@@ -169,10 +140,14 @@ impl<'a> ScheduleManager<'a> {
         Ok(())
     }
 
-    fn reset_tasks(&mut self) {
-        for t in self.ctx.tasks.values_mut() {
-            t.subtasks.clear();
-            t.remaining_hours = t.hours;
-        }
+    /// Plans with file logging as configured and stores the result in the context.
+    pub fn plan(&mut self) -> Result<Schedule> {
+        let schedule = plan_with_logger(
+            ScheduleSnapshot::of(self.ctx),
+            &self.options,
+            &self.ctx.logger,
+        )?;
+        self.ctx.tasks = schedule.tasks.clone();
+        Ok(schedule)
     }
 }
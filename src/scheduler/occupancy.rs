@@ -0,0 +1,62 @@
+use crate::core::models::{Event, FreeTimeBlock, SubTask, Task};
+use crate::core::repository::{Repository, Sort};
+use crate::core::types::{DayOfWeek, TimeRange};
+use crate::extensions::chrono::WeekdayExt;
+use crate::scheduler::calendar_view::CalendarView;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+
+/// Busy time per date, built once per planning run and kept current as slices are placed,
+/// so free time is computed without rescanning every event and task for each day.
+pub struct OccupancyIndex {
+    /// Events repeat weekly, so they are bucketed by weekday.
+    events_by_weekday: HashMap<DayOfWeek, Vec<TimeRange>>,
+    slices_by_date: HashMap<NaiveDate, Vec<TimeRange>>,
+}
+
+impl OccupancyIndex {
+    pub fn new(events: &Repository<Event>, tasks: &Repository<Task>) -> Self {
+        let mut events_by_weekday: HashMap<DayOfWeek, Vec<TimeRange>> = HashMap::new();
+        for event in events.values(Sort::IdAsc) {
+            for day in &event.days {
+                events_by_weekday
+                    .entry(*day)
+                    .or_default()
+                    .push(event.time_range.clone());
+            }
+        }
+
+        let mut index = Self {
+            events_by_weekday,
+            slices_by_date: HashMap::new(),
+        };
+        for task in tasks.values(Sort::Unordered) {
+            for st in &task.subtasks {
+                index.occupy(st);
+            }
+        }
+        index
+    }
+
+    /// Marks a newly placed slice as busy.
+    pub fn occupy(&mut self, slice: &SubTask) {
+        self.slices_by_date
+            .entry(slice.date)
+            .or_default()
+            .push(slice.time_range.clone());
+    }
+
+    /// Day window minus that weekday's events and the date's slices.
+    pub fn free_blocks(&self, date: NaiveDate, day_range: &TimeRange) -> Vec<FreeTimeBlock> {
+        let mut free = vec![FreeTimeBlock::new(
+            NaiveDateTime::new(date, day_range.start),
+            NaiveDateTime::new(date, day_range.end),
+        )];
+        let events = self.events_by_weekday.get(&date.weekday().to_day_of_week());
+        let slices = self.slices_by_date.get(&date);
+        for busy in events.into_iter().chain(slices).flatten() {
+            free = CalendarView::subtract_busy_from_free(date, &free, busy);
+        }
+        CalendarView::coalesce_free_blocks(free)
+    }
+}
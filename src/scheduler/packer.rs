@@ -25,12 +25,21 @@ pub trait ScheduleObserver {
         scheduled_hours: f32,
         outcome: &PackOutcome,
     );
+
+    /// Called when an atomic task has no single free block large enough on `date`.
+    fn task_unplaceable(&self, _task_id: i32, _date: NaiveDate, _needed_hours: f32) {}
 }
 
 pub trait BlockPacker {
     /// Pick which free block to try next (return its index in `free`).
     fn select_block_idx(&mut self, free: &Vec<FreeTimeBlock>) -> Option<usize>;
 
+    /// Pick a single block that can hold `need` hours in one piece (used for atomic tasks).
+    fn select_contiguous_block_idx(&mut self, free: &[FreeTimeBlock], need: f32) -> Option<usize> {
+        free.iter()
+            .position(|b| b.remaining_free_time + f32::EPSILON >= need)
+    }
+
     /// Place the task into `block` according to the packer’s rule.
     fn place_one_block(&self, task: &mut Task, date: NaiveDate, block: FreeTimeBlock) -> PlaceStep;
 
@@ -47,6 +56,21 @@ pub trait BlockPacker {
         }
         let start_remaining = task.remaining_hours;
 
+        if task.atomic {
+            // Atomic tasks are placed whole or not at all; never split across blocks.
+            let Some(idx) = self.select_contiguous_block_idx(free, start_remaining) else {
+                observer.task_unplaceable(task.id, date, start_remaining);
+                return PackOutcome::None;
+            };
+            let block = free.remove(idx);
+            if let PlaceStep::Finished { leftover: Some(b) } =
+                self.place_one_block(task, date, block)
+            {
+                free.insert(idx, b);
+            }
+            task.remaining_hours = 0.0;
+        }
+
         while task.remaining_hours > 0.0 {
             let idx = match self.select_block_idx(free) {
                 Some(i) => i,
@@ -0,0 +1,255 @@
+use crate::config::Config;
+use crate::core::context::AppContext;
+use crate::core::models::{Card, Event, SubTask, Task, TaskTemplate};
+use crate::core::repository::{Repository, Sort};
+use crate::core::types::{TaskOverflowPolicy, TaskPackingMode, TaskSchedulingOrder, TimeRange};
+use crate::errors::Result;
+use crate::logging::{LogTarget, Logger};
+use crate::scheduler::LoggerObserver;
+use crate::scheduler::calendar_view::CalendarView;
+use crate::scheduler::comparator::make_task_order_comparator;
+use crate::scheduler::grouping::{CardGrouper, context_switches};
+use crate::scheduler::occupancy::OccupancyIndex;
+use crate::scheduler::overflow::make_overflow_handler;
+use crate::scheduler::packer::{BlockPacker, FirstFitPacker, PackOutcome};
+use crate::scheduler::stability::Baseline;
+use crate::scheduler::template_expander::TemplateExpander;
+use chrono::{Local, NaiveDate};
+
+const DEFAULT_PLAN_DAYS: u32 = 7;
+
+/// Everything that shapes a plan besides the entities themselves.
+#[derive(Debug, Clone)]
+pub struct ScheduleOptions {
+    pub day_window: TimeRange,
+    pub start_date: NaiveDate,
+    pub days: u32,
+    pub order: TaskSchedulingOrder,
+    pub overflow_policy: TaskOverflowPolicy,
+    pub packing: TaskPackingMode,
+    /// Keep the tasks' current slices where still feasible.
+    pub stable: bool,
+}
+
+impl ScheduleOptions {
+    /// Options as configured, planning a week from the configured (or today's) start date.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            day_window: config.range().clone(),
+            start_date: config
+                .schedule_start_date()
+                .unwrap_or_else(|| Local::now().date_naive()),
+            days: DEFAULT_PLAN_DAYS,
+            order: *config.task_scheduling_order(),
+            overflow_policy: *config.task_overflow_policy(),
+            packing: *config.task_packing_mode(),
+            stable: config.stable_rescheduling(),
+        }
+    }
+}
+
+/// Read-only view of the entities a plan is built from.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleSnapshot<'a> {
+    pub tasks: &'a Repository<Task>,
+    pub events: &'a Repository<Event>,
+    pub cards: &'a Repository<Card>,
+    pub templates: &'a Repository<TaskTemplate>,
+}
+
+impl<'a> ScheduleSnapshot<'a> {
+    pub fn of(ctx: &'a AppContext) -> Self {
+        Self {
+            tasks: &ctx.tasks,
+            events: &ctx.events,
+            cards: &ctx.cards,
+            templates: &ctx.templates,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleStats {
+    pub context_switches: usize,
+    /// (moved, total) previous slices; only set with stable rescheduling.
+    pub moved: Option<(usize, usize)>,
+    pub scheduled_hours: f32,
+    /// Open hours of tasks due within the planned days that could not be placed by their due
+    /// date.
+    pub unplaced_hours: f32,
+}
+
+/// Result of planning: the input tasks plus generated template occurrences, with slices.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub days: Vec<NaiveDate>,
+    pub tasks: Repository<Task>,
+    pub stats: ScheduleStats,
+}
+
+impl Schedule {
+    /// Every planned slice, ordered by date and start time.
+    pub fn slices(&self) -> Vec<&SubTask> {
+        let mut slices: Vec<&SubTask> = self
+            .tasks
+            .values(Sort::Unordered)
+            .into_iter()
+            .flat_map(|t| t.subtasks.iter())
+            .collect();
+        slices.sort_by_key(|st| (st.date, st.time_range.start, st.task_id));
+        slices
+    }
+
+    /// Open tasks with hours the window could not hold.
+    pub fn unscheduled(&self) -> Vec<&Task> {
+        self.tasks
+            .query()
+            .r#where(|t| !t.completed && t.remaining_hours > 0.0)
+            .order(Sort::IdAsc)
+            .collect()
+    }
+}
+
+/// Plans `snapshot` without touching it, printing, or writing log files.
+pub fn plan_schedule(
+    snapshot: ScheduleSnapshot<'_>,
+    options: &ScheduleOptions,
+) -> Result<Schedule> {
+    let logger = Logger::new();
+    logger.set_file_logging_enabled(false);
+    plan_with_logger(snapshot, options, &logger)
+}
+
+/// Template Method: reset → iterate days → schedule tasks → apply overflow policy.
+/// Only `FileOnly` messages are written to `logger`.
+pub(crate) fn plan_with_logger(
+    snapshot: ScheduleSnapshot<'_>,
+    options: &ScheduleOptions,
+    logger: &Logger,
+) -> Result<Schedule> {
+    logger.info("Starting scheduling...", LogTarget::FileOnly);
+
+    let cmp = make_task_order_comparator(options.order);
+    let overflow = make_overflow_handler(options.overflow_policy);
+    let mut packer: Box<dyn BlockPacker> = Box::new(FirstFitPacker);
+    let observer = LoggerObserver {
+        logger: logger.clone(),
+    };
+
+    let days: Vec<_> = CalendarView::new()
+        .with_start_date(options.start_date)
+        .with_days(options.days)
+        .days();
+
+    let mut tasks = snapshot.tasks.clone();
+    TemplateExpander::new().expand(&mut tasks, snapshot.templates, logger, &days);
+    let baseline = options
+        .stable
+        .then(|| Baseline::capture(tasks.values(Sort::Unordered), &days));
+    for t in tasks.values_mut() {
+        t.subtasks.clear();
+        t.remaining_hours = t.open_hours();
+    }
+
+    let mut occupancy = OccupancyIndex::new(snapshot.events, &tasks);
+    // Tasks leave for good once done or past due, so later days only sort what is still open.
+    let mut open: Vec<i32> = tasks
+        .query()
+        .r#where(|t| t.remaining_hours > 0.0)
+        .order(Sort::IdAsc)
+        .ids();
+
+    let mut total_switches = 0;
+    for date in &days {
+        let mut free_blocks = occupancy.free_blocks(*date, &options.day_window);
+
+        for event in snapshot.events.values(Sort::Unordered) {
+            if event.is_active_on_date(*date) {
+                let msg = format!("Event with id {} scheduled on date {}", event.id, date);
+                logger.info(msg, LogTarget::FileOnly);
+            }
+        }
+
+        open.retain(|id| {
+            tasks
+                .get(*id)
+                .is_ok_and(|t| *date <= t.date.0 && t.remaining_hours > 0.0)
+        });
+        let mut ordered: Vec<&Task> = open.iter().filter_map(|id| tasks.get(*id).ok()).collect();
+        ordered.sort_by(|a, b| cmp.cmp(a, b));
+        let ordered: Vec<i32> = ordered.into_iter().map(|t| t.id).collect();
+
+        // Free time left once atomic tasks (ordered first) hold their blocks.
+        let mut shared_free = None;
+        let mut placed = Vec::new();
+        for id in ordered {
+            let Ok(task) = tasks.get_mut(id) else {
+                continue;
+            };
+            if !task.atomic && shared_free.is_none() {
+                shared_free = Some(free_blocks.clone());
+            }
+
+            let outcome = if let Some(baseline) = &baseline {
+                baseline.place(task, *date, &mut free_blocks, packer.as_mut(), &observer)
+            } else {
+                packer.pack(task, *date, &mut free_blocks, &observer)
+            };
+
+            match outcome {
+                PackOutcome::None => { /* nothing placed */ }
+                PackOutcome::Partial | PackOutcome::Full => {
+                    let _ = overflow.handle(task, true);
+                    placed.push(id);
+                }
+            }
+        }
+
+        // Regrouping would undo the kept slices, so stability takes precedence.
+        if options.packing == TaskPackingMode::GroupByCard
+            && baseline.is_none()
+            && let Some(free) = shared_free
+        {
+            let today = tasks.values_mut().filter(|t| placed.contains(&t.id));
+            CardGrouper::new().regroup(today, *date, &free);
+        }
+
+        let today: Vec<&Task> = placed.iter().filter_map(|id| tasks.get(*id).ok()).collect();
+        for st in today
+            .iter()
+            .flat_map(|t| &t.subtasks)
+            .filter(|st| st.date == *date)
+        {
+            occupancy.occupy(st);
+        }
+        let switches = context_switches(today, *date);
+        total_switches += switches;
+        let msg = format!("Context switches on date {}: {}", date, switches);
+        logger.info(msg, LogTarget::FileOnly);
+    }
+
+    let moved = baseline
+        .as_ref()
+        .map(|b| b.moved(tasks.values(Sort::Unordered)));
+    logger.info("Finished planning.", LogTarget::FileOnly);
+
+    let mut schedule = Schedule {
+        days,
+        tasks,
+        stats: ScheduleStats {
+            context_switches: total_switches,
+            moved,
+            scheduled_hours: 0.0,
+            unplaced_hours: 0.0,
+        },
+    };
+    schedule.stats.scheduled_hours = schedule.slices().iter().map(|st| st.hours()).sum();
+    let last_day = schedule.days.last().copied();
+    schedule.stats.unplaced_hours = schedule
+        .unscheduled()
+        .iter()
+        .filter(|t| last_day.is_some_and(|last| t.date.0 <= last))
+        .map(|t| t.remaining_hours)
+        .sum();
+    Ok(schedule)
+}
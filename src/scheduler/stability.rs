@@ -0,0 +1,157 @@
+use crate::core::models::{FreeTimeBlock, SubTask, Task};
+use crate::core::types::TimeRange;
+use crate::scheduler::calendar_view::CalendarView;
+use crate::scheduler::packer::{BlockPacker, PackOutcome, ScheduleObserver};
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::{HashMap, HashSet};
+
+/// Slices from the previous run, used to keep rescheduling churn low.
+pub struct Baseline {
+    slices: HashMap<i32, Vec<SubTask>>,
+    by_date: HashMap<NaiveDate, Vec<SubTask>>,
+}
+
+impl Baseline {
+    /// Snapshot current subtasks inside `days`; must run before they are reset.
+    pub fn capture<'t>(tasks: impl IntoIterator<Item = &'t Task>, days: &[NaiveDate]) -> Self {
+        let window: HashSet<&NaiveDate> = days.iter().collect();
+        let slices = tasks
+            .into_iter()
+            .map(|t| {
+                let kept: Vec<SubTask> = t
+                    .subtasks
+                    .iter()
+                    .filter(|st| window.contains(&st.date))
+                    .cloned()
+                    .collect();
+                (t.id, kept)
+            })
+            .filter(|(_, kept)| !kept.is_empty())
+            .collect::<HashMap<_, _>>();
+        let mut by_date: HashMap<NaiveDate, Vec<SubTask>> = HashMap::new();
+        for st in slices.values().flatten() {
+            by_date.entry(st.date).or_default().push(st.clone());
+        }
+        Self { slices, by_date }
+    }
+
+    /// Places `task` on `date`, preferring (1) its own previous slices, (2) time no other task
+    /// held before, and only then (3) time held by others, if the task is due that day.
+    pub fn place(
+        &self,
+        task: &mut Task,
+        date: NaiveDate,
+        free: &mut Vec<FreeTimeBlock>,
+        packer: &mut dyn BlockPacker,
+        observer: &dyn ScheduleObserver,
+    ) -> PackOutcome {
+        let start_remaining = task.remaining_hours;
+
+        let mut previous = self.slices_on(task.id, date);
+        if task.atomic {
+            // An atomic task keeps its old time only whole: one unbroken, still free block.
+            previous = Self::one_sitting(&previous, task.remaining_hours)
+                .filter(|st| Self::is_free(free, st))
+                .into_iter()
+                .collect();
+        }
+        for st in previous {
+            let hours = st.hours();
+            if hours <= task.remaining_hours + f32::EPSILON && Self::is_free(free, &st) {
+                *free = CalendarView::subtract_busy_from_free(date, free, &st.time_range);
+                task.push_subtask_with_hours(st.time_range.clone(), date, hours);
+            }
+        }
+
+        if task.remaining_hours > 0.0 {
+            let mut open = self
+                .by_date
+                .get(&date)
+                .into_iter()
+                .flatten()
+                .filter(|st| st.task_id != task.id)
+                .fold(free.clone(), |acc, st| {
+                    CalendarView::subtract_busy_from_free(date, &acc, &st.time_range)
+                });
+            let placed_before = task.subtasks.len();
+            packer.pack(task, date, &mut open, observer);
+            for st in &task.subtasks[placed_before..] {
+                *free = CalendarView::subtract_busy_from_free(date, free, &st.time_range);
+            }
+        }
+
+        if task.remaining_hours > 0.0 && task.date.0 == date {
+            packer.pack(task, date, free, observer);
+        }
+
+        if (task.remaining_hours - start_remaining).abs() < f32::EPSILON {
+            PackOutcome::None
+        } else if task.remaining_hours > 0.0 {
+            PackOutcome::Partial
+        } else {
+            PackOutcome::Full
+        }
+    }
+
+    /// Returns (moved, total): previous slices of still-known tasks that did not survive.
+    pub fn moved<'t>(&self, tasks: impl IntoIterator<Item = &'t Task>) -> (usize, usize) {
+        tasks
+            .into_iter()
+            .filter_map(|t| self.slices.get(&t.id).map(|prev| (t, prev)))
+            .fold((0, 0), |(moved, total), (t, prev)| {
+                let kept = prev
+                    .iter()
+                    .filter(|st| {
+                        t.subtasks
+                            .iter()
+                            .any(|cur| cur.date == st.date && cur.time_range == st.time_range)
+                    })
+                    .count();
+                (moved + prev.len() - kept, total + prev.len())
+            })
+    }
+
+    fn slices_on(&self, task_id: i32, date: NaiveDate) -> Vec<SubTask> {
+        self.slices
+            .get(&task_id)
+            .map(|slices| {
+                slices
+                    .iter()
+                    .filter(|st| st.date == date)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// `slices` merged into one slice, if they run back to back and hold exactly `hours`.
+    fn one_sitting(slices: &[SubTask], hours: f32) -> Option<SubTask> {
+        let mut sorted = slices.to_vec();
+        sorted.sort_by_key(|st| st.time_range.start);
+        let contiguous = sorted
+            .windows(2)
+            .all(|w| w[0].time_range.end == w[1].time_range.start);
+        let total: f32 = sorted.iter().map(SubTask::hours).sum();
+        if !contiguous || (total - hours).abs() > f32::EPSILON {
+            return None;
+        }
+        let (first, last) = (sorted.first()?, sorted.last()?);
+        Some(SubTask {
+            time_range: TimeRange {
+                start: first.time_range.start,
+                end: last.time_range.end,
+            },
+            ..first.clone()
+        })
+    }
+
+    fn is_free(free: &[FreeTimeBlock], st: &SubTask) -> bool {
+        let TimeRange { start, end } = &st.time_range;
+        let (start, end) = (
+            NaiveDateTime::new(st.date, *start),
+            NaiveDateTime::new(st.date, *end),
+        );
+        free.iter()
+            .any(|b| b.start_time <= start && end <= b.end_time)
+    }
+}
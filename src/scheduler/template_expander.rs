@@ -0,0 +1,54 @@
+use crate::core::models::{Task, TaskTemplate};
+use crate::core::repository::{Repository, Sort};
+use crate::logging::{LogTarget, Logger};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+
+/// Materializes template occurrences inside the planning window as concrete tasks.
+pub struct TemplateExpander;
+
+impl TemplateExpander {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Inserts one task per (template, date) that has no instance yet; returns the new task ids.
+    /// Existing instances are left untouched so completions and overrides survive re-scheduling.
+    pub fn expand(
+        &self,
+        tasks: &mut Repository<Task>,
+        templates: &Repository<TaskTemplate>,
+        logger: &Logger,
+        days: &[NaiveDate],
+    ) -> Vec<i32> {
+        let existing: HashSet<(i32, NaiveDate)> = tasks
+            .values(Sort::Unordered)
+            .into_iter()
+            .filter_map(|t| t.origin.map(|o| (o.template_id, o.occurrence)))
+            .collect();
+
+        let pending: Vec<_> = templates
+            .values(Sort::IdAsc)
+            .into_iter()
+            .flat_map(|template| {
+                days.iter()
+                    .filter(|date| template.occurs_on(**date))
+                    .filter(|date| !existing.contains(&(template.id, **date)))
+                    .map(|date| template.instantiate(*date))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut added = Vec::with_capacity(pending.len());
+        for task in pending {
+            let stored = tasks.insert(task);
+            let msg = format!(
+                "Generated task with id {} from template on date {}",
+                stored.id, stored.date
+            );
+            added.push(stored.id);
+            logger.info(msg, LogTarget::FileOnly);
+        }
+        added
+    }
+}
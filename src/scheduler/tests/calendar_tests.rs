@@ -1,5 +1,5 @@
-use crate::core::models::{Event, FreeTimeBlock, Task};
-use crate::core::types::{Date, DayOfWeek, TimeRange};
+use crate::core::models::FreeTimeBlock;
+use crate::core::types::TimeRange;
 use crate::scheduler::calendar_view::CalendarView;
 use chrono::NaiveDateTime;
 
@@ -15,37 +15,6 @@ fn calendar_view_generates_days() {
     assert_eq!(days[2], start + chrono::Duration::days(2));
 }
 
-#[test]
-fn calendar_view_builds_free_blocks() {
-    let start = super::sample_date();
-    let mut ctx = super::make_ctx();
-
-    let event = Event::new(
-        true,
-        "e",
-        None,
-        vec![
-            DayOfWeek::Mon,
-            DayOfWeek::Tue,
-            DayOfWeek::Wed,
-            DayOfWeek::Thu,
-            DayOfWeek::Fri,
-        ],
-        TimeRange::try_from_str("9AM-10AM").unwrap(),
-    );
-    ctx.events.insert(event);
-
-    let mut task = Task::new("t", 1.0, None, Date(start));
-    task.push_subtask_with_hours(TimeRange::try_from_str("10AM-11AM").unwrap(), start, 1.0);
-    ctx.tasks.insert(task);
-
-    let day_range = TimeRange::try_from_str("8AM-12PM").unwrap();
-    let free = CalendarView::free_blocks_for_date(&ctx, start, &day_range);
-    assert_eq!(free.len(), 2);
-    assert!((free[0].remaining_free_time - 1.0).abs() < f32::EPSILON); // 8-9
-    assert!((free[1].remaining_free_time - 1.0).abs() < f32::EPSILON); // 11-12
-}
-
 #[test]
 fn coalesce_merges_adjacent_free_blocks() {
     // Build overlapping/adjacent free blocks to hit coalesce logic directly.
@@ -66,3 +66,17 @@ fn due_only_orders_by_due_date() {
     let cmp = make_task_order_comparator(TaskSchedulingOrder::DueOnly);
     assert!(cmp.cmp(&t1, &t2).is_lt());
 }
+
+#[test]
+fn atomic_tasks_order_before_earlier_due_tasks() {
+    let (t1, mut t2) = sample_tasks();
+    t2.atomic = true;
+    for order in [
+        TaskSchedulingOrder::LongestTaskFirst,
+        TaskSchedulingOrder::ShortestTaskFirst,
+        TaskSchedulingOrder::DueOnly,
+    ] {
+        let cmp = make_task_order_comparator(order);
+        assert!(cmp.cmp(&t2, &t1).is_lt());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::core::models::Task;
+use crate::core::types::{Date, Estimate};
+use crate::scheduler::Forecaster;
+use chrono::Days;
+
+#[test]
+fn forecast_reports_certain_task_as_on_time() {
+    let mut ctx = super::make_ctx();
+    let due = Date(super::sample_date());
+    ctx.tasks.insert(Task::new("fixed", 2.0, None, due));
+
+    let forecasts = Forecaster::new(20).run(&ctx).unwrap();
+
+    assert_eq!(forecasts.len(), 1);
+    let f = &forecasts[0];
+    assert_eq!(f.on_time, 1.0);
+    assert_eq!(f.p50, Some(super::sample_date()));
+    assert_eq!(f.p90, Some(super::sample_date()));
+    assert!(ctx.tasks.get(1).unwrap().subtasks.is_empty());
+}
+
+#[test]
+fn forecast_reports_risk_for_wide_estimates() {
+    let mut ctx = super::make_ctx();
+    // The sample config gives 10 free hours on the due date.
+    let mut risky = Task::new("risky", 6.0, None, Date(super::sample_date()));
+    risky.estimate = Some(Estimate::try_from_str("~4/6/20").unwrap());
+    ctx.tasks.insert(risky);
+    let mut done = Task::new("done", 1.0, None, Date(super::sample_date()));
+    done.complete();
+    ctx.tasks.insert(done);
+
+    let forecasts = Forecaster::new(200).run(&ctx).unwrap();
+
+    assert_eq!(forecasts.len(), 1, "completed tasks are not forecast");
+    let f = &forecasts[0];
+    assert!(f.on_time > 0.0 && f.on_time < 1.0, "on_time={}", f.on_time);
+    assert_eq!(f.p50, Some(super::sample_date()));
+    assert!(
+        f.p90.is_some_and(|p90| p90 > super::sample_date()),
+        "p90={:?}",
+        f.p90
+    );
+    assert_eq!(ctx.tasks.get(1).unwrap().hours, 6.0);
+}
+
+#[test]
+fn forecast_carries_late_hours_past_the_due_date() {
+    let mut ctx = super::make_ctx();
+    // 10 free hours a day: 10 on the due date, then 10 and 5 on the days after it.
+    let due = super::sample_date();
+    ctx.tasks.insert(Task::new("long", 25.0, None, Date(due)));
+    let mut atomic = Task::new("whole day", 11.0, None, Date(due));
+    atomic.atomic = true;
+    ctx.tasks.insert(atomic);
+
+    let forecasts = Forecaster::new(5).run(&ctx).unwrap();
+
+    let long = &forecasts[0];
+    assert_eq!(long.on_time, 0.0);
+    assert_eq!(long.p50, due.checked_add_days(Days::new(2)));
+    assert_eq!(long.p90, long.p50);
+    let whole_day = &forecasts[1];
+    assert_eq!(whole_day.on_time, 0.0);
+    assert_eq!(whole_day.p50, None, "never fits in one sitting");
+}
+
+#[test]
+fn forecast_rejects_zero_runs() {
+    let ctx = super::make_ctx();
+    assert!(Forecaster::new(0).run(&ctx).is_err());
+}
@@ -0,0 +1,101 @@
+use crate::core::models::{BaseEntity, FreeTimeBlock, SubTask, Task};
+use crate::core::types::{Date, TimeRange};
+use crate::scheduler::grouping::{CardGrouper, context_switches};
+use chrono::NaiveDateTime;
+
+fn task_with_slices(id: i32, card_id: Option<i32>, ranges: &[&str]) -> Task {
+    let date = super::sample_date();
+    let mut task = Task::new(format!("t{id}"), ranges.len() as f32, card_id, Date(date));
+    task.set_id(id);
+    for r in ranges {
+        task.subtasks.push(SubTask {
+            task_id: id,
+            date,
+            time_range: TimeRange::try_from_str(r).unwrap(),
+            overflow: false,
+        });
+    }
+    task
+}
+
+fn block(range: &str) -> FreeTimeBlock {
+    let date = super::sample_date();
+    let r = TimeRange::try_from_str(range).unwrap();
+    FreeTimeBlock::new(
+        NaiveDateTime::new(date, r.start),
+        NaiveDateTime::new(date, r.end),
+    )
+}
+
+#[test]
+fn context_switches_count_card_changes_between_slices() {
+    let a = task_with_slices(1, Some(1), &["8AM-9AM"]);
+    let b = task_with_slices(2, Some(2), &["9AM-10AM"]);
+    let c = task_with_slices(3, Some(1), &["10AM-11AM"]);
+    let d = task_with_slices(4, Some(1), &["11AM-12PM"]);
+    assert_eq!(context_switches([&a, &b, &c, &d], super::sample_date()), 2);
+}
+
+#[test]
+fn regroup_puts_same_card_tasks_back_to_back() {
+    let mut a = task_with_slices(1, Some(1), &["8AM-9AM"]);
+    let mut b = task_with_slices(2, Some(2), &["9AM-10AM"]);
+    let mut c = task_with_slices(3, Some(1), &["10AM-11AM", "1PM-2PM"]);
+    let free = vec![block("8AM-11AM"), block("1PM-2PM")];
+    let date = super::sample_date();
+    assert_eq!(context_switches([&a, &b, &c], date), 2);
+
+    CardGrouper::new().regroup([&mut a, &mut b, &mut c], date, &free);
+
+    let ranges = |t: &Task| {
+        t.subtasks
+            .iter()
+            .map(|st| st.time_range.to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        ranges(&a),
+        vec![TimeRange::try_from_str("8AM-9AM").unwrap().to_string()]
+    );
+    assert_eq!(
+        ranges(&c),
+        vec![TimeRange::try_from_str("9AM-11AM").unwrap().to_string()]
+    );
+    assert_eq!(
+        ranges(&b),
+        vec![TimeRange::try_from_str("1PM-2PM").unwrap().to_string()]
+    );
+    assert_eq!(context_switches([&a, &b, &c], date), 1);
+}
+
+#[test]
+fn regroup_keeps_atomic_tasks_and_overflow_flags() {
+    let mut exam = task_with_slices(1, Some(2), &["8AM-10AM"]);
+    exam.atomic = true;
+    let mut a = task_with_slices(2, Some(1), &["10AM-11AM"]);
+    let mut b = task_with_slices(3, None, &["11AM-12PM"]);
+    let mut c = task_with_slices(4, Some(1), &["12PM-1PM"]);
+    c.subtasks[0].overflow = true;
+    let free = vec![block("10AM-1PM")];
+
+    CardGrouper::new().regroup(
+        [&mut exam, &mut a, &mut b, &mut c],
+        super::sample_date(),
+        &free,
+    );
+
+    assert_eq!(
+        exam.subtasks[0].time_range,
+        TimeRange::try_from_str("8AM-10AM").unwrap()
+    );
+    assert_eq!(
+        c.subtasks[0].time_range,
+        TimeRange::try_from_str("11AM-12PM").unwrap()
+    );
+    assert!(c.subtasks[0].overflow);
+    assert_eq!(
+        b.subtasks[0].time_range,
+        TimeRange::try_from_str("12PM-1PM").unwrap()
+    );
+    assert!(!b.subtasks[0].overflow);
+}
@@ -1,8 +1,10 @@
-use crate::core::models::Task;
-use crate::core::types::Date;
+use crate::config::ConfigKey;
+use crate::core::models::{Task, TaskTemplate};
+use crate::core::types::{Date, DayOfWeek};
 use crate::logging::Logger;
 use crate::scheduler::{
     LoggerObserver, ScheduleManager,
+    grouping::context_switches,
     packer::{PackOutcome, ScheduleObserver},
 };
 use chrono::NaiveDate;
@@ -36,3 +38,175 @@ fn schedule_manager_compute_schedule_runs() {
     assert!(!scheduled.subtasks.is_empty());
     assert!(scheduled.remaining_hours <= 0.0);
 }
+
+#[test]
+fn schedule_manager_expands_templates_once_per_occurrence() {
+    let mut ctx = super::make_ctx();
+    // 2099-01-01 is a Thursday; the 7-day window holds one Friday and one Thursday.
+    let template = TaskTemplate::new("report", 2.0, None, vec![DayOfWeek::Thu, DayOfWeek::Fri]);
+    ctx.templates.insert(template);
+
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    assert_eq!(ctx.tasks.len(), 2);
+    let dates: Vec<_> = ctx
+        .tasks
+        .values(crate::core::repository::Sort::IdAsc)
+        .into_iter()
+        .map(|t| t.origin.unwrap().occurrence)
+        .collect();
+    assert_eq!(
+        dates,
+        vec![
+            NaiveDate::from_ymd_opt(2099, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2099, 1, 2).unwrap(),
+        ]
+    );
+
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    assert_eq!(ctx.tasks.len(), 2);
+}
+
+#[test]
+fn schedule_manager_skips_completed_instances() {
+    let mut ctx = super::make_ctx();
+    ctx.templates
+        .insert(TaskTemplate::new("report", 2.0, None, vec![DayOfWeek::Fri]));
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    ctx.tasks.get_mut(1).unwrap().complete();
+
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    let done = ctx.tasks.get(1).unwrap();
+    assert_eq!(ctx.tasks.len(), 1);
+    assert!(done.subtasks.is_empty());
+    assert_eq!(done.remaining_hours, 0.0);
+}
+
+#[test]
+fn schedule_manager_keeps_atomic_task_in_one_block() {
+    let mut ctx = super::make_ctx();
+    let date = Date(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap());
+    ctx.tasks
+        .insert(Task::new("small", 1.0, None, date.clone()));
+    let mut exam = Task::new("exam", 9.0, None, date);
+    exam.atomic = true;
+    ctx.tasks.insert(exam);
+
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    let exam = ctx.tasks.get(2).unwrap();
+    assert_eq!(exam.subtasks.len(), 1);
+    assert_eq!(exam.remaining_hours, 0.0);
+    assert_eq!(
+        exam.subtasks[0].time_range,
+        crate::core::types::TimeRange::try_from_str("8AM-5PM").unwrap()
+    );
+}
+
+#[test]
+fn schedule_manager_groups_tasks_by_card_when_configured() {
+    let date = NaiveDate::from_ymd_opt(2099, 1, 1).unwrap();
+    let build = |grouped: bool| {
+        let mut ctx = super::make_ctx();
+        if grouped {
+            ctx.config
+                .set_key(ConfigKey::TaskPackingMode, "group-by-card")
+                .unwrap();
+        }
+        for card_id in [1, 2, 1] {
+            ctx.tasks
+                .insert(Task::new("t", 2.0, Some(card_id), Date(date)));
+        }
+        ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+        ctx
+    };
+
+    let greedy = build(false);
+    assert_eq!(
+        context_switches(
+            greedy.tasks.values(crate::core::repository::Sort::IdAsc),
+            date
+        ),
+        2
+    );
+
+    let grouped = build(true);
+    assert_eq!(
+        context_switches(
+            grouped.tasks.values(crate::core::repository::Sort::IdAsc),
+            date
+        ),
+        1
+    );
+    for task in grouped.tasks.values(crate::core::repository::Sort::IdAsc) {
+        assert_eq!(task.remaining_hours, 0.0);
+        assert_eq!(task.subtasks.len(), 1);
+    }
+    assert_eq!(
+        grouped.tasks.get(3).unwrap().subtasks[0].time_range,
+        crate::core::types::TimeRange::try_from_str("10AM-12PM").unwrap()
+    );
+}
+
+fn slices(ctx: &crate::core::context::AppContext, id: i32) -> Vec<String> {
+    ctx.tasks
+        .get(id)
+        .unwrap()
+        .subtasks
+        .iter()
+        .map(|st| st.to_string())
+        .collect()
+}
+
+#[test]
+fn stable_rescheduling_keeps_previous_slices_for_new_task() {
+    let mut ctx = super::make_ctx();
+    ctx.config
+        .set_key(ConfigKey::StableRescheduling, "true")
+        .unwrap();
+    let due = Date(NaiveDate::from_ymd_opt(2099, 1, 3).unwrap());
+    ctx.tasks.insert(Task::new("a", 4.0, None, due.clone()));
+    ctx.tasks.insert(Task::new("b", 4.0, None, due.clone()));
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    let before = (slices(&ctx, 1), slices(&ctx, 2));
+
+    // Shorter tasks go first under longest-task-first, so a fresh run would shift a and b.
+    ctx.tasks.insert(Task::new("c", 1.0, None, due));
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+
+    assert_eq!((slices(&ctx, 1), slices(&ctx, 2)), before);
+    assert_eq!(
+        slices(&ctx, 3),
+        vec!["2099-01-01: 4:00PM-5:00PM".to_string()]
+    );
+}
+
+#[test]
+fn stable_rescheduling_moves_only_what_an_urgent_task_needs() {
+    let mut ctx = super::make_ctx();
+    ctx.config
+        .set_key(ConfigKey::StableRescheduling, "true")
+        .unwrap();
+    let later = Date(NaiveDate::from_ymd_opt(2099, 1, 3).unwrap());
+    ctx.tasks.insert(Task::new("a", 5.0, None, later.clone()));
+    ctx.tasks.insert(Task::new("b", 5.0, None, later));
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+    let b_before = slices(&ctx, 2);
+
+    let today = Date(NaiveDate::from_ymd_opt(2099, 1, 1).unwrap());
+    ctx.tasks.insert(Task::new("urgent", 2.0, None, today));
+    ScheduleManager::new(&mut ctx).compute_schedule().unwrap();
+
+    assert_eq!(
+        slices(&ctx, 3),
+        vec!["2099-01-01: 8:00AM-10:00AM".to_string()]
+    );
+    assert_eq!(slices(&ctx, 2), b_before);
+    let a = ctx.tasks.get(1).unwrap();
+    assert_eq!(a.remaining_hours, 0.0);
+    assert_eq!(
+        slices(&ctx, 1),
+        vec![
+            "2099-01-01: 10:00AM-1:00PM".to_string(),
+            "2099-01-02: 8:00AM-10:00AM".to_string(),
+        ]
+    );
+}
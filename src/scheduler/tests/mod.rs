@@ -1,8 +1,13 @@
 mod calendar_tests;
 mod comparator_tests;
+mod forecast_tests;
+mod grouping_tests;
 mod manager_tests;
+mod occupancy_tests;
 mod overflow_tests;
 mod packer_tests;
+mod planner_tests;
+mod stability_tests;
 
 use crate::config::Config;
 use crate::core::models::{Card, Event, Task};
@@ -46,7 +51,10 @@ pub(super) fn make_ctx() -> AppContext {
         tasks: Repository::<Task>::new(),
         events: Repository::<Event>::new(),
         cards: Repository::<Card>::new(),
+        templates: Repository::new(),
         logger,
+        history: Default::default(),
+        workspace: Default::default(),
         startup_displayed: false,
         config_path: path,
         schedules_dir,
@@ -0,0 +1,55 @@
+use crate::core::models::{Event, SubTask, Task};
+use crate::core::types::{Date, DayOfWeek, TimeRange};
+use crate::scheduler::occupancy::OccupancyIndex;
+
+#[test]
+fn occupancy_index_subtracts_events_and_slices() {
+    let start = super::sample_date();
+    let mut ctx = super::make_ctx();
+
+    let event = Event::new(
+        true,
+        "e",
+        None,
+        vec![
+            DayOfWeek::Mon,
+            DayOfWeek::Tue,
+            DayOfWeek::Wed,
+            DayOfWeek::Thu,
+            DayOfWeek::Fri,
+        ],
+        TimeRange::try_from_str("9AM-10AM").unwrap(),
+    );
+    ctx.events.insert(event);
+
+    let mut task = Task::new("t", 1.0, None, Date(start));
+    task.push_subtask_with_hours(TimeRange::try_from_str("10AM-11AM").unwrap(), start, 1.0);
+    ctx.tasks.insert(task);
+
+    let day_range = TimeRange::try_from_str("8AM-12PM").unwrap();
+    let free = OccupancyIndex::new(&ctx.events, &ctx.tasks).free_blocks(start, &day_range);
+    assert_eq!(free.len(), 2);
+    assert!((free[0].remaining_free_time - 1.0).abs() < f32::EPSILON); // 8-9
+    assert!((free[1].remaining_free_time - 1.0).abs() < f32::EPSILON); // 11-12
+}
+
+#[test]
+fn occupancy_index_tracks_placed_slices_per_date() {
+    let start = super::sample_date();
+    let ctx = super::make_ctx();
+    let day_range = TimeRange::try_from_str("8AM-12PM").unwrap();
+    let mut index = OccupancyIndex::new(&ctx.events, &ctx.tasks);
+
+    index.occupy(&SubTask {
+        task_id: 1,
+        date: start,
+        time_range: TimeRange::try_from_str("8AM-10AM").unwrap(),
+        overflow: false,
+    });
+
+    let today = index.free_blocks(start, &day_range);
+    assert_eq!(today.len(), 1);
+    assert!((today[0].remaining_free_time - 2.0).abs() < f32::EPSILON);
+    let tomorrow = index.free_blocks(start + chrono::Duration::days(1), &day_range);
+    assert!((tomorrow[0].remaining_free_time - 4.0).abs() < f32::EPSILON);
+}
@@ -54,3 +54,67 @@ fn packer_returns_full_when_additional_block_available() {
     assert!(matches!(outcome, PackOutcome::Full));
     assert_eq!(task.remaining_hours, 0.0);
 }
+
+#[test]
+fn packer_places_atomic_task_in_single_block() {
+    let mut packer = FirstFitPacker;
+    let mut task = Task::new("exam", 3.0, None, Date(super::sample_date()));
+    task.set_id(3);
+    task.atomic = true;
+    let date = task.date.0;
+    let short = TimeRange::try_from_str("8AM-10AM").unwrap();
+    let long = TimeRange::try_from_str("1PM-5PM").unwrap();
+    let mut free = vec![
+        FreeTimeBlock::new(
+            NaiveDateTime::new(date, short.start),
+            NaiveDateTime::new(date, short.end),
+        ),
+        FreeTimeBlock::new(
+            NaiveDateTime::new(date, long.start),
+            NaiveDateTime::new(date, long.end),
+        ),
+    ];
+
+    let obs = LoggerObserver {
+        logger: Logger::new(),
+    };
+    let outcome = packer.pack(&mut task, date, &mut free, &obs);
+
+    assert!(matches!(outcome, PackOutcome::Full));
+    assert_eq!(task.subtasks.len(), 1);
+    assert_eq!(
+        task.subtasks[0].time_range,
+        TimeRange::try_from_str("1PM-4PM").unwrap()
+    );
+    assert_eq!(free.len(), 2);
+    assert_eq!(free[1].remaining_free_time, 1.0);
+}
+
+#[test]
+fn packer_leaves_atomic_task_unplaced_without_large_enough_block() {
+    let mut packer = FirstFitPacker;
+    let mut task = Task::new("exam", 3.0, None, Date(super::sample_date()));
+    task.set_id(4);
+    task.atomic = true;
+    let date = task.date.0;
+    let mut free: Vec<_> = ["8AM-10AM", "11AM-1PM"]
+        .iter()
+        .map(|r| {
+            let r = TimeRange::try_from_str(r).unwrap();
+            FreeTimeBlock::new(
+                NaiveDateTime::new(date, r.start),
+                NaiveDateTime::new(date, r.end),
+            )
+        })
+        .collect();
+
+    let obs = LoggerObserver {
+        logger: Logger::new(),
+    };
+    let outcome = packer.pack(&mut task, date, &mut free, &obs);
+
+    assert!(matches!(outcome, PackOutcome::None));
+    assert!(task.subtasks.is_empty());
+    assert_eq!(task.remaining_hours, 3.0);
+    assert_eq!(free.len(), 2);
+}
@@ -0,0 +1,67 @@
+use crate::core::integrity;
+use crate::core::models::{Task, TaskTemplate};
+use crate::core::persist::{apply_save_file, build_save_file};
+use crate::core::types::{Date, DayOfWeek};
+use crate::scheduler::{ScheduleOptions, ScheduleSnapshot, plan_schedule};
+
+#[test]
+fn plan_schedule_expands_templates_only_in_the_result() {
+    let mut ctx = super::make_ctx();
+    ctx.templates
+        .insert(TaskTemplate::new("report", 2.0, None, vec![DayOfWeek::Fri]));
+    ctx.tasks
+        .insert(Task::new("essay", 1.0, None, Date(super::sample_date())));
+
+    let options = ScheduleOptions::from_config(&ctx.config);
+    let schedule = plan_schedule(ScheduleSnapshot::of(&ctx), &options).unwrap();
+
+    assert_eq!(ctx.tasks.len(), 1);
+    assert!(ctx.tasks.get(1).unwrap().subtasks.is_empty());
+    assert_eq!(schedule.tasks.len(), 2);
+    assert_eq!(schedule.days.len(), 7);
+    assert_eq!(schedule.stats.scheduled_hours, 3.0);
+    assert_eq!(schedule.stats.unplaced_hours, 0.0);
+    let dates: Vec<_> = schedule.slices().iter().map(|st| st.date).collect();
+    assert!(dates.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn plan_schedule_reports_what_does_not_fit() {
+    let mut ctx = super::make_ctx();
+    ctx.config
+        .set_key(crate::config::ConfigKey::TaskOverflowPolicy, "block")
+        .unwrap();
+    // The sample config leaves 10 free hours on the due date.
+    ctx.tasks
+        .insert(Task::new("big", 12.0, None, Date(super::sample_date())));
+
+    let mut options = ScheduleOptions::from_config(&ctx.config);
+    options.days = 1;
+    let schedule = plan_schedule(ScheduleSnapshot::of(&ctx), &options).unwrap();
+
+    let unscheduled = schedule.unscheduled();
+    assert_eq!(unscheduled.len(), 1);
+    assert_eq!(unscheduled[0].remaining_hours, 2.0);
+    assert_eq!(schedule.stats.scheduled_hours, 10.0);
+    assert_eq!(schedule.stats.unplaced_hours, 2.0);
+}
+
+#[test]
+fn deleted_template_instances_stay_deleted() {
+    let mut ctx = super::make_ctx();
+    ctx.templates
+        .insert(TaskTemplate::new("report", 2.0, None, vec![DayOfWeek::Fri]));
+    let options = ScheduleOptions::from_config(&ctx.config);
+    ctx.tasks = plan_schedule(ScheduleSnapshot::of(&ctx), &options)
+        .unwrap()
+        .tasks;
+    let instance = ctx.tasks.get(1).unwrap().clone();
+    integrity::delete_task(&mut ctx, instance.id).unwrap();
+
+    let save = build_save_file(&ctx.tasks, &ctx.events, &ctx.cards, &ctx.templates).unwrap();
+    apply_save_file(&mut ctx, &save).unwrap();
+    assert_eq!(ctx.templates.get(1).unwrap().skipped, [instance.date.0]);
+
+    let schedule = plan_schedule(ScheduleSnapshot::of(&ctx), &options).unwrap();
+    assert_eq!(schedule.tasks.len(), 0);
+}
@@ -0,0 +1,69 @@
+use crate::core::models::{BaseEntity, FreeTimeBlock, SubTask, Task};
+use crate::core::types::{Date, TimeRange};
+use crate::logging::Logger;
+use crate::scheduler::LoggerObserver;
+use crate::scheduler::packer::{FirstFitPacker, PackOutcome};
+use crate::scheduler::stability::Baseline;
+use chrono::NaiveDateTime;
+
+fn slice(id: i32, range: &str) -> SubTask {
+    SubTask {
+        task_id: id,
+        date: super::sample_date(),
+        time_range: TimeRange::try_from_str(range).unwrap(),
+        overflow: false,
+    }
+}
+
+#[test]
+fn baseline_counts_moved_slices_inside_window() {
+    let date = super::sample_date();
+    let mut task = Task::new("t", 2.0, None, Date(date));
+    task.set_id(1);
+    task.subtasks = vec![slice(1, "8AM-9AM"), slice(1, "1PM-2PM")];
+    let mut outside = slice(1, "8AM-9AM");
+    outside.date = date.succ_opt().unwrap();
+    task.subtasks.push(outside);
+
+    let baseline = Baseline::capture([&task], &[date]);
+    task.subtasks = vec![slice(1, "8AM-9AM"), slice(1, "3PM-4PM")];
+
+    assert_eq!(baseline.moved([&task]), (1, 2));
+}
+
+#[test]
+fn stable_atomic_task_keeps_only_a_whole_previous_block() {
+    let date = super::sample_date();
+    let day = TimeRange::try_from_str("8AM-6PM").unwrap();
+    let place = |previous: Vec<SubTask>| -> Vec<String> {
+        let mut task = Task::new("t", 3.0, None, Date(date));
+        task.set_id(1);
+        task.atomic = true;
+        task.subtasks = previous;
+        let baseline = Baseline::capture([&task], &[date]);
+        task.subtasks.clear();
+        let mut free = vec![FreeTimeBlock::new(
+            NaiveDateTime::new(date, day.start),
+            NaiveDateTime::new(date, day.end),
+        )];
+        let observer = LoggerObserver {
+            logger: Logger::new(),
+        };
+        let outcome = baseline.place(&mut task, date, &mut free, &mut FirstFitPacker, &observer);
+        assert!(matches!(outcome, PackOutcome::Full));
+        task.subtasks
+            .iter()
+            .map(|st| st.time_range.to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        place(vec![slice(1, "1PM-2PM"), slice(1, "2PM-4PM")]),
+        ["1:00PM-4:00PM"]
+    );
+    // Split slices, e.g. from before the task was made atomic, are not kept.
+    assert_eq!(
+        place(vec![slice(1, "9AM-10AM"), slice(1, "1PM-3PM")]),
+        ["8:00AM-11:00AM"]
+    );
+}
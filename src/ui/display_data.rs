@@ -1,6 +1,11 @@
-use crate::core::models::{Card, Event, Task};
+use crate::core::models::{BaseEntity, Card, Event, Task, TaskTemplate};
 use crate::core::repository::{Repository, Sort};
+use crate::core::types::{DayOfWeek, EntityType};
+use crate::core::versions::Commit;
+use crate::core::view::{TableView, columns};
+use crate::scheduler::TaskForecast;
 use chrono::{NaiveDate, NaiveTime};
+use std::cmp::Ordering;
 
 #[derive(Debug, Clone)]
 pub struct ScheduleSection {
@@ -16,67 +21,124 @@ impl DisplayDataBuilder {
         Self::default()
     }
 
-    pub fn task_rows(
+    /// Rows of an entity table: the view's columns, ordered by its sort keys, then by id.
+    pub fn entity_rows<T: TableRow>(
         &self,
-        tasks: &Repository<Task>,
+        entities: &Repository<T>,
         cards: &Repository<Card>,
+        view: &TableView,
     ) -> Vec<Vec<String>> {
-        tasks
-            .values(Sort::IdAsc)
+        entities
+            .query()
+            .order_with(|a, b| {
+                view.sort
+                    .iter()
+                    .fold(Ordering::Equal, |acc, key| {
+                        acc.then_with(|| {
+                            let ord = a.compare(b, &key.column, cards);
+                            if key.descending { ord.reverse() } else { ord }
+                        })
+                    })
+                    .then(a.id().cmp(&b.id()))
+            })
+            .collect()
             .into_iter()
-            .map(|t| {
-                let card_opt: Option<&Card> = t.card_id.and_then(|id| cards.get(id).ok());
-                vec![
-                    paint_opt(card_opt, &t.id.to_string()),
-                    paint_opt(card_opt, t.name.as_str()),
-                    paint_opt(card_opt, card_opt.map(|c| c.name.as_str()).unwrap_or("-")),
-                    paint_opt(card_opt, &format!("{:.2}", t.hours)),
-                    paint_opt(card_opt, &t.date.to_string()),
-                ]
+            .map(|e| {
+                let card_opt = e.card_id().and_then(|id| cards.get(id).ok());
+                view.columns
+                    .iter()
+                    .map(|col| paint_opt(card_opt, &e.cell(col, cards)))
+                    .collect()
             })
             .collect()
     }
 
-    pub fn event_rows(
+    /// Rows of the `show` view: every table column of the entity, then its notes, links and
+    /// metadata.
+    pub fn detail_rows<T: TableRow>(
         &self,
-        events: &Repository<Event>,
+        kind: EntityType,
+        entity: &T,
         cards: &Repository<Card>,
     ) -> Vec<Vec<String>> {
-        events
-            .values(Sort::IdAsc)
-            .into_iter()
-            .map(|e| {
-                let card_opt = e.card_id.and_then(|id| cards.get(id).ok());
-                let days = if e.days.is_empty() {
-                    "-".to_string()
-                } else {
-                    e.days
-                        .iter()
-                        .map(|d| d.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                };
+        let row = |field: &str, value: &str| vec![field.to_string(), value.to_string()];
+        let details = entity.details();
+        let mut rows: Vec<Vec<String>> = columns(kind)
+            .iter()
+            .map(|col| row(col, &entity.detail_cell(col, cards)))
+            .collect();
+        rows.insert(1, row("uid", entity.uid().as_str()));
+        rows.extend(
+            entity
+                .detail_fields()
+                .iter()
+                .map(|(field, value)| row(field, value)),
+        );
+        rows.push(row("note", details.notes.as_deref().unwrap_or("-")));
+        if details.links.is_empty() {
+            rows.push(row("links", "-"));
+        }
+        rows.extend(details.links.iter().map(|link| row("links", link)));
+        rows.extend(
+            details
+                .meta
+                .iter()
+                .map(|(key, value)| row(&format!("meta.{key}"), value)),
+        );
+        rows
+    }
+
+    pub fn forecast_rows(
+        &self,
+        forecasts: &[TaskForecast],
+        cards: &Repository<Card>,
+    ) -> Vec<Vec<String>> {
+        let date_or_missed = |d: Option<NaiveDate>| {
+            d.map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "missed".to_string())
+        };
+        forecasts
+            .iter()
+            .map(|f| {
+                let card_opt = f.card_id.and_then(|id| cards.get(id).ok());
                 vec![
-                    paint_opt(card_opt, &e.id.to_string()),
-                    paint_opt(card_opt, e.name.as_str()),
-                    paint_opt(card_opt, card_opt.map(|c| c.name.as_str()).unwrap_or("-")),
-                    paint_opt(card_opt, &e.time_range.to_string()),
-                    paint_opt(card_opt, days.as_str()),
-                    paint_opt(card_opt, &e.recurring.to_string().to_uppercase()),
+                    paint_opt(card_opt, &f.task_id.to_string()),
+                    paint_opt(card_opt, f.name.as_str()),
+                    paint_opt(card_opt, &f.due.format("%Y-%m-%d").to_string()),
+                    paint_opt(card_opt, &format!("{:.0}%", f.on_time * 100.0)),
+                    paint_opt(card_opt, &date_or_missed(f.p50)),
+                    paint_opt(card_opt, &date_or_missed(f.p90)),
                 ]
             })
             .collect()
     }
 
-    pub fn card_rows(&self, cards: &Repository<Card>) -> Vec<Vec<String>> {
-        cards
-            .values(Sort::IdAsc)
-            .into_iter()
+    pub fn checklist_rows(&self, task: &Task) -> Vec<Vec<String>> {
+        task.checklist
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                vec![
+                    (i + 1).to_string(),
+                    item.text.clone(),
+                    item.hours
+                        .map(|h| format!("{h:.2}"))
+                        .unwrap_or_else(|| "-".to_string()),
+                    item.done.to_string().to_uppercase(),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn commit_rows(&self, commits: &[Commit]) -> Vec<Vec<String>> {
+        commits
+            .iter()
             .map(|c| {
                 vec![
-                    c.color.paint(c.id.to_string()),
-                    c.color.paint(&c.name),
-                    c.color.paint(c.color.to_string()),
+                    c.short_id().to_string(),
+                    c.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                    c.author.clone(),
+                    c.message.clone(),
                 ]
             })
             .collect()
@@ -144,6 +206,209 @@ impl DisplayDataBuilder {
     }
 }
 
+/// Cell text and ordering for the columns listed in `view::columns`.
+pub trait TableRow: BaseEntity {
+    /// Card whose color paints the row.
+    fn card_id(&self) -> Option<i32>;
+    fn cell(&self, column: &str, cards: &Repository<Card>) -> String;
+    fn compare(&self, other: &Self, column: &str, cards: &Repository<Card>) -> Ordering;
+    /// The value `show` gives a column; the table cell unless that folds in other fields.
+    fn detail_cell(&self, column: &str, cards: &Repository<Card>) -> String {
+        self.cell(column, cards)
+    }
+    /// Fields `show` lists after the columns because the table has none for them.
+    fn detail_fields(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+fn card_name(card_id: Option<i32>, cards: &Repository<Card>) -> Option<&str> {
+    card_id
+        .and_then(|id| cards.get(id).ok())
+        .map(|c| c.name.as_str())
+}
+
+fn tag_cell(card_id: Option<i32>, cards: &Repository<Card>) -> String {
+    card_name(card_id, cards).unwrap_or("-").to_string()
+}
+
+fn days_cell(days: &[DayOfWeek]) -> String {
+    if days.is_empty() {
+        return "-".to_string();
+    }
+    days.iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn by_name(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Entities without a card sort before those with one, then by card name.
+fn by_tag(a: Option<i32>, b: Option<i32>, cards: &Repository<Card>) -> Ordering {
+    let name = |id| card_name(id, cards).map(str::to_lowercase);
+    name(a).cmp(&name(b))
+}
+
+fn by_days(a: &[DayOfWeek], b: &[DayOfWeek]) -> Ordering {
+    let idx = |days: &[DayOfWeek]| days.iter().map(|d| *d as u8).collect::<Vec<_>>();
+    idx(a).cmp(&idx(b))
+}
+
+impl TableRow for Task {
+    fn card_id(&self) -> Option<i32> {
+        self.card_id
+    }
+
+    fn cell(&self, column: &str, cards: &Repository<Card>) -> String {
+        match column {
+            "id" => self.id.to_string(),
+            "name" => {
+                let mut name = self.name.clone();
+                if self.atomic {
+                    name.push_str(" (atomic)");
+                }
+                if self.completed {
+                    name.push_str(" (done)");
+                }
+                if let Some((done, total)) = self.progress() {
+                    name.push_str(&format!(" ({done}/{total})"));
+                }
+                name
+            }
+            "tag" => tag_cell(self.card_id, cards),
+            "hours" => format!("{:.2}", self.hours),
+            "due" => self.date.to_string(),
+            "remaining" => format!("{:.2}", self.remaining_hours),
+            "estimate" => self
+                .estimate
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: &str, cards: &Repository<Card>) -> Ordering {
+        match column {
+            "name" => by_name(&self.name, &other.name),
+            "tag" => by_tag(self.card_id, other.card_id, cards),
+            "hours" => self.hours.total_cmp(&other.hours),
+            "due" => self.date.0.cmp(&other.date.0),
+            "remaining" => self.remaining_hours.total_cmp(&other.remaining_hours),
+            "estimate" => {
+                let likely = |t: &Task| t.estimate.map(|e| e.likely);
+                match (likely(self), likely(other)) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (a, b) => a.is_some().cmp(&b.is_some()),
+                }
+            }
+            _ => self.id.cmp(&other.id),
+        }
+    }
+
+    fn detail_cell(&self, column: &str, cards: &Repository<Card>) -> String {
+        match column {
+            "name" => self.name.clone(),
+            _ => self.cell(column, cards),
+        }
+    }
+
+    fn detail_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("atomic", self.atomic.to_string()),
+            ("done", self.completed.to_string()),
+        ];
+        if let Some((done, total)) = self.progress() {
+            fields.push(("progress", format!("{done}/{total} items")));
+        }
+        fields
+    }
+}
+
+impl TableRow for Event {
+    fn card_id(&self) -> Option<i32> {
+        self.card_id
+    }
+
+    fn cell(&self, column: &str, cards: &Repository<Card>) -> String {
+        match column {
+            "id" => self.id.to_string(),
+            "name" => self.name.clone(),
+            "tag" => tag_cell(self.card_id, cards),
+            "time" => self.time_range.to_string(),
+            "days" => days_cell(&self.days),
+            "recurring" => self.recurring.to_string().to_uppercase(),
+            "hours" => format!("{:.2}", self.hours()),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: &str, cards: &Repository<Card>) -> Ordering {
+        match column {
+            "name" => by_name(&self.name, &other.name),
+            "tag" => by_tag(self.card_id, other.card_id, cards),
+            "time" => (self.time_range.start, self.time_range.end)
+                .cmp(&(other.time_range.start, other.time_range.end)),
+            "days" => by_days(&self.days, &other.days),
+            "recurring" => self.recurring.cmp(&other.recurring),
+            "hours" => self.hours().total_cmp(&other.hours()),
+            _ => self.id.cmp(&other.id),
+        }
+    }
+}
+
+impl TableRow for TaskTemplate {
+    fn card_id(&self) -> Option<i32> {
+        self.card_id
+    }
+
+    fn cell(&self, column: &str, cards: &Repository<Card>) -> String {
+        match column {
+            "id" => self.id.to_string(),
+            "name" => self.name.clone(),
+            "tag" => tag_cell(self.card_id, cards),
+            "hours" => format!("{:.2}", self.hours),
+            "days" => days_cell(&self.days),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: &str, cards: &Repository<Card>) -> Ordering {
+        match column {
+            "name" => by_name(&self.name, &other.name),
+            "tag" => by_tag(self.card_id, other.card_id, cards),
+            "hours" => self.hours.total_cmp(&other.hours),
+            "days" => by_days(&self.days, &other.days),
+            _ => self.id.cmp(&other.id),
+        }
+    }
+}
+
+impl TableRow for Card {
+    fn card_id(&self) -> Option<i32> {
+        Some(self.id)
+    }
+
+    fn cell(&self, column: &str, _cards: &Repository<Card>) -> String {
+        match column {
+            "id" => self.id.to_string(),
+            "name" => self.name.clone(),
+            "color" => self.color.to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: &str, _cards: &Repository<Card>) -> Ordering {
+        match column {
+            "name" => by_name(&self.name, &other.name),
+            "color" => self.color.to_string().cmp(&other.color.to_string()),
+            _ => self.id.cmp(&other.id),
+        }
+    }
+}
+
 fn paint_opt(card: Option<&Card>, s: &str) -> String {
     match card {
         Some(c) => c.color.paint(s),
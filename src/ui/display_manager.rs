@@ -1,8 +1,11 @@
 use crate::config::Config;
-use crate::core::models::{Card, Event, Task};
+use crate::core::models::{Card, Event, Task, TaskTemplate};
 use crate::core::repository::Repository;
 use crate::core::types::EntityType;
-use crate::ui::display_data::{DisplayDataBuilder, ScheduleSection};
+use crate::core::versions::Commit;
+use crate::core::view::TableView;
+use crate::scheduler::TaskForecast;
+use crate::ui::display_data::{DisplayDataBuilder, ScheduleSection, TableRow};
 use crate::ui::table_printer::TablePrinter;
 use crate::ui::width_util::WidthUtil;
 use chrono::NaiveDate;
@@ -70,35 +73,93 @@ impl DisplayManager {
         );
     }
 
-    pub fn display_tasks(&self, tasks: &Repository<Task>, cards: &Repository<Card>) {
-        let headers = ["ID", "NAME", "TAG", "HOURS", "DUE"];
+    pub fn display_tasks(
+        &self,
+        tasks: &Repository<Task>,
+        cards: &Repository<Card>,
+        view: &TableView,
+    ) {
+        let rows = self.data.entity_rows(tasks, cards, view);
+        self.print_entity_table("Tasks", view, &rows, "No tasks available.");
+    }
 
-        let rows = self.data.task_rows(tasks, cards);
+    pub fn display_events(
+        &self,
+        events: &Repository<Event>,
+        cards: &Repository<Card>,
+        view: &TableView,
+    ) {
+        let rows = self.data.entity_rows(events, cards, view);
+        self.print_entity_table("Events", view, &rows, "No events available.");
+    }
+
+    pub fn display_cards(&self, cards: &Repository<Card>, view: &TableView) {
+        let rows = self.data.entity_rows(cards, cards, view);
+        self.print_entity_table("Cards", view, &rows, "No cards available.");
+    }
 
+    pub fn display_templates(
+        &self,
+        templates: &Repository<TaskTemplate>,
+        cards: &Repository<Card>,
+        view: &TableView,
+    ) {
+        let rows = self.data.entity_rows(templates, cards, view);
+        self.print_entity_table("Templates", view, &rows, "No templates available.");
+    }
+
+    fn print_entity_table(&self, title: &str, view: &TableView, rows: &[Vec<String>], empty: &str) {
+        let headers = view.headers();
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+        self.printer
+            .print_table(title, &headers, rows, Some(empty), None);
+    }
+
+    pub fn display_details<T: TableRow>(
+        &self,
+        kind: EntityType,
+        entity: &T,
+        cards: &Repository<Card>,
+    ) {
+        let headers = ["FIELD", "VALUE"];
+        let rows = self.data.detail_rows(kind, entity, cards);
+        let title = format!("{kind} {}", entity.id());
         self.printer
-            .print_table("Tasks", &headers, &rows, Some("No tasks available."), None);
+            .print_table(&title, &headers, &rows, None, None);
     }
 
-    pub fn display_events(&self, events: &Repository<Event>, cards: &Repository<Card>) {
-        let headers = ["ID", "NAME", "TAG", "TIME", "DAYS", "RECURRING"];
+    pub fn display_checklist(&self, task: &Task) {
+        let headers = ["#", "ITEM", "HOURS", "DONE"];
+        let rows = self.data.checklist_rows(task);
+        let title = format!("Checklist of task {}", task.id);
+        self.printer.print_table(
+            &title,
+            &headers,
+            &rows,
+            Some("No checklist items yet."),
+            None,
+        );
+    }
 
-        let rows = self.data.event_rows(events, cards);
+    pub fn display_forecast(&self, forecasts: &[TaskForecast], cards: &Repository<Card>) {
+        let headers = ["ID", "NAME", "DUE", "ON TIME", "P50", "P90"];
+        let rows = self.data.forecast_rows(forecasts, cards);
 
         self.printer.print_table(
-            "Events",
+            "Forecast",
             &headers,
             &rows,
-            Some("No events available."),
+            Some("No open tasks to forecast."),
             None,
         );
     }
 
-    pub fn display_cards(&self, cards: &Repository<Card>) {
-        let headers = ["ID", "NAME", "COLOR"];
-        let rows = self.data.card_rows(cards);
+    pub fn display_history(&self, commits: &[Commit]) {
+        let headers = ["REV", "DATE", "AUTHOR", "MESSAGE"];
+        let rows = self.data.commit_rows(commits);
 
         self.printer
-            .print_table("Cards", &headers, &rows, Some("No cards available."), None);
+            .print_table("History", &headers, &rows, Some("No commits yet."), None);
     }
 
     pub fn display_entities_for(
@@ -107,11 +168,14 @@ impl DisplayManager {
         tasks: &Repository<Task>,
         events: &Repository<Event>,
         cards: &Repository<Card>,
+        templates: &Repository<TaskTemplate>,
+        view: &TableView,
     ) {
         match which {
-            EntityType::Task => self.display_tasks(tasks, cards),
-            EntityType::Event => self.display_events(events, cards),
-            EntityType::Card => self.display_cards(cards),
+            EntityType::Task => self.display_tasks(tasks, cards, view),
+            EntityType::Event => self.display_events(events, cards, view),
+            EntityType::Card => self.display_cards(cards, view),
+            EntityType::Template => self.display_templates(templates, cards, view),
         }
     }
 
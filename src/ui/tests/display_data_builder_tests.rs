@@ -1,7 +1,8 @@
 use crate::core::{
-    models::{BaseEntity, Card, Event, Task},
+    models::{BaseEntity, Card, ChecklistItem, Event, Task},
     repository::Repository,
-    types::{CardColor, Date},
+    types::{CardColor, Date, EntityType},
+    view::{SortKey, TableView},
 };
 use crate::ui::display_data::DisplayDataBuilder;
 
@@ -19,7 +20,7 @@ fn display_data_builder_builds_painted_rows() {
     task.set_id(1);
     tasks.insert(task);
 
-    let rows = builder.task_rows(&tasks, &cards);
+    let rows = builder.entity_rows(&tasks, &cards, &TableView::default_for(EntityType::Task));
     assert_eq!(
         rows[0],
         vec![
@@ -32,6 +33,35 @@ fn display_data_builder_builds_painted_rows() {
     );
 }
 
+#[test]
+fn detail_rows_show_the_task_name_apart_from_its_progress() {
+    let mut task = Task::new(
+        "Essay",
+        1.0,
+        None,
+        Date::try_from_str("2099-01-01").unwrap(),
+    );
+    task.set_id(1);
+    task.atomic = true;
+    let mut outline = ChecklistItem::new("Outline", Some(2.0));
+    outline.done = true;
+    task.checklist.push(outline);
+    task.checklist.push(ChecklistItem::new("Draft", Some(2.5)));
+    task.roll_up_checklist();
+
+    let rows = DisplayDataBuilder::new().detail_rows(EntityType::Task, &task, &Repository::new());
+    let value = |field: &str| {
+        rows.iter()
+            .find(|row| row[0] == field)
+            .map(|row| row[1].clone())
+    };
+    assert_eq!(value("name").as_deref(), Some("Essay"));
+    assert_eq!(value("hours").as_deref(), Some("4.50"));
+    assert_eq!(value("atomic").as_deref(), Some("true"));
+    assert_eq!(value("done").as_deref(), Some("false"));
+    assert_eq!(value("progress").as_deref(), Some("1/2 items"));
+}
+
 #[test]
 fn display_data_builder_sorts_schedule_rows_by_time() {
     let builder = DisplayDataBuilder::new();
@@ -59,3 +89,38 @@ fn display_data_builder_sorts_schedule_rows_by_time() {
     assert_eq!(rows[0][4], "8:00AM-9:00AM");
     assert_eq!(rows[1][4], "9:00AM-10:00AM");
 }
+
+#[test]
+fn entity_rows_follow_view_columns_and_sort_keys() {
+    let builder = DisplayDataBuilder::new();
+    let cards = Repository::<Card>::new();
+    let mut tasks = Repository::new();
+    let due = |d: &str| Date::try_from_str(d).unwrap();
+    tasks.insert(Task::new("a", 1.0, None, due("2099-01-02")));
+    tasks.insert(Task::new("b", 3.0, None, due("2099-01-01")));
+    tasks.insert(Task::new("c", 2.0, None, due("2099-01-02")));
+
+    let view = TableView {
+        columns: vec!["name".into(), "remaining".into()],
+        sort: vec![
+            SortKey {
+                column: "due".into(),
+                descending: false,
+            },
+            SortKey {
+                column: "hours".into(),
+                descending: true,
+            },
+        ],
+    };
+    let rows = builder.entity_rows(&tasks, &cards, &view);
+    assert_eq!(
+        rows,
+        vec![
+            vec!["b".to_string(), "3.00".to_string()],
+            vec!["c".to_string(), "2.00".to_string()],
+            vec!["a".to_string(), "1.00".to_string()],
+        ]
+    );
+    assert_eq!(view.headers(), vec!["NAME", "REMAINING"]);
+}
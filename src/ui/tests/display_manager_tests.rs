@@ -1,4 +1,5 @@
 use crate::config::Config;
+use crate::core::view::TableView;
 use crate::core::{
     models::{Card, Event, Task},
     repository::Repository,
@@ -23,7 +24,13 @@ fn write_sample_config(path: &PathBuf) {
       "task_overflow_policy": { "value": "allow", "description": "overflow" },
       "task_scheduling_order": { "value": "longest-task-first", "description": "order" },
       "schedule_start_date": { "value": null, "description": "start date" },
-      "file_logging_enabled": { "value": "True", "description": "Enable writing log messages to file." }
+      "file_logging_enabled": { "value": "True", "description": "Enable writing log messages to file." },
+      "task_packing_mode": { "value": "group-by-card", "description": "packing" },
+      "stable_rescheduling": { "value": "True", "description": "stable" },
+      "history_depth": { "value": 20, "description": "history" },
+      "card_delete_policy": { "value": "set-null", "description": "card delete" },
+      "default_sort": { "value": "task=due,-hours", "description": "sort" },
+      "default_columns": { "value": "", "description": "columns" }
     }
     "#;
     fs::write(path, json).unwrap();
@@ -75,7 +82,7 @@ fn display_manager_tasks_table_matches_expected() {
 
     let builder = DisplayDataBuilder::new();
     let headers = ["ID", "NAME", "TAG", "HOURS", "DUE"];
-    let rows = builder.task_rows(&tasks, &cards);
+    let rows = builder.entity_rows(&tasks, &cards, &TableView::default_for(EntityType::Task));
 
     let mut buf = Vec::new();
     DisplayManager::new()
@@ -111,7 +118,7 @@ fn display_manager_events_table_matches_expected() {
 
     let builder = DisplayDataBuilder::new();
     let headers = ["ID", "NAME", "TAG", "TIME", "DAYS", "RECURRING"];
-    let rows = builder.event_rows(&events, &cards);
+    let rows = builder.entity_rows(&events, &cards, &TableView::default_for(EntityType::Event));
 
     let mut buf = Vec::new();
     DisplayManager::new()
@@ -138,7 +145,7 @@ fn display_manager_cards_table_matches_expected() {
 
     let builder = DisplayDataBuilder::new();
     let headers = ["ID", "NAME", "COLOR"];
-    let rows = builder.card_rows(&cards);
+    let rows = builder.entity_rows(&cards, &cards, &TableView::default_for(EntityType::Card));
 
     let mut buf = Vec::new();
     DisplayManager::new()
@@ -242,7 +249,14 @@ fn display_entities_for_tasks_does_not_mutate_repo() {
     let cards: Repository<Card> = Repository::new();
 
     let before = tasks.len();
-    dm.display_entities_for(EntityType::Task, &tasks, &events, &cards);
+    dm.display_entities_for(
+        EntityType::Task,
+        &tasks,
+        &events,
+        &cards,
+        &Repository::new(),
+        &TableView::default_for(EntityType::Task),
+    );
     let after = tasks.len();
     assert_eq!(before, after);
 }
@@ -255,7 +269,14 @@ fn display_entities_for_events_does_not_mutate_repo() {
     let cards: Repository<Card> = Repository::new();
 
     let before = events.len();
-    dm.display_entities_for(EntityType::Event, &tasks, &events, &cards);
+    dm.display_entities_for(
+        EntityType::Event,
+        &tasks,
+        &events,
+        &cards,
+        &Repository::new(),
+        &TableView::default_for(EntityType::Event),
+    );
     let after = events.len();
     assert_eq!(before, after);
 }
@@ -268,7 +289,14 @@ fn display_entities_for_cards_does_not_mutate_repo() {
     let cards: Repository<Card> = Repository::new();
 
     let before = cards.len();
-    dm.display_entities_for(EntityType::Card, &tasks, &events, &cards);
+    dm.display_entities_for(
+        EntityType::Card,
+        &tasks,
+        &events,
+        &cards,
+        &Repository::new(),
+        &TableView::default_for(EntityType::Card),
+    );
     let after = cards.len();
     assert_eq!(before, after);
 }
@@ -8,3 +8,5 @@ mod config;
 mod core;
 #[path = "integration/persist.rs"]
 mod persist;
+#[path = "integration/scheduler.rs"]
+mod scheduler;
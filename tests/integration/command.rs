@@ -2,7 +2,7 @@ use planit::core::types::{CardColor, DayOfWeek};
 
 use crate::common::{
     ArgParser, CommandParser, build_context, execute_command, make_temp_dir, normalized_lines,
-    read_log_contents, run_with_input, write_valid_config,
+    read_log_contents, run_with_input, write_config_with_start, write_valid_config,
 };
 
 #[test]
@@ -35,6 +35,23 @@ fn main_can_schedule_after_adding_task() {
     );
 }
 
+#[test]
+fn forecast_command_prints_probabilities() {
+    let dir = make_temp_dir("command");
+    write_config_with_start(&dir, "2099-01-01");
+    let input = "task \"Essay\" 3 ~2/3/8 @ 2099-01-02\nforecast 50\nforecast 0\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("FORECAST"), "stdout was: {stdout}");
+    assert!(stdout.contains("Essay"));
+    assert!(stdout.contains("100%"));
+    assert!(stdout.contains("Forecast complete (50 runs)."));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Expected a positive number of runs."));
+}
+
 #[test]
 fn man_command_prints_general_manual() {
     let dir = make_temp_dir("command");
@@ -59,11 +76,8 @@ fn man_command_prints_task_manual() {
 
     assert!(output.status.success());
     let stdout_lines = normalized_lines(&output.stdout);
-    assert!(
-        stdout_lines
-            .iter()
-            .any(|line| line == "task \"<name>\" <hours> [cardId] @ <date>")
-    );
+    assert!(stdout_lines.iter().any(|line| line
+        == "task \"<name>\" <hours> [estimate] [cardId] [templateId] [markers] @ <date> [details]"));
 }
 
 #[test]
@@ -106,6 +120,223 @@ fn task_add_modify_and_delete_flow_succeeds() {
     assert_eq!(ctx.tasks.len(), 0, "task should be deleted from repository");
 }
 
+#[test]
+fn mod_by_field_changes_only_named_fields() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let arg_parser = ArgParser::new();
+    let command_parser = CommandParser::new();
+    let mut ctx = build_context(&dir);
+
+    for line in [
+        "card \"School\" blue",
+        "task \"Essay\" 2 @ 2099-01-01",
+        "schedule",
+        "mod task 1 name=\"Long essay\" card=+C1",
+    ] {
+        execute_command(line, &arg_parser, &command_parser, &mut ctx);
+    }
+    let t = ctx.tasks.get(1).expect("task 1 should exist");
+    assert_eq!(t.name, "Long essay");
+    assert_eq!(t.card_id, Some(1));
+    assert!(
+        !t.subtasks.is_empty(),
+        "name and card changes keep the schedule"
+    );
+
+    execute_command("mod task 1 hours=5", &arg_parser, &command_parser, &mut ctx);
+    let t = ctx.tasks.get(1).unwrap();
+    assert_eq!(t.name, "Long essay");
+    assert!((t.hours - 5.0).abs() < f32::EPSILON);
+    assert!(t.subtasks.is_empty());
+
+    execute_command(
+        "event true \"Standup\" @ mon 9:00AM-10:00AM",
+        &arg_parser,
+        &command_parser,
+        &mut ctx,
+    );
+    execute_command(
+        "mod event 1 time=10-11AM",
+        &arg_parser,
+        &command_parser,
+        &mut ctx,
+    );
+    let e = ctx.events.get(1).unwrap();
+    assert_eq!(e.time_range.to_string(), "10:00AM-11:00AM");
+    assert_eq!(e.name, "Standup");
+    assert_eq!(e.days, vec![DayOfWeek::Mon]);
+}
+
+#[test]
+fn bulk_commands_apply_to_every_selected_entity_or_none() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = [
+        "card \"Spare\" red",
+        "card \"Work\" blue",
+        "task \"A\" 2 +C2 @ 2099-01-01",
+        "task \"B\" 2 @ 2099-01-02",
+        "task \"C\" 2 @ 2099-01-03",
+        "task \"D\" 2 +C2 @ 2099-01-04",
+        "del task 2-3 --yes",
+        "mod task where card=+C2 set hours=7 --yes",
+        "del card 1-2 --yes",
+        "task",
+        "card",
+        "exit",
+    ]
+    .join("\n");
+    let output = run_with_input(&dir, &format!("{input}\n"));
+    assert!(output.status.success());
+
+    let stdout = normalized_lines(&output.stdout);
+    assert!(
+        stdout
+            .iter()
+            .any(|l| l == "Deleted 2 task(s) with ids 2-3.")
+    );
+    assert!(
+        stdout
+            .iter()
+            .any(|l| l == "Modified 2 task(s) with ids 1,4.")
+    );
+    let rows: Vec<&String> = stdout.iter().filter(|l| l.contains(" | ")).collect();
+    assert!(
+        rows.iter()
+            .any(|l| l.starts_with("1  | A") && l.contains("7.00"))
+    );
+    assert!(
+        !rows
+            .iter()
+            .any(|l| l.contains("| B ") || l.contains("| C "))
+    );
+    // Card 2 is still used, so the whole delete is rolled back and card 1 is kept as well.
+    assert!(rows.iter().any(|l| l.contains("Spare")));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Card 2 is used by task 1, task 4"),
+        "stderr: {stderr}"
+    );
+}
+
+#[test]
+fn show_lists_details_that_survive_save_and_read() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = [
+        "task \"Essay\" 3 @ 2099-01-01 note=\"Chapters 3-4\" meta.course=CS101",
+        "task \"Quiz\" 1 @ 2099-01-02",
+        "mod task 2 links=https://a.org meta.course=MA201",
+        "save \"plan\"",
+        "read \"schedules/plan.json\"",
+        "show task 1",
+        "show task 2",
+        "find task meta.course=cs101 --columns id,name",
+        "exit",
+    ]
+    .join("\n");
+    let output = run_with_input(&dir, &format!("{input}\n"));
+    assert!(output.status.success());
+
+    let stdout = normalized_lines(&output.stdout);
+    let has_row = |field: &str, value: &str| {
+        stdout.iter().any(|l| {
+            let cells: Vec<&str> = l.split(" | ").map(str::trim).collect();
+            cells == [field, value]
+        })
+    };
+    assert!(stdout.iter().any(|l| l == "TASK 1"));
+    assert!(has_row("note", "Chapters 3-4"));
+    assert!(has_row("meta.course", "CS101"));
+    assert!(has_row("links", "https://a.org"));
+    assert!(has_row("meta.course", "MA201"));
+    let rows: Vec<&String> = stdout
+        .iter()
+        .filter(|l| l.starts_with("1  |") || l.starts_with("2  |"))
+        .collect();
+    assert_eq!(rows.len(), 1, "only the CS101 task matches: {rows:?}");
+    assert!(rows[0].contains("Essay"));
+}
+
+#[test]
+fn item_command_manages_a_checklist_that_survives_save_and_read() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = [
+        "task \"Essay\" 6 @ 2099-01-01",
+        "item add 1 \"Outline the essay\" ~1.5",
+        "item add 1 Draft ~3",
+        "item add 1 Proofread",
+        "item check 1 1",
+        "item move 1 3 1",
+        "item check 1 9",
+        "save \"plan\"",
+        "read \"schedules/plan.json\"",
+        "item 1",
+        "task --columns id,name,hours",
+        "exit",
+    ]
+    .join("\n");
+    let output = run_with_input(&dir, &format!("{input}\n"));
+    assert!(output.status.success());
+
+    let stdout = normalized_lines(&output.stdout);
+    let rows: Vec<Vec<&str>> = stdout
+        .iter()
+        .map(|l| l.split(" | ").map(str::trim).collect())
+        .collect();
+    assert!(rows.contains(&vec!["1", "Proofread", "-", "FALSE"]));
+    assert!(rows.contains(&vec!["2", "Outline the essay", "1.50", "TRUE"]));
+    assert!(rows.contains(&vec!["3", "Draft", "3.00", "FALSE"]));
+    assert!(
+        rows.contains(&vec!["1", "Essay (1/3)", "4.50"]),
+        "{stdout:#?}"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let all = format!("{}{}", stdout.join("\n"), stderr);
+    assert!(all.contains("Task 1 has no item 9 (it has 3 items)."));
+}
+
+#[test]
+fn template_schedule_and_done_flow_succeeds() {
+    let dir = make_temp_dir("command");
+    write_config_with_start(&dir, "2099-01-01");
+    let arg_parser = ArgParser::new();
+    let command_parser = CommandParser::new();
+    let mut ctx = build_context(&dir);
+
+    execute_command(
+        "template \"Status\" 2 @ fri",
+        &arg_parser,
+        &command_parser,
+        &mut ctx,
+    );
+    assert_eq!(ctx.templates.len(), 1, "template should be inserted");
+
+    execute_command("schedule", &arg_parser, &command_parser, &mut ctx);
+    assert_eq!(ctx.tasks.len(), 1, "one friday falls inside the window");
+    let t = ctx.tasks.get(1).expect("generated task should exist");
+    assert_eq!(t.name, "Status");
+    assert_eq!(t.date.to_string(), "2099-01-02");
+    assert!(!t.subtasks.is_empty());
+
+    execute_command("done task 1", &arg_parser, &command_parser, &mut ctx);
+    execute_command("schedule", &arg_parser, &command_parser, &mut ctx);
+    let t = ctx.tasks.get(1).expect("generated task should remain");
+    assert!(t.completed);
+    assert!(t.subtasks.is_empty());
+    assert_eq!(
+        ctx.tasks.len(),
+        1,
+        "completed occurrence is not regenerated"
+    );
+
+    execute_command("del template 1", &arg_parser, &command_parser, &mut ctx);
+    assert_eq!(ctx.templates.len(), 0);
+    assert!(ctx.tasks.get(1).unwrap().origin.is_none());
+}
+
 #[test]
 fn event_add_modify_and_delete_flow_succeeds() {
     let dir = make_temp_dir("command");
@@ -190,6 +421,87 @@ fn card_add_modify_delete_flow_succeeds() {
     assert_eq!(ctx.cards.len(), 0, "card should be deleted from repository");
 }
 
+#[test]
+fn deleting_a_used_card_follows_the_chosen_policy() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = "card \"Work\" red\ntask \"Essay\" 2 +C1 @ 2099-01-01\ndel card 1\ndel card 1 --set-null\ncard \"Home\" blue\nevent False \"Gym\" +C2 @ 9:00AM-10:00AM\ndel card 2 --cascade\nevent\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Card 1 is used by task 1. Use --cascade"),
+        "stderr was: {stderr}"
+    );
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line == "cleared card on task 1")
+    );
+    assert!(stdout_lines.iter().any(|line| line == "deleted event 1"));
+    assert!(
+        !stdout_lines
+            .iter()
+            .skip_while(|line| *line != "deleted event 1")
+            .any(|line| line.contains("Gym"))
+    );
+}
+
+#[test]
+fn find_lists_only_entities_matching_every_filter() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = "card \"Work\" red\ntask \"Weekly report\" 2 +C1 @ 2099-01-01\ntask \"Report draft\" 5 @ 2099-01-01\ntask \"Groceries\" 1 +C1 @ 2099-02-01\nfind task card=+C1 name~\"weekly report\"\nfind task color=red\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    let found: Vec<&String> = stdout_lines
+        .iter()
+        .skip_while(|line| *line != "TASKS")
+        .collect();
+    assert!(found.iter().any(|line| line.contains("Weekly report")));
+    assert!(!found.iter().any(|line| line.contains("Report draft")));
+    assert!(!found.iter().any(|line| line.contains("Groceries")));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unknown filter field: 'color'. Valid fields: name, hours, due"),
+        "stderr was: {stderr}"
+    );
+}
+
+#[test]
+fn listing_sorts_rows_and_shows_chosen_columns() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = "task \"Short\" 1 @ 2099-01-02\ntask \"Long\" 4 @ 2099-01-02\ntask \"First\" 2 @ 2099-01-01\ntask --sort due,-hours --columns name,due\ntask --columns id,priority\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    let table: Vec<&String> = stdout_lines
+        .iter()
+        .skip_while(|line| *line != "TASKS")
+        .collect();
+    assert!(table.iter().any(|line| line.starts_with("NAME")));
+    let names: Vec<&str> = table
+        .iter()
+        .filter_map(|line| line.split(" | ").next())
+        .map(str::trim)
+        .filter(|cell| ["Short", "Long", "First"].contains(cell))
+        .collect();
+    assert_eq!(names, vec!["First", "Long", "Short"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unknown task column: 'priority'. Valid columns: id, name, tag"),
+        "stderr was: {stderr}"
+    );
+}
+
 #[test]
 fn parse_error_does_not_stop_followup_command() {
     let dir = make_temp_dir("command");
@@ -311,3 +623,32 @@ fn log_command_prints_session_log_without_logging_itself() {
         "log file should include the logged command:\n{log_text}"
     );
 }
+
+#[test]
+fn undo_and_redo_revert_entity_and_config_changes() {
+    let dir = make_temp_dir("command");
+    write_valid_config(&dir);
+    let input = "task \"Essay\" 2 @ 2099-01-01\nmod task 1 \"Essay\" 5 @ 2099-01-01\nundo\nredo\nundo\nundo\nundo\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line == "Undid 'mod task 1 \"Essay\" 5 @ 2099-01-01'.")
+    );
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("changed task 1: "))
+    );
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line == "Redid 'mod task 1 \"Essay\" 5 @ 2099-01-01'.")
+    );
+    assert!(stdout_lines.iter().any(|line| line == "removed task 1"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Nothing to undo."), "stderr was: {stderr}");
+}
@@ -149,7 +149,10 @@ pub fn build_context(dir: &PathBuf) -> AppContext {
         tasks: Repository::<Task>::new(),
         events: Repository::<Event>::new(),
         cards: Repository::<Card>::new(),
+        templates: Repository::new(),
         logger,
+        history: Default::default(),
+        workspace: Default::default(),
         startup_displayed: false,
         config_path,
         schedules_dir,
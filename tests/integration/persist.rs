@@ -1,12 +1,16 @@
-use planit::core::persist::SaveFile;
+use planit::core::migrations::SAVE_VERSION;
+use planit::core::persist::{SaveFile, load_save_file, load_state};
+use planit::core::repository::Sort;
 
 use crate::common::{
-    ArgParser, CommandParser, build_context, execute_command, make_temp_dir, run_with_input,
-    write_valid_config,
+    ArgParser, CommandParser, build_context, execute_command, make_temp_dir, normalized_lines,
+    run_with_input, write_valid_config,
 };
 use std::fs;
 use std::path::PathBuf;
 
+const SAVES_DIR: &str = "tests/integration/fixtures/saves";
+
 fn write_save_file(path: &PathBuf, save_file: &SaveFile) {
     let contents = serde_json::to_string_pretty(save_file).unwrap();
     fs::write(path, contents).unwrap();
@@ -31,6 +35,7 @@ fn read_command_loads_cards() {
     let save_path = dir.join("cards.json");
     let save_file = SaveFile {
         cards: vec![vec!["\"Card\"".into(), "RED".into()]],
+        templates: Vec::new(),
         events: Vec::new(),
         tasks: Vec::new(),
     };
@@ -54,6 +59,7 @@ fn read_command_loads_tasks_with_card_mapping() {
     let save_path = dir.join("tasks.json");
     let save_file = SaveFile {
         cards: vec![vec!["\"Tag\"".into(), "GREEN".into()]],
+        templates: Vec::new(),
         events: Vec::new(),
         tasks: vec![vec![
             "\"Task\"".into(),
@@ -75,3 +81,554 @@ fn read_command_loads_tasks_with_card_mapping() {
     let task = ctx.tasks.get(1).unwrap();
     assert_eq!(task.card_id, Some(1));
 }
+
+#[test]
+fn commit_history_checkout_and_diff_track_plan_versions() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let input = "task \"Essay\" 2 @ 2099-01-01\ncommit \"first\"\ntask \"Lab\" 3 @ 2099-01-02\ncommit \"second\"\ncommit \"again\"\nhistory\ndiff HEAD~1 HEAD\ncheckout HEAD~1\ntask\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Committed ") && line.ends_with("'first'"))
+    );
+    assert!(stdout_lines.iter().any(|line| line.contains("second")));
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line == "+ task \"Lab\" 3 @ 2099-01-02")
+    );
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Checked out "))
+    );
+    assert!(
+        !stdout_lines
+            .iter()
+            .skip_while(|line| !line.starts_with("Checked out "))
+            .any(|line| line.contains("Lab"))
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Nothing to commit"), "stderr was: {stderr}");
+    assert!(dir.join("schedules").join(".planit").join("HEAD").exists());
+}
+
+#[test]
+fn merge_combines_changes_made_on_two_branches() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let input = "task \"Essay\" 2 @ 2099-01-01\ncommit \"start\"\nbranch teammate\ncheckout teammate\ntask \"Lab\" 3 @ 2099-01-02\ncommit \"lab\"\ncheckout main\nmod task 1 \"Essay\" 4 @ 2099-01-01\ncommit \"longer essay\"\nmerge teammate\nbranch\ntask\nexit\n";
+    let output = run_with_input(&dir, input);
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Switched to branch 'teammate'"))
+    );
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Merged teammate into main as ")
+                && line.ends_with("(0 conflict(s) resolved)"))
+    );
+    assert!(stdout_lines.iter().any(|line| line.starts_with("* main ")));
+    let after_merge: Vec<&String> = stdout_lines
+        .iter()
+        .skip_while(|line| !line.starts_with("Merged "))
+        .collect();
+    assert!(after_merge.iter().any(|line| line.contains("Lab")));
+    assert!(
+        after_merge
+            .iter()
+            .any(|line| line.contains("Essay") && line.contains('4'))
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("error"), "stderr was: {stderr}");
+}
+
+#[test]
+fn clean_exit_clears_the_journal_and_a_leftover_one_is_offered_for_replay() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let journal = dir.join("schedules").join(".journal");
+
+    let output = run_with_input(&dir, "task \"Essay\" 2 @ 2099-01-01\nexit\n");
+    assert!(output.status.success());
+    assert!(!journal.exists());
+
+    // What a session that was killed after these commands leaves behind.
+    fs::create_dir_all(&journal).unwrap();
+    fs::write(
+        journal.join("commands"),
+        "task \"Essay\" 2 @ 2099-01-01\ntask \"Lab\" 3 @ 2099-01-02\ndel task 1\nsave \"recovered\"\n",
+    )
+    .unwrap();
+    let output = run_with_input(&dir, "y\n");
+
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("The last session did not exit cleanly. 4 change(s)"))
+    );
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line == "Recovered 4 journaled change(s).")
+    );
+    let saved = fs::read_to_string(dir.join("schedules").join("recovered.json")).unwrap();
+    let saved: SaveFile = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved.tasks.len(), 1);
+    assert_eq!(saved.tasks[0][0], "\"Lab\"");
+    assert!(!journal.exists());
+}
+
+#[test]
+fn workspace_is_autosaved_and_loaded_on_the_next_start() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let config_path = dir.join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["workspace"] = serde_json::json!({ "value": "main", "description": "workspace" });
+    config["autosave_interval"] = serde_json::json!({ "value": 5, "description": "autosave" });
+    fs::write(&config_path, config.to_string()).unwrap();
+
+    // Fewer changes than the interval are still saved on exit.
+    let output = run_with_input(&dir, "task \"Essay\" 2 @ 2099-01-01\nexit\n");
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines.iter().any(
+            |line| line == "Workspace main.json: 1 unsaved change(s), autosave every 5 changes"
+        )
+    );
+    assert!(dir.join("schedules").join("main.json").exists());
+
+    let output = run_with_input(&dir, "task\nsave\nexit\n");
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Loaded workspace from "))
+    );
+    assert!(stdout_lines.iter().any(|line| line.contains("Essay")));
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Saved state to ") && line.ends_with("main.json"))
+    );
+}
+
+#[test]
+fn every_historical_save_file_loads_into_the_current_build() {
+    let expected = [
+        ("v1-baseline.json", 1, (2, 0, 2, 2)),
+        ("v1-templates.json", 1, (1, 1, 1, 3)),
+        ("v1-details.json", 1, (1, 0, 1, 1)),
+        ("v1-uids-checklists.json", 1, (1, 0, 0, 1)),
+        ("v2-current.json", 2, (1, 1, 1, 2)),
+        ("v3-typed.json", SAVE_VERSION, (1, 1, 1, 2)),
+    ];
+    let mut corpus = fs::read_dir(SAVES_DIR)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    corpus.sort();
+    let mut names = expected.iter().map(|(name, ..)| *name).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        corpus, names,
+        "every file in the corpus needs an expectation"
+    );
+
+    for (name, version, (cards, templates, events, tasks)) in expected {
+        let path = PathBuf::from(SAVES_DIR).join(name);
+        let upgraded = load_save_file(&path).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(upgraded.from_version, version, "{name}");
+
+        let dir = make_temp_dir("persist");
+        write_valid_config(&dir);
+        let mut ctx = build_context(&dir);
+        load_state(&mut ctx, &path).unwrap_or_else(|e| panic!("{name}: {e}"));
+        assert_eq!(
+            (
+                ctx.cards.len(),
+                ctx.templates.len(),
+                ctx.events.len(),
+                ctx.tasks.len()
+            ),
+            (cards, templates, events, tasks),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn historical_save_files_keep_what_each_format_recorded() {
+    let load = |name: &str| {
+        let dir = make_temp_dir("persist");
+        write_valid_config(&dir);
+        let mut ctx = build_context(&dir);
+        load_state(&mut ctx, &PathBuf::from(SAVES_DIR).join(name)).unwrap();
+        ctx
+    };
+
+    let ctx = load("v1-baseline.json");
+    let essay = ctx.tasks.get(1).unwrap();
+    assert_eq!((essay.name.as_str(), essay.hours), ("Essay", 3.0));
+    assert_eq!(essay.card_id, Some(1));
+    assert_eq!(ctx.cards.get(2).unwrap().name, "Home");
+
+    let ctx = load("v1-templates.json");
+    let essay = ctx.tasks.get(1).unwrap();
+    assert!(essay.atomic);
+    assert!(essay.estimate.is_some());
+    assert_eq!(
+        ctx.tasks
+            .get(2)
+            .unwrap()
+            .origin
+            .as_ref()
+            .unwrap()
+            .template_id,
+        1
+    );
+    assert!(ctx.tasks.get(3).unwrap().completed);
+
+    let ctx = load("v1-details.json");
+    assert_eq!(
+        ctx.cards.get(1).unwrap().details.notes.as_deref(),
+        Some("Day job")
+    );
+    let essay = ctx.tasks.get(1).unwrap();
+    assert_eq!(essay.details.links.len(), 2);
+    assert_eq!(essay.details.meta["course"], "CS101");
+
+    let ctx = load("v1-uids-checklists.json");
+    let essay = ctx.tasks.get(1).unwrap();
+    assert!(!essay.uid.as_str().is_empty());
+    assert_eq!(essay.checklist.len(), 2);
+    assert!(essay.checklist[0].done);
+
+    let ctx = load("v2-current.json");
+    let report = ctx.tasks.get(2).unwrap();
+    assert!(report.completed);
+    assert_eq!(report.checklist[0].text, "Gather numbers");
+    assert_eq!(
+        ctx.cards.get(1).unwrap().uid.as_str(),
+        "01M5872EV99EJX8SMEE6T2B0MH"
+    );
+
+    let ctx = load("v3-typed.json");
+    let essay = ctx.tasks.get(1).unwrap();
+    assert!(essay.atomic);
+    assert!(essay.checklist[0].done);
+    assert_eq!(essay.details.links, ["https://a.org", "https://b.org"]);
+    let report = ctx.tasks.get(2).unwrap();
+    assert!(report.completed);
+    assert_eq!(report.origin.as_ref().unwrap().template_id, 1);
+    assert_eq!(ctx.events.get(1).unwrap().details.meta["room"], "B2");
+}
+
+#[test]
+fn read_reports_upgraded_files_and_refuses_newer_ones() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let old = PathBuf::from(SAVES_DIR).join("v1-baseline.json");
+    let old = fs::canonicalize(old).unwrap();
+    let newer = dir.join("newer.json");
+    fs::write(
+        &newer,
+        r#"{"version": 99, "cards": [], "templates": [], "events": [], "tasks": []}"#,
+    )
+    .unwrap();
+
+    let input = format!(
+        "read \"{}\"\nread \"{}\"\ncard\nsave \"upgraded\"\nexit\n",
+        old.display(),
+        newer.display()
+    );
+    let output = run_with_input(&dir, &input);
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.ends_with("(upgraded from save format 1)"))
+    );
+    let stderr_lines = normalized_lines(&output.stderr);
+    assert!(stderr_lines.iter().any(|line| line.contains(
+        "This save file uses format version 99, but this build of planit reads versions up to"
+    )));
+    // The refused file leaves the plan read before it in place.
+    assert!(stdout_lines.iter().any(|line| line.contains("Home")));
+
+    let saved = fs::read_to_string(dir.join("schedules").join("upgraded.json")).unwrap();
+    let saved: serde_json::Value = serde_json::from_str(&saved).unwrap();
+    assert_eq!(saved["version"], SAVE_VERSION);
+}
+
+#[test]
+fn typed_save_format_is_written_by_save_and_converted_back_to_tokens() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let config_path = dir.join("config.json");
+    let mut config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    config["save_format"] = serde_json::json!({ "value": "typed", "description": "format" });
+    fs::write(&config_path, config.to_string()).unwrap();
+
+    let schedules = dir.join("schedules");
+    let input = format!(
+        "card \"Work\" BLUE\ntask \"Essay\" 2 +C1 @ 2099-01-01\nsave \"typed\"\nconvert \"{}\" \"{}\"\nexit\n",
+        schedules.join("typed.json").display(),
+        schedules.join("tokens.json").display()
+    );
+    let output = run_with_input(&dir, &input);
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Converted ") && line.ends_with("(tokens format)"))
+    );
+
+    let typed = fs::read_to_string(schedules.join("typed.json")).unwrap();
+    let typed: serde_json::Value = serde_json::from_str(&typed).unwrap();
+    assert_eq!(typed["format"], "typed");
+    assert_eq!(typed["tasks"][0]["name"], "Essay");
+    assert_eq!(typed["tasks"][0]["card_id"], 1);
+    assert_eq!(typed["cards"][0]["color"], "BLUE");
+
+    let tokens = fs::read_to_string(schedules.join("tokens.json")).unwrap();
+    let tokens: SaveFile = serde_json::from_str(&tokens).unwrap();
+    assert_eq!(tokens.tasks[0][..3], ["\"Essay\"", "2", "+C1"]);
+
+    // read takes either format.
+    for name in ["typed.json", "tokens.json"] {
+        let mut ctx = build_context(&dir);
+        load_state(&mut ctx, &schedules.join(name)).unwrap();
+        assert_eq!(ctx.tasks.get(1).unwrap().card_id, Some(1), "{name}");
+    }
+}
+
+#[test]
+fn import_merges_a_save_file_into_the_current_plan() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let path = dir.join("shared.json");
+    let save_file = SaveFile {
+        cards: vec![vec!["\"Work\"".into(), "RED".into()]],
+        templates: Vec::new(),
+        events: Vec::new(),
+        tasks: vec![
+            vec![
+                "\"Essay\"".into(),
+                "2".into(),
+                "+C1".into(),
+                "@".into(),
+                "2099-01-01".into(),
+            ],
+            vec![
+                "\"Slides\"".into(),
+                "1".into(),
+                "@".into(),
+                "2099-01-02".into(),
+            ],
+        ],
+    };
+    write_save_file(&path, &save_file);
+
+    let input = format!(
+        "card \"Home\" BLUE\ncard \"Work\" RED\ntask \"Essay\" 5 @ 2099-03-01\nimport \"{0}\" --rename\nimport \"{0}\" --sideways\nsave \"merged\"\nexit\n",
+        path.display()
+    );
+    let output = run_with_input(&dir, &input);
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(stdout_lines.iter().any(|line| line.starts_with(
+        "Imported 0 card(s) (1 matched existing), 0 template(s), 0 event(s), 2 task(s), 1 task(s) renamed from "
+    )));
+    let stderr_lines = normalized_lines(&output.stderr);
+    assert!(
+        stderr_lines
+            .iter()
+            .any(|line| line.contains("Unrecognized argument: '--sideways'"))
+    );
+
+    let mut ctx = build_context(&dir);
+    load_state(&mut ctx, &dir.join("schedules").join("merged.json")).unwrap();
+    assert_eq!(ctx.cards.len(), 2);
+    let tasks = ctx
+        .tasks
+        .values(Sort::IdAsc)
+        .into_iter()
+        .map(|t| (t.name.clone(), t.card_id))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tasks,
+        [
+            ("Essay".to_string(), None),
+            ("Essay (2)".to_string(), Some(2)),
+            ("Slides".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn read_preview_shows_the_file_and_its_changes_without_loading_it() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let path = dir.join("plan.json");
+    let save_file = SaveFile {
+        cards: vec![vec!["\"Work\"".into(), "RED".into()]],
+        templates: Vec::new(),
+        events: Vec::new(),
+        tasks: vec![vec![
+            "\"Essay\"".into(),
+            "2".into(),
+            "+C1".into(),
+            "@".into(),
+            "2099-01-01".into(),
+        ]],
+    };
+    write_save_file(&path, &save_file);
+    let broken = dir.join("broken.json");
+    fs::write(
+        &broken,
+        r#"{"tasks": [["\"Essay\"", "2", "+C3", "@", "2099-01-01"]]}"#,
+    )
+    .unwrap();
+
+    let input = format!(
+        "card \"Home\" BLUE\nread \"{}\" --preview\nread \"{}\" --preview\nundo\nsave \"after\"\nexit\n",
+        path.display(),
+        broken.display()
+    );
+    let output = run_with_input(&dir, &input);
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    let expected = [
+        format!(
+            "Preview of {} (upgraded from save format 1): 1 card(s), 0 template(s), 0 event(s), 1 task(s)",
+            path.display()
+        ),
+        "Reading it would change the current plan:".to_string(),
+        "- card \"Home\" BLUE".to_string(),
+        "+ card \"Work\" RED".to_string(),
+        "+ task \"Essay\" 2 +C1 @ 2099-01-01".to_string(),
+        "Nothing was loaded.".to_string(),
+    ];
+    for line in &expected {
+        assert!(stdout_lines.contains(line), "missing {line:?}");
+    }
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.contains("| Essay | Work |"))
+    );
+    let stderr_lines = normalized_lines(&output.stderr);
+    assert!(stderr_lines.iter().any(|line| line.contains(
+        "Invalid save file at tasks row 1 (\"Essay\" 2 +C3 @ 2099-01-01). Parse error: Card id 3 does not exist."
+    )));
+
+    // Previews are not changes: undo reverts the card added before them.
+    let mut ctx = build_context(&dir);
+    load_state(&mut ctx, &dir.join("schedules").join("after.json")).unwrap();
+    assert_eq!(ctx.cards.len(), 0);
+    assert_eq!(ctx.tasks.len(), 0);
+}
+
+#[test]
+fn plan_settings_saved_with_a_plan_are_offered_when_it_is_read() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+    let config_path = dir.join("config.json");
+    let set_range = |range: &str| {
+        let mut config: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        config["range"]["value"] = serde_json::json!(range);
+        fs::write(&config_path, config.to_string()).unwrap();
+    };
+
+    set_range("7:00AM-3:00PM");
+    let output = run_with_input(
+        &dir,
+        "card \"Work\" RED\nsave \"shared\" --with-config\nexit\n",
+    );
+    assert!(output.status.success());
+    let saved = dir.join("schedules").join("shared.json");
+    let contents: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&saved).unwrap()).unwrap();
+    assert_eq!(contents["config"]["RANGE"], "7:00AM-3:00PM");
+    assert!(contents["config"].get("WORKSPACE").is_none());
+
+    set_range("9:00AM-5:00PM");
+    let input = format!(
+        "read \"{0}\" --preview\nread \"{0}\" --yes\nundo\nexit\n",
+        saved.display()
+    );
+    let output = run_with_input(&dir, &input);
+    assert!(output.status.success());
+    let stdout_lines = normalized_lines(&output.stdout);
+    for line in [
+        "It was saved with different plan settings:",
+        "The file was saved with different plan settings:",
+        "RANGE: 9:00AM-5:00PM -> 7:00AM-3:00PM",
+        "Applied 1 plan setting(s).",
+        "config RANGE: 7:00AM-3:00PM -> 9:00AM-5:00PM",
+    ] {
+        assert!(stdout_lines.iter().any(|l| l == line), "missing {line:?}");
+    }
+    let config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(
+        config["range"]["value"], "9:00AM-5:00PM",
+        "undo restores it"
+    );
+
+    let output = run_with_input(&dir, &format!("read \"{}\" --yes\nexit\n", saved.display()));
+    assert!(output.status.success());
+    let config: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config["range"]["value"], "7:00AM-3:00PM");
+}
+
+#[test]
+fn bare_save_file_names_are_looked_up_in_the_schedules_directory() {
+    let dir = make_temp_dir("persist");
+    write_valid_config(&dir);
+
+    let input = [
+        "task \"Essay\" 2 @ 2099-01-01",
+        "save \"plan\"",
+        "convert \"plan.json\" \"plan-typed.json\"",
+        "import \"plan-typed.json\" --rename",
+        "read \"plan.json\" --yes",
+        "exit",
+    ]
+    .join("\n");
+    let output = run_with_input(&dir, &format!("{input}\n"));
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.trim().is_empty(), "stderr: {stderr}");
+
+    let stdout_lines = normalized_lines(&output.stdout);
+    assert!(
+        stdout_lines
+            .iter()
+            .any(|line| line.starts_with("Imported ") && line.contains("1 task(s) renamed"))
+    );
+    assert!(dir.join("schedules").join("plan-typed.json").exists());
+    assert!(!dir.join("plan-typed.json").exists());
+}
@@ -0,0 +1,43 @@
+use chrono::NaiveDate;
+use planit::core::models::{Card, Event, Task, TaskTemplate};
+use planit::core::repository::Repository;
+use planit::core::types::{
+    Date, TaskOverflowPolicy, TaskPackingMode, TaskSchedulingOrder, TimeRange,
+};
+use planit::scheduler::{ScheduleOptions, ScheduleSnapshot, plan_schedule};
+
+#[test]
+fn library_plans_snapshot_without_mutating_it() {
+    let date = NaiveDate::from_ymd_opt(2099, 1, 1).unwrap();
+    let mut tasks = Repository::<Task>::new();
+    tasks.insert(Task::new("Essay", 3.0, None, Date(date)));
+    let events = Repository::<Event>::new();
+    let cards = Repository::<Card>::new();
+    let templates = Repository::<TaskTemplate>::new();
+    let options = ScheduleOptions {
+        day_window: TimeRange::try_from_str("9AM-5PM").unwrap(),
+        start_date: date,
+        days: 1,
+        order: TaskSchedulingOrder::LongestTaskFirst,
+        overflow_policy: TaskOverflowPolicy::Allow,
+        packing: TaskPackingMode::Greedy,
+        stable: false,
+    };
+
+    let schedule = plan_schedule(
+        ScheduleSnapshot {
+            tasks: &tasks,
+            events: &events,
+            cards: &cards,
+            templates: &templates,
+        },
+        &options,
+    )
+    .unwrap();
+
+    let slices: Vec<String> = schedule.slices().iter().map(|st| st.to_string()).collect();
+    assert_eq!(slices, vec!["2099-01-01: 9:00AM-12:00PM".to_string()]);
+    assert_eq!(schedule.stats.scheduled_hours, 3.0);
+    assert!(schedule.unscheduled().is_empty());
+    assert!(tasks.get(1).unwrap().subtasks.is_empty());
+}